@@ -0,0 +1,99 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+/// Definition of Collateral Swap Contract
+#[openbrush::contract]
+pub mod contract {
+    use ink::{
+        codegen::{
+            EmitEvent,
+            Env,
+        },
+        prelude::vec::Vec,
+    };
+    use logics::{
+        impls::{
+            collateral_swap::*,
+            flashloan_adapter::{
+                self,
+                Internal as FlashloanAdapterInternal,
+            },
+        },
+        traits::flashloan_receiver,
+    };
+    use openbrush::traits::Storage;
+
+    /// Contract's Storage
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct CollateralSwapContract {
+        #[storage_field]
+        flashloan_adapter: flashloan_adapter::Data,
+    }
+
+    /// Event: Collateral is swapped
+    #[ink(event)]
+    pub struct CollateralSwapped {
+        #[ink(topic)]
+        user: AccountId,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount_from: Balance,
+        amount_to: Balance,
+    }
+
+    impl CollateralSwap for CollateralSwapContract {}
+    impl Internal for CollateralSwapContract {
+        fn _emit_collateral_swap_event(
+            &self,
+            user: AccountId,
+            asset_from: AccountId,
+            asset_to: AccountId,
+            amount_from: Balance,
+            amount_to: Balance,
+        ) {
+            self.env().emit_event(CollateralSwapped {
+                user,
+                asset_from,
+                asset_to,
+                amount_from,
+                amount_to,
+            })
+        }
+    }
+
+    impl flashloan_receiver::FlashloanReceiver for CollateralSwapContract {
+        #[ink(message)]
+        fn execute_operation(
+            &self,
+            assets: Vec<AccountId>,
+            amounts: Vec<Balance>,
+            premiums: Vec<Balance>,
+            initiator: AccountId,
+            params: Vec<u8>,
+        ) -> bool {
+            self._execute_operation(assets, amounts, premiums, initiator, params)
+                .is_ok()
+        }
+    }
+
+    impl CollateralSwapContract {
+        /// Generate this contract
+        #[ink(constructor)]
+        pub fn new(flashloan_gateway: AccountId, controller: AccountId, router: AccountId) -> Self {
+            let mut instance = Self::default();
+            instance._initialize(flashloan_gateway, controller, router);
+            instance
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,465 @@
+use crate::contract::*;
+use core::cell::RefCell;
+use ink::{
+    env::{
+        test::{
+            self,
+            DefaultAccounts,
+        },
+        DefaultEnvironment,
+    },
+    prelude::collections::BTreeMap,
+};
+use logics::{
+    impls::{
+        collateral_swap::*,
+        flashloan_adapter::{
+            self,
+            Error as FlashloanAdapterError,
+            Internal as FlashloanAdapterInternal,
+        },
+    },
+    traits::{
+        dex_router::Error as DexRouterError,
+        flashloan_gateway::{
+            Error as FlashloanGatewayError,
+            FlashLoanType,
+        },
+        flashloan_receiver::FlashloanReceiver,
+    },
+};
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+use scale::Encode;
+
+fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+    test::default_accounts::<DefaultEnvironment>()
+}
+fn set_caller(id: AccountId) {
+    test::set_caller::<DefaultEnvironment>(id);
+}
+fn contract_id() -> AccountId {
+    ink::env::test::callee::<ink::env::DefaultEnvironment>()
+}
+
+fn new_contract() -> CollateralSwapContract {
+    let accounts = default_accounts();
+    CollateralSwapContract::new(accounts.bob, accounts.charlie, accounts.django)
+}
+
+#[ink::test]
+fn new_works() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    assert_eq!(contract.flashloan_gateway(), Some(accounts.bob));
+    assert_eq!(contract.controller(), Some(accounts.charlie));
+    assert_eq!(contract.router(), Some(accounts.django));
+}
+
+#[ink::test]
+fn swap_collateral_fails_with_same_asset() {
+    let accounts = default_accounts();
+    let mut contract = new_contract();
+    assert_eq!(
+        contract
+            .swap_collateral(accounts.eve, accounts.eve, 100, 0)
+            .unwrap_err(),
+        Error::SameAsset
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_when_caller_is_not_flashloan_gateway() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.alice);
+    let params = CollateralSwapParams {
+        user: accounts.alice,
+        asset_from: accounts.eve,
+        amount: 100,
+    };
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                contract_id(),
+                params.encode(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::CallerIsNotFlashloanGateway)
+    );
+    assert!(!contract.execute_operation(
+        vec![accounts.frank],
+        vec![100],
+        vec![0],
+        contract_id(),
+        params.encode(),
+    ));
+}
+
+#[ink::test]
+fn execute_operation_fails_when_initiator_is_not_this() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.bob);
+    let params = CollateralSwapParams {
+        user: accounts.alice,
+        asset_from: accounts.eve,
+        amount: 100,
+    };
+    // the off-chain environment runs the contract as alice
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                accounts.charlie,
+                params.encode(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InitiatorIsNotThis)
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_with_invalid_params() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.bob);
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank, accounts.eve],
+                vec![100, 100],
+                vec![0, 0],
+                contract_id(),
+                Vec::new(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InvalidFlashloanParams)
+    );
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                contract_id(),
+                Vec::new(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InvalidFlashloanParams)
+    );
+}
+
+/// Units of the new collateral asset the router gives for a unit of the old collateral asset
+const PRICE: Balance = 2;
+/// Flashloan premium in basis points
+const PREMIUM_TOTAL: u128 = 100;
+
+/// Collateral Swap whose Pools, DEX Router and Flashloan Gateway are replaced by `LEDGER`,
+/// a pool being identified by its underlying asset
+#[ink::storage_item]
+#[derive(Default, Storage)]
+struct MockCollateralSwap {
+    #[storage_field]
+    flashloan_adapter: flashloan_adapter::Data,
+}
+
+thread_local! {
+    static LEDGER: RefCell<Ledger> = RefCell::new(Ledger::default());
+}
+
+fn with_ledger<R>(f: impl FnOnce(&mut Ledger) -> R) -> R {
+    LEDGER.with(|ledger| f(&mut ledger.borrow_mut()))
+}
+
+#[derive(Default)]
+struct Ledger {
+    /// Balances by (asset, account)
+    balances: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Supplied underlying by (asset, account)
+    supplies: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Part of the amount quoted by the router that the swap does not give
+    swap_shortfall: Balance,
+    /// Error of `execute_operation` which made the flashloan fail
+    executor_error: Option<Error>,
+    events: Vec<(AccountId, AccountId, AccountId, Balance, Balance)>,
+}
+
+impl Ledger {
+    fn balance_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.balances.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn supply_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.supplies.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn transfer(&mut self, asset: AccountId, from: AccountId, to: AccountId, amount: Balance) {
+        let balance = self.balance_of(asset, from);
+        assert!(balance >= amount, "insufficient balance");
+        self.balances.insert((asset, from), balance - amount);
+        let balance = self.balance_of(asset, to);
+        self.balances.insert((asset, to), balance + amount);
+    }
+}
+
+impl FlashloanAdapterInternal for MockCollateralSwap {
+    fn _pool_of(&self, asset: AccountId) -> flashloan_adapter::Result<AccountId> {
+        let accounts = default_accounts();
+        if asset != accounts.eve && asset != accounts.frank {
+            return Err(FlashloanAdapterError::MarketNotListed)
+        }
+        Ok(asset)
+    }
+
+    fn _flashloan_premium(&self) -> flashloan_adapter::Result<u128> {
+        Ok(PREMIUM_TOTAL)
+    }
+
+    fn _flashloan(
+        &self,
+        asset: AccountId,
+        amount: Balance,
+        loan_type: FlashLoanType,
+        params: Vec<u8>,
+    ) -> flashloan_adapter::Result<()> {
+        assert_eq!(loan_type as u8, FlashLoanType::None as u8);
+        let on_behalf_of = ink::env::caller::<DefaultEnvironment>();
+        let premium = amount * PREMIUM_TOTAL / 10000;
+        // the whole flashloan is reverted if it is not repaid
+        let snapshot = with_ledger(|ledger| {
+            let snapshot = (ledger.balances.clone(), ledger.supplies.clone());
+            ledger.transfer(asset, asset, contract_id(), amount);
+            snapshot
+        });
+        set_caller(self._flashloan_gateway().unwrap());
+        let result = self._execute_operation(
+            vec![asset],
+            vec![amount],
+            vec![premium],
+            contract_id(),
+            params,
+        );
+        set_caller(on_behalf_of);
+        with_ledger(|ledger| {
+            match result {
+                Ok(()) => {
+                    ledger.transfer(asset, contract_id(), asset, amount + premium);
+                    Ok(())
+                }
+                Err(error) => {
+                    (ledger.balances, ledger.supplies) = snapshot;
+                    ledger.executor_error = Some(error);
+                    Err(FlashloanAdapterError::FlashloanGateway(
+                        FlashloanGatewayError::InvalidFlashloanExecutorReturn,
+                    ))
+                }
+            }
+        })
+    }
+
+    fn _approve_repayment(
+        &self,
+        _asset: AccountId,
+        _amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        Ok(())
+    }
+
+    fn _supply_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| {
+            ledger.transfer(asset, contract_id(), asset, amount);
+            let supply = ledger.supply_of(asset, user);
+            ledger.supplies.insert((asset, user), supply + amount);
+        });
+        Ok(())
+    }
+
+    fn _redeem_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| {
+            let supply = ledger.supply_of(asset, user);
+            assert!(supply >= amount, "redeem exceeds the supply");
+            ledger.supplies.insert((asset, user), supply - amount);
+            ledger.transfer(asset, asset, contract_id(), amount);
+        });
+        Ok(())
+    }
+
+    fn _get_amount_out(
+        &self,
+        _asset_in: AccountId,
+        _asset_out: AccountId,
+        amount_in: Balance,
+    ) -> flashloan_adapter::Result<Balance> {
+        Ok(amount_in * PRICE)
+    }
+
+    fn _swap_exact_in(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_in: Balance,
+        min_amount_out: Balance,
+    ) -> flashloan_adapter::Result<Balance> {
+        let router = self._router().unwrap();
+        with_ledger(|ledger| {
+            let amount_out = amount_in * PRICE - ledger.swap_shortfall;
+            if amount_out < min_amount_out {
+                return Err(FlashloanAdapterError::DexRouter(
+                    DexRouterError::InsufficientOutputAmount,
+                ))
+            }
+            ledger.transfer(asset_in, contract_id(), router, amount_in);
+            ledger.transfer(asset_out, router, contract_id(), amount_out);
+            Ok(amount_out)
+        })
+    }
+
+    fn _transfer(
+        &self,
+        asset: AccountId,
+        to: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| ledger.transfer(asset, contract_id(), to, amount));
+        Ok(())
+    }
+}
+
+impl Internal for MockCollateralSwap {
+    fn _emit_collateral_swap_event(
+        &self,
+        user: AccountId,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount_from: Balance,
+        amount_to: Balance,
+    ) {
+        with_ledger(|ledger| {
+            ledger
+                .events
+                .push((user, asset_from, asset_to, amount_from, amount_to))
+        })
+    }
+}
+
+/// Alice supplies 1_000 of eve, which is swapped into a supply of frank
+fn new_mock() -> MockCollateralSwap {
+    let accounts = default_accounts();
+    // the off-chain environment runs the contract as alice, who is the user here
+    test::set_callee::<DefaultEnvironment>(AccountId::from([0xff; 32]));
+    let mut mock = MockCollateralSwap::default();
+    mock._initialize(accounts.bob, accounts.charlie, accounts.django);
+    with_ledger(|ledger| {
+        *ledger = Ledger::default();
+        ledger
+            .supplies
+            .insert((accounts.eve, accounts.alice), 1_000);
+        // cash of the pools and liquidity of the router
+        ledger
+            .balances
+            .insert((accounts.eve, accounts.eve), 100_000);
+        ledger
+            .balances
+            .insert((accounts.frank, accounts.frank), 100_000);
+        ledger
+            .balances
+            .insert((accounts.frank, accounts.django), 100_000);
+    });
+    mock
+}
+
+#[ink::test]
+fn swap_collateral_works() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    // the router gives 800, of which 792 are flashloaned with a premium of 7
+    mock.swap_collateral(accounts.eve, accounts.frank, 400, 792)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.supply_of(accounts.eve, accounts.alice), 600);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 792);
+    // the output of the swap above the repayment goes to the user
+    assert_eq!(ledger.balance_of(accounts.frank, accounts.alice), 1);
+    assert_eq!(ledger.balance_of(accounts.eve, contract_id()), 0);
+    assert_eq!(ledger.balance_of(accounts.frank, contract_id()), 0);
+    assert_eq!(ledger.balance_of(accounts.eve, accounts.django), 400);
+    // the supply stays in the pool, which also gets the flashloan back with its premium
+    assert_eq!(ledger.balance_of(accounts.frank, accounts.frank), 100_799);
+    assert_eq!(
+        ledger.events,
+        vec![(accounts.alice, accounts.eve, accounts.frank, 400, 792)]
+    );
+}
+
+#[ink::test]
+fn swap_collateral_fails_with_insufficient_amount_to_receive() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    assert_eq!(
+        mock.swap_collateral(accounts.eve, accounts.frank, 400, 793)
+            .unwrap_err(),
+        Error::InsufficientAmountToReceive
+    );
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.supply_of(accounts.eve, accounts.alice), 1_000);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 0);
+}
+
+#[ink::test]
+fn swap_collateral_fails_when_swap_cannot_repay_flashloan() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    with_ledger(|ledger| ledger.swap_shortfall = 2);
+    set_caller(accounts.alice);
+    assert_eq!(
+        mock.swap_collateral(accounts.eve, accounts.frank, 400, 792)
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::FlashloanGateway(
+            FlashloanGatewayError::InvalidFlashloanExecutorReturn
+        ))
+    );
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(
+        ledger.executor_error,
+        Some(Error::FlashloanAdapter(FlashloanAdapterError::DexRouter(
+            DexRouterError::InsufficientOutputAmount
+        )))
+    );
+    assert_eq!(ledger.supply_of(accounts.eve, accounts.alice), 1_000);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 0);
+    assert!(ledger.events.is_empty());
+}
+
+#[ink::test]
+fn swap_collateral_fails_with_unlisted_market() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    assert_eq!(
+        mock.swap_collateral(accounts.eve, accounts.django, 400, 0)
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::MarketNotListed)
+    );
+}
@@ -7,20 +7,26 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of Controller Contract
 #[openbrush::contract]
 pub mod contract {
-    use ink::codegen::{
-        EmitEvent,
-        Env,
+    use ink::{
+        codegen::{
+            EmitEvent,
+            Env,
+        },
+        prelude::{
+            vec,
+            vec::Vec,
+        },
     };
     use logics::{
-        impls::controller::{
-            Internal,
-            *,
+        impls::{
+            controller::{
+                Internal,
+                *,
+            },
+            upgradeable,
         },
         traits::types::WrappedU256,
     };
@@ -35,6 +41,8 @@ pub mod contract {
     pub struct ControllerContract {
         #[storage_field]
         controller: Data,
+        #[storage_field]
+        upgradeable: upgradeable::Data,
     }
 
     /// Event: Controller starts to support Pool
@@ -103,6 +111,41 @@ pub mod contract {
         pub new: WrappedU256,
     }
 
+    #[ink(event)]
+    pub struct NewMaxUtilization {
+        pub pool: AccountId,
+        pub old: WrappedU256,
+        pub new: WrappedU256,
+    }
+
+    #[ink(event)]
+    pub struct NewOutflowLimit {
+        pub pool: AccountId,
+        pub new: OutflowLimit,
+    }
+
+    #[ink(event)]
+    pub struct OutflowLimitRaised {
+        pub pool: AccountId,
+        pub amount: Balance,
+        pub expires_at: Timestamp,
+    }
+
+    /// Event: The code of this contract has been replaced
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        pub code_hash: Hash,
+    }
+
+    /// Event: The storage struct under storage_key has been migrated
+    #[ink(event)]
+    pub struct Migrated {
+        pub storage_key: u32,
+        pub from: u32,
+        pub to: u32,
+    }
+
     impl Controller for ControllerContract {}
 
     impl ControllerContract {
@@ -111,10 +154,34 @@ pub mod contract {
         pub fn new(manager: AccountId) -> Self {
             let mut instance = Self::default();
             instance.controller.manager = Some(manager);
+            upgradeable::Internal::_init_storage_versions(&mut instance);
             instance
         }
     }
 
+    impl upgradeable::Upgradeable for ControllerContract {}
+    impl upgradeable::Internal for ControllerContract {
+        fn _code_storage_versions(&self) -> Vec<(u32, u32)> {
+            vec![(STORAGE_KEY, STORAGE_VERSION)]
+        }
+
+        fn _upgrader(&self) -> Option<AccountId> {
+            self.controller.manager
+        }
+
+        fn _emit_upgraded_event(&self, code_hash: Hash) {
+            self.env().emit_event(Upgraded { code_hash })
+        }
+
+        fn _emit_migrated_event(&self, storage_key: u32, from: u32, to: u32) {
+            self.env().emit_event(Migrated {
+                storage_key,
+                from,
+                to,
+            })
+        }
+    }
+
     impl Internal for ControllerContract {
         fn _emit_market_listed_event(&self, pool: AccountId) {
             self.env().emit_event(MarketListed { pool });
@@ -169,5 +236,34 @@ pub mod contract {
         fn _emit_new_borrow_cap_event(&self, pool: AccountId, new: Balance) {
             self.env().emit_event(NewBorrowCap { pool, new });
         }
+
+        fn _emit_new_max_utilization_event(
+            &self,
+            pool: AccountId,
+            old: WrappedU256,
+            new: WrappedU256,
+        ) {
+            self.env().emit_event(NewMaxUtilization { pool, old, new });
+        }
+
+        fn _emit_new_outflow_limit_event(&self, pool: AccountId, new: OutflowLimit) {
+            self.env().emit_event(NewOutflowLimit { pool, new });
+        }
+
+        fn _emit_outflow_limit_raised_event(
+            &self,
+            pool: AccountId,
+            amount: Balance,
+            expires_at: Timestamp,
+        ) {
+            self.env().emit_event(OutflowLimitRaised {
+                pool,
+                amount,
+                expires_at,
+            });
+        }
     }
 }
+
+#[cfg(test)]
+mod tests;
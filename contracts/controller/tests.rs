@@ -14,13 +14,27 @@ use ink::env::{
     DefaultEnvironment,
 };
 use logics::{
+    events::{
+        controller as events,
+        decode_event,
+        ControllerEvent,
+        ProtocolEvent,
+    },
     impls::{
         controller::*,
-        exp_no_err::exp_scale,
+        fixed_point::exp_scale,
+        upgradeable::{
+            Error as UpgradeableError,
+            Upgradeable,
+        },
     },
     traits::types::WrappedU256,
 };
-use openbrush::traits::AccountId;
+use openbrush::traits::{
+    AccountId,
+    Hash,
+    Timestamp,
+};
 use primitive_types::U256;
 use scale::Decode;
 
@@ -194,6 +208,40 @@ fn support_market_works() {
     assert_eq!(contract.markets(), [p1, p2]);
 }
 
+#[ink::test]
+fn emitted_events_are_decoded_by_logics() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let oracle = AccountId::from([0x02; 32]);
+    contract.support_market(pool, pool).unwrap();
+    contract.set_price_oracle(oracle).unwrap();
+
+    let decoded: Vec<_> = get_emitted_events()
+        .into_iter()
+        .map(|event| {
+            let topics: Vec<Hash> = event
+                .topics
+                .iter()
+                .map(|topic| Hash::decode(&mut &topic[..]).unwrap())
+                .collect();
+            decode_event(&event.data, &topics).unwrap()
+        })
+        .collect();
+    assert_eq!(
+        decoded,
+        vec![
+            ProtocolEvent::Controller(ControllerEvent::MarketListed(events::MarketListed { pool })),
+            ProtocolEvent::Controller(ControllerEvent::NewPriceOracle(events::NewPriceOracle {
+                old: None,
+                new: Some(oracle),
+            })),
+        ]
+    );
+}
+
 #[ink::test]
 fn support_market_fails_when_duplicate() {
     let accounts = default_accounts();
@@ -223,7 +271,7 @@ fn support_market_with_collateral_factor_mantissa_fails_by_call_price_oracle_in_
     let underlying = AccountId::from([0x01; 32]);
 
     let oracle_addr = AccountId::from([0x02; 32]);
-    assert_eq!(contract.set_price_oracle(oracle_addr).unwrap(), ());
+    contract.set_price_oracle(oracle_addr).unwrap();
 
     contract
         .support_market_with_collateral_factor_mantissa(p1, underlying, WrappedU256::from(1))
@@ -258,7 +306,7 @@ fn set_collateral_factor_mantissa_works() {
     assert_eq!(contract.collateral_factor_mantissa(pool_addr), None);
 
     let oracle_addr = AccountId::from([0x02; 32]);
-    assert_eq!(contract.set_price_oracle(oracle_addr).unwrap(), ());
+    contract.set_price_oracle(oracle_addr).unwrap();
     let max = exp_scale().mul(U256::from(90)).div(U256::from(100));
     contract.support_market(pool_addr, pool_addr).unwrap();
     contract
@@ -419,3 +467,238 @@ fn accept_manager_works() {
     assert_eq!(contract.pending_manager(), None);
     assert_eq!(contract.manager().unwrap(), accounts.alice);
 }
+
+#[ink::test]
+fn set_max_utilization_mantissa_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let underlying = AccountId::from([0x01; 32]);
+    let max_utilization = WrappedU256::from(exp_scale().mul(U256::from(80)).div(U256::from(100)));
+    assert_eq!(
+        contract
+            .set_max_utilization_mantissa(pool, max_utilization)
+            .unwrap_err(),
+        Error::MarketNotListed
+    );
+    assert!(contract.support_market(pool, underlying).is_ok());
+    assert_eq!(contract.max_utilization_mantissa(pool), None);
+    assert!(contract
+        .set_max_utilization_mantissa(pool, max_utilization)
+        .is_ok());
+    assert_eq!(
+        contract.max_utilization_mantissa(pool),
+        Some(max_utilization)
+    );
+
+    set_caller(accounts.charlie);
+    assert_eq!(
+        contract
+            .set_max_utilization_mantissa(pool, WrappedU256::from(0))
+            .unwrap_err(),
+        Error::CallerIsNotManager
+    );
+}
+
+fn outflow_limit(percent: u128, window: Timestamp) -> OutflowLimit {
+    OutflowLimit {
+        max_outflow_mantissa: WrappedU256::from(
+            exp_scale().mul(U256::from(percent)).div(U256::from(100)),
+        ),
+        window,
+    }
+}
+
+#[ink::test]
+fn set_outflow_limit_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let underlying = AccountId::from([0x01; 32]);
+    assert!(contract.support_market(pool, underlying).is_ok());
+    assert_eq!(contract.outflow_limit(pool), None);
+    assert_eq!(contract.remaining_outflow_capacity(pool), None);
+
+    let limit = outflow_limit(10, 3_600_000);
+    assert!(contract.set_outflow_limit(pool, limit).is_ok());
+    assert_eq!(contract.outflow_limit(pool), Some(limit));
+
+    assert!(contract.raise_outflow_limit(pool, 1_000, 100).is_ok());
+    assert_eq!(
+        contract.outflow_limit_raise(pool),
+        Some(OutflowLimitRaise {
+            amount: 1_000,
+            expires_at: 100,
+        })
+    );
+}
+
+#[ink::test]
+fn set_outflow_limit_fails() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let underlying = AccountId::from([0x01; 32]);
+    let limit = outflow_limit(10, 3_600_000);
+    assert_eq!(
+        contract.set_outflow_limit(pool, limit).unwrap_err(),
+        Error::MarketNotListed
+    );
+    assert!(contract.support_market(pool, underlying).is_ok());
+    assert_eq!(
+        contract
+            .set_outflow_limit(pool, outflow_limit(101, 3_600_000))
+            .unwrap_err(),
+        Error::InvalidOutflowLimit
+    );
+    assert_eq!(
+        contract
+            .set_outflow_limit(pool, outflow_limit(10, 0))
+            .unwrap_err(),
+        Error::InvalidOutflowLimit
+    );
+
+    set_caller(accounts.charlie);
+    assert_eq!(
+        contract.set_outflow_limit(pool, limit).unwrap_err(),
+        Error::CallerIsNotManager
+    );
+    assert_eq!(
+        contract.raise_outflow_limit(pool, 1_000, 100).unwrap_err(),
+        Error::CallerIsNotManager
+    );
+}
+
+#[ink::test]
+fn record_outflow_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let underlying = AccountId::from([0x01; 32]);
+    let window = 3_600_000;
+    assert!(contract.support_market(pool, underlying).is_ok());
+
+    // no limit
+    set_caller(pool);
+    assert!(contract.record_outflow(pool, 10_000, 10_000).is_ok());
+
+    // 10% of liquidity per window
+    set_caller(accounts.bob);
+    assert!(contract
+        .set_outflow_limit(pool, outflow_limit(10, window))
+        .is_ok());
+    set_caller(pool);
+    assert!(contract.record_outflow(pool, 600, 10_000).is_ok());
+    assert_eq!(contract.remaining_outflow_capacity(pool), Some(400));
+    assert_eq!(
+        contract.record_outflow(pool, 401, 9_400).unwrap_err(),
+        Error::OutflowLimitExceeded
+    );
+
+    // inflow offsets the outflow
+    assert!(contract.record_inflow(pool, 100, 9_400).is_ok());
+    assert_eq!(contract.remaining_outflow_capacity(pool), Some(500));
+
+    // guardian raises the limit temporarily
+    set_caller(accounts.bob);
+    assert!(contract.raise_outflow_limit(pool, 1_000, window).is_ok());
+    set_caller(pool);
+    assert_eq!(contract.remaining_outflow_capacity(pool), Some(1_500));
+    assert!(contract.record_outflow(pool, 1_200, 9_500).is_ok());
+    assert_eq!(contract.remaining_outflow_capacity(pool), Some(300));
+
+    // half of the previous bucket is still in the window
+    test::set_block_timestamp::<DefaultEnvironment>(window + window / 2);
+    assert!(contract.record_outflow(pool, 0, 8_000).is_ok());
+    assert_eq!(contract.remaining_outflow_capacity(pool), Some(0));
+    test::set_block_timestamp::<DefaultEnvironment>(window * 2 - 1);
+    assert_eq!(contract.remaining_outflow_capacity(pool), Some(800));
+}
+
+#[ink::test]
+fn record_outflow_works_when_bucket_starts_without_cash() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let underlying = AccountId::from([0x01; 32]);
+    assert!(contract.support_market(pool, underlying).is_ok());
+    assert!(contract
+        .set_outflow_limit(pool, outflow_limit(10, 3_600_000))
+        .is_ok());
+
+    // first deposit into an empty pool
+    set_caller(pool);
+    assert!(contract.record_inflow(pool, 10_000, 0).is_ok());
+    // 10% of the current cash
+    assert!(contract.record_outflow(pool, 1_000, 10_000).is_ok());
+    assert_eq!(
+        contract.record_outflow(pool, 1, 9_000).unwrap_err(),
+        Error::OutflowLimitExceeded
+    );
+}
+
+#[ink::test]
+fn record_outflow_fails_when_caller_is_not_pool() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = ControllerContract::new(accounts.bob);
+
+    let pool = AccountId::from([0x01; 32]);
+    let underlying = AccountId::from([0x01; 32]);
+    assert_eq!(
+        contract.record_outflow(pool, 100, 1_000).unwrap_err(),
+        Error::MarketNotListed
+    );
+    assert!(contract.support_market(pool, underlying).is_ok());
+    assert_eq!(
+        contract.record_outflow(pool, 100, 1_000).unwrap_err(),
+        Error::CallerIsNotPool
+    );
+    assert_eq!(
+        contract.record_inflow(pool, 100, 1_000).unwrap_err(),
+        Error::CallerIsNotPool
+    );
+}
+
+#[ink::test]
+fn storage_versions_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = ControllerContract::new(accounts.bob);
+    assert_eq!(
+        contract.storage_versions(),
+        vec![(STORAGE_KEY, STORAGE_VERSION)]
+    );
+
+    // nothing to migrate
+    assert!(contract.migrate().is_ok());
+    assert!(get_emitted_events().is_empty());
+}
+
+#[ink::test]
+fn upgrade_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = ControllerContract::new(accounts.bob);
+    set_caller(accounts.charlie);
+    assert_eq!(
+        contract.upgrade(Hash::from([0x01; 32])).unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+    assert_eq!(
+        contract.migrate().unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+}
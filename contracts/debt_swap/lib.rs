@@ -0,0 +1,99 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+/// Definition of Debt Swap Contract
+#[openbrush::contract]
+pub mod contract {
+    use ink::{
+        codegen::{
+            EmitEvent,
+            Env,
+        },
+        prelude::vec::Vec,
+    };
+    use logics::{
+        impls::{
+            debt_swap::*,
+            flashloan_adapter::{
+                self,
+                Internal as FlashloanAdapterInternal,
+            },
+        },
+        traits::flashloan_receiver,
+    };
+    use openbrush::traits::Storage;
+
+    /// Contract's Storage
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct DebtSwapContract {
+        #[storage_field]
+        flashloan_adapter: flashloan_adapter::Data,
+    }
+
+    /// Event: Debt is swapped
+    #[ink(event)]
+    pub struct DebtSwapped {
+        #[ink(topic)]
+        user: AccountId,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        new_debt_amount: Balance,
+    }
+
+    impl DebtSwap for DebtSwapContract {}
+    impl Internal for DebtSwapContract {
+        fn _emit_debt_swap_event(
+            &self,
+            user: AccountId,
+            debt_asset_from: AccountId,
+            debt_asset_to: AccountId,
+            repay_amount: Balance,
+            new_debt_amount: Balance,
+        ) {
+            self.env().emit_event(DebtSwapped {
+                user,
+                debt_asset_from,
+                debt_asset_to,
+                repay_amount,
+                new_debt_amount,
+            })
+        }
+    }
+
+    impl flashloan_receiver::FlashloanReceiver for DebtSwapContract {
+        #[ink(message)]
+        fn execute_operation(
+            &self,
+            assets: Vec<AccountId>,
+            amounts: Vec<Balance>,
+            premiums: Vec<Balance>,
+            initiator: AccountId,
+            params: Vec<u8>,
+        ) -> bool {
+            self._execute_operation(assets, amounts, premiums, initiator, params)
+                .is_ok()
+        }
+    }
+
+    impl DebtSwapContract {
+        /// Generate this contract
+        #[ink(constructor)]
+        pub fn new(flashloan_gateway: AccountId, controller: AccountId, router: AccountId) -> Self {
+            let mut instance = Self::default();
+            instance._initialize(flashloan_gateway, controller, router);
+            instance
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,437 @@
+use crate::contract::*;
+use core::cell::RefCell;
+use ink::{
+    env::{
+        test::{
+            self,
+            DefaultAccounts,
+        },
+        DefaultEnvironment,
+    },
+    prelude::collections::BTreeMap,
+};
+use logics::{
+    impls::{
+        debt_swap::*,
+        flashloan_adapter::{
+            self,
+            Error as FlashloanAdapterError,
+            Internal as FlashloanAdapterInternal,
+        },
+    },
+    traits::{
+        flashloan_gateway::{
+            Error as FlashloanGatewayError,
+            FlashLoanType,
+        },
+        flashloan_receiver::FlashloanReceiver,
+    },
+};
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+use scale::Encode;
+
+fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+    test::default_accounts::<DefaultEnvironment>()
+}
+fn set_caller(id: AccountId) {
+    test::set_caller::<DefaultEnvironment>(id);
+}
+fn contract_id() -> AccountId {
+    ink::env::test::callee::<ink::env::DefaultEnvironment>()
+}
+
+fn new_contract() -> DebtSwapContract {
+    let accounts = default_accounts();
+    DebtSwapContract::new(accounts.bob, accounts.charlie, accounts.django)
+}
+
+#[ink::test]
+fn new_works() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    assert_eq!(contract.flashloan_gateway(), Some(accounts.bob));
+    assert_eq!(contract.controller(), Some(accounts.charlie));
+    assert_eq!(contract.router(), Some(accounts.django));
+}
+
+#[ink::test]
+fn swap_debt_fails_with_same_asset() {
+    let accounts = default_accounts();
+    let mut contract = new_contract();
+    assert_eq!(
+        contract
+            .swap_debt(accounts.eve, accounts.eve, 100, 100)
+            .unwrap_err(),
+        Error::SameAsset
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_when_caller_is_not_flashloan_gateway() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.alice);
+    let params = DebtSwapParams {
+        user: accounts.alice,
+        debt_asset_from: accounts.eve,
+        repay_amount: 100,
+    };
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                contract_id(),
+                params.encode(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::CallerIsNotFlashloanGateway)
+    );
+    assert!(!contract.execute_operation(
+        vec![accounts.frank],
+        vec![100],
+        vec![0],
+        contract_id(),
+        params.encode(),
+    ));
+}
+
+#[ink::test]
+fn execute_operation_fails_when_initiator_is_not_this() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.bob);
+    let params = DebtSwapParams {
+        user: accounts.alice,
+        debt_asset_from: accounts.eve,
+        repay_amount: 100,
+    };
+    // the off-chain environment runs the contract as alice
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                accounts.charlie,
+                params.encode(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InitiatorIsNotThis)
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_with_invalid_params() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.bob);
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank, accounts.eve],
+                vec![100, 100],
+                vec![0, 0],
+                contract_id(),
+                Vec::new(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InvalidFlashloanParams)
+    );
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                contract_id(),
+                Vec::new(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InvalidFlashloanParams)
+    );
+}
+
+/// Units of the new debt asset the router takes for a unit of the old debt asset
+const PRICE: Balance = 2;
+
+/// Debt Swap whose Pools, DEX Router and Flashloan Gateway are replaced by `LEDGER`,
+/// a pool being identified by its underlying asset
+#[ink::storage_item]
+#[derive(Default, Storage)]
+struct MockDebtSwap {
+    #[storage_field]
+    flashloan_adapter: flashloan_adapter::Data,
+}
+
+thread_local! {
+    static LEDGER: RefCell<Ledger> = RefCell::new(Ledger::default());
+}
+
+fn with_ledger<R>(f: impl FnOnce(&mut Ledger) -> R) -> R {
+    LEDGER.with(|ledger| f(&mut ledger.borrow_mut()))
+}
+
+#[derive(Default)]
+struct Ledger {
+    /// Balances by (asset, account)
+    balances: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Borrow balances by (asset, account)
+    borrows: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Part of the amount quoted by the router that the swap does not spend
+    swap_discount: Balance,
+    events: Vec<(AccountId, AccountId, AccountId, Balance, Balance)>,
+}
+
+impl Ledger {
+    fn balance_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.balances.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn borrow_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.borrows.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn transfer(&mut self, asset: AccountId, from: AccountId, to: AccountId, amount: Balance) {
+        let balance = self.balance_of(asset, from);
+        assert!(balance >= amount, "insufficient balance");
+        self.balances.insert((asset, from), balance - amount);
+        let balance = self.balance_of(asset, to);
+        self.balances.insert((asset, to), balance + amount);
+    }
+}
+
+impl FlashloanAdapterInternal for MockDebtSwap {
+    fn _pool_of(&self, asset: AccountId) -> flashloan_adapter::Result<AccountId> {
+        let accounts = default_accounts();
+        if asset != accounts.eve && asset != accounts.frank {
+            return Err(FlashloanAdapterError::MarketNotListed)
+        }
+        Ok(asset)
+    }
+
+    fn _flashloan(
+        &self,
+        asset: AccountId,
+        amount: Balance,
+        loan_type: FlashLoanType,
+        params: Vec<u8>,
+    ) -> flashloan_adapter::Result<()> {
+        assert_eq!(loan_type as u8, FlashLoanType::Borrowing as u8);
+        let on_behalf_of = ink::env::caller::<DefaultEnvironment>();
+        // the loan is kept as a borrow of the user, so no premium is charged
+        with_ledger(|ledger| {
+            let borrow = ledger.borrow_of(asset, on_behalf_of);
+            ledger
+                .borrows
+                .insert((asset, on_behalf_of), borrow + amount);
+            ledger.transfer(asset, asset, contract_id(), amount);
+        });
+        set_caller(self._flashloan_gateway().unwrap());
+        let result =
+            self._execute_operation(vec![asset], vec![amount], vec![0], contract_id(), params);
+        set_caller(on_behalf_of);
+        result.map_err(|_| {
+            FlashloanAdapterError::FlashloanGateway(
+                FlashloanGatewayError::InvalidFlashloanExecutorReturn,
+            )
+        })
+    }
+
+    fn _repay_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| {
+            let borrow = ledger.borrow_of(asset, user);
+            assert!(borrow >= amount, "repay exceeds the borrow");
+            ledger.borrows.insert((asset, user), borrow - amount);
+            ledger.transfer(asset, contract_id(), asset, amount);
+        });
+        Ok(())
+    }
+
+    fn _borrow_balance_of(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+    ) -> flashloan_adapter::Result<Balance> {
+        self._pool_of(asset)?;
+        Ok(with_ledger(|ledger| ledger.borrow_of(asset, user)))
+    }
+
+    fn _get_amount_in(
+        &self,
+        _asset_in: AccountId,
+        _asset_out: AccountId,
+        amount_out: Balance,
+    ) -> flashloan_adapter::Result<Balance> {
+        Ok(amount_out * PRICE)
+    }
+
+    fn _swap_exact_out(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_out: Balance,
+        max_amount_in: Balance,
+    ) -> flashloan_adapter::Result<Balance> {
+        let router = self._router().unwrap();
+        Ok(with_ledger(|ledger| {
+            let amount_in = amount_out * PRICE - ledger.swap_discount;
+            assert!(amount_in <= max_amount_in, "excessive input amount");
+            ledger.transfer(asset_in, contract_id(), router, amount_in);
+            ledger.transfer(asset_out, router, contract_id(), amount_out);
+            amount_in
+        }))
+    }
+
+    fn _transfer(
+        &self,
+        asset: AccountId,
+        to: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| ledger.transfer(asset, contract_id(), to, amount));
+        Ok(())
+    }
+}
+
+impl Internal for MockDebtSwap {
+    fn _emit_debt_swap_event(
+        &self,
+        user: AccountId,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        new_debt_amount: Balance,
+    ) {
+        with_ledger(|ledger| {
+            ledger.events.push((
+                user,
+                debt_asset_from,
+                debt_asset_to,
+                repay_amount,
+                new_debt_amount,
+            ))
+        })
+    }
+}
+
+/// Alice borrows 1_000 of eve, which is swapped into a borrow of frank
+fn new_mock() -> MockDebtSwap {
+    let accounts = default_accounts();
+    // the off-chain environment runs the contract as alice, who is the user here
+    test::set_callee::<DefaultEnvironment>(AccountId::from([0xff; 32]));
+    let mut mock = MockDebtSwap::default();
+    mock._initialize(accounts.bob, accounts.charlie, accounts.django);
+    with_ledger(|ledger| {
+        *ledger = Ledger::default();
+        ledger.borrows.insert((accounts.eve, accounts.alice), 1_000);
+        // cash of the pools and liquidity of the router
+        ledger
+            .balances
+            .insert((accounts.eve, accounts.eve), 100_000);
+        ledger
+            .balances
+            .insert((accounts.frank, accounts.frank), 100_000);
+        ledger
+            .balances
+            .insert((accounts.eve, accounts.django), 100_000);
+    });
+    mock
+}
+
+#[ink::test]
+fn swap_debt_works() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    mock.swap_debt(accounts.eve, accounts.frank, 400, 800)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 600);
+    assert_eq!(ledger.borrow_of(accounts.frank, accounts.alice), 800);
+    assert_eq!(ledger.balance_of(accounts.eve, contract_id()), 0);
+    assert_eq!(ledger.balance_of(accounts.frank, contract_id()), 0);
+    assert_eq!(ledger.balance_of(accounts.frank, accounts.django), 800);
+    assert_eq!(
+        ledger.events,
+        vec![(accounts.alice, accounts.eve, accounts.frank, 400, 800)]
+    );
+}
+
+#[ink::test]
+fn swap_debt_repays_at_most_the_borrow() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    mock.swap_debt(accounts.eve, accounts.frank, Balance::MAX, 2_000)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 0);
+    assert_eq!(ledger.borrow_of(accounts.frank, accounts.alice), 2_000);
+}
+
+#[ink::test]
+fn swap_debt_returns_new_debt_not_spent_by_the_swap() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    with_ledger(|ledger| ledger.swap_discount = 10);
+    set_caller(accounts.alice);
+    mock.swap_debt(accounts.eve, accounts.frank, 400, 800)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 600);
+    assert_eq!(ledger.borrow_of(accounts.frank, accounts.alice), 800);
+    assert_eq!(ledger.balance_of(accounts.frank, accounts.alice), 10);
+    assert_eq!(ledger.balance_of(accounts.frank, contract_id()), 0);
+}
+
+#[ink::test]
+fn swap_debt_fails_with_excessive_new_debt_amount() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    assert_eq!(
+        mock.swap_debt(accounts.eve, accounts.frank, 400, 799)
+            .unwrap_err(),
+        Error::ExcessiveNewDebtAmount
+    );
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.frank, accounts.alice), 0);
+}
+
+#[ink::test]
+fn swap_debt_fails_without_borrow() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.bob);
+    assert_eq!(
+        mock.swap_debt(accounts.eve, accounts.frank, 400, 800)
+            .unwrap_err(),
+        Error::NoBorrowToRepay
+    );
+}
+
+#[ink::test]
+fn swap_debt_fails_with_unlisted_market() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    assert_eq!(
+        mock.swap_debt(accounts.eve, accounts.django, 400, 800)
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::MarketNotListed)
+    );
+}
@@ -8,9 +8,6 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of Interest Rate Model Contract
 #[openbrush::contract]
 pub mod contract {
@@ -50,3 +47,6 @@ pub mod contract {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
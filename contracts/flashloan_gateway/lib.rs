@@ -11,15 +11,28 @@
 /// Definition of Flashloan Gateway Contract
 #[openbrush::contract]
 pub mod contract {
-    use ink::codegen::{
-        EmitEvent,
-        Env,
+    use ink::{
+        codegen::{
+            EmitEvent,
+            Env,
+        },
+        prelude::{
+            vec,
+            vec::Vec,
+        },
     };
 
-    use logics::impls::flashloan_gateway::{
-        Data,
-        Internal,
-        *,
+    use logics::{
+        impls::{
+            flash_lender,
+            flashloan_gateway::{
+                Data,
+                Internal,
+                *,
+            },
+            upgradeable,
+        },
+        traits::controller::ControllerRef,
     };
     use openbrush::traits::Storage;
 
@@ -29,6 +42,8 @@ pub mod contract {
     pub struct FlashloanGatewayContract {
         #[storage_field]
         gateway: Data,
+        #[storage_field]
+        upgradeable: upgradeable::Data,
     }
 
     /// Event: Flashloan is executed.
@@ -41,6 +56,58 @@ pub mod contract {
         asset: AccountId,
         amount: Balance,
         premium: Balance,
+        premium_to_protocol: Balance,
+    }
+
+    /// Event: Flashloan fee is changed
+    #[ink(event)]
+    pub struct NewFlashloanPremiumTotal {
+        old: u128,
+        new: u128,
+    }
+
+    /// Event: Share of Flashloan fee added to the reserves is changed
+    #[ink(event)]
+    pub struct NewFlashloanPremiumToProtocol {
+        old: u128,
+        new: u128,
+    }
+
+    /// Event: Flashloan fee override of a borrower is changed
+    #[ink(event)]
+    pub struct NewBorrowerPremium {
+        #[ink(topic)]
+        borrower: AccountId,
+        premium: Option<u128>,
+    }
+
+    /// Event: Restriction of flash loans to whitelisted receivers is changed
+    #[ink(event)]
+    pub struct NewWhitelistOnly {
+        enabled: bool,
+    }
+
+    /// Event: Receiver is added to or removed from the whitelist
+    #[ink(event)]
+    pub struct NewWhitelistedReceiver {
+        #[ink(topic)]
+        receiver: AccountId,
+        whitelisted: bool,
+    }
+
+    /// Event: The code of this contract has been replaced
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        pub code_hash: Hash,
+    }
+
+    /// Event: The storage struct under storage_key has been migrated
+    #[ink(event)]
+    pub struct Migrated {
+        pub storage_key: u32,
+        pub from: u32,
+        pub to: u32,
     }
 
     impl Internal for FlashloanGatewayContract {
@@ -51,6 +118,7 @@ pub mod contract {
             asset: AccountId,
             amount: Balance,
             premium: Balance,
+            premium_to_protocol: Balance,
         ) {
             self.env().emit_event(FlashLoan {
                 target,
@@ -58,10 +126,62 @@ pub mod contract {
                 asset,
                 amount,
                 premium,
+                premium_to_protocol,
+            })
+        }
+
+        fn _emit_new_flashloan_premium_total_event(&self, old: u128, new: u128) {
+            self.env().emit_event(NewFlashloanPremiumTotal { old, new })
+        }
+
+        fn _emit_new_flashloan_premium_to_protocol_event(&self, old: u128, new: u128) {
+            self.env()
+                .emit_event(NewFlashloanPremiumToProtocol { old, new })
+        }
+
+        fn _emit_new_borrower_premium_event(&self, borrower: AccountId, premium: Option<u128>) {
+            self.env()
+                .emit_event(NewBorrowerPremium { borrower, premium })
+        }
+
+        fn _emit_new_whitelist_only_event(&self, enabled: bool) {
+            self.env().emit_event(NewWhitelistOnly { enabled })
+        }
+
+        fn _emit_new_whitelisted_receiver_event(&self, receiver: AccountId, whitelisted: bool) {
+            self.env().emit_event(NewWhitelistedReceiver {
+                receiver,
+                whitelisted,
             })
         }
     }
     impl FlashloanGateway for FlashloanGatewayContract {}
+    impl flash_lender::FlashLender for FlashloanGatewayContract {}
+
+    impl upgradeable::Upgradeable for FlashloanGatewayContract {}
+    impl upgradeable::Internal for FlashloanGatewayContract {
+        fn _code_storage_versions(&self) -> Vec<(u32, u32)> {
+            vec![(STORAGE_KEY, STORAGE_VERSION)]
+        }
+
+        fn _upgrader(&self) -> Option<AccountId> {
+            // Flashloan Gateway is managed by the manager of Controller
+            let controller = self.gateway.controller?;
+            ControllerRef::manager(&controller)
+        }
+
+        fn _emit_upgraded_event(&self, code_hash: Hash) {
+            self.env().emit_event(Upgraded { code_hash })
+        }
+
+        fn _emit_migrated_event(&self, storage_key: u32, from: u32, to: u32) {
+            self.env().emit_event(Migrated {
+                storage_key,
+                from,
+                to,
+            })
+        }
+    }
 
     impl FlashloanGatewayContract {
         /// Generate this contract
@@ -69,7 +189,11 @@ pub mod contract {
         pub fn new(controller: AccountId) -> Self {
             let mut instance = Self::default();
             instance._initialize(controller);
+            upgradeable::Internal::_init_storage_versions(&mut instance);
             instance
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,109 @@
+use crate::contract::*;
+use ink::env::{
+    test::{
+        self,
+        recorded_events,
+        DefaultAccounts,
+        EmittedEvent,
+    },
+    DefaultEnvironment,
+};
+use logics::{
+    events::{
+        decode_event,
+        flashloan_gateway as events,
+        FlashloanGatewayEvent,
+        ProtocolEvent,
+    },
+    impls::flashloan_gateway::*,
+};
+use openbrush::traits::{
+    AccountId,
+    Hash,
+};
+use scale::Decode;
+
+fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+    test::default_accounts::<DefaultEnvironment>()
+}
+fn set_caller(id: AccountId) {
+    test::set_caller::<DefaultEnvironment>(id);
+}
+fn get_emitted_events() -> Vec<EmittedEvent> {
+    recorded_events().collect::<Vec<_>>()
+}
+
+#[ink::test]
+fn new_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let controller = AccountId::from([0x01; 32]);
+    let contract = FlashloanGatewayContract::new(controller);
+    assert_eq!(contract.controller(), Some(controller));
+    assert_eq!(
+        contract.flashloan_premium_of(accounts.alice),
+        contract.flashloan_premium_total()
+    );
+}
+
+#[ink::test]
+fn emitted_events_are_decoded_by_logics() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let contract = FlashloanGatewayContract::new(AccountId::from([0x01; 32]));
+
+    let asset = AccountId::from([0x02; 32]);
+    contract._emit_flashloan_event(accounts.alice, accounts.bob, asset, 1000, 9, 3);
+    contract._emit_new_flashloan_premium_total_event(9, 5);
+    contract._emit_new_flashloan_premium_to_protocol_event(3000, 0);
+    contract._emit_new_borrower_premium_event(accounts.alice, Some(0));
+    contract._emit_new_whitelist_only_event(true);
+    contract._emit_new_whitelisted_receiver_event(accounts.alice, true);
+
+    let decoded: Vec<_> = get_emitted_events()
+        .into_iter()
+        .map(|event| {
+            let topics: Vec<Hash> = event
+                .topics
+                .iter()
+                .map(|topic| Hash::decode(&mut &topic[..]).unwrap())
+                .collect();
+            decode_event(&event.data, &topics).unwrap()
+        })
+        .collect();
+    assert_eq!(
+        decoded,
+        vec![
+            ProtocolEvent::FlashloanGateway(FlashloanGatewayEvent::FlashLoan(events::FlashLoan {
+                target: accounts.alice,
+                initiator: accounts.bob,
+                asset,
+                amount: 1000,
+                premium: 9,
+                premium_to_protocol: 3,
+            })),
+            ProtocolEvent::FlashloanGateway(FlashloanGatewayEvent::NewFlashloanPremiumTotal(
+                events::NewFlashloanPremiumTotal { old: 9, new: 5 }
+            )),
+            ProtocolEvent::FlashloanGateway(FlashloanGatewayEvent::NewFlashloanPremiumToProtocol(
+                events::NewFlashloanPremiumToProtocol { old: 3000, new: 0 }
+            )),
+            ProtocolEvent::FlashloanGateway(FlashloanGatewayEvent::NewBorrowerPremium(
+                events::NewBorrowerPremium {
+                    borrower: accounts.alice,
+                    premium: Some(0),
+                }
+            )),
+            ProtocolEvent::FlashloanGateway(FlashloanGatewayEvent::NewWhitelistOnly(
+                events::NewWhitelistOnly { enabled: true }
+            )),
+            ProtocolEvent::FlashloanGateway(FlashloanGatewayEvent::NewWhitelistedReceiver(
+                events::NewWhitelistedReceiver {
+                    receiver: accounts.alice,
+                    whitelisted: true,
+                }
+            )),
+        ]
+    );
+}
@@ -8,20 +8,38 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of Lens Contract
 ///
 /// This is a contract to make it easier to get protocol status and data for the frontend
 #[openbrush::contract]
 pub mod contract {
+    use core::ops::{
+        Div,
+        Mul,
+    };
     use ink::prelude::vec::Vec;
-    use logics::traits::{
-        controller::ControllerRef,
-        pool::PoolRef,
-        price_oracle::PriceOracleRef,
-        types::WrappedU256,
+    use logics::{
+        impls::{
+            controller::calculate_available_borrow_in_base_currency,
+            fixed_point::{
+                exp_scale,
+                mul_div_down,
+                to_balance,
+                Exp,
+            },
+            interest_rate_model::utilization_rate,
+            pool::utils::calculate_apy,
+            price_oracle::PRICE_PRECISION,
+        },
+        traits::{
+            controller::{
+                AccountData,
+                ControllerRef,
+            },
+            pool::PoolRef,
+            price_oracle::PriceOracleRef,
+            types::WrappedU256,
+        },
     };
     use openbrush::{
         contracts::traits::psp22::{
@@ -33,6 +51,7 @@ pub mod contract {
             String,
         },
     };
+    use primitive_types::U256;
     use scale::{
         Decode,
         Encode,
@@ -58,6 +77,7 @@ pub mod contract {
         collateral_factor_mantissa: WrappedU256,
         reserve_factor_mantissa: WrappedU256,
         borrow_cap: Option<u128>,
+        max_utilization_mantissa: WrappedU256,
         mint_guardian_paused: bool,
         borrow_guardian_paused: bool,
     }
@@ -82,12 +102,92 @@ pub mod contract {
         underlying_price: u128,
     }
 
+    /// Account's position in a Pool
+    #[derive(Decode, Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AccountPoolSnapshot {
+        pool: AccountId,
+        underlying: Option<AccountId>,
+        supplied: Balance,
+        borrowed: Balance,
+        collateral_enabled: bool,
+        /// annual percentage yield for suppliers (Decimals: 18)
+        supply_apy: WrappedU256,
+        /// annual percentage yield for borrowers (Decimals: 18)
+        borrow_apy: WrappedU256,
+        underlying_price: u128,
+        supplied_in_base_currency: WrappedU256,
+        borrowed_in_base_currency: WrappedU256,
+        /// amount of underlying the account can additionally borrow from the pool
+        borrowable: Balance,
+    }
+
+    /// Account's portfolio over all Pools
+    #[derive(Decode, Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AccountSnapshot {
+        pools: Vec<AccountPoolSnapshot>,
+        /// None if the Controller fails to calculate (e.g. oracle is not set)
+        account_data: Option<AccountData>,
+        available_borrow_in_base_currency: WrappedU256,
+    }
+
+    /// Liquidation opportunity of an underwater account
+    #[derive(Decode, Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct LiquidatableAccount {
+        account: AccountId,
+        health_factor: WrappedU256,
+        shortfall: WrappedU256,
+        /// pool of the largest debt in base currency
+        pool_borrowed: AccountId,
+        /// pool of the largest collateral in base currency
+        pool_collateral: AccountId,
+        /// maximum repay amount allowed by the close factor, capped by the collateral to be seized
+        max_repay_amount: Balance,
+        /// pool tokens of the collateral seized by repaying `max_repay_amount`
+        seize_tokens: Balance,
+    }
+
+    /// Result of scanning a page of accounts
+    #[derive(Decode, Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct LiquidatableAccounts {
+        accounts: Vec<LiquidatableAccount>,
+        /// offset to scan the next page, None if all accounts have been scanned
+        next_offset: Option<u32>,
+    }
+
+    /// Aggregates of a Pool in base currency
+    #[derive(Decode, Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct MarketSummary {
+        pool: AccountId,
+        underlying: Option<AccountId>,
+        underlying_price: u128,
+        total_supplied_in_base_currency: WrappedU256,
+        total_borrowed_in_base_currency: WrappedU256,
+        total_reserves_in_base_currency: WrappedU256,
+        available_liquidity_in_base_currency: WrappedU256,
+        /// (Decimals: 18)
+        utilization_rate: WrappedU256,
+        /// annual percentage yield for suppliers (Decimals: 18)
+        supply_apy: WrappedU256,
+        /// annual percentage yield for borrowers (Decimals: 18)
+        borrow_apy: WrappedU256,
+        /// share of the supply in this market over the supply in all markets with collateral factor (Decimals: 18)
+        collateral_share: WrappedU256,
+    }
+
+    /// Aggregates of all Pools in base currency
     #[derive(Decode, Encode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub struct AccountLimits {
-        pools: Vec<AccountId>,
-        liquidity: Balance,
-        shortfall: Balance,
+    pub struct ProtocolSummary {
+        total_supplied_in_base_currency: WrappedU256,
+        total_borrowed_in_base_currency: WrappedU256,
+        total_reserves_in_base_currency: WrappedU256,
+        available_liquidity_in_base_currency: WrappedU256,
+        markets: Vec<MarketSummary>,
     }
 
     /// Protocol's Configuration
@@ -187,6 +287,34 @@ pub mod contract {
                 .collect()
         }
 
+        /// Get positions of a specified user in all pools and the aggregated account data
+        #[ink(message)]
+        pub fn account_snapshot(
+            &self,
+            controller: AccountId,
+            account: AccountId,
+        ) -> AccountSnapshot {
+            self._account_snapshot(controller, account)
+        }
+
+        /// Get underwater accounts in `accounts[offset..offset + limit]` and how to liquidate them
+        #[ink(message)]
+        pub fn scan_liquidatable(
+            &self,
+            controller: AccountId,
+            accounts: Vec<AccountId>,
+            offset: u32,
+            limit: u32,
+        ) -> LiquidatableAccounts {
+            self._scan_liquidatable(controller, accounts, offset, limit)
+        }
+
+        /// Get aggregates of all pools and analytics of each market
+        #[ink(message)]
+        pub fn protocol_summary(&self, controller: AccountId) -> ProtocolSummary {
+            self._protocol_summary(controller)
+        }
+
         /// Get protocol's configuration
         #[ink(message)]
         pub fn configuration(&self, controller: AccountId) -> Configuration {
@@ -224,6 +352,7 @@ pub mod contract {
                 is_listed,
                 collateral_factor_mantissa,
                 borrow_cap,
+                max_utilization_mantissa,
                 mint_guardian_paused,
                 borrow_guardian_paused,
             ) = if let Some(_controller) = controller {
@@ -232,11 +361,19 @@ pub mod contract {
                     ControllerRef::collateral_factor_mantissa(&_controller, pool)
                         .unwrap_or_default(),
                     ControllerRef::borrow_cap(&_controller, pool),
+                    ControllerRef::max_utilization_mantissa(&_controller, pool).unwrap_or_default(),
                     ControllerRef::mint_guardian_paused(&_controller, pool).unwrap_or_default(),
                     ControllerRef::borrow_guardian_paused(&_controller, pool).unwrap_or_default(),
                 )
             } else {
-                (false, Default::default(), Some(0), true, true)
+                (
+                    false,
+                    Default::default(),
+                    Some(0),
+                    Default::default(),
+                    true,
+                    true,
+                )
             };
 
             PoolMetadata {
@@ -256,6 +393,7 @@ pub mod contract {
                 collateral_factor_mantissa,
                 reserve_factor_mantissa: PoolRef::reserve_factor_mantissa(&pool),
                 borrow_cap,
+                max_utilization_mantissa,
                 mint_guardian_paused,
                 borrow_guardian_paused,
             }
@@ -308,6 +446,308 @@ pub mod contract {
             }
         }
 
+        fn _account_snapshot(&self, controller: AccountId, account: AccountId) -> AccountSnapshot {
+            let account_data =
+                ControllerRef::calculate_user_account_data(&controller, account, None).ok();
+            let available_borrow_in_base_currency = if let Some(data) = &account_data {
+                calculate_available_borrow_in_base_currency(
+                    data.total_collateral_in_base_currency,
+                    data.total_debt_in_base_currency,
+                    data.avg_ltv,
+                )
+            } else {
+                U256::from(0)
+            };
+
+            let oracle = ControllerRef::oracle(&controller);
+            let pools = self
+                ._pools(controller)
+                .iter()
+                .map(|pool| {
+                    self._account_pool_snapshot(
+                        controller,
+                        *pool,
+                        oracle,
+                        account,
+                        available_borrow_in_base_currency,
+                    )
+                })
+                .collect();
+
+            AccountSnapshot {
+                pools,
+                account_data,
+                available_borrow_in_base_currency: WrappedU256::from(
+                    available_borrow_in_base_currency,
+                ),
+            }
+        }
+
+        fn _account_pool_snapshot(
+            &self,
+            controller: AccountId,
+            pool: AccountId,
+            oracle: Option<AccountId>,
+            account: AccountId,
+            available_borrow_in_base_currency: U256,
+        ) -> AccountPoolSnapshot {
+            let underlying = PoolRef::underlying(&pool);
+            let underlying_price = self._price(oracle, underlying);
+            let supplied = PoolRef::balance_of_underlying(&pool, account);
+            let borrowed = PoolRef::borrow_balance_current(&pool, account).unwrap_or_default();
+
+            let borrowable = borrowable_amount(
+                available_borrow_in_base_currency,
+                underlying_price,
+                PoolRef::get_cash_prior(&pool),
+                PoolRef::total_borrows(&pool),
+                PoolRef::total_reserves(&pool),
+                ControllerRef::borrow_cap(&controller, pool).unwrap_or_default(),
+                ControllerRef::max_utilization_mantissa(&controller, pool)
+                    .map(U256::from)
+                    .unwrap_or_default(),
+            );
+
+            AccountPoolSnapshot {
+                pool,
+                underlying,
+                supplied,
+                borrowed,
+                collateral_enabled: PoolRef::using_reserve_as_collateral(&pool, account),
+                supply_apy: WrappedU256::from(
+                    calculate_apy(PoolRef::supply_rate_per_msec(&pool).into()).unwrap_or_default(),
+                ),
+                borrow_apy: WrappedU256::from(
+                    calculate_apy(PoolRef::borrow_rate_per_msec(&pool).into()).unwrap_or_default(),
+                ),
+                underlying_price,
+                supplied_in_base_currency: WrappedU256::from(to_base_currency(
+                    supplied,
+                    underlying_price,
+                )),
+                borrowed_in_base_currency: WrappedU256::from(to_base_currency(
+                    borrowed,
+                    underlying_price,
+                )),
+                borrowable,
+            }
+        }
+
+        fn _scan_liquidatable(
+            &self,
+            controller: AccountId,
+            accounts: Vec<AccountId>,
+            offset: u32,
+            limit: u32,
+        ) -> LiquidatableAccounts {
+            let start = (offset as usize).min(accounts.len());
+            let end = start.saturating_add(limit as usize).min(accounts.len());
+            let next_offset = if end < accounts.len() {
+                Some(end as u32)
+            } else {
+                None
+            };
+            if start == end {
+                return LiquidatableAccounts {
+                    accounts: Vec::new(),
+                    next_offset,
+                }
+            }
+
+            let oracle = ControllerRef::oracle(&controller);
+            // (pool, price, whether the collateral factor is non-zero)
+            let markets: Vec<(AccountId, u128, bool)> = self
+                ._pools(controller)
+                .iter()
+                .map(|pool| {
+                    let is_collateral = matches!(
+                        ControllerRef::collateral_factor_mantissa(&controller, *pool),
+                        Some(factor) if !U256::from(factor).is_zero()
+                    );
+                    (
+                        *pool,
+                        self._price(oracle, PoolRef::underlying(pool)),
+                        is_collateral,
+                    )
+                })
+                .collect();
+            let close_factor = Exp::from(ControllerRef::close_factor_mantissa(&controller));
+
+            LiquidatableAccounts {
+                accounts: accounts[start..end]
+                    .iter()
+                    .filter_map(|account| {
+                        self._liquidatable_account(controller, &markets, &close_factor, *account)
+                    })
+                    .collect(),
+                next_offset,
+            }
+        }
+
+        fn _liquidatable_account(
+            &self,
+            controller: AccountId,
+            markets: &[(AccountId, u128, bool)],
+            close_factor: &Exp,
+            account: AccountId,
+        ) -> Option<LiquidatableAccount> {
+            let (_, shortfall) = ControllerRef::get_account_liquidity(&controller, account).ok()?;
+            if shortfall.is_zero() {
+                return None
+            }
+            let account_data =
+                ControllerRef::calculate_user_account_data(&controller, account, None).ok()?;
+
+            // (pool, balance, value in base currency) of the largest debt and collateral
+            let mut borrowed: Option<(AccountId, Balance, U256)> = None;
+            let mut collateral: Option<(AccountId, Balance, U256)> = None;
+            for (pool, price, is_collateral) in markets {
+                // a failing pool must not hide the positions in the other ones
+                let Ok((balance, borrow_balance, _)) = PoolRef::get_account_snapshot(pool, account)
+                else {
+                    continue
+                };
+                let borrow_value = to_base_currency(borrow_balance, *price);
+                if borrow_balance != 0 && borrowed.map_or(true, |(_, _, v)| borrow_value > v) {
+                    borrowed = Some((*pool, borrow_balance, borrow_value));
+                }
+                // only collateral enabled by the account in a market with a collateral factor can be seized
+                if !*is_collateral || !PoolRef::using_reserve_as_collateral(pool, account) {
+                    continue
+                }
+                let collateral_value = to_base_currency(balance, *price);
+                if balance != 0 && collateral.map_or(true, |(_, _, v)| collateral_value > v) {
+                    collateral = Some((*pool, balance, collateral_value));
+                }
+            }
+            let (pool_borrowed, borrow_balance, _) = borrowed?;
+            let (pool_collateral, collateral_balance, _) = collateral?;
+
+            let exchange_rate = PoolRef::exchange_rate_stored(&pool_collateral);
+            let seize_tokens_of = |repay_amount: Balance| {
+                ControllerRef::liquidate_calculate_seize_tokens(
+                    &controller,
+                    pool_borrowed,
+                    pool_collateral,
+                    exchange_rate,
+                    repay_amount,
+                    None,
+                    None,
+                )
+                .ok()
+            };
+
+            let mut max_repay_amount =
+                to_balance(close_factor.mul_int_down(U256::from(borrow_balance)).ok()?).ok()?;
+            let mut seize_tokens = seize_tokens_of(max_repay_amount)?;
+            if seize_tokens > collateral_balance {
+                // seize_tokens is proportional to the repay amount
+                max_repay_amount = to_balance(
+                    mul_div_down(
+                        U256::from(max_repay_amount),
+                        U256::from(collateral_balance),
+                        U256::from(seize_tokens),
+                    )
+                    .ok()?,
+                )
+                .ok()?;
+                seize_tokens = seize_tokens_of(max_repay_amount)?;
+            }
+
+            Some(LiquidatableAccount {
+                account,
+                health_factor: WrappedU256::from(account_data.health_factor),
+                shortfall: WrappedU256::from(shortfall),
+                pool_borrowed,
+                pool_collateral,
+                max_repay_amount,
+                seize_tokens,
+            })
+        }
+
+        fn _protocol_summary(&self, controller: AccountId) -> ProtocolSummary {
+            let oracle = ControllerRef::oracle(&controller);
+            let mut markets: Vec<MarketSummary> = Vec::new();
+            let mut collaterals: Vec<U256> = Vec::new();
+            let mut total_supplied = U256::from(0);
+            let mut total_borrowed = U256::from(0);
+            let mut total_reserves = U256::from(0);
+            let mut available_liquidity = U256::from(0);
+            let mut total_collateral = U256::from(0);
+
+            for pool in self._pools(controller) {
+                let underlying = PoolRef::underlying(&pool);
+                let underlying_price = self._price(oracle, underlying);
+                let cash = PoolRef::get_cash_prior(&pool);
+                let borrows = PoolRef::total_borrows(&pool);
+                let reserves = PoolRef::total_reserves(&pool);
+
+                let supplied = to_base_currency(
+                    cash.saturating_add(borrows).saturating_sub(reserves),
+                    underlying_price,
+                );
+                let borrowed = to_base_currency(borrows, underlying_price);
+                let reserves_value = to_base_currency(reserves, underlying_price);
+                let liquidity = to_base_currency(cash, underlying_price);
+                let collateral = match ControllerRef::collateral_factor_mantissa(&controller, pool)
+                {
+                    Some(factor) if !U256::from(factor).is_zero() => supplied,
+                    _ => U256::from(0),
+                };
+
+                total_supplied = total_supplied.saturating_add(supplied);
+                total_borrowed = total_borrowed.saturating_add(borrowed);
+                total_reserves = total_reserves.saturating_add(reserves_value);
+                available_liquidity = available_liquidity.saturating_add(liquidity);
+                total_collateral = total_collateral.saturating_add(collateral);
+                collaterals.push(collateral);
+
+                markets.push(MarketSummary {
+                    pool,
+                    underlying,
+                    underlying_price,
+                    total_supplied_in_base_currency: WrappedU256::from(supplied),
+                    total_borrowed_in_base_currency: WrappedU256::from(borrowed),
+                    total_reserves_in_base_currency: WrappedU256::from(reserves_value),
+                    available_liquidity_in_base_currency: WrappedU256::from(liquidity),
+                    utilization_rate: WrappedU256::from(utilization_rate(cash, borrows, reserves)),
+                    supply_apy: WrappedU256::from(
+                        calculate_apy(PoolRef::supply_rate_per_msec(&pool).into())
+                            .unwrap_or_default(),
+                    ),
+                    borrow_apy: WrappedU256::from(
+                        calculate_apy(PoolRef::borrow_rate_per_msec(&pool).into())
+                            .unwrap_or_default(),
+                    ),
+                    collateral_share: WrappedU256::from(U256::from(0)),
+                });
+            }
+
+            if !total_collateral.is_zero() {
+                for (market, collateral) in markets.iter_mut().zip(collaterals) {
+                    market.collateral_share =
+                        WrappedU256::from(collateral.mul(exp_scale()).div(total_collateral));
+                }
+            }
+
+            ProtocolSummary {
+                total_supplied_in_base_currency: WrappedU256::from(total_supplied),
+                total_borrowed_in_base_currency: WrappedU256::from(total_borrowed),
+                total_reserves_in_base_currency: WrappedU256::from(total_reserves),
+                available_liquidity_in_base_currency: WrappedU256::from(available_liquidity),
+                markets,
+            }
+        }
+
+        fn _price(&self, oracle: Option<AccountId>, underlying: Option<AccountId>) -> u128 {
+            match (oracle, underlying) {
+                (Some(_oracle), Some(_underlying)) => {
+                    PriceOracleRef::get_price(&_oracle, _underlying).unwrap_or(0)
+                }
+                _ => 0,
+            }
+        }
+
         fn _underlying_balance(&self, pool: &AccountId, account: AccountId) -> Balance {
             let underlying = PoolRef::underlying(pool);
             if let Some(_underlying) = underlying {
@@ -316,4 +756,47 @@ pub mod contract {
             0
         }
     }
+
+    fn to_base_currency(amount: Balance, price: u128) -> U256 {
+        U256::from(price)
+            .mul(U256::from(amount))
+            .div(U256::from(PRICE_PRECISION))
+    }
+
+    /// Amount of underlying the account can borrow from a pool
+    ///
+    /// Bounded by the account's borrowing power, the pool's cash and the headrooms
+    /// left by the borrow cap and the maximum utilization (0 means no limit for both)
+    pub fn borrowable_amount(
+        available_borrow_in_base_currency: U256,
+        price: u128,
+        cash: Balance,
+        total_borrows: Balance,
+        total_reserves: Balance,
+        borrow_cap: Balance,
+        max_utilization: U256,
+    ) -> Balance {
+        if price == 0 {
+            return 0
+        }
+        let mut borrowable = available_borrow_in_base_currency
+            .mul(U256::from(PRICE_PRECISION))
+            .div(U256::from(price))
+            .min(U256::from(cash));
+        if borrow_cap != 0 {
+            borrowable = borrowable.min(U256::from(borrow_cap.saturating_sub(total_borrows)));
+        }
+        if !max_utilization.is_zero() {
+            // borrowing moves cash to borrows, so cash + borrows - reserves stays the same
+            let total = cash
+                .saturating_add(total_borrows)
+                .saturating_sub(total_reserves);
+            let max_borrows = max_utilization.mul(U256::from(total)).div(exp_scale());
+            borrowable = borrowable.min(max_borrows.saturating_sub(U256::from(total_borrows)));
+        }
+        borrowable.as_u128()
+    }
 }
+
+#[cfg(test)]
+mod tests;
@@ -6,7 +6,14 @@ use ink::env::{
     },
     DefaultEnvironment,
 };
+use logics::impls::{
+    fixed_point::exp_scale,
+    interest_rate_model::utilization_rate,
+    price_oracle::PRICE_PRECISION,
+};
 use openbrush::traits::AccountId;
+use primitive_types::U256;
+use scale::Encode;
 
 fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
     test::default_accounts::<DefaultEnvironment>()
@@ -22,3 +29,130 @@ fn new_works() {
 
     let _contract = LensContract::new();
 }
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn account_snapshot_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let contract = LensContract::new();
+    contract.account_snapshot(accounts.charlie, accounts.bob);
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn scan_liquidatable_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let contract = LensContract::new();
+    contract.scan_liquidatable(
+        accounts.charlie,
+        vec![accounts.bob, accounts.django, accounts.eve],
+        1,
+        1,
+    );
+}
+
+#[ink::test]
+fn scan_liquidatable_returns_empty_page_without_calls() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let contract = LensContract::new();
+    let scanned = vec![accounts.bob, accounts.django];
+    let empty_page = |next_offset: Option<u32>| (Vec::<()>::new(), next_offset).encode();
+
+    assert_eq!(
+        contract
+            .scan_liquidatable(accounts.charlie, scanned.clone(), 0, 0)
+            .encode(),
+        empty_page(Some(0))
+    );
+    assert_eq!(
+        contract
+            .scan_liquidatable(accounts.charlie, scanned.clone(), 2, 10)
+            .encode(),
+        empty_page(None)
+    );
+    assert_eq!(
+        contract
+            .scan_liquidatable(accounts.charlie, scanned, u32::MAX, u32::MAX)
+            .encode(),
+        empty_page(None)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn protocol_summary_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let contract = LensContract::new();
+    contract.protocol_summary(accounts.charlie);
+}
+
+#[test]
+fn borrowable_amount_is_limited_by_borrowing_power_and_cash() {
+    // 100 in base currency at a price of 2
+    let power = U256::from(100);
+    let price = 2 * PRICE_PRECISION;
+
+    assert_eq!(
+        borrowable_amount(power, price, 1_000, 0, 0, 0, U256::zero()),
+        50
+    );
+    assert_eq!(
+        borrowable_amount(power, price, 30, 0, 0, 0, U256::zero()),
+        30
+    );
+    assert_eq!(borrowable_amount(power, 0, 1_000, 0, 0, 0, U256::zero()), 0);
+}
+
+#[test]
+fn borrowable_amount_is_limited_by_borrow_cap() {
+    let power = U256::from(1_000);
+
+    assert_eq!(
+        borrowable_amount(power, PRICE_PRECISION, 1_000, 100, 0, 150, U256::zero()),
+        50
+    );
+    // no headroom once the cap is reached
+    assert_eq!(
+        borrowable_amount(power, PRICE_PRECISION, 1_000, 200, 0, 150, U256::zero()),
+        0
+    );
+}
+
+#[test]
+fn borrowable_amount_is_limited_by_max_utilization() {
+    let power = U256::from(1_000);
+    // 80%
+    let max_utilization = exp_scale() * 8 / 10;
+
+    // cash 700 + borrows 200 - reserves 100 = 800, so borrows can reach 640
+    let borrowable = borrowable_amount(power, PRICE_PRECISION, 700, 200, 100, 0, max_utilization);
+    assert_eq!(borrowable, 440);
+    assert_eq!(
+        utilization_rate(700 - borrowable, 200 + borrowable, 100),
+        max_utilization
+    );
+    // no headroom once the pool is above the maximum utilization
+    assert_eq!(
+        borrowable_amount(power, PRICE_PRECISION, 100, 900, 0, 0, max_utilization),
+        0
+    );
+    // the smaller headroom wins
+    assert_eq!(
+        borrowable_amount(power, PRICE_PRECISION, 700, 200, 100, 300, max_utilization),
+        100
+    );
+}
@@ -8,13 +8,14 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of Leverager Contract
 #[openbrush::contract]
 pub mod contract {
-    use logics::impls::leverager::*;
+    use ink::prelude::vec::Vec;
+    use logics::{
+        impls::leverager::*,
+        traits::flashloan_receiver,
+    };
     use openbrush::traits::Storage;
 
     /// Contract's Storage
@@ -28,6 +29,21 @@ pub mod contract {
     impl Leverager for LeveragerContract {}
     impl Internal for LeveragerContract {}
 
+    impl flashloan_receiver::FlashloanReceiver for LeveragerContract {
+        #[ink(message)]
+        fn execute_operation(
+            &self,
+            assets: Vec<AccountId>,
+            amounts: Vec<Balance>,
+            premiums: Vec<Balance>,
+            initiator: AccountId,
+            params: Vec<u8>,
+        ) -> bool {
+            self._execute_operation(assets, amounts, premiums, initiator, params)
+                .is_ok()
+        }
+    }
+
     impl LeveragerContract {
         /// Generate this contract
         #[ink(constructor)]
@@ -43,3 +59,6 @@ pub mod contract {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
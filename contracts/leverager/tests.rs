@@ -6,9 +6,13 @@ use ink::env::{
     },
     DefaultEnvironment,
 };
-use logics::impls::leverager::*;
+use logics::{
+    impls::leverager::*,
+    traits::flashloan_receiver::FlashloanReceiver,
+};
 use openbrush::traits::AccountId;
 use primitive_types::U256;
+use scale::Encode;
 
 fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
     test::default_accounts::<DefaultEnvironment>()
@@ -90,3 +94,126 @@ fn all_methods_not_working_without_initialize() {
     let liquidation_threshold = contract.liquidation_threshold(asset);
     assert_eq!(liquidation_threshold, 0);
 }
+
+#[ink::test]
+fn leverage_with_flashloan_fails_by_inappropriate_leverage() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = LeveragerContract::new(accounts.bob);
+    let asset = AccountId::from([0x01; 32]);
+
+    assert_eq!(
+        contract
+            .leverage_with_flashloan(asset, 1000, 10000)
+            .unwrap_err(),
+        Error::InappropriateLeverage
+    );
+    // loan to value is 0 without Controller
+    assert_eq!(
+        contract
+            .leverage_with_flashloan(asset, 1000, 20000)
+            .unwrap_err(),
+        Error::InappropriateLeverage
+    );
+}
+
+#[ink::test]
+fn deleverage_with_flashloan_fails_without_controller() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = LeveragerContract::new(accounts.bob);
+    let asset = AccountId::from([0x01; 32]);
+
+    assert_eq!(
+        contract.deleverage_with_flashloan(asset, 1000).unwrap_err(),
+        Error::ControllerIsNotSet
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_without_flashloan_gateway() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let contract = LeveragerContract::new(accounts.bob);
+    let asset = AccountId::from([0x01; 32]);
+
+    assert!(!contract.execute_operation(
+        vec![asset],
+        vec![1000],
+        vec![0],
+        accounts.bob,
+        FlashloanOperation::Deleverage { user: accounts.bob }.encode(),
+    ));
+}
+
+#[ink::test]
+fn deleverage_to_fails_by_inappropriate_health_factor() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = LeveragerContract::new(accounts.bob);
+    let asset = AccountId::from([0x01; 32]);
+
+    assert_eq!(
+        contract
+            .deleverage_to(asset, WrappedU256::from(U256::from(10_u128.pow(18))))
+            .unwrap_err(),
+        Error::InappropriateHealthFactor
+    );
+}
+
+#[ink::test]
+fn deleverage_to_eth_fails_without_weth() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = LeveragerContract::new(accounts.bob);
+
+    assert_eq!(
+        contract
+            .deleverage_to_eth(WrappedU256::from(U256::from(2 * 10_u128.pow(18))))
+            .unwrap_err(),
+        Error::WETHIsNotSet
+    );
+}
+
+#[ink::test]
+fn set_health_factor_limit_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = LeveragerContract::new(accounts.bob);
+    assert_eq!(
+        U256::from(contract.health_factor_limit()),
+        U256::from(DEFAULT_HEALTH_FACTOR_LIMIT)
+    );
+
+    let limit = WrappedU256::from(U256::from(105 * 10_u128.pow(16)));
+    assert!(contract.set_health_factor_limit(limit).is_ok());
+    assert_eq!(contract.health_factor_limit(), limit);
+}
+
+#[ink::test]
+fn set_health_factor_limit_fails() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let mut contract = LeveragerContract::new(accounts.bob);
+    assert_eq!(
+        contract
+            .set_health_factor_limit(WrappedU256::from(U256::from(10_u128.pow(18) - 1)))
+            .unwrap_err(),
+        Error::InappropriateHealthFactor
+    );
+
+    set_caller(accounts.alice);
+    assert_eq!(
+        contract
+            .set_health_factor_limit(WrappedU256::from(U256::from(105 * 10_u128.pow(16))))
+            .unwrap_err(),
+        Error::CallerIsNotManager
+    );
+}
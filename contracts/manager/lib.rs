@@ -8,22 +8,30 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of Manager Contract
 #[openbrush::contract]
 pub mod contract {
-    use ink::codegen::{
-        EmitEvent,
-        Env,
+    use ink::{
+        codegen::{
+            EmitEvent,
+            Env,
+        },
+        prelude::{
+            vec,
+            vec::Vec,
+        },
+    };
+    use logics::impls::{
+        manager,
+        upgradeable,
     };
-    use logics::impls::manager;
     use openbrush::{
         contracts::access_control::{
             self,
+            AccessControl,
             Internal as AccessControlInternal,
             RoleType,
+            DEFAULT_ADMIN_ROLE,
         },
         traits::Storage,
     };
@@ -36,6 +44,8 @@ pub mod contract {
         manager: manager::Data,
         #[storage_field]
         access: access_control::Data,
+        #[storage_field]
+        upgradeable: upgradeable::Data,
     }
 
     /// Event: The admin role holder has changed
@@ -71,6 +81,21 @@ pub mod contract {
         pub admin: AccountId,
     }
 
+    /// Event: The code of this contract has been replaced
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        pub code_hash: Hash,
+    }
+
+    /// Event: The storage struct under storage_key has been migrated
+    #[ink(event)]
+    pub struct Migrated {
+        pub storage_key: u32,
+        pub from: u32,
+        pub to: u32,
+    }
+
     impl manager::Manager for ManagerContract {}
 
     impl access_control::AccessControl for ManagerContract {}
@@ -111,16 +136,53 @@ pub mod contract {
         }
     }
 
+    impl upgradeable::Upgradeable for ManagerContract {}
+    impl upgradeable::Internal for ManagerContract {
+        fn _code_storage_versions(&self) -> Vec<(u32, u32)> {
+            vec![(manager::STORAGE_KEY, manager::STORAGE_VERSION)]
+        }
+
+        fn _assert_upgrader(&self) -> upgradeable::Result<()> {
+            // Manager is upgraded by the default admin instead of itself
+            if !self.has_role(DEFAULT_ADMIN_ROLE, self.env().caller()) {
+                return Err(upgradeable::Error::CallerIsNotManager)
+            }
+            Ok(())
+        }
+
+        fn _emit_upgraded_event(&self, code_hash: Hash) {
+            self.env().emit_event(Upgraded { code_hash })
+        }
+
+        fn _emit_migrated_event(&self, storage_key: u32, from: u32, to: u32) {
+            self.env().emit_event(Migrated {
+                storage_key,
+                from,
+                to,
+            })
+        }
+    }
+
     impl ManagerContract {
         /// Generate this contract
         #[ink(constructor)]
         pub fn new(controller: AccountId) -> Self {
             let mut instance = Self {
-                manager: manager::Data { controller },
+                manager: manager::Data {
+                    controller,
+                    risk_steward_config: Default::default(),
+                    last_risk_parameter_update: Default::default(),
+                    steward_interest_rate_models: Default::default(),
+                },
                 access: access_control::Data::default(),
+                upgradeable: upgradeable::Data::default(),
             };
             instance._init_with_caller();
+            upgradeable::Internal::_init_storage_versions(&mut instance);
             instance
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -7,14 +7,27 @@ use ink::env::{
     DefaultEnvironment,
 };
 use logics::{
-    impls::manager::{
-        Manager,
-        BORROW_CAP_GUARDIAN,
-        CONTROLLER_ADMIN,
-        PAUSE_GUARDIAN,
-        TOKEN_ADMIN,
+    impls::{
+        manager::{
+            ConfigAction,
+            Manager,
+            RiskParameter,
+            RiskStewardConfig,
+            BORROW_CAP_GUARDIAN,
+            CONTROLLER_ADMIN,
+            PAUSE_GUARDIAN,
+            RISK_STEWARD,
+            STORAGE_KEY,
+            STORAGE_VERSION,
+            TOKEN_ADMIN,
+        },
+        upgradeable::{
+            Error as UpgradeableError,
+            Upgradeable,
+        },
     },
     traits::{
+        controller::OutflowLimit,
         manager::Error,
         types::WrappedU256,
     },
@@ -25,7 +38,10 @@ use openbrush::{
         AccessControlError,
         DEFAULT_ADMIN_ROLE,
     },
-    traits::AccountId,
+    traits::{
+        AccountId,
+        Hash,
+    },
 };
 use scale::Decode;
 
@@ -308,6 +324,266 @@ fn set_borrow_guardian_paused_fails_by_no_authority() {
     );
 }
 
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_max_utilization_mantissa_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let pool = AccountId::from([0x01; 32]);
+    contract
+        .set_max_utilization_mantissa(pool, WrappedU256::from(0))
+        .unwrap();
+}
+#[ink::test]
+fn set_max_utilization_mantissa_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x01; 32]);
+    assert_eq!(
+        contract
+            .set_max_utilization_mantissa(pool, WrappedU256::from(0))
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_flashloan_premium_total_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    contract.set_flashloan_premium_total(100).unwrap();
+}
+#[ink::test]
+fn set_flashloan_premium_total_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert_eq!(
+        contract.set_flashloan_premium_total(100).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_flashloan_premium_to_protocol_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    contract.set_flashloan_premium_to_protocol(3000).unwrap();
+}
+#[ink::test]
+fn set_flashloan_premium_to_protocol_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert_eq!(
+        contract
+            .set_flashloan_premium_to_protocol(3000)
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_flashloan_borrower_premium_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    let borrower = AccountId::from([0x02; 32]);
+    contract
+        .set_flashloan_borrower_premium(borrower, Some(0))
+        .unwrap();
+}
+#[ink::test]
+fn set_flashloan_borrower_premium_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let borrower = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract
+            .set_flashloan_borrower_premium(borrower, Some(0))
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_flashloan_whitelisted_receiver_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    let receiver = AccountId::from([0x02; 32]);
+    contract
+        .set_flashloan_whitelisted_receiver(receiver, true)
+        .unwrap();
+}
+#[ink::test]
+fn set_flashloan_whitelisted_receiver_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let receiver = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract
+            .set_flashloan_whitelisted_receiver(receiver, true)
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_flashloan_whitelist_only_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    contract.set_flashloan_whitelist_only(true).unwrap();
+}
+#[ink::test]
+fn set_flashloan_whitelist_only_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert_eq!(
+        contract.set_flashloan_whitelist_only(true).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn raise_outflow_limit_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x01; 32]);
+    contract.raise_outflow_limit(pool, 100, 1_000).unwrap();
+}
+#[ink::test]
+fn raise_outflow_limit_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let pool = AccountId::from([0x01; 32]);
+    assert_eq!(
+        contract.raise_outflow_limit(pool, 100, 1_000).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn set_outflow_limit_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x01; 32]);
+    contract
+        .set_outflow_limit(pool, OutflowLimit::default())
+        .unwrap();
+}
+#[ink::test]
+fn set_outflow_limit_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let pool = AccountId::from([0x01; 32]);
+    assert_eq!(
+        contract
+            .set_outflow_limit(pool, OutflowLimit::default())
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
 #[ink::test]
 #[should_panic(
     expected = "not implemented: off-chain environment does not support contract invocation"
@@ -503,3 +779,408 @@ fn sweep_token_fails_by_no_authority() {
         Error::AccessControl(AccessControlError::MissingRole)
     );
 }
+
+#[ink::test]
+fn set_risk_steward_config_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert_eq!(contract.risk_steward_config(), RiskStewardConfig::default());
+
+    let config = RiskStewardConfig {
+        max_change_mantissa: WrappedU256::from(10_u64.pow(17)),
+        min_delay: 24 * 60 * 60 * 1000,
+    };
+    assert!(contract.set_risk_steward_config(config).is_ok());
+    assert_eq!(contract.risk_steward_config(), config);
+    let pool = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract.last_risk_parameter_update(pool, RiskParameter::BorrowCap),
+        None
+    );
+}
+#[ink::test]
+fn set_risk_steward_config_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.alice).is_ok());
+
+    set_caller(accounts.alice);
+    assert_eq!(
+        contract
+            .set_risk_steward_config(RiskStewardConfig::default())
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn steward_set_borrow_cap_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    contract.steward_set_borrow_cap(pool, 100).unwrap();
+}
+#[ink::test]
+fn steward_set_borrow_cap_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract.steward_set_borrow_cap(pool, 100).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn steward_set_collateral_factor_mantissa_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    contract
+        .steward_set_collateral_factor_mantissa(pool, WrappedU256::from(0))
+        .unwrap();
+}
+#[ink::test]
+fn steward_set_collateral_factor_mantissa_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract
+            .steward_set_collateral_factor_mantissa(pool, WrappedU256::from(0))
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn steward_set_liquidation_threshold_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    contract
+        .steward_set_liquidation_threshold(pool, 8000)
+        .unwrap();
+}
+#[ink::test]
+fn steward_set_liquidation_threshold_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract
+            .steward_set_liquidation_threshold(pool, 8000)
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn steward_set_interest_rate_model_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    let rate_model = AccountId::from([0x03; 32]);
+    assert!(contract
+        .set_steward_interest_rate_model_allowed(rate_model, true)
+        .is_ok());
+    contract
+        .steward_set_interest_rate_model(pool, rate_model)
+        .unwrap();
+}
+#[ink::test]
+fn steward_set_interest_rate_model_fails_with_model_not_allowed() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    let rate_model = AccountId::from([0x03; 32]);
+    assert_eq!(
+        contract
+            .steward_set_interest_rate_model(pool, rate_model)
+            .unwrap_err(),
+        Error::StewardInterestRateModelNotAllowed
+    );
+
+    assert!(contract
+        .set_steward_interest_rate_model_allowed(rate_model, true)
+        .is_ok());
+    assert!(contract
+        .set_steward_interest_rate_model_allowed(rate_model, false)
+        .is_ok());
+    assert_eq!(
+        contract
+            .steward_set_interest_rate_model(pool, rate_model)
+            .unwrap_err(),
+        Error::StewardInterestRateModelNotAllowed
+    );
+}
+#[ink::test]
+fn steward_set_interest_rate_model_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(CONTROLLER_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    let rate_model = AccountId::from([0x03; 32]);
+    assert_eq!(
+        contract
+            .steward_set_interest_rate_model(pool, rate_model)
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+fn set_steward_interest_rate_model_allowed_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    let rate_model = AccountId::from([0x03; 32]);
+    assert!(!contract.steward_interest_rate_model_allowed(rate_model));
+
+    assert!(contract
+        .set_steward_interest_rate_model_allowed(rate_model, true)
+        .is_ok());
+    assert!(contract.steward_interest_rate_model_allowed(rate_model));
+
+    assert!(contract
+        .set_steward_interest_rate_model_allowed(rate_model, false)
+        .is_ok());
+    assert!(!contract.steward_interest_rate_model_allowed(rate_model));
+}
+#[ink::test]
+fn set_steward_interest_rate_model_allowed_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(RISK_STEWARD, accounts.alice).is_ok());
+
+    set_caller(accounts.alice);
+    let rate_model = AccountId::from([0x03; 32]);
+    assert_eq!(
+        contract
+            .set_steward_interest_rate_model_allowed(rate_model, true)
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+fn batch_configure_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+
+    assert!(contract.batch_configure(Vec::new()).is_ok());
+
+    let config = RiskStewardConfig {
+        max_change_mantissa: WrappedU256::from(10_u64.pow(17)),
+        min_delay: 1000,
+    };
+    assert!(contract
+        .batch_configure(vec![ConfigAction::SetRiskStewardConfig(config)])
+        .is_ok());
+    assert_eq!(contract.risk_steward_config(), config);
+}
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn batch_configure_calls_controller() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let pool = AccountId::from([0x02; 32]);
+    contract
+        .batch_configure(vec![
+            ConfigAction::SetBorrowCap { pool, new_cap: 100 },
+            ConfigAction::SetMintGuardianPaused { pool, paused: true },
+        ])
+        .unwrap();
+}
+#[ink::test]
+fn batch_configure_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.bob).is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.bob).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.bob)
+        .is_ok());
+    let oracle = AccountId::from([0x02; 32]);
+    assert_eq!(
+        contract
+            .batch_configure(vec![
+                ConfigAction::SetRiskStewardConfig(RiskStewardConfig::default()),
+                ConfigAction::SetPriceOracle(oracle),
+            ])
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+
+    set_caller(accounts.alice);
+    assert_eq!(
+        contract
+            .batch_configure(vec![ConfigAction::SetRiskStewardConfig(
+                RiskStewardConfig::default()
+            )])
+            .unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
+
+#[ink::test]
+fn storage_versions_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert_eq!(
+        contract.storage_versions(),
+        vec![(STORAGE_KEY, STORAGE_VERSION)]
+    );
+
+    // nothing to migrate
+    let emitted = get_emitted_events().len();
+    assert!(contract.migrate().is_ok());
+    assert_eq!(get_emitted_events().len(), emitted);
+}
+
+#[ink::test]
+fn upgrade_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract
+        .grant_role(CONTROLLER_ADMIN, accounts.alice)
+        .is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.alice).is_ok());
+
+    set_caller(accounts.alice);
+    assert_eq!(
+        contract.upgrade(Hash::from([0x02; 32])).unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+
+    set_caller(accounts.charlie);
+    assert_eq!(
+        contract.migrate().unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn upgrade_controller_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    contract.upgrade_controller(Hash::from([0x02; 32])).unwrap();
+}
+#[ink::test]
+fn upgrade_contracts_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let controller = AccountId::from([0x01; 32]);
+    let mut contract = ManagerContract::new(controller);
+    assert!(contract
+        .grant_role(CONTROLLER_ADMIN, accounts.alice)
+        .is_ok());
+    assert!(contract.grant_role(TOKEN_ADMIN, accounts.alice).is_ok());
+    assert!(contract
+        .grant_role(BORROW_CAP_GUARDIAN, accounts.alice)
+        .is_ok());
+    assert!(contract.grant_role(PAUSE_GUARDIAN, accounts.alice).is_ok());
+    assert!(contract.grant_role(RISK_STEWARD, accounts.alice).is_ok());
+
+    set_caller(accounts.alice);
+    let pool = AccountId::from([0x02; 32]);
+    let code_hash = Hash::from([0x03; 32]);
+    assert_eq!(
+        contract.upgrade_controller(code_hash).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+    assert_eq!(
+        contract.upgrade_pool(pool, code_hash).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+    assert_eq!(
+        contract.upgrade_flashloan_gateway(code_hash).unwrap_err(),
+        Error::AccessControl(AccessControlError::MissingRole)
+    );
+}
@@ -8,9 +8,6 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of Pool Contract
 #[openbrush::contract]
 pub mod contract {
@@ -19,12 +16,18 @@ pub mod contract {
             EmitEvent,
             Env,
         },
-        prelude::vec::Vec,
+        prelude::{
+            vec,
+            vec::Vec,
+        },
     };
     use logics::{
-        impls::pool::{
-            Internal,
-            *,
+        impls::{
+            pool::{
+                Internal,
+                *,
+            },
+            upgradeable,
         },
         traits::types::WrappedU256,
     };
@@ -54,6 +57,8 @@ pub mod contract {
         psp22: psp22::Data,
         #[storage_field]
         metadata: metadata::Data,
+        #[storage_field]
+        upgradeable: upgradeable::Data,
     }
 
     /// Event: Execute `Mint`
@@ -196,6 +201,21 @@ pub mod contract {
         pub new: WrappedU256,
     }
 
+    /// Event: The code of this contract has been replaced
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        pub code_hash: Hash,
+    }
+
+    /// Event: The storage struct under storage_key has been migrated
+    #[ink(event)]
+    pub struct Migrated {
+        pub storage_key: u32,
+        pub from: u32,
+        pub to: u32,
+    }
+
     impl Pool for PoolContract {}
     impl Internal for PoolContract {
         fn _emit_mint_event(&self, minter: AccountId, mint_amount: Balance, mint_tokens: Balance) {
@@ -382,6 +402,29 @@ pub mod contract {
 
     impl metadata::PSP22Metadata for PoolContract {}
 
+    impl upgradeable::Upgradeable for PoolContract {}
+    impl upgradeable::Internal for PoolContract {
+        fn _code_storage_versions(&self) -> Vec<(u32, u32)> {
+            vec![(STORAGE_KEY, STORAGE_VERSION)]
+        }
+
+        fn _upgrader(&self) -> Option<AccountId> {
+            self.pool.manager
+        }
+
+        fn _emit_upgraded_event(&self, code_hash: Hash) {
+            self.env().emit_event(Upgraded { code_hash })
+        }
+
+        fn _emit_migrated_event(&self, storage_key: u32, from: u32, to: u32) {
+            self.env().emit_event(Migrated {
+                storage_key,
+                from,
+                to,
+            })
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     impl PoolContract {
         /// Generate this contract
@@ -503,6 +546,10 @@ pub mod contract {
             self.metadata.name = Some(name);
             self.metadata.symbol = Some(symbol);
             self.metadata.decimals = decimals;
+            upgradeable::Internal::_init_storage_versions(self);
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -3,24 +3,45 @@ use ink::{
     env::{
         test::{
             self,
+            recorded_events,
             DefaultAccounts,
+            EmittedEvent,
         },
         DefaultEnvironment,
     },
     prelude::vec::Vec,
 };
 use logics::{
+    events::{
+        decode_event,
+        pool as events,
+        PoolEvent,
+        ProtocolEvent,
+    },
     impls::{
-        exp_no_err::exp_scale,
+        fixed_point::exp_scale,
         pool::*,
+        upgradeable::{
+            Error as UpgradeableError,
+            Upgradeable,
+        },
     },
     traits::types::WrappedU256,
 };
 use openbrush::{
-    contracts::psp22::PSP22,
-    traits::AccountId,
+    contracts::psp22::{
+        self,
+        PSP22Error,
+        PSP22,
+    },
+    traits::{
+        AccountId,
+        Balance,
+        Hash,
+    },
 };
 use primitive_types::U256;
+use scale::Decode;
 use std::ops::{
     Add,
     Div,
@@ -32,6 +53,9 @@ fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
 fn set_caller(id: AccountId) {
     test::set_caller::<DefaultEnvironment>(id);
 }
+fn get_emitted_events() -> Vec<EmittedEvent> {
+    recorded_events().collect::<Vec<_>>()
+}
 
 #[ink::test]
 fn new_works() {
@@ -129,6 +153,103 @@ fn transfer_from_works_overridden() {
         .unwrap();
 }
 
+fn redeem_on_behalf_contract(accounts: &DefaultAccounts<DefaultEnvironment>) -> PoolContract {
+    let dummy_id = AccountId::from([0x01; 32]);
+    let liquidation_threshold = 10000;
+    PoolContract::new(
+        None,
+        dummy_id,
+        dummy_id,
+        dummy_id,
+        accounts.bob,
+        WrappedU256::from(exp_scale()),
+        liquidation_threshold,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    )
+}
+
+/// Owner holding pool tokens used as collateral, as after `mint`
+fn fund_owner(contract: &mut PoolContract, owner: AccountId, amount: Balance) {
+    psp22::Internal::_mint_to(contract, owner, amount).unwrap();
+    Internal::_set_use_reserve_as_collateral(contract, owner, true);
+}
+
+#[ink::test]
+fn redeem_underlying_on_behalf_fails_when_nothing_is_redeemed() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = redeem_on_behalf_contract(&accounts);
+
+    set_caller(accounts.charlie);
+    contract.approve(accounts.bob, 100).unwrap();
+
+    // the owner has no pool tokens used as collateral
+    set_caller(accounts.bob);
+    assert_eq!(
+        contract
+            .redeem_underlying_on_behalf(accounts.charlie, 100, accounts.bob)
+            .unwrap_err(),
+        Error::ReserveIsNotEnabledAsCollateral
+    );
+    assert_eq!(contract.allowance(accounts.charlie, accounts.bob), 100);
+
+    fund_owner(&mut contract, accounts.charlie, 1_000);
+    assert_eq!(
+        contract
+            .redeem_underlying_on_behalf(accounts.charlie, 0, accounts.bob)
+            .unwrap_err(),
+        Error::InvalidParameter
+    );
+    assert_eq!(contract.allowance(accounts.charlie, accounts.bob), 100);
+    assert_eq!(contract.balance_of(accounts.charlie), 1_000);
+}
+
+#[ink::test]
+fn redeem_underlying_on_behalf_fails_by_insufficient_allowance() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = redeem_on_behalf_contract(&accounts);
+    fund_owner(&mut contract, accounts.charlie, 1_000);
+
+    assert_eq!(
+        contract
+            .redeem_underlying_on_behalf(accounts.charlie, 1, accounts.bob)
+            .unwrap_err(),
+        Error::PSP22(PSP22Error::InsufficientAllowance)
+    );
+
+    set_caller(accounts.charlie);
+    contract.approve(accounts.bob, 100).unwrap();
+
+    set_caller(accounts.bob);
+    assert_eq!(
+        contract
+            .redeem_underlying_on_behalf(accounts.charlie, 101, accounts.bob)
+            .unwrap_err(),
+        Error::PSP22(PSP22Error::InsufficientAllowance)
+    );
+    assert_eq!(contract.allowance(accounts.charlie, accounts.bob), 100);
+    assert_eq!(contract.balance_of(accounts.charlie), 1_000);
+}
+
+#[ink::test]
+#[should_panic(
+    expected = "not implemented: off-chain environment does not support contract invocation"
+)]
+fn redeem_underlying_on_behalf_of_self_does_not_require_allowance() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+    let mut contract = redeem_on_behalf_contract(&accounts);
+    fund_owner(&mut contract, accounts.bob, 1_000);
+
+    // passes the allowance check and reaches the interest rate model
+    contract
+        .redeem_underlying_on_behalf(accounts.bob, 100, accounts.charlie)
+        .unwrap();
+}
+
 #[ink::test]
 fn set_controller_works() {
     let accounts = default_accounts();
@@ -154,6 +275,101 @@ fn set_controller_works() {
     assert_eq!(contract.controller(), Some(dummy_id1));
 }
 
+#[ink::test]
+fn emitted_events_are_decoded_by_logics() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let dummy_id = AccountId::from([0x01; 32]);
+    let controller = AccountId::from([0x02; 32]);
+    let mut contract = PoolContract::new(
+        Some(dummy_id),
+        dummy_id,
+        dummy_id,
+        dummy_id,
+        accounts.bob,
+        WrappedU256::from(U256::from(0)),
+        10000,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    );
+    let emitted_by_constructor = get_emitted_events().len();
+
+    let reserve_factor = WrappedU256::from(exp_scale().div(2));
+    contract.set_controller(controller).unwrap();
+    contract
+        .set_reserve_factor_mantissa(reserve_factor)
+        .unwrap();
+    contract.approve(accounts.alice, 100).unwrap();
+    contract.approve_delegate(accounts.alice, 200).unwrap();
+    contract._emit_mint_event(accounts.bob, 1000, 990);
+    contract._emit_borrow_event(accounts.bob, 300, 300, 500);
+    contract._emit_repay_borrow_event(accounts.alice, accounts.bob, 100, 200, 400);
+    contract._emit_liquidate_borrow_event(accounts.alice, accounts.bob, 50, dummy_id, 60);
+
+    let decoded: Vec<_> = get_emitted_events()
+        .into_iter()
+        .skip(emitted_by_constructor)
+        .map(|event| {
+            let topics: Vec<Hash> = event
+                .topics
+                .iter()
+                .map(|topic| Hash::decode(&mut &topic[..]).unwrap())
+                .collect();
+            decode_event(&event.data, &topics).unwrap()
+        })
+        .collect();
+    assert_eq!(
+        decoded,
+        vec![
+            ProtocolEvent::Pool(PoolEvent::NewController(events::NewController {
+                old: Some(dummy_id),
+                new: Some(controller),
+            })),
+            ProtocolEvent::Pool(PoolEvent::NewReserveFactor(events::NewReserveFactor {
+                old: WrappedU256::from(U256::from(0)),
+                new: reserve_factor,
+            })),
+            ProtocolEvent::Pool(PoolEvent::Approval(events::Approval {
+                owner: accounts.bob,
+                spender: accounts.alice,
+                value: 100,
+            })),
+            ProtocolEvent::Pool(PoolEvent::DelegateApproval(events::DelegateApproval {
+                owner: accounts.bob,
+                delegatee: accounts.alice,
+                amount: 200,
+            })),
+            ProtocolEvent::Pool(PoolEvent::Mint(events::Mint {
+                minter: accounts.bob,
+                mint_amount: 1000,
+                mint_tokens: 990,
+            })),
+            ProtocolEvent::Pool(PoolEvent::Borrow(events::Borrow {
+                borrower: accounts.bob,
+                borrow_amount: 300,
+                account_borrows: 300,
+                total_borrows: 500,
+            })),
+            ProtocolEvent::Pool(PoolEvent::RepayBorrow(events::RepayBorrow {
+                payer: accounts.alice,
+                borrower: accounts.bob,
+                repay_amount: 100,
+                account_borrows: 200,
+                total_borrows: 400,
+            })),
+            ProtocolEvent::Pool(PoolEvent::LiquidateBorrow(events::LiquidateBorrow {
+                liquidator: accounts.alice,
+                borrower: accounts.bob,
+                repay_amount: 50,
+                token_collateral: dummy_id,
+                seize_tokens: 60,
+            })),
+        ]
+    );
+}
+
 #[ink::test]
 #[should_panic(
     expected = "not implemented: off-chain environment does not support contract invocation"
@@ -241,6 +457,7 @@ fn assert_manager_works() {
         contract.reduce_reserves(100),
         contract.sweep_token(dummy_id),
         contract.set_reserve_factor_mantissa(WrappedU256::from(0)),
+        contract.set_outflow_limit_enabled(true),
     ];
     for func in admin_funcs {
         assert_eq!(func.unwrap_err(), Error::CallerIsNotManager);
@@ -275,6 +492,32 @@ fn set_liquidation_threshold_works() {
     assert_eq!(contract.liquidation_threshold(), liquidation_threshold);
 }
 
+#[ink::test]
+fn set_outflow_limit_enabled_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let dummy_id = AccountId::from([0x01; 32]);
+    let mut contract = PoolContract::new(
+        Some(dummy_id),
+        dummy_id,
+        dummy_id,
+        dummy_id,
+        accounts.bob,
+        WrappedU256::from(U256::from(0)),
+        10000,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    );
+
+    assert!(!contract.outflow_limit_enabled());
+    assert!(contract.set_outflow_limit_enabled(true).is_ok());
+    assert!(contract.outflow_limit_enabled());
+    assert!(contract.set_outflow_limit_enabled(false).is_ok());
+    assert!(!contract.outflow_limit_enabled());
+}
+
 #[ink::test]
 fn set_manager_works() {
     let accounts = default_accounts();
@@ -349,3 +592,62 @@ fn accept_manager_works() {
     assert_eq!(contract.pending_manager(), None);
     assert_eq!(contract.manager().unwrap(), accounts.alice);
 }
+
+#[ink::test]
+fn storage_versions_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let dummy_id = AccountId::from([0x01; 32]);
+    let mut contract = PoolContract::new(
+        Some(dummy_id),
+        dummy_id,
+        dummy_id,
+        dummy_id,
+        accounts.bob,
+        WrappedU256::from(U256::from(0)),
+        10000,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    );
+    assert_eq!(
+        contract.storage_versions(),
+        vec![(STORAGE_KEY, STORAGE_VERSION)]
+    );
+
+    // nothing to migrate
+    let emitted = get_emitted_events().len();
+    assert!(contract.migrate().is_ok());
+    assert_eq!(get_emitted_events().len(), emitted);
+}
+
+#[ink::test]
+fn upgrade_fails_by_no_authority() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let dummy_id = AccountId::from([0x01; 32]);
+    let mut contract = PoolContract::new(
+        Some(dummy_id),
+        dummy_id,
+        dummy_id,
+        dummy_id,
+        accounts.bob,
+        WrappedU256::from(U256::from(0)),
+        10000,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    );
+
+    set_caller(accounts.charlie);
+    assert_eq!(
+        contract.upgrade(Hash::from([0x02; 32])).unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+    assert_eq!(
+        contract.migrate().unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+}
@@ -8,9 +8,6 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
-#[cfg(test)]
-mod tests;
-
 /// Definition of PriceOracle Contract
 #[openbrush::contract]
 pub mod contract {
@@ -49,3 +46,6 @@ pub mod contract {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
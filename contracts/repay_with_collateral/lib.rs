@@ -0,0 +1,99 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+/// Definition of Repay With Collateral Contract
+#[openbrush::contract]
+pub mod contract {
+    use ink::{
+        codegen::{
+            EmitEvent,
+            Env,
+        },
+        prelude::vec::Vec,
+    };
+    use logics::{
+        impls::{
+            flashloan_adapter::{
+                self,
+                Internal as FlashloanAdapterInternal,
+            },
+            repay_with_collateral::*,
+        },
+        traits::flashloan_receiver,
+    };
+    use openbrush::traits::Storage;
+
+    /// Contract's Storage
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct RepayWithCollateralContract {
+        #[storage_field]
+        flashloan_adapter: flashloan_adapter::Data,
+    }
+
+    /// Event: Borrow is repaid with collateral
+    #[ink(event)]
+    pub struct RepaidWithCollateral {
+        #[ink(topic)]
+        user: AccountId,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        collateral_amount: Balance,
+        repay_amount: Balance,
+    }
+
+    impl RepayWithCollateral for RepayWithCollateralContract {}
+    impl Internal for RepayWithCollateralContract {
+        fn _emit_repay_with_collateral_event(
+            &self,
+            user: AccountId,
+            collateral_asset: AccountId,
+            debt_asset: AccountId,
+            collateral_amount: Balance,
+            repay_amount: Balance,
+        ) {
+            self.env().emit_event(RepaidWithCollateral {
+                user,
+                collateral_asset,
+                debt_asset,
+                collateral_amount,
+                repay_amount,
+            })
+        }
+    }
+
+    impl flashloan_receiver::FlashloanReceiver for RepayWithCollateralContract {
+        #[ink(message)]
+        fn execute_operation(
+            &self,
+            assets: Vec<AccountId>,
+            amounts: Vec<Balance>,
+            premiums: Vec<Balance>,
+            initiator: AccountId,
+            params: Vec<u8>,
+        ) -> bool {
+            self._execute_operation(assets, amounts, premiums, initiator, params)
+                .is_ok()
+        }
+    }
+
+    impl RepayWithCollateralContract {
+        /// Generate this contract
+        #[ink(constructor)]
+        pub fn new(flashloan_gateway: AccountId, controller: AccountId, router: AccountId) -> Self {
+            let mut instance = Self::default();
+            instance._initialize(flashloan_gateway, controller, router);
+            instance
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
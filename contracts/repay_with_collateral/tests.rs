@@ -0,0 +1,501 @@
+use crate::contract::*;
+use core::cell::RefCell;
+use ink::{
+    env::{
+        test::{
+            self,
+            DefaultAccounts,
+        },
+        DefaultEnvironment,
+    },
+    prelude::collections::BTreeMap,
+};
+use logics::{
+    impls::{
+        flashloan_adapter::{
+            self,
+            Error as FlashloanAdapterError,
+            Internal as FlashloanAdapterInternal,
+        },
+        repay_with_collateral::*,
+    },
+    traits::{
+        dex_router::Error as DexRouterError,
+        flashloan_gateway::{
+            Error as FlashloanGatewayError,
+            FlashLoanType,
+        },
+        flashloan_receiver::FlashloanReceiver,
+    },
+};
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+use scale::Encode;
+
+fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+    test::default_accounts::<DefaultEnvironment>()
+}
+fn set_caller(id: AccountId) {
+    test::set_caller::<DefaultEnvironment>(id);
+}
+fn contract_id() -> AccountId {
+    ink::env::test::callee::<ink::env::DefaultEnvironment>()
+}
+
+fn new_contract() -> RepayWithCollateralContract {
+    let accounts = default_accounts();
+    RepayWithCollateralContract::new(accounts.bob, accounts.charlie, accounts.django)
+}
+
+#[ink::test]
+fn new_works() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    assert_eq!(contract.flashloan_gateway(), Some(accounts.bob));
+    assert_eq!(contract.controller(), Some(accounts.charlie));
+    assert_eq!(contract.router(), Some(accounts.django));
+}
+
+#[ink::test]
+fn repay_with_collateral_fails_with_same_asset() {
+    let accounts = default_accounts();
+    let mut contract = new_contract();
+    assert_eq!(
+        contract
+            .repay_with_collateral(accounts.eve, accounts.eve, 100, 100)
+            .unwrap_err(),
+        Error::SameAsset
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_when_caller_is_not_flashloan_gateway() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.alice);
+    let params = RepayWithCollateralParams {
+        user: accounts.alice,
+        collateral_asset: accounts.eve,
+        max_collateral_amount: 100,
+    };
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                contract_id(),
+                params.encode(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::CallerIsNotFlashloanGateway)
+    );
+    assert!(!contract.execute_operation(
+        vec![accounts.frank],
+        vec![100],
+        vec![0],
+        contract_id(),
+        params.encode(),
+    ));
+}
+
+#[ink::test]
+fn execute_operation_fails_when_initiator_is_not_this() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.bob);
+    let params = RepayWithCollateralParams {
+        user: accounts.alice,
+        collateral_asset: accounts.eve,
+        max_collateral_amount: 100,
+    };
+    // the off-chain environment runs the contract as alice
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                accounts.charlie,
+                params.encode(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InitiatorIsNotThis)
+    );
+}
+
+#[ink::test]
+fn execute_operation_fails_with_invalid_params() {
+    let accounts = default_accounts();
+    let contract = new_contract();
+    set_caller(accounts.bob);
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank, accounts.eve],
+                vec![100, 100],
+                vec![0, 0],
+                contract_id(),
+                Vec::new(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InvalidFlashloanParams)
+    );
+    assert_eq!(
+        contract
+            ._execute_operation(
+                vec![accounts.frank],
+                vec![100],
+                vec![0],
+                contract_id(),
+                Vec::new(),
+            )
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::InvalidFlashloanParams)
+    );
+}
+
+/// Units of the collateral asset the router takes for a unit of the debt asset
+const PRICE: Balance = 2;
+/// Flashloan premium in basis points
+const PREMIUM_TOTAL: u128 = 100;
+
+/// Repay With Collateral whose Pools, DEX Router and Flashloan Gateway are replaced by `LEDGER`,
+/// a pool being identified by its underlying asset
+#[ink::storage_item]
+#[derive(Default, Storage)]
+struct MockRepayWithCollateral {
+    #[storage_field]
+    flashloan_adapter: flashloan_adapter::Data,
+}
+
+thread_local! {
+    static LEDGER: RefCell<Ledger> = RefCell::new(Ledger::default());
+}
+
+fn with_ledger<R>(f: impl FnOnce(&mut Ledger) -> R) -> R {
+    LEDGER.with(|ledger| f(&mut ledger.borrow_mut()))
+}
+
+#[derive(Default)]
+struct Ledger {
+    /// Balances by (asset, account)
+    balances: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Supplied underlying by (asset, account)
+    supplies: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Borrow balances by (asset, account)
+    borrows: BTreeMap<(AccountId, AccountId), Balance>,
+    /// Part of the amount quoted by the router that the swap does not spend
+    swap_discount: Balance,
+    /// Error of `execute_operation` which made the flashloan fail
+    executor_error: Option<Error>,
+    events: Vec<(AccountId, AccountId, AccountId, Balance, Balance)>,
+}
+
+impl Ledger {
+    fn balance_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.balances.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn supply_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.supplies.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn borrow_of(&self, asset: AccountId, account: AccountId) -> Balance {
+        *self.borrows.get(&(asset, account)).unwrap_or(&0)
+    }
+
+    fn transfer(&mut self, asset: AccountId, from: AccountId, to: AccountId, amount: Balance) {
+        let balance = self.balance_of(asset, from);
+        assert!(balance >= amount, "insufficient balance");
+        self.balances.insert((asset, from), balance - amount);
+        let balance = self.balance_of(asset, to);
+        self.balances.insert((asset, to), balance + amount);
+    }
+}
+
+impl FlashloanAdapterInternal for MockRepayWithCollateral {
+    fn _pool_of(&self, asset: AccountId) -> flashloan_adapter::Result<AccountId> {
+        let accounts = default_accounts();
+        if asset != accounts.eve && asset != accounts.frank {
+            return Err(FlashloanAdapterError::MarketNotListed)
+        }
+        Ok(asset)
+    }
+
+    fn _flashloan(
+        &self,
+        asset: AccountId,
+        amount: Balance,
+        loan_type: FlashLoanType,
+        params: Vec<u8>,
+    ) -> flashloan_adapter::Result<()> {
+        assert_eq!(loan_type as u8, FlashLoanType::None as u8);
+        let on_behalf_of = ink::env::caller::<DefaultEnvironment>();
+        let premium = amount * PREMIUM_TOTAL / 10000;
+        // the whole flashloan is reverted if it is not repaid
+        let snapshot = with_ledger(|ledger| {
+            let snapshot = (
+                ledger.balances.clone(),
+                ledger.supplies.clone(),
+                ledger.borrows.clone(),
+            );
+            ledger.transfer(asset, asset, contract_id(), amount);
+            snapshot
+        });
+        set_caller(self._flashloan_gateway().unwrap());
+        let result = self._execute_operation(
+            vec![asset],
+            vec![amount],
+            vec![premium],
+            contract_id(),
+            params,
+        );
+        set_caller(on_behalf_of);
+        with_ledger(|ledger| {
+            match result {
+                Ok(()) => {
+                    ledger.transfer(asset, contract_id(), asset, amount + premium);
+                    Ok(())
+                }
+                Err(error) => {
+                    (ledger.balances, ledger.supplies, ledger.borrows) = snapshot;
+                    ledger.executor_error = Some(error);
+                    Err(FlashloanAdapterError::FlashloanGateway(
+                        FlashloanGatewayError::InvalidFlashloanExecutorReturn,
+                    ))
+                }
+            }
+        })
+    }
+
+    fn _approve_repayment(
+        &self,
+        _asset: AccountId,
+        _amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        Ok(())
+    }
+
+    fn _supply_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| {
+            ledger.transfer(asset, contract_id(), asset, amount);
+            let supply = ledger.supply_of(asset, user);
+            ledger.supplies.insert((asset, user), supply + amount);
+        });
+        Ok(())
+    }
+
+    fn _redeem_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| {
+            let supply = ledger.supply_of(asset, user);
+            assert!(supply >= amount, "redeem exceeds the supply");
+            ledger.supplies.insert((asset, user), supply - amount);
+            ledger.transfer(asset, asset, contract_id(), amount);
+        });
+        Ok(())
+    }
+
+    fn _repay_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> flashloan_adapter::Result<()> {
+        with_ledger(|ledger| {
+            let borrow = ledger.borrow_of(asset, user);
+            assert!(borrow >= amount, "repay exceeds the borrow");
+            ledger.borrows.insert((asset, user), borrow - amount);
+            ledger.transfer(asset, contract_id(), asset, amount);
+        });
+        Ok(())
+    }
+
+    fn _borrow_balance_of(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+    ) -> flashloan_adapter::Result<Balance> {
+        self._pool_of(asset)?;
+        Ok(with_ledger(|ledger| ledger.borrow_of(asset, user)))
+    }
+
+    fn _get_amount_in(
+        &self,
+        _asset_in: AccountId,
+        _asset_out: AccountId,
+        amount_out: Balance,
+    ) -> flashloan_adapter::Result<Balance> {
+        Ok(amount_out * PRICE)
+    }
+
+    fn _swap_exact_out(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_out: Balance,
+        max_amount_in: Balance,
+    ) -> flashloan_adapter::Result<Balance> {
+        let router = self._router().unwrap();
+        with_ledger(|ledger| {
+            let amount_in = amount_out * PRICE - ledger.swap_discount;
+            if amount_in > max_amount_in {
+                return Err(FlashloanAdapterError::DexRouter(
+                    DexRouterError::ExcessiveInputAmount,
+                ))
+            }
+            ledger.transfer(asset_in, contract_id(), router, amount_in);
+            ledger.transfer(asset_out, router, contract_id(), amount_out);
+            Ok(amount_in)
+        })
+    }
+}
+
+impl Internal for MockRepayWithCollateral {
+    fn _emit_repay_with_collateral_event(
+        &self,
+        user: AccountId,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        collateral_amount: Balance,
+        repay_amount: Balance,
+    ) {
+        with_ledger(|ledger| {
+            ledger.events.push((
+                user,
+                collateral_asset,
+                debt_asset,
+                collateral_amount,
+                repay_amount,
+            ))
+        })
+    }
+}
+
+/// Alice borrows 1_000 of eve against a supply of 5_000 of frank
+fn new_mock() -> MockRepayWithCollateral {
+    let accounts = default_accounts();
+    // the off-chain environment runs the contract as alice, who is the user here
+    test::set_callee::<DefaultEnvironment>(AccountId::from([0xff; 32]));
+    let mut mock = MockRepayWithCollateral::default();
+    mock._initialize(accounts.bob, accounts.charlie, accounts.django);
+    with_ledger(|ledger| {
+        *ledger = Ledger::default();
+        ledger.borrows.insert((accounts.eve, accounts.alice), 1_000);
+        ledger
+            .supplies
+            .insert((accounts.frank, accounts.alice), 5_000);
+        // cash of the pools and liquidity of the router
+        ledger
+            .balances
+            .insert((accounts.eve, accounts.eve), 100_000);
+        ledger
+            .balances
+            .insert((accounts.frank, accounts.frank), 100_000);
+        ledger
+            .balances
+            .insert((accounts.eve, accounts.django), 100_000);
+    });
+    mock
+}
+
+#[ink::test]
+fn repay_with_collateral_works() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    // 400 and a premium of 4 are bought back with 808 of the collateral
+    mock.repay_with_collateral(accounts.frank, accounts.eve, 400, 808)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 600);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 4_192);
+    assert_eq!(ledger.balance_of(accounts.eve, contract_id()), 0);
+    assert_eq!(ledger.balance_of(accounts.frank, contract_id()), 0);
+    assert_eq!(ledger.balance_of(accounts.frank, accounts.django), 808);
+    assert_eq!(ledger.balance_of(accounts.eve, accounts.eve), 100_404);
+    assert_eq!(
+        ledger.events,
+        vec![(accounts.alice, accounts.frank, accounts.eve, 808, 400)]
+    );
+}
+
+#[ink::test]
+fn repay_with_collateral_repays_at_most_the_borrow() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    mock.repay_with_collateral(accounts.frank, accounts.eve, Balance::MAX, 2_020)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 0);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 2_980);
+}
+
+#[ink::test]
+fn repay_with_collateral_supplies_back_collateral_not_spent_by_the_swap() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    with_ledger(|ledger| ledger.swap_discount = 8);
+    set_caller(accounts.alice);
+    mock.repay_with_collateral(accounts.frank, accounts.eve, 400, 808)
+        .unwrap();
+
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 600);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 4_200);
+    assert_eq!(ledger.balance_of(accounts.frank, contract_id()), 0);
+    assert_eq!(
+        ledger.events,
+        vec![(accounts.alice, accounts.frank, accounts.eve, 800, 400)]
+    );
+}
+
+#[ink::test]
+fn repay_with_collateral_fails_with_excessive_collateral_amount() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.alice);
+    assert_eq!(
+        mock.repay_with_collateral(accounts.frank, accounts.eve, 400, 807)
+            .unwrap_err(),
+        Error::FlashloanAdapter(FlashloanAdapterError::FlashloanGateway(
+            FlashloanGatewayError::InvalidFlashloanExecutorReturn
+        ))
+    );
+    let ledger = LEDGER.with(|ledger| ledger.take());
+    assert_eq!(
+        ledger.executor_error,
+        Some(Error::ExcessiveCollateralAmount)
+    );
+    assert_eq!(ledger.borrow_of(accounts.eve, accounts.alice), 1_000);
+    assert_eq!(ledger.supply_of(accounts.frank, accounts.alice), 5_000);
+    assert!(ledger.events.is_empty());
+}
+
+#[ink::test]
+fn repay_with_collateral_fails_without_borrow() {
+    let accounts = default_accounts();
+    let mut mock = new_mock();
+    set_caller(accounts.bob);
+    assert_eq!(
+        mock.repay_with_collateral(accounts.frank, accounts.eve, 400, 808)
+            .unwrap_err(),
+        Error::NoBorrowToRepay
+    );
+}
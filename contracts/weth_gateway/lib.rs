@@ -100,9 +100,9 @@ pub mod contract {
     impl WETHGatewayContract {
         /// Generate this contract
         #[ink(constructor)]
-        pub fn new(weth: AccountId, pool: AccountId) -> Self {
+        pub fn new(weth: AccountId, controller: AccountId) -> Self {
             let mut instance = Self {
-                gateway: Data { weth, pool },
+                gateway: Data { weth, controller },
                 ownable: Default::default(),
             };
             let caller = Self::env().caller();
@@ -0,0 +1,212 @@
+//! Port of `tests/Flashloan.spec.ts`
+
+use crate::protocol::*;
+use drink::AccountId32;
+use logics::traits::{
+    controller::Error as ControllerError,
+    flashloan_gateway::Error as FlashloanGatewayError,
+    pool::Error as PoolError,
+};
+use openbrush::traits::AccountId;
+
+const DEPOSITED: u128 = 5_000_000;
+const FLASHLOAN_AMOUNT: u128 = 200_000;
+
+/// Deposits the liquidity of every market from the deployer
+fn setup() -> Result<Protocol> {
+    let mut protocol = Protocol::new()?;
+    for market in [
+        protocol.dai.clone(),
+        protocol.usdc.clone(),
+        protocol.usdt.clone(),
+    ] {
+        protocol.deposit(&deployer(), &market, DEPOSITED)?;
+    }
+    Ok(protocol)
+}
+
+fn premium(protocol: &mut Protocol, amount: u128) -> Result<u128> {
+    let gateway = protocol.flashloan_gateway.clone();
+    let premium_total: u128 = protocol.chain.call(
+        &deployer(),
+        &gateway,
+        "FlashloanGateway::flashloan_premium_total",
+        &[],
+    )?;
+    Ok(amount * premium_total / 10000)
+}
+
+fn flashloan(
+    protocol: &mut Protocol,
+    initiator: &AccountId32,
+    amount: u128,
+    mode: u8,
+    on_behalf_of: &AccountId32,
+) -> Result<core::result::Result<(), FlashloanGatewayError>> {
+    let (gateway, receiver, dai) = (
+        protocol.flashloan_gateway.clone(),
+        protocol.flashloan_receiver.clone(),
+        protocol.dai.clone(),
+    );
+    protocol.chain.call(
+        initiator,
+        &gateway,
+        "FlashloanGateway::flashloan",
+        &[
+            account(&receiver),
+            accounts(&[&dai.token]),
+            format!("[{}]", amount),
+            format!("[{}]", mode),
+            account(on_behalf_of),
+            "[]".to_string(),
+        ],
+    )
+}
+
+#[test]
+fn instantiate() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let (controller, gateway) = (
+        protocol.controller.clone(),
+        protocol.flashloan_gateway.clone(),
+    );
+    let flashloan_gateway: Option<AccountId> = protocol.chain.call(
+        &deployer(),
+        &controller,
+        "Controller::flashloan_gateway",
+        &[],
+    )?;
+    assert_eq!(flashloan_gateway, Some(to_account_id(&gateway)));
+    let gateway_controller: Option<AccountId> =
+        protocol
+            .chain
+            .call(&deployer(), &gateway, "FlashloanGateway::controller", &[])?;
+    assert_eq!(gateway_controller, Some(to_account_id(&controller)));
+    Ok(())
+}
+
+#[test]
+fn flashloan_returning_funds() -> Result<()> {
+    let mut protocol = setup()?;
+    let [user, ..] = users();
+    let (dai, receiver) = (protocol.dai.clone(), protocol.flashloan_receiver.clone());
+    let initial_user_balance = 100_000;
+    protocol.mint_underlying(&dai, &user, initial_user_balance)?;
+    let premium = premium(&mut protocol, FLASHLOAN_AMOUNT)?;
+    protocol.approve(&user, &dai.token, &receiver, premium)?;
+
+    flashloan(&mut protocol, &user, FLASHLOAN_AMOUNT, 0, &user)?.map_err(|e| format!("{:?}", e))?;
+
+    assert_eq!(
+        protocol.balance_of(&dai.token, &user)?,
+        initial_user_balance - premium
+    );
+    assert_eq!(
+        protocol.balance_of(&dai.token, &dai.pool)?,
+        DEPOSITED + premium
+    );
+    Ok(())
+}
+
+#[test]
+fn flashloan_borrowing_fails_without_collateral() -> Result<()> {
+    let mut protocol = setup()?;
+    let [user, ..] = users();
+    let (dai, receiver) = (protocol.dai.clone(), protocol.flashloan_receiver.clone());
+    let premium = premium(&mut protocol, FLASHLOAN_AMOUNT)?;
+    protocol.mint_underlying(&dai, &user, premium)?;
+    protocol.approve(&user, &dai.token, &receiver, premium)?;
+
+    assert_eq!(
+        flashloan(&mut protocol, &user, FLASHLOAN_AMOUNT, 1, &user)?,
+        Err(FlashloanGatewayError::Pool(PoolError::Controller(
+            ControllerError::InsufficientLiquidity
+        )))
+    );
+    Ok(())
+}
+
+#[test]
+fn flashloan_fails_if_receiver_fails() -> Result<()> {
+    let mut protocol = setup()?;
+    let [user, ..] = users();
+    let (dai, receiver) = (protocol.dai.clone(), protocol.flashloan_receiver.clone());
+    protocol.chain.call::<()>(
+        &deployer(),
+        &receiver,
+        "set_fail_execution_transfer",
+        &[true.to_string()],
+    )?;
+    let premium = premium(&mut protocol, FLASHLOAN_AMOUNT)?;
+    protocol.mint_underlying(&dai, &user, premium)?;
+    protocol.approve(&user, &dai.token, &receiver, premium)?;
+
+    assert_eq!(
+        flashloan(&mut protocol, &user, FLASHLOAN_AMOUNT, 0, &user)?,
+        Err(FlashloanGatewayError::InvalidFlashloanExecutorReturn)
+    );
+    Ok(())
+}
+
+#[test]
+fn flashloan_borrowing_creates_a_loan() -> Result<()> {
+    let mut protocol = setup()?;
+    let [user, ..] = users();
+    let (dai, usdc) = (protocol.dai.clone(), protocol.usdc.clone());
+    let receiver = protocol.flashloan_receiver.clone();
+    protocol.deposit(&user, &usdc, 10_000_000)?;
+
+    let flashloan_amount = 100_000;
+    let premium = premium(&mut protocol, flashloan_amount)?;
+    protocol.mint_underlying(&dai, &user, premium)?;
+    protocol.approve(&user, &dai.token, &receiver, premium)?;
+    flashloan(&mut protocol, &user, flashloan_amount, 1, &user)?.map_err(|e| format!("{:?}", e))?;
+
+    assert_eq!(
+        protocol.borrow_balance_stored(&dai, &user)?,
+        flashloan_amount
+    );
+    Ok(())
+}
+
+#[test]
+fn flashloan_borrowing_on_behalf_requires_delegation() -> Result<()> {
+    let mut protocol = setup()?;
+    let [user0, user1, _] = users();
+    let (dai, usdc) = (protocol.dai.clone(), protocol.usdc.clone());
+    let receiver = protocol.flashloan_receiver.clone();
+    protocol.deposit(&user1, &usdc, 10_000_000)?;
+
+    let flashloan_amount = 100_000;
+    assert_eq!(
+        flashloan(&mut protocol, &user0, flashloan_amount, 1, &user1)?,
+        Err(FlashloanGatewayError::InsufficientDelegateAllowance)
+    );
+
+    let delegated = 150_000;
+    protocol.pool_execute(
+        &user1,
+        &dai,
+        "Pool::approve_delegate",
+        &[account(&user0), delegated.to_string()],
+    )?;
+    let premium = premium(&mut protocol, flashloan_amount)?;
+    protocol.mint_underlying(&dai, &user0, premium)?;
+    protocol.approve(&user0, &dai.token, &receiver, premium)?;
+    flashloan(&mut protocol, &user0, flashloan_amount, 1, &user1)?
+        .map_err(|e| format!("{:?}", e))?;
+
+    assert_eq!(
+        protocol.borrow_balance_stored(&dai, &user1)?,
+        flashloan_amount
+    );
+    let delegate_allowance: u128 = protocol.chain.call(
+        &user0,
+        &dai.pool,
+        "Pool::delegate_allowance",
+        &[account(&user1), account(&user0)],
+    )?;
+    assert_eq!(delegate_allowance, delegated - flashloan_amount);
+    assert_eq!(protocol.borrow_balance_stored(&dai, &user0)?, 0);
+    Ok(())
+}
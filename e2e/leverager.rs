@@ -0,0 +1,245 @@
+//! Port of `tests/Leverager.spec.ts`
+
+use crate::protocol::*;
+use drink::AccountId32;
+use logics::traits::{
+    flashloan_gateway::Error as FlashloanGatewayError,
+    leverager::Error as LeveragerError,
+};
+use primitive_types::U256;
+
+const DEPOSIT_AMOUNT: u128 = 100_000;
+const FLASHLOAN_AMOUNT: u128 = 200_000;
+// 3x of the deposit in percentage * 100
+const TARGET_LEVERAGE: u128 = 30000;
+
+/// Deploys and initializes Leverager, returning its address
+fn setup(protocol: &mut Protocol) -> Result<AccountId32> {
+    let leverager =
+        protocol
+            .chain
+            .deploy(BundleProvider::Leverager, "new", &[account(&deployer())])?;
+    let (controller, price_oracle, weth) = (
+        protocol.controller.clone(),
+        protocol.price_oracle.clone(),
+        protocol.weth.token.clone(),
+    );
+    protocol.chain.execute::<LeveragerError>(
+        &deployer(),
+        &leverager,
+        "Leverager::initialize",
+        &[
+            some_account(&controller),
+            some_account(&price_oracle),
+            some_account(&weth),
+        ],
+    )?;
+    Ok(leverager)
+}
+
+fn leverage_with_flashloan(
+    protocol: &mut Protocol,
+    leverager: &AccountId32,
+    user: &AccountId32,
+    target_leverage: u128,
+) -> Result<core::result::Result<(), LeveragerError>> {
+    let dai = protocol.dai.clone();
+    protocol.chain.call(
+        user,
+        leverager,
+        "Leverager::leverage_with_flashloan",
+        &[
+            account(&dai.token),
+            DEPOSIT_AMOUNT.to_string(),
+            target_leverage.to_string(),
+        ],
+    )
+}
+
+/// Deposits the liquidity of DAI and leverages the deposit of `user` with a flashloan
+fn setup_leveraged(
+    protocol: &mut Protocol,
+    leverager: &AccountId32,
+    user: &AccountId32,
+) -> Result<()> {
+    let dai = protocol.dai.clone();
+    protocol.deposit(&deployer(), &dai, 1_000_000)?;
+    protocol.mint_underlying(&dai, user, DEPOSIT_AMOUNT)?;
+    protocol.approve(user, &dai.token, leverager, DEPOSIT_AMOUNT)?;
+    protocol.pool_execute(
+        user,
+        &dai,
+        "Pool::approve_delegate",
+        &[account(leverager), FLASHLOAN_AMOUNT.to_string()],
+    )?;
+    leverage_with_flashloan(protocol, leverager, user, TARGET_LEVERAGE)?
+        .map_err(|e| format!("{:?}", e).into())
+}
+
+fn health_factor(
+    protocol: &mut Protocol,
+    leverager: &AccountId32,
+    user: &AccountId32,
+) -> Result<U256> {
+    let dai = protocol.dai.clone();
+    protocol.chain.call(
+        user,
+        leverager,
+        "Leverager::get_health_factor",
+        &[account(user), account(&dai.token), 0.to_string()],
+    )
+}
+
+#[test]
+fn loop_asset() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let leverager = setup(&mut protocol)?;
+    let (dai, deployer) = (protocol.dai.clone(), deployer());
+
+    let deposit_amount = 2_000;
+    protocol.mint_underlying(&dai, &deployer, deposit_amount)?;
+    protocol.approve(&deployer, &dai.token, &leverager, deposit_amount)?;
+    protocol.pool_execute(
+        &deployer,
+        &dai,
+        "Pool::approve_delegate",
+        &[account(&leverager), ONE_ETHER.to_string()],
+    )?;
+    protocol.chain.execute::<LeveragerError>(
+        &deployer,
+        &leverager,
+        "Leverager::loop_asset",
+        &[
+            account(&dai.token),
+            deposit_amount.to_string(),
+            5000.to_string(),
+            2.to_string(),
+        ],
+    )?;
+
+    let borrow_total = 1_500;
+    assert_eq!(
+        protocol.borrow_balance_stored(&dai, &deployer)?,
+        borrow_total
+    );
+    assert_eq!(
+        protocol.balance_of(&dai.pool, &deployer)?,
+        borrow_total + deposit_amount
+    );
+    Ok(())
+}
+
+#[test]
+fn leverage_with_flashloan_fails_without_delegation() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let leverager = setup(&mut protocol)?;
+    let dai = protocol.dai.clone();
+    let [user, ..] = users();
+    protocol.deposit(&deployer(), &dai, 1_000_000)?;
+    protocol.mint_underlying(&dai, &user, DEPOSIT_AMOUNT)?;
+    protocol.approve(&user, &dai.token, &leverager, DEPOSIT_AMOUNT)?;
+
+    assert_eq!(
+        leverage_with_flashloan(&mut protocol, &leverager, &user, TARGET_LEVERAGE)?,
+        Err(LeveragerError::FlashloanGateway(
+            FlashloanGatewayError::InsufficientDelegateAllowance
+        ))
+    );
+    // borrows 95% of the deposit, over the collateral factor of 90%
+    assert_eq!(
+        leverage_with_flashloan(&mut protocol, &leverager, &user, 200000)?,
+        Err(LeveragerError::InappropriateLeverage)
+    );
+    Ok(())
+}
+
+#[test]
+fn leverage_and_deleverage_with_flashloan() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let leverager = setup(&mut protocol)?;
+    let dai = protocol.dai.clone();
+    let [user, ..] = users();
+
+    // deposits and borrows in one transaction
+    setup_leveraged(&mut protocol, &leverager, &user)?;
+    assert_eq!(
+        protocol.balance_of(&dai.pool, &user)?,
+        DEPOSIT_AMOUNT + FLASHLOAN_AMOUNT
+    );
+    assert_eq!(
+        protocol.borrow_balance_stored(&dai, &user)?,
+        FLASHLOAN_AMOUNT
+    );
+    let delegate_allowance: u128 = protocol.chain.call(
+        &user,
+        &dai.pool,
+        "Pool::delegate_allowance",
+        &[account(&user), account(&leverager)],
+    )?;
+    assert_eq!(delegate_allowance, 0);
+
+    // repays and redeems in one transaction
+    let premium = FLASHLOAN_AMOUNT * 9 / 10000;
+    protocol.approve(&user, &dai.pool, &leverager, FLASHLOAN_AMOUNT + premium)?;
+    protocol.chain.execute::<LeveragerError>(
+        &user,
+        &leverager,
+        "Leverager::deleverage_with_flashloan",
+        &[account(&dai.token), FLASHLOAN_AMOUNT.to_string()],
+    )?;
+    assert_eq!(protocol.borrow_balance_stored(&dai, &user)?, 0);
+    assert_eq!(
+        protocol.balance_of(&dai.pool, &user)?,
+        DEPOSIT_AMOUNT - premium
+    );
+    Ok(())
+}
+
+#[test]
+fn deleverage_to() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let leverager = setup(&mut protocol)?;
+    let dai = protocol.dai.clone();
+    let [_, user, _] = users();
+    setup_leveraged(&mut protocol, &leverager, &user)?;
+
+    let deleverage = |protocol: &mut Protocol, target: u128| {
+        protocol
+            .chain
+            .call::<core::result::Result<(u128, u128), LeveragerError>>(
+                &user,
+                &leverager,
+                "Leverager::deleverage_to",
+                &[account(&dai.token), wrapped_u256(target)],
+            )
+    };
+    assert_eq!(
+        deleverage(&mut protocol, ONE_ETHER)?,
+        Err(LeveragerError::InappropriateHealthFactor)
+    );
+
+    let target_health_factor = ONE_ETHER * 2;
+    let health_factor_before = health_factor(&mut protocol, &leverager, &user)?;
+    assert!(health_factor_before < U256::from(target_health_factor));
+    protocol.approve(
+        &user,
+        &dai.pool,
+        &leverager,
+        DEPOSIT_AMOUNT + FLASHLOAN_AMOUNT,
+    )?;
+    let (withdrawn, repaid) =
+        deleverage(&mut protocol, target_health_factor)?.map_err(|e| format!("{:?}", e))?;
+    assert!(withdrawn > 0);
+    assert_eq!(repaid, withdrawn);
+    let health_factor_after = health_factor(&mut protocol, &leverager, &user)?;
+    assert!(health_factor_after >= U256::from(target_health_factor));
+    assert_eq!(
+        protocol.borrow_balance_stored(&dai, &user)?,
+        FLASHLOAN_AMOUNT - repaid
+    );
+    assert_eq!(
+        protocol.balance_of(&dai.pool, &user)?,
+        DEPOSIT_AMOUNT + FLASHLOAN_AMOUNT - withdrawn
+    );
+    Ok(())
+}
@@ -0,0 +1,12 @@
+//! End-to-end tests of the cross-contract flows.
+//!
+//! The contracts are deployed into the in-process runtime of
+//! [drink!](https://github.com/Cardinal-Cryptography/drink), so the scenarios run under
+//! `cargo test` without a local node. They port the core scenarios of `tests/Pool1.spec.ts`,
+//! `tests/Flashloan.spec.ts` and `tests/Leverager.spec.ts`.
+#![cfg(test)]
+
+mod flashloan;
+mod leverager;
+mod pool;
+mod protocol;
@@ -0,0 +1,354 @@
+//! Port of `tests/Pool1.spec.ts`
+
+use crate::protocol::*;
+use drink::AccountId32;
+use logics::traits::pool::Error as PoolError;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    String,
+};
+use primitive_types::U256;
+
+#[test]
+fn instantiate() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let (dai, deployer) = (protocol.dai.clone(), deployer());
+
+    let underlying: Option<AccountId> =
+        protocol
+            .chain
+            .call(&deployer, &dai.pool, "Pool::underlying", &[])?;
+    assert_eq!(underlying, Some(to_account_id(&dai.token)));
+    let controller: Option<AccountId> =
+        protocol
+            .chain
+            .call(&deployer, &dai.pool, "Pool::controller", &[])?;
+    assert_eq!(controller, Some(to_account_id(&protocol.controller)));
+    let name: Option<String> =
+        protocol
+            .chain
+            .call(&deployer, &dai.pool, "PSP22Metadata::token_name", &[])?;
+    assert_eq!(name, Some(String::from("Starlay Dai Stablecoin")));
+    let symbol: Option<String> =
+        protocol
+            .chain
+            .call(&deployer, &dai.pool, "PSP22Metadata::token_symbol", &[])?;
+    assert_eq!(symbol, Some(String::from("sDAI")));
+    let decimals: u8 =
+        protocol
+            .chain
+            .call(&deployer, &dai.pool, "PSP22Metadata::token_decimals", &[])?;
+    assert_eq!(decimals, 18);
+    let liquidation_threshold: u128 =
+        protocol
+            .chain
+            .call(&deployer, &dai.pool, "Pool::liquidation_threshold", &[])?;
+    assert_eq!(liquidation_threshold, 10000);
+    Ok(())
+}
+
+#[test]
+fn mint() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let deployer = deployer();
+    let sample = Metadata {
+        name: "Sample",
+        symbol: "SAMPLE",
+        decimals: 6,
+    };
+    // pool = underlying * 2
+    let market = protocol.deploy_market(&sample, ONE_ETHER / 2)?;
+    protocol.list_market(&market, ONE_ETHER)?;
+
+    let balance = 10_000;
+    let deposit_amount = 3_000;
+    protocol.mint_underlying(&market, &deployer, balance)?;
+    protocol.approve(&deployer, &market.token, &market.pool, deposit_amount)?;
+    protocol.pool_execute(
+        &deployer,
+        &market,
+        "Pool::mint",
+        &[deposit_amount.to_string()],
+    )?;
+
+    assert_eq!(
+        protocol.balance_of(&market.token, &deployer)?,
+        balance - deposit_amount
+    );
+    assert_eq!(
+        protocol.balance_of(&market.token, &market.pool)?,
+        deposit_amount
+    );
+    let balance_of_underlying: Balance = protocol.chain.call(
+        &deployer,
+        &market.pool,
+        "Pool::balance_of_underlying",
+        &[account(&deployer)],
+    )?;
+    assert_eq!(balance_of_underlying, deposit_amount);
+    assert_eq!(
+        protocol.balance_of(&market.pool, &deployer)?,
+        deposit_amount * 2
+    );
+    let using_as_collateral: bool = protocol.chain.call(
+        &deployer,
+        &market.pool,
+        "Pool::using_reserve_as_collateral",
+        &[account(&deployer)],
+    )?;
+    assert!(using_as_collateral);
+    Ok(())
+}
+
+#[test]
+fn redeem_underlying() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let (dai, deployer) = (protocol.dai.clone(), deployer());
+
+    let deposited = 10_000;
+    protocol.deposit(&deployer, &dai, deposited)?;
+    assert_eq!(protocol.balance_of(&dai.pool, &deployer)?, deposited);
+
+    let redeem_amount = 3_000;
+    protocol.pool_execute(
+        &deployer,
+        &dai,
+        "Pool::redeem_underlying",
+        &[redeem_amount.to_string()],
+    )?;
+    assert_eq!(protocol.balance_of(&dai.token, &deployer)?, redeem_amount);
+    assert_eq!(
+        protocol.balance_of(&dai.token, &dai.pool)?,
+        deposited - redeem_amount
+    );
+    assert_eq!(
+        protocol.balance_of(&dai.pool, &deployer)?,
+        deposited - redeem_amount
+    );
+    Ok(())
+}
+
+#[test]
+fn redeem_fails_when_no_cash_in_pool() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let (usdc, usdt, deployer) = (protocol.usdc.clone(), protocol.usdt.clone(), deployer());
+    protocol.deposit(&deployer, &usdc, to_dec6(10_000))?;
+    protocol.deposit(&deployer, &usdt, to_dec6(10_000))?;
+
+    let cash: Balance = protocol
+        .chain
+        .call(&deployer, &usdc.pool, "Pool::get_cash_prior", &[])?;
+    let result: core::result::Result<(), PoolError> = protocol.chain.call(
+        &deployer,
+        &usdc.pool,
+        "Pool::redeem_underlying",
+        &[(cash + 1).to_string()],
+    )?;
+    assert_eq!(result, Err(PoolError::RedeemTransferOutNotPossible));
+    Ok(())
+}
+
+#[test]
+fn borrow() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let dai = protocol.dai.clone();
+    let [user1, user2, _] = users();
+    protocol.deposit(&user1, &dai, 5_000)?;
+    protocol.deposit(&user2, &dai, 5_000)?;
+    let total_supply: Balance =
+        protocol
+            .chain
+            .call(&user1, &dai.pool, "PSP22::total_supply", &[])?;
+    assert_eq!(total_supply, 10_000);
+
+    protocol.pool_execute(&user1, &dai, "Pool::borrow", &[3_000.to_string()])?;
+    assert_eq!(protocol.balance_of(&dai.token, &user1)?, 3_000);
+    assert_eq!(protocol.balance_of(&dai.token, &user2)?, 0);
+    assert_eq!(protocol.balance_of(&dai.token, &dai.pool)?, 7_000);
+    assert_eq!(protocol.borrow_balance_stored(&dai, &user1)?, 3_000);
+
+    protocol.pool_execute(&user2, &dai, "Pool::borrow", &[2_500.to_string()])?;
+    assert_eq!(protocol.balance_of(&dai.token, &user1)?, 3_000);
+    assert_eq!(protocol.balance_of(&dai.token, &user2)?, 2_500);
+    assert_eq!(protocol.balance_of(&dai.token, &dai.pool)?, 4_500);
+    assert_eq!(protocol.borrow_balance_stored(&dai, &user2)?, 2_500);
+    let total_borrows: Balance =
+        protocol
+            .chain
+            .call(&user1, &dai.pool, "Pool::total_borrows", &[])?;
+    assert_eq!(total_borrows, 5_500);
+    Ok(())
+}
+
+#[test]
+fn repay_borrow() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let (dai, usdc, deployer) = (protocol.dai.clone(), protocol.usdc.clone(), deployer());
+    let [user1, ..] = users();
+    protocol.deposit(&deployer, &usdc, to_dec6(10_000))?;
+    protocol.deposit(&user1, &dai, to_dec18(20_000))?;
+    protocol.pool_execute(
+        &user1,
+        &usdc,
+        "Pool::borrow",
+        &[to_dec6(10_000).to_string()],
+    )?;
+    assert_eq!(protocol.balance_of(&usdc.token, &user1)?, to_dec6(10_000));
+
+    protocol.approve(&user1, &usdc.token, &usdc.pool, to_dec6(4_500))?;
+    protocol.pool_execute(
+        &user1,
+        &usdc,
+        "Pool::repay_borrow",
+        &[to_dec6(4_500).to_string()],
+    )?;
+    assert_eq!(protocol.balance_of(&usdc.token, &user1)?, to_dec6(5_500));
+    assert_eq!(
+        protocol.balance_of(&usdc.token, &usdc.pool)?,
+        to_dec6(4_500)
+    );
+    assert_eq!(
+        protocol.borrow_balance_stored(&usdc, &user1)?,
+        to_dec6(5_500)
+    );
+    Ok(())
+}
+
+/// Leaves the borrower of USDC with a shortfall by lowering the collateral factor of DAI
+fn setup_for_shortage(protocol: &mut Protocol, borrower: &AccountId32) -> Result<()> {
+    let (dai, usdc, deployer) = (protocol.dai.clone(), protocol.usdc.clone(), deployer());
+    protocol.deposit(&deployer, &usdc, to_dec6(10_000))?;
+    protocol.deposit(borrower, &dai, to_dec18(20_000))?;
+    protocol.pool_execute(
+        borrower,
+        &usdc,
+        "Pool::borrow",
+        &[to_dec6(10_000).to_string()],
+    )?;
+
+    let controller = protocol.controller.clone();
+    protocol
+        .chain
+        .execute::<logics::traits::controller::Error>(
+            &deployer,
+            &controller,
+            "Controller::set_collateral_factor_mantissa",
+            &[account(&dai.pool), wrapped_u256(1)],
+        )?;
+    let (collateral_value, shortfall_value): (U256, U256) = protocol
+        .chain
+        .call::<core::result::Result<(U256, U256), logics::traits::controller::Error>>(
+            &deployer,
+            &controller,
+            "Controller::get_hypothetical_account_liquidity",
+            &[
+                account(borrower),
+                account(&AccountId32::new([0; 32])),
+                0.to_string(),
+                0.to_string(),
+            ],
+        )?
+        .map_err(|e| format!("{:?}", e))?;
+    assert_eq!(collateral_value, U256::zero());
+    assert!(shortfall_value >= U256::from(to_dec18(9_999)));
+    Ok(())
+}
+
+#[test]
+fn liquidate_borrow() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let [borrower, liquidator, _] = users();
+    setup_for_shortage(&mut protocol, &borrower)?;
+    let (collateral, borrowing) = (protocol.dai.clone(), protocol.usdc.clone());
+
+    protocol.mint_underlying(&borrowing, &liquidator, to_dec6(5_000))?;
+    protocol.approve(
+        &liquidator,
+        &borrowing.token,
+        &borrowing.pool,
+        to_dec6(5_000),
+    )?;
+    let controller = protocol.controller.clone();
+    protocol
+        .chain
+        .execute::<logics::traits::controller::Error>(
+            &deployer(),
+            &controller,
+            "Controller::set_liquidation_incentive_mantissa",
+            &[wrapped_u256(ONE_ETHER * 108 / 100)],
+        )?;
+    let collateral_before = protocol.balance_of(&collateral.pool, &borrower)?;
+    protocol.pool_execute(
+        &liquidator,
+        &borrowing,
+        "Pool::liquidate_borrow",
+        &[
+            account(&borrower),
+            to_dec6(5_000).to_string(),
+            account(&collateral.pool),
+        ],
+    )?;
+
+    assert_eq!(protocol.balance_of(&borrowing.token, &liquidator)?, 0);
+    assert_eq!(
+        protocol.balance_of(&borrowing.token, &borrower)?,
+        to_dec6(10_000)
+    );
+    assert_eq!(
+        protocol.borrow_balance_stored(&borrowing, &borrower)?,
+        to_dec6(5_000)
+    );
+    // seize_tokens = actual_repay_amount * liquidation_incentive
+    let seize_tokens = to_dec18(5_000) * 108 / 100;
+    assert_eq!(
+        protocol.balance_of(&collateral.pool, &borrower)?,
+        collateral_before - seize_tokens
+    );
+    // the protocol share of 2.8% is added to the reserves of the collateral pool
+    let protocol_seize_tokens = seize_tokens * 28 / 1000;
+    assert_eq!(
+        protocol.balance_of(&collateral.pool, &liquidator)?,
+        seize_tokens - protocol_seize_tokens
+    );
+    let total_reserves: Balance =
+        protocol
+            .chain
+            .call(&liquidator, &collateral.pool, "Pool::total_reserves", &[])?;
+    assert_eq!(total_reserves, protocol_seize_tokens);
+    Ok(())
+}
+
+#[test]
+fn liquidate_borrow_fails_when_liquidator_is_borrower() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let [borrower, ..] = users();
+    setup_for_shortage(&mut protocol, &borrower)?;
+    let (collateral, borrowing) = (protocol.dai.clone(), protocol.usdc.clone());
+
+    let result: core::result::Result<(), PoolError> = protocol.chain.call(
+        &borrower,
+        &borrowing.pool,
+        "Pool::liquidate_borrow",
+        &[account(&borrower), 0.to_string(), account(&collateral.pool)],
+    )?;
+    assert_eq!(result, Err(PoolError::LiquidateLiquidatorIsBorrower));
+    Ok(())
+}
+
+#[test]
+fn liquidate_borrow_fails_when_repay_amount_is_zero() -> Result<()> {
+    let mut protocol = Protocol::new()?;
+    let [borrower, liquidator, _] = users();
+    setup_for_shortage(&mut protocol, &borrower)?;
+    let (collateral, borrowing) = (protocol.dai.clone(), protocol.usdc.clone());
+
+    let result: core::result::Result<(), PoolError> = protocol.chain.call(
+        &liquidator,
+        &borrowing.pool,
+        "Pool::liquidate_borrow",
+        &[account(&borrower), 0.to_string(), account(&collateral.pool)],
+    )?;
+    assert_eq!(result, Err(PoolError::LiquidateCloseAmountIsZero));
+    Ok(())
+}
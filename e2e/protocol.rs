@@ -0,0 +1,541 @@
+use contract_build::{
+    BuildMode,
+    ExecuteArgs,
+    ManifestPath,
+};
+use core::fmt::Debug;
+use drink::{
+    runtime::MinimalRuntime,
+    session::{
+        error::SessionError,
+        Session,
+        NO_ENDOWMENT,
+    },
+    AccountId32,
+    ContractBundle,
+    Weight,
+};
+use logics::traits::{
+    controller::Error as ControllerError,
+    price_oracle::Error as PriceOracleError,
+};
+use openbrush::{
+    contracts::psp22::PSP22Error,
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+use scale::Decode;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Mutex,
+        OnceLock,
+    },
+};
+
+/// Contracts deployed by the tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BundleProvider {
+    Controller,
+    DefaultInterestRateModel,
+    FlashloanGateway,
+    FlashloanReceiver,
+    IncentivesController,
+    Leverager,
+    Pool,
+    PriceOracle,
+    Psp22Token,
+    Weth,
+}
+
+impl BundleProvider {
+    fn manifest_dir(self) -> &'static str {
+        match self {
+            Self::Controller => "contracts/controller",
+            Self::DefaultInterestRateModel => "contracts/default_interest_rate_model",
+            Self::FlashloanGateway => "contracts/flashloan_gateway",
+            Self::FlashloanReceiver => "mocks/contracts/flashloan_receiver",
+            Self::IncentivesController => "mocks/contracts/incentives_controller",
+            Self::Leverager => "contracts/leverager",
+            Self::Pool => "contracts/pool",
+            Self::PriceOracle => "contracts/price_oracle",
+            Self::Psp22Token => "mocks/contracts/psp22",
+            Self::Weth => "mocks/contracts/weth",
+        }
+    }
+
+    /// Builds the contract with `cargo contract build --release` on first use and loads its bundle.
+    // NOTE: `drink::contract_bundle_provider` builds the bundles at compile time instead,
+    // it resolves the contracts from the root package, which a virtual workspace doesn't have
+    pub fn bundle(self) -> Result<ContractBundle> {
+        static BUNDLES: OnceLock<Mutex<HashMap<BundleProvider, PathBuf>>> = OnceLock::new();
+        let mut bundles = BUNDLES
+            .get_or_init(Default::default)
+            .lock()
+            .map_err(|e| e.to_string())?;
+        let path = match bundles.get(&self) {
+            Some(path) => path.clone(),
+            None => {
+                let manifest_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("..")
+                    .join(self.manifest_dir())
+                    .join("Cargo.toml");
+                let result = contract_build::execute(ExecuteArgs {
+                    manifest_path: ManifestPath::new(manifest_path)?,
+                    build_mode: BuildMode::Release,
+                    ..Default::default()
+                })?;
+                let path = result
+                    .metadata_result
+                    .ok_or("metadata should have been generated")?
+                    .dest_bundle;
+                bundles.insert(self, path.clone());
+                path
+            }
+        };
+        Ok(ContractBundle::load(path)?)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+pub const ONE_ETHER: u128 = 10_u128.pow(18);
+// native balance given to every account to pay the storage deposits
+const NATIVE_ENDOWMENT: u128 = 10_u128.pow(24);
+// the default limit of drink! runs out of gas in the flows calling across several contracts
+const GAS_LIMIT: Weight = Weight::from_parts(10_000_000_000_000, 64 * 1024 * 1024);
+
+pub fn deployer() -> AccountId32 {
+    AccountId32::new([1; 32])
+}
+
+pub fn users() -> [AccountId32; 3] {
+    [
+        AccountId32::new([2; 32]),
+        AccountId32::new([3; 32]),
+        AccountId32::new([4; 32]),
+    ]
+}
+
+pub fn to_dec6(value: u128) -> Balance {
+    value * 10_u128.pow(6)
+}
+
+pub fn to_dec18(value: u128) -> Balance {
+    value * 10_u128.pow(18)
+}
+
+/// Converts an account of the runtime into the `AccountId` returned by the contracts
+pub fn to_account_id(id: &AccountId32) -> AccountId {
+    AccountId::from(*<AccountId32 as AsRef<[u8; 32]>>::as_ref(id))
+}
+
+// Arguments are passed to the contracts in the textual format of `contract-transcode`
+
+pub fn account(id: &AccountId32) -> String {
+    id.to_string()
+}
+
+pub fn some_account(id: &AccountId32) -> String {
+    format!("Some({})", id)
+}
+
+pub fn accounts(ids: &[&AccountId32]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| account(id)).collect();
+    format!("[{}]", ids.join(", "))
+}
+
+pub fn string(value: &str) -> String {
+    format!("{:?}", value)
+}
+
+pub fn some_string(value: &str) -> String {
+    format!("Some({:?})", value)
+}
+
+pub fn wrapped_u256(value: u128) -> String {
+    format!(
+        "WrappedU256(U256([{}, {}, 0, 0]))",
+        value as u64,
+        (value >> 64) as u64
+    )
+}
+
+/// Session of the in-process runtime the contracts are deployed to
+pub struct Chain {
+    session: Session<MinimalRuntime>,
+    salt: u32,
+}
+
+impl Chain {
+    pub fn new() -> Result<Self> {
+        let mut session = Session::<MinimalRuntime>::new()?.with_gas_limit(GAS_LIMIT);
+        for actor in [deployer()].into_iter().chain(users()) {
+            session
+                .sandbox()
+                .mint_into(actor, NATIVE_ENDOWMENT)
+                .map_err(|e| format!("failed to endow account: {:?}", e))?;
+        }
+        Ok(Self { session, salt: 0 })
+    }
+
+    pub fn deploy(
+        &mut self,
+        bundle: BundleProvider,
+        constructor: &str,
+        args: &[String],
+    ) -> Result<AccountId32> {
+        self.salt += 1;
+        self.session.set_actor(deployer());
+        Ok(self.session.deploy_bundle(
+            bundle.bundle()?,
+            constructor,
+            args,
+            self.salt.to_le_bytes().to_vec(),
+            NO_ENDOWMENT,
+        )?)
+    }
+
+    /// Calls `message` of `contract` from `caller` and returns the decoded output.
+    /// The output of a message returning an error is returned as well,
+    /// its state changes are reverted.
+    pub fn call<T: Decode>(
+        &mut self,
+        caller: &AccountId32,
+        contract: &AccountId32,
+        message: &str,
+        args: &[String],
+    ) -> Result<T> {
+        self.session.set_actor(caller.clone());
+        let result = match self.session.call_with_address::<_, T>(
+            contract.clone(),
+            message,
+            args,
+            NO_ENDOWMENT,
+        ) {
+            Ok(result) => result,
+            Err(SessionError::CallReverted) => {
+                let data = match &self.session.record().last_call_result().result {
+                    Ok(result) => result.data.clone(),
+                    Err(e) => return Err(format!("{} failed: {:?}", message, e).into()),
+                };
+                drink::errors::MessageResult::<T>::decode(&mut &data[..])?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        result.map_err(|e| format!("{} failed to dispatch: {:?}", message, e).into())
+    }
+
+    /// Calls a message returning `Result<(), E>` and fails if it returns an error
+    pub fn execute<E: Decode + Debug>(
+        &mut self,
+        caller: &AccountId32,
+        contract: &AccountId32,
+        message: &str,
+        args: &[String],
+    ) -> Result<()> {
+        self.call::<core::result::Result<(), E>>(caller, contract, message, args)?
+            .map_err(|e| format!("{} returned {:?}", message, e).into())
+    }
+}
+
+pub struct Metadata {
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub decimals: u8,
+}
+
+pub const DAI: Metadata = Metadata {
+    name: "Dai Stablecoin",
+    symbol: "DAI",
+    decimals: 18,
+};
+pub const USDC: Metadata = Metadata {
+    name: "USD Coin",
+    symbol: "USDC",
+    decimals: 6,
+};
+pub const USDT: Metadata = Metadata {
+    name: "USD Tether",
+    symbol: "USDT",
+    decimals: 6,
+};
+pub const WETH: Metadata = Metadata {
+    name: "Wrapped Astar",
+    symbol: "WASTR",
+    decimals: 18,
+};
+
+#[derive(Clone)]
+pub struct Market {
+    pub token: AccountId32,
+    pub pool: AccountId32,
+}
+
+fn deploy_token(chain: &mut Chain, metadata: &Metadata) -> Result<AccountId32> {
+    chain.deploy(
+        BundleProvider::Psp22Token,
+        "new",
+        &[
+            0.to_string(),
+            some_string(metadata.name),
+            some_string(metadata.symbol),
+            metadata.decimals.to_string(),
+        ],
+    )
+}
+
+fn deploy_pool(
+    chain: &mut Chain,
+    incentives_controller: &AccountId32,
+    controller: &AccountId32,
+    rate_model: &AccountId32,
+    token: &AccountId32,
+    metadata: &Metadata,
+    initial_exchange_rate_mantissa: u128,
+) -> Result<Market> {
+    // NOTE: the metadata is passed to the constructor as in `deployPoolFromAsset`,
+    // `new_from_asset` fails to fetch it from the underlying
+    let pool = chain.deploy(
+        BundleProvider::Pool,
+        "new",
+        &[
+            some_account(incentives_controller),
+            account(token),
+            account(controller),
+            account(rate_model),
+            account(&deployer()),
+            wrapped_u256(initial_exchange_rate_mantissa),
+            10000.to_string(),
+            string(&format!("Starlay {}", metadata.name)),
+            string(&format!("s{}", metadata.symbol)),
+            metadata.decimals.to_string(),
+        ],
+    )?;
+    Ok(Market {
+        token: token.clone(),
+        pool,
+    })
+}
+
+/// Contracts deployed and initialized in the same way as `setup` of the TypeScript specs
+pub struct Protocol {
+    pub chain: Chain,
+    pub controller: AccountId32,
+    pub price_oracle: AccountId32,
+    pub rate_model: AccountId32,
+    pub incentives_controller: AccountId32,
+    pub flashloan_gateway: AccountId32,
+    pub flashloan_receiver: AccountId32,
+    pub dai: Market,
+    pub usdc: Market,
+    pub usdt: Market,
+    pub weth: Market,
+}
+
+impl Protocol {
+    pub fn new() -> Result<Self> {
+        let mut chain = Chain::new()?;
+        let controller =
+            chain.deploy(BundleProvider::Controller, "new", &[account(&deployer())])?;
+        let price_oracle = chain.deploy(BundleProvider::PriceOracle, "new", &[])?;
+        let rate_model = chain.deploy(
+            BundleProvider::DefaultInterestRateModel,
+            "new",
+            &[
+                wrapped_u256(0),
+                wrapped_u256(0),
+                wrapped_u256(0),
+                wrapped_u256(0),
+            ],
+        )?;
+        let incentives_controller =
+            chain.deploy(BundleProvider::IncentivesController, "new", &[])?;
+        let flashloan_gateway = chain.deploy(
+            BundleProvider::FlashloanGateway,
+            "new",
+            &[account(&controller)],
+        )?;
+        let flashloan_receiver = chain.deploy(
+            BundleProvider::FlashloanReceiver,
+            "new",
+            &[account(&flashloan_gateway)],
+        )?;
+
+        let mut markets = Vec::new();
+        for metadata in [DAI, USDC, USDT] {
+            let token = deploy_token(&mut chain, &metadata)?;
+            markets.push(deploy_pool(
+                &mut chain,
+                &incentives_controller,
+                &controller,
+                &rate_model,
+                &token,
+                &metadata,
+                ONE_ETHER,
+            )?);
+        }
+        let weth = chain.deploy(BundleProvider::Weth, "new", &[])?;
+        markets.push(deploy_pool(
+            &mut chain,
+            &incentives_controller,
+            &controller,
+            &rate_model,
+            &weth,
+            &WETH,
+            ONE_ETHER,
+        )?);
+        let [dai, usdc, usdt, weth]: [Market; 4] = markets
+            .try_into()
+            .map_err(|_| "unexpected number of markets")?;
+
+        let mut protocol = Self {
+            chain,
+            controller,
+            price_oracle,
+            rate_model,
+            incentives_controller,
+            flashloan_gateway,
+            flashloan_receiver,
+            dai,
+            usdc,
+            usdt,
+            weth,
+        };
+        let (controller, deployer) = (protocol.controller.clone(), deployer());
+        protocol.chain.execute::<ControllerError>(
+            &deployer,
+            &controller,
+            "Controller::set_price_oracle",
+            &[account(&protocol.price_oracle)],
+        )?;
+        protocol.chain.execute::<ControllerError>(
+            &deployer,
+            &controller,
+            "Controller::set_close_factor_mantissa",
+            &[wrapped_u256(ONE_ETHER)],
+        )?;
+        protocol.chain.execute::<ControllerError>(
+            &deployer,
+            &controller,
+            "Controller::set_flashloan_gateway",
+            &[account(&protocol.flashloan_gateway)],
+        )?;
+        for market in [
+            protocol.dai.clone(),
+            protocol.usdc.clone(),
+            protocol.usdt.clone(),
+            protocol.weth.clone(),
+        ] {
+            protocol.list_market(&market, ONE_ETHER)?;
+        }
+        Ok(protocol)
+    }
+
+    /// Deploys a mock token and its pool without listing it to Controller
+    pub fn deploy_market(
+        &mut self,
+        metadata: &Metadata,
+        initial_exchange_rate_mantissa: u128,
+    ) -> Result<Market> {
+        let token = deploy_token(&mut self.chain, metadata)?;
+        deploy_pool(
+            &mut self.chain,
+            &self.incentives_controller,
+            &self.controller,
+            &self.rate_model,
+            &token,
+            metadata,
+            initial_exchange_rate_mantissa,
+        )
+    }
+
+    /// Sets the price of the underlying and supports the market with a collateral factor of 90%
+    pub fn list_market(&mut self, market: &Market, price: u128) -> Result<()> {
+        let (price_oracle, controller) = (self.price_oracle.clone(), self.controller.clone());
+        self.chain.execute::<PriceOracleError>(
+            &deployer(),
+            &price_oracle,
+            "PriceOracle::set_fixed_price",
+            &[account(&market.token), price.to_string()],
+        )?;
+        self.chain.execute::<ControllerError>(
+            &deployer(),
+            &controller,
+            "Controller::support_market_with_collateral_factor_mantissa",
+            &[
+                account(&market.pool),
+                account(&market.token),
+                wrapped_u256(ONE_ETHER * 90 / 100),
+            ],
+        )
+    }
+
+    pub fn mint_underlying(
+        &mut self,
+        market: &Market,
+        to: &AccountId32,
+        amount: Balance,
+    ) -> Result<()> {
+        self.chain.execute::<PSP22Error>(
+            &deployer(),
+            &market.token,
+            "PSP22Mintable::mint",
+            &[account(to), amount.to_string()],
+        )
+    }
+
+    pub fn approve(
+        &mut self,
+        owner: &AccountId32,
+        token: &AccountId32,
+        spender: &AccountId32,
+        amount: Balance,
+    ) -> Result<()> {
+        self.chain.execute::<PSP22Error>(
+            owner,
+            token,
+            "PSP22::approve",
+            &[account(spender), amount.to_string()],
+        )
+    }
+
+    /// Mints the underlying to `user` and deposits it into the pool
+    pub fn deposit(&mut self, user: &AccountId32, market: &Market, amount: Balance) -> Result<()> {
+        self.mint_underlying(market, user, amount)?;
+        self.approve(user, &market.token, &market.pool, amount)?;
+        self.pool_execute(user, market, "Pool::mint", &[amount.to_string()])
+    }
+
+    pub fn pool_execute(
+        &mut self,
+        caller: &AccountId32,
+        market: &Market,
+        message: &str,
+        args: &[String],
+    ) -> Result<()> {
+        self.chain
+            .execute::<logics::traits::pool::Error>(caller, &market.pool, message, args)
+    }
+
+    pub fn balance_of(&mut self, token: &AccountId32, owner: &AccountId32) -> Result<Balance> {
+        self.chain
+            .call(&deployer(), token, "PSP22::balance_of", &[account(owner)])
+    }
+
+    pub fn borrow_balance_stored(
+        &mut self,
+        market: &Market,
+        account_id: &AccountId32,
+    ) -> Result<Balance> {
+        self.chain.call(
+            &deployer(),
+            &market.pool,
+            "Pool::borrow_balance_stored",
+            &[account(account_id)],
+        )
+    }
+}
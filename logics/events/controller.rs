@@ -0,0 +1,149 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Events of `contracts/controller`
+
+use super::contract_events;
+use crate::traits::{
+    controller::OutflowLimit,
+    types::WrappedU256,
+};
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Hash,
+    String,
+    Timestamp,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+/// Event: Controller starts to support Pool
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct MarketListed {
+    pub pool: AccountId,
+}
+
+/// Event: Controller Manager changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ManagerAddressUpdated {
+    /// topic
+    pub old: AccountId,
+    /// topic
+    pub new: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewCollateralFactor {
+    /// topic
+    pub pool: AccountId,
+    pub old: WrappedU256,
+    pub new: WrappedU256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct PoolActionPaused {
+    pub pool: AccountId,
+    pub action: String,
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ActionPaused {
+    pub action: String,
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewPriceOracle {
+    pub old: Option<AccountId>,
+    pub new: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewFlashloanGateway {
+    pub old: Option<AccountId>,
+    pub new: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewCloseFactor {
+    pub old: WrappedU256,
+    pub new: WrappedU256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewBorrowCap {
+    pub pool: AccountId,
+    pub new: Balance,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewLiquidationIncentive {
+    pub old: WrappedU256,
+    pub new: WrappedU256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewMaxUtilization {
+    pub pool: AccountId,
+    pub old: WrappedU256,
+    pub new: WrappedU256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewOutflowLimit {
+    pub pool: AccountId,
+    pub new: OutflowLimit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct OutflowLimitRaised {
+    pub pool: AccountId,
+    pub amount: Balance,
+    pub expires_at: Timestamp,
+}
+
+/// Event: The code of this contract has been replaced
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Upgraded {
+    /// topic
+    pub code_hash: Hash,
+}
+
+/// Event: The storage struct under storage_key has been migrated
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Migrated {
+    pub storage_key: u32,
+    pub from: u32,
+    pub to: u32,
+}
+
+contract_events!(
+    /// Events of `ControllerContract`
+    ControllerEvent,
+    "ControllerContract",
+    [
+        MarketListed,
+        ManagerAddressUpdated,
+        NewCollateralFactor,
+        PoolActionPaused,
+        ActionPaused,
+        NewPriceOracle,
+        NewFlashloanGateway,
+        NewCloseFactor,
+        NewBorrowCap,
+        NewLiquidationIncentive,
+        NewMaxUtilization,
+        NewOutflowLimit,
+        OutflowLimitRaised,
+        Upgraded,
+        Migrated,
+    ]
+);
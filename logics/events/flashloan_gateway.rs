@@ -0,0 +1,99 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Events of `contracts/flashloan_gateway`
+
+use super::contract_events;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Hash,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+/// Event: Flashloan is executed.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct FlashLoan {
+    /// topic
+    pub target: AccountId,
+    /// topic
+    pub initiator: AccountId,
+    pub asset: AccountId,
+    pub amount: Balance,
+    pub premium: Balance,
+    pub premium_to_protocol: Balance,
+}
+
+/// Event: Flashloan fee is changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewFlashloanPremiumTotal {
+    pub old: u128,
+    pub new: u128,
+}
+
+/// Event: Share of Flashloan fee added to the reserves is changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewFlashloanPremiumToProtocol {
+    pub old: u128,
+    pub new: u128,
+}
+
+/// Event: Flashloan fee override of a borrower is changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewBorrowerPremium {
+    /// topic
+    pub borrower: AccountId,
+    pub premium: Option<u128>,
+}
+
+/// Event: Restriction of flash loans to whitelisted receivers is changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewWhitelistOnly {
+    pub enabled: bool,
+}
+
+/// Event: Receiver is added to or removed from the whitelist
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewWhitelistedReceiver {
+    /// topic
+    pub receiver: AccountId,
+    pub whitelisted: bool,
+}
+
+/// Event: The code of this contract has been replaced
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Upgraded {
+    /// topic
+    pub code_hash: Hash,
+}
+
+/// Event: The storage struct under storage_key has been migrated
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Migrated {
+    pub storage_key: u32,
+    pub from: u32,
+    pub to: u32,
+}
+
+contract_events!(
+    /// Events of `FlashloanGatewayContract`
+    FlashloanGatewayEvent,
+    "FlashloanGatewayContract",
+    [
+        FlashLoan,
+        NewFlashloanPremiumTotal,
+        NewFlashloanPremiumToProtocol,
+        NewBorrowerPremium,
+        NewWhitelistOnly,
+        NewWhitelistedReceiver,
+        Upgraded,
+        Migrated,
+    ]
+);
@@ -0,0 +1,302 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Events emitted by the contracts, for off-chain consumers such as indexers
+//!
+//! The types mirror the `#[ink(event)]` definitions of each contract,
+//! which are not reachable from outside of the contract.
+//! `ink!` encodes an event as the index of the event in its contract followed by all of its fields,
+//! and emits a first topic identifying the contract and the event.
+//! [`decode_event`] identifies the event by this topic and decodes it into a [`ProtocolEvent`].
+
+pub mod controller;
+pub mod flashloan_gateway;
+pub mod pool;
+
+pub use self::{
+    controller::ControllerEvent,
+    flashloan_gateway::FlashloanGatewayEvent,
+    pool::PoolEvent,
+};
+use ink::env::hash::{
+    Blake2x256,
+    HashOutput,
+};
+use openbrush::traits::Hash;
+use scale::{
+    Decode,
+    DecodeAll,
+    Encode,
+};
+
+/// Defines the enum of the events of a contract,
+/// whose variants must be in the order of the definitions in the contract
+macro_rules! contract_events {
+    ($(#[$attr:meta])* $name:ident, $contract:literal, [$($event:ident),* $(,)?]) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+        pub enum $name {
+            $($event($event),)*
+        }
+
+        impl $name {
+            /// Name of the storage of the contract, which prefixes the signature topics
+            pub const CONTRACT: &'static str = $contract;
+            /// Names of the events in the order of their definitions
+            pub const EVENTS: &'static [&'static str] = &[$(stringify!($event),)*];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$event(_) => stringify!($event),)*
+                }
+            }
+
+            /// Signature topic of the event
+            pub fn signature_topic(&self) -> openbrush::traits::Hash {
+                crate::events::signature_topic(Self::CONTRACT, self.name())
+            }
+
+            /// Returns `None` if `signature` is not the topic of an event of this contract
+            pub fn decode_event(
+                data: &[u8],
+                signature: openbrush::traits::Hash,
+            ) -> Result<Option<Self>, crate::events::DecodeError> {
+                crate::events::decode_contract_event(
+                    Self::CONTRACT,
+                    Self::EVENTS,
+                    Self::name,
+                    data,
+                    signature,
+                )
+            }
+        }
+
+        $(
+            impl From<$event> for $name {
+                fn from(event: $event) -> Self {
+                    Self::$event(event)
+                }
+            }
+        )*
+    };
+}
+pub(crate) use contract_events;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolEvent {
+    Pool(PoolEvent),
+    Controller(ControllerEvent),
+    FlashloanGateway(FlashloanGatewayEvent),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The event has no topic, the events of the contracts are never anonymous
+    MissingSignatureTopic,
+    /// The signature topic matches no event of the contracts
+    UnknownSignature(Hash),
+    /// The data does not match the event of the signature topic
+    InvalidData,
+}
+
+/// Decodes the data and the topics of an event emitted by one of the contracts
+pub fn decode_event(data: &[u8], topics: &[Hash]) -> Result<ProtocolEvent, DecodeError> {
+    let signature = *topics.first().ok_or(DecodeError::MissingSignatureTopic)?;
+    if let Some(event) = PoolEvent::decode_event(data, signature)? {
+        return Ok(ProtocolEvent::Pool(event))
+    }
+    if let Some(event) = ControllerEvent::decode_event(data, signature)? {
+        return Ok(ProtocolEvent::Controller(event))
+    }
+    if let Some(event) = FlashloanGatewayEvent::decode_event(data, signature)? {
+        return Ok(ProtocolEvent::FlashloanGateway(event))
+    }
+    Err(DecodeError::UnknownSignature(signature))
+}
+
+/// Returns `None` if `signature` is not the topic of an event of `contract`
+pub(crate) fn decode_contract_event<E: Decode>(
+    contract: &str,
+    events: &[&str],
+    name: fn(&E) -> &'static str,
+    mut data: &[u8],
+    signature: Hash,
+) -> Result<Option<E>, DecodeError> {
+    let Some(expected) = events
+        .iter()
+        .find(|event| signature_topic(contract, event) == signature)
+    else {
+        return Ok(None)
+    };
+    let event = E::decode_all(&mut data).map_err(|_| DecodeError::InvalidData)?;
+    // the index in the data must designate the same event as the topic
+    if name(&event) != *expected {
+        return Err(DecodeError::InvalidData)
+    }
+    Ok(Some(event))
+}
+
+/// Topic identifying `event` of `contract`, as generated by `ink!` for non-anonymous events
+pub fn signature_topic(contract: &str, event: &str) -> Hash {
+    // `ink!` encodes the signature with an empty prefix
+    let mut encoded = Vec::<u8>::new().encode();
+    encoded.extend_from_slice(format!("{}::{}", contract, event).as_bytes());
+    topic(&encoded)
+}
+
+/// Topic of the field `field` of `event` of `contract` marked with `#[ink(topic)]`
+pub fn field_topic<T: Encode>(contract: &str, event: &str, field: &str, value: &T) -> Hash {
+    let mut encoded = format!("{}::{}::{}", contract, event, field)
+        .as_bytes()
+        .encode();
+    value.encode_to(&mut encoded);
+    topic(&encoded)
+}
+
+/// Encoded values up to 32 bytes are used as is, longer ones are hashed
+fn topic(encoded: &[u8]) -> Hash {
+    let mut result = [0_u8; 32];
+    if encoded.len() <= result.len() {
+        result[..encoded.len()].copy_from_slice(encoded);
+    } else {
+        let mut hash = <Blake2x256 as HashOutput>::Type::default();
+        ink::env::hash_bytes::<Blake2x256>(encoded, &mut hash);
+        result.copy_from_slice(&hash);
+    }
+    Hash::from(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::types::WrappedU256;
+    use openbrush::traits::AccountId;
+    use primitive_types::U256;
+
+    fn mint() -> PoolEvent {
+        PoolEvent::from(pool::Mint {
+            minter: AccountId::from([0x01; 32]),
+            mint_amount: 1_000,
+            mint_tokens: 900,
+        })
+    }
+
+    #[test]
+    fn test_signature_topic() {
+        // short signatures are padded
+        let mut expected = [0_u8; 32];
+        expected[1..19].copy_from_slice(b"PoolContract::Mint");
+        assert_eq!(
+            signature_topic("PoolContract", "Mint"),
+            Hash::from(expected)
+        );
+        assert_eq!(mint().signature_topic(), Hash::from(expected));
+
+        // long signatures are hashed
+        let long = signature_topic("FlashloanGatewayContract", "NewFlashloanPremiumToProtocol");
+        let mut hash = [0_u8; 32];
+        ink::env::hash_bytes::<Blake2x256>(
+            b"\0FlashloanGatewayContract::NewFlashloanPremiumToProtocol",
+            &mut hash,
+        );
+        assert_eq!(long, Hash::from(hash));
+    }
+
+    #[test]
+    fn test_signature_topics_are_unique() {
+        let mut topics: Vec<Hash> = [
+            (PoolEvent::CONTRACT, PoolEvent::EVENTS),
+            (ControllerEvent::CONTRACT, ControllerEvent::EVENTS),
+            (
+                FlashloanGatewayEvent::CONTRACT,
+                FlashloanGatewayEvent::EVENTS,
+            ),
+        ]
+        .iter()
+        .flat_map(|(contract, events)| {
+            events
+                .iter()
+                .map(move |event| signature_topic(contract, event))
+        })
+        .collect();
+        let count = topics.len();
+        topics.sort();
+        topics.dedup();
+        assert_eq!(topics.len(), count);
+    }
+
+    #[test]
+    fn test_decode_event() {
+        let event = mint();
+        assert_eq!(
+            decode_event(&event.encode(), &[event.signature_topic()]),
+            Ok(ProtocolEvent::Pool(event))
+        );
+
+        let event = ControllerEvent::from(controller::NewCollateralFactor {
+            pool: AccountId::from([0x02; 32]),
+            old: WrappedU256::from(U256::zero()),
+            new: WrappedU256::from(U256::from(10_u128.pow(17))),
+        });
+        let topics = [
+            event.signature_topic(),
+            field_topic(
+                ControllerEvent::CONTRACT,
+                "NewCollateralFactor",
+                "pool",
+                &AccountId::from([0x02; 32]),
+            ),
+        ];
+        assert_eq!(
+            decode_event(&event.encode(), &topics),
+            Ok(ProtocolEvent::Controller(event))
+        );
+
+        let event = FlashloanGatewayEvent::from(flashloan_gateway::NewBorrowerPremium {
+            borrower: AccountId::from([0x03; 32]),
+            premium: Some(5),
+        });
+        assert_eq!(
+            decode_event(&event.encode(), &[event.signature_topic()]),
+            Ok(ProtocolEvent::FlashloanGateway(event))
+        );
+    }
+
+    #[test]
+    fn test_decode_event_fails() {
+        let event = mint();
+        let data = event.encode();
+        assert_eq!(
+            decode_event(&data, &[]),
+            Err(DecodeError::MissingSignatureTopic)
+        );
+
+        let unknown = signature_topic("PoolContract", "Unknown");
+        assert_eq!(
+            decode_event(&data, &[unknown]),
+            Err(DecodeError::UnknownSignature(unknown))
+        );
+
+        // the index designates `Mint` but the topic designates `Redeem`
+        let redeem = signature_topic(PoolEvent::CONTRACT, "Redeem");
+        assert_eq!(
+            decode_event(&data, &[redeem]),
+            Err(DecodeError::InvalidData)
+        );
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(
+            decode_event(&trailing, &[event.signature_topic()]),
+            Err(DecodeError::InvalidData)
+        );
+        assert_eq!(
+            decode_event(&data[..data.len() - 1], &[event.signature_topic()]),
+            Err(DecodeError::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Events of `contracts/pool`
+
+use super::contract_events;
+use crate::traits::types::WrappedU256;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Hash,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+/// Event: Execute `Mint`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Mint {
+    pub minter: AccountId,
+    pub mint_amount: Balance,
+    pub mint_tokens: Balance,
+}
+
+/// Event: Execute `Redeem`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Redeem {
+    pub redeemer: AccountId,
+    pub redeem_amount: Balance,
+}
+
+/// Event: Execute `Borrow`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Borrow {
+    pub borrower: AccountId,
+    pub borrow_amount: Balance,
+    pub account_borrows: Balance,
+    pub total_borrows: Balance,
+}
+
+/// Event: Execute `RepayBorrow`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct RepayBorrow {
+    pub payer: AccountId,
+    pub borrower: AccountId,
+    pub repay_amount: Balance,
+    pub account_borrows: Balance,
+    pub total_borrows: Balance,
+}
+
+/// Event: Execute `LiquidateBorrow`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct LiquidateBorrow {
+    pub liquidator: AccountId,
+    pub borrower: AccountId,
+    pub repay_amount: Balance,
+    pub token_collateral: AccountId,
+    pub seize_tokens: Balance,
+}
+
+/// Event: Adding to Reserves
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ReservesAdded {
+    pub benefactor: AccountId,
+    pub add_amount: Balance,
+    pub new_total_reserves: Balance,
+}
+
+/// Event: Transfer Pool Token
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Transfer {
+    /// topic
+    pub from: Option<AccountId>,
+    /// topic
+    pub to: Option<AccountId>,
+    pub value: Balance,
+}
+
+/// Event: Allowance of a spender for an owner is set
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Approval {
+    /// topic
+    pub owner: AccountId,
+    /// topic
+    pub spender: AccountId,
+    pub value: Balance,
+}
+
+/// Event: Delegation Allowance for Borrowing is changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct DelegateApproval {
+    /// topic
+    pub owner: AccountId,
+    /// topic
+    pub delegatee: AccountId,
+    pub amount: Balance,
+}
+
+/// Event: User has enabled Reserve as Collateral
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ReserveUsedAsCollateralEnabled {
+    /// topic
+    pub user: AccountId,
+}
+
+/// Event: User has disabled Reserve as Collateral
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ReserveUsedAsCollateralDisabled {
+    /// topic
+    pub user: AccountId,
+}
+
+/// Event: Pool Manager changed
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ManagerAddressUpdated {
+    /// topic
+    pub old: AccountId,
+    /// topic
+    pub new: AccountId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct AccrueInterest {
+    pub interest_accumulated: Balance,
+    pub new_index: WrappedU256,
+    pub new_total_borrows: Balance,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct ReservesReduced {
+    pub reduce_amount: Balance,
+    pub total_reserves_new: Balance,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewController {
+    /// topic
+    pub old: Option<AccountId>,
+    /// topic
+    pub new: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewInterestRateModel {
+    /// topic
+    pub old: Option<AccountId>,
+    /// topic
+    pub new: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct NewReserveFactor {
+    pub old: WrappedU256,
+    pub new: WrappedU256,
+}
+
+/// Event: The code of this contract has been replaced
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Upgraded {
+    /// topic
+    pub code_hash: Hash,
+}
+
+/// Event: The storage struct under storage_key has been migrated
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Migrated {
+    pub storage_key: u32,
+    pub from: u32,
+    pub to: u32,
+}
+
+contract_events!(
+    /// Events of `PoolContract`
+    PoolEvent,
+    "PoolContract",
+    [
+        Mint,
+        Redeem,
+        Borrow,
+        RepayBorrow,
+        LiquidateBorrow,
+        ReservesAdded,
+        Transfer,
+        Approval,
+        DelegateApproval,
+        ReserveUsedAsCollateralEnabled,
+        ReserveUsedAsCollateralDisabled,
+        ManagerAddressUpdated,
+        AccrueInterest,
+        ReservesReduced,
+        NewController,
+        NewInterestRateModel,
+        NewReserveFactor,
+        Upgraded,
+        Migrated,
+    ]
+);
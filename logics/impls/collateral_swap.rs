@@ -0,0 +1,168 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::ops::{
+    Add,
+    Div,
+    Mul,
+};
+
+use super::flashloan_adapter::{
+    Data,
+    Internal as FlashloanAdapterInternal,
+    ReceivedFlashloan,
+};
+pub use crate::traits::collateral_swap::*;
+use crate::traits::flashloan_gateway::FlashLoanType;
+use ink::prelude::vec::Vec;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+use primitive_types::U256;
+use scale::Encode;
+
+pub trait Internal {
+    fn _swap_collateral(
+        &mut self,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount: Balance,
+        min_amount_to_receive: Balance,
+    ) -> Result<()>;
+
+    /// Body of `FlashloanReceiver::execute_operation`
+    fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()>;
+
+    // event emission
+    fn _emit_collateral_swap_event(
+        &self,
+        user: AccountId,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount_from: Balance,
+        amount_to: Balance,
+    );
+}
+
+impl<T: Storage<Data>> CollateralSwap for T {
+    default fn flashloan_gateway(&self) -> Option<AccountId> {
+        self._flashloan_gateway()
+    }
+
+    default fn controller(&self) -> Option<AccountId> {
+        self._controller()
+    }
+
+    default fn router(&self) -> Option<AccountId> {
+        self._router()
+    }
+
+    default fn swap_collateral(
+        &mut self,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount: Balance,
+        min_amount_to_receive: Balance,
+    ) -> Result<()> {
+        self._swap_collateral(asset_from, asset_to, amount, min_amount_to_receive)
+    }
+}
+
+impl<T: Storage<Data>> Internal for T {
+    default fn _swap_collateral(
+        &mut self,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount: Balance,
+        min_amount_to_receive: Balance,
+    ) -> Result<()> {
+        if asset_from == asset_to {
+            return Err(Error::SameAsset)
+        }
+        self._pool_of(asset_from)?;
+        self._pool_of(asset_to)?;
+
+        // flashloan as much as the swap output can repay with the premium
+        let amount_out = self._get_amount_out(asset_from, asset_to, amount)?;
+        let premium_total = self._flashloan_premium()?;
+        let flashloan_amount = U256::from(amount_out)
+            .mul(U256::from(10000))
+            .div(U256::from(10000).add(U256::from(premium_total)))
+            .as_u128();
+        if flashloan_amount < min_amount_to_receive {
+            return Err(Error::InsufficientAmountToReceive)
+        }
+
+        let params = CollateralSwapParams {
+            user: Self::env().caller(),
+            asset_from,
+            amount,
+        };
+        self._flashloan(
+            asset_to,
+            flashloan_amount,
+            FlashLoanType::None,
+            params.encode(),
+        )?;
+        Ok(())
+    }
+
+    default fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()> {
+        let ReceivedFlashloan {
+            asset: asset_to,
+            amount: amount_to,
+            premium,
+            params:
+                CollateralSwapParams {
+                    user,
+                    asset_from,
+                    amount,
+                },
+        } = self._receive_flashloan(assets, amounts, premiums, initiator, params)?;
+
+        // supply the flashloaned asset on behalf of the user,
+        // then withdraw the user's collateral to be swapped
+        self._supply_on_behalf(asset_to, user, amount_to)?;
+        self._redeem_on_behalf(asset_from, user, amount)?;
+
+        let amount_to_return = amount_to + premium;
+        let amount_out = self._swap_exact_in(asset_from, asset_to, amount, amount_to_return)?;
+        if amount_out > amount_to_return {
+            self._transfer(asset_to, user, amount_out - amount_to_return)?;
+        }
+
+        self._approve_repayment(asset_to, amount_to_return)?;
+        self._emit_collateral_swap_event(user, asset_from, asset_to, amount, amount_to);
+        Ok(())
+    }
+
+    default fn _emit_collateral_swap_event(
+        &self,
+        _user: AccountId,
+        _asset_from: AccountId,
+        _asset_to: AccountId,
+        _amount_from: Balance,
+        _amount_to: Balance,
+    ) {
+    }
+}
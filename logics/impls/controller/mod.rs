@@ -1,5 +1,5 @@
 use super::{
-    exp_no_err::Exp,
+    fixed_point::Exp,
     pool::{
         utils::underlying_balance,
         COLLATERAL_FACTOR_MANTISSA_DECIMALS,
@@ -14,7 +14,10 @@ pub use crate::traits::{
     },
 };
 use crate::{
-    impls::price_oracle::PRICE_PRECISION,
+    impls::{
+        interest_rate_model::utilization_rate,
+        price_oracle::PRICE_PRECISION,
+    },
     traits::{
         price_oracle::PriceOracleRef,
         types::WrappedU256,
@@ -34,24 +37,40 @@ use openbrush::{
         Balance,
         Storage,
         String,
+        Timestamp,
     },
 };
 use primitive_types::U256;
+mod outflow;
 mod utils;
-pub use self::utils::{
-    balance_decrease_allowed,
-    calculate_available_borrow_in_base_currency,
-    calculate_health_factor_from_balances,
-    collateral_factor_max_mantissa,
-    get_hypothetical_account_liquidity,
-    liquidate_calculate_seize_tokens,
-    BalanceDecreaseAllowedParam,
-    GetHypotheticalAccountLiquidityInput,
-    HypotheticalAccountLiquidityCalculationParam,
-    LiquidateCalculateSeizeTokensInput,
+pub use self::{
+    outflow::{
+        offset_inflow,
+        outflow_capacity,
+        outflow_in_window,
+        outflow_limit_enabled,
+        outflow_limit_valid,
+        remaining_outflow_capacity,
+        roll_outflow_state,
+        OutflowState,
+    },
+    utils::{
+        balance_decrease_allowed,
+        calculate_available_borrow_in_base_currency,
+        calculate_health_factor_from_balances,
+        collateral_factor_max_mantissa,
+        get_hypothetical_account_liquidity,
+        liquidate_calculate_seize_tokens,
+        BalanceDecreaseAllowedParam,
+        GetHypotheticalAccountLiquidityInput,
+        HypotheticalAccountLiquidityCalculationParam,
+        LiquidateCalculateSeizeTokensInput,
+    },
 };
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+/// Version of the storage layout of `Data`, incremented when a migration is required
+pub const STORAGE_VERSION: u32 = 1;
 
 pub const MAXIMUM_MARKETS: usize = 8;
 
@@ -82,12 +101,20 @@ pub struct Data {
     pub liquidation_incentive_mantissa: WrappedU256,
     /// Maximum that can be borrowed per Pool
     pub borrow_caps: Mapping<AccountId, Balance>,
+    /// Maximum utilization after borrowing per Pool (Decimals: 18)
+    pub max_utilization_mantissa: Mapping<AccountId, WrappedU256>,
     /// Manager's AccountId associated with this contract
     pub manager: Option<AccountId>,
     /// AccountId of Pending Manager use for transfer manager role
     pub pending_manager: Option<AccountId>,
     /// Flashloan Gateway's AccountId associated with this contract
     pub flashloan_gateway: Option<AccountId>,
+    /// Outflow limit per Pool
+    pub outflow_limits: Mapping<AccountId, OutflowLimit>,
+    /// Temporary raise of the outflow limit per Pool
+    pub outflow_limit_raises: Mapping<AccountId, OutflowLimitRaise>,
+    /// Recorded net outflow per Pool
+    pub outflow_states: Mapping<AccountId, OutflowState>,
 }
 
 impl Default for Data {
@@ -105,9 +132,13 @@ impl Default for Data {
             close_factor_mantissa: WrappedU256::from(U256::zero()),
             liquidation_incentive_mantissa: WrappedU256::from(U256::zero()),
             borrow_caps: Default::default(),
+            max_utilization_mantissa: Default::default(),
             manager: None,
             pending_manager: None,
             flashloan_gateway: None,
+            outflow_limits: Default::default(),
+            outflow_limit_raises: Default::default(),
+            outflow_states: Default::default(),
         }
     }
 }
@@ -169,8 +200,11 @@ pub trait Internal {
         pool_borrowed_attributes: Option<PoolAttributesForSeizeCalculation>,
         pool_collateral_attributes: Option<PoolAttributesForSeizeCalculation>,
     ) -> Result<Balance>;
+    fn _record_outflow(&mut self, pool: AccountId, amount: Balance, cash: Balance) -> Result<()>;
+    fn _record_inflow(&mut self, pool: AccountId, amount: Balance, cash: Balance) -> Result<()>;
     fn _assert_manager(&self) -> Result<()>;
     fn _assert_pending_manager(&self) -> Result<()>;
+    fn _assert_pool(&self, pool: AccountId) -> Result<()>;
 
     // admin functions
     fn _set_price_oracle(&mut self, new_oracle: AccountId) -> Result<()>;
@@ -196,6 +230,18 @@ pub trait Internal {
         new_liquidation_incentive_mantissa: WrappedU256,
     ) -> Result<()>;
     fn _set_borrow_cap(&mut self, pool: &AccountId, new_cap: Balance) -> Result<()>;
+    fn _set_max_utilization_mantissa(
+        &mut self,
+        pool: &AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()>;
+    fn _set_outflow_limit(&mut self, pool: &AccountId, limit: OutflowLimit) -> Result<()>;
+    fn _raise_outflow_limit(
+        &mut self,
+        pool: &AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()>;
     fn _set_manager(&mut self, manager: AccountId) -> Result<()>;
     fn _accept_manager(&mut self) -> Result<()>;
 
@@ -214,6 +260,11 @@ pub trait Internal {
     fn _close_factor_mantissa(&self) -> WrappedU256;
     fn _liquidation_incentive_mantissa(&self) -> WrappedU256;
     fn _borrow_cap(&self, pool: AccountId) -> Option<Balance>;
+    fn _max_utilization_mantissa(&self, pool: AccountId) -> Option<WrappedU256>;
+    fn _outflow_limit(&self, pool: AccountId) -> Option<OutflowLimit>;
+    fn _outflow_limit_raise(&self, pool: AccountId) -> Option<OutflowLimitRaise>;
+    fn _outflow_state(&self, pool: AccountId) -> Option<OutflowState>;
+    fn _remaining_outflow_capacity(&self, pool: AccountId) -> Option<Balance>;
     fn _manager(&self) -> Option<AccountId>;
     fn _pending_manager(&self) -> Option<AccountId>;
     fn _account_assets(
@@ -261,6 +312,14 @@ pub trait Internal {
     fn _emit_new_close_factor_event(&self, old: WrappedU256, new: WrappedU256);
     fn _emit_new_liquidation_incentive_event(&self, old: WrappedU256, new: WrappedU256);
     fn _emit_new_borrow_cap_event(&self, pool: AccountId, new: Balance);
+    fn _emit_new_max_utilization_event(&self, pool: AccountId, old: WrappedU256, new: WrappedU256);
+    fn _emit_new_outflow_limit_event(&self, pool: AccountId, new: OutflowLimit);
+    fn _emit_outflow_limit_raised_event(
+        &self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    );
     fn _emit_manager_updated_event(&self, old: AccountId, new: AccountId);
 }
 
@@ -341,6 +400,26 @@ impl<T: Storage<Data>> Controller for T {
         self._transfer_allowed(pool, src, dst, transfer_tokens, pool_attribute)
     }
 
+    default fn record_outflow(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        cash: Balance,
+    ) -> Result<()> {
+        self._assert_pool(pool)?;
+        self._record_outflow(pool, amount, cash)
+    }
+
+    default fn record_inflow(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        cash: Balance,
+    ) -> Result<()> {
+        self._assert_pool(pool)?;
+        self._record_inflow(pool, amount, cash)
+    }
+
     default fn liquidate_calculate_seize_tokens(
         &self,
         pool_borrowed: AccountId,
@@ -464,6 +543,37 @@ impl<T: Storage<Data>> Controller for T {
         Ok(())
     }
 
+    default fn set_max_utilization_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()> {
+        self._assert_manager()?;
+        let old = self._max_utilization_mantissa(pool).unwrap_or_default();
+        self._set_max_utilization_mantissa(&pool, new_max_utilization_mantissa)?;
+        self._emit_new_max_utilization_event(pool, old, new_max_utilization_mantissa);
+        Ok(())
+    }
+
+    default fn set_outflow_limit(&mut self, pool: AccountId, limit: OutflowLimit) -> Result<()> {
+        self._assert_manager()?;
+        self._set_outflow_limit(&pool, limit)?;
+        self._emit_new_outflow_limit_event(pool, limit);
+        Ok(())
+    }
+
+    default fn raise_outflow_limit(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()> {
+        self._assert_manager()?;
+        self._raise_outflow_limit(&pool, amount, expires_at)?;
+        self._emit_outflow_limit_raised_event(pool, amount, expires_at);
+        Ok(())
+    }
+
     default fn set_manager(&mut self, manager: AccountId) -> Result<()> {
         self._assert_manager()?;
         self._set_manager(manager)?;
@@ -528,6 +638,22 @@ impl<T: Storage<Data>> Controller for T {
         self._borrow_cap(pool)
     }
 
+    default fn max_utilization_mantissa(&self, pool: AccountId) -> Option<WrappedU256> {
+        self._max_utilization_mantissa(pool)
+    }
+
+    default fn outflow_limit(&self, pool: AccountId) -> Option<OutflowLimit> {
+        self._outflow_limit(pool)
+    }
+
+    default fn outflow_limit_raise(&self, pool: AccountId) -> Option<OutflowLimitRaise> {
+        self._outflow_limit_raise(pool)
+    }
+
+    default fn remaining_outflow_capacity(&self, pool: AccountId) -> Option<Balance> {
+        self._remaining_outflow_capacity(pool)
+    }
+
     default fn manager(&self) -> Option<AccountId> {
         self._manager()
     }
@@ -637,7 +763,7 @@ impl<T: Storage<Data>> Internal for T {
                 token_modify: Some(pool),
                 redeem_tokens: redeem_amount,
                 borrow_amount: 0,
-            });
+            })?;
 
         // These are safe, as the underflow condition is checked first
         if sum_collateral < sum_borrow_plus_effect {
@@ -707,6 +833,27 @@ impl<T: Storage<Data>> Internal for T {
             }
         }
 
+        let max_utilization = U256::from(self._max_utilization_mantissa(pool).unwrap_or_default());
+        if !max_utilization.is_zero() {
+            let (cash, total_reserves) = match &pool_attributes {
+                Some(attrs) => (attrs.cash, attrs.total_reserves),
+                None => {
+                    (
+                        PoolRef::get_cash_prior(&pool),
+                        PoolRef::total_reserves(&pool),
+                    )
+                }
+            };
+            let utilization = utilization_rate(
+                cash.saturating_sub(borrow_amount),
+                total_borrow + borrow_amount,
+                total_reserves,
+            );
+            if utilization > max_utilization {
+                return Err(Error::MaxUtilizationExceeded)
+            }
+        }
+
         let (_, shortfall) = self._get_hypothetical_account_liquidity(
             borrower,
             Some(pool),
@@ -753,10 +900,8 @@ impl<T: Storage<Data>> Internal for T {
         }
 
         // The liquidator may not repay more than what is allowed by the closeFactor
-        let max_close = Exp {
-            mantissa: self._close_factor_mantissa(),
-        }
-        .mul_scalar_truncate(U256::from(borrow_balance));
+        let max_close =
+            Exp::from(self._close_factor_mantissa()).mul_int_down(U256::from(borrow_balance))?;
         if U256::from(repay_amount).gt(&max_close) {
             return Err(Error::TooMuchRepay)
         }
@@ -811,12 +956,8 @@ impl<T: Storage<Data>> Internal for T {
             PoolRef::exchange_rate_stored(&pool)
         };
 
-        let transfer_tokens_in_underlying = underlying_balance(
-            Exp {
-                mantissa: exchange_rate,
-            },
-            transfer_tokens,
-        );
+        let transfer_tokens_in_underlying =
+            underlying_balance(Exp::from(exchange_rate), transfer_tokens)?;
 
         self._redeem_allowed(pool, src, transfer_tokens_in_underlying, pool_attribute)?;
 
@@ -877,11 +1018,56 @@ impl<T: Storage<Data>> Internal for T {
             exchange_rate_mantissa: exchange_rate_mantissa.into(),
             liquidation_incentive_mantissa: self._liquidation_incentive_mantissa().into(),
             actual_repay_amount: repay_amount,
-        });
+        })?;
 
         Ok(result)
     }
 
+    default fn _record_outflow(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        cash: Balance,
+    ) -> Result<()> {
+        let limit = match self._outflow_limit(pool) {
+            Some(limit) if outflow_limit_enabled(&limit) => limit,
+            _ => return Ok(()),
+        };
+        let now = Self::env().block_timestamp();
+        let state = roll_outflow_state(self._outflow_state(pool), now, limit.window, cash);
+        let remaining =
+            remaining_outflow_capacity(&limit, self._outflow_limit_raise(pool), &state, now)?;
+        if amount > remaining {
+            return Err(Error::OutflowLimitExceeded)
+        }
+        self.data().outflow_states.insert(
+            &pool,
+            &OutflowState {
+                current: state.current + amount,
+                ..state
+            },
+        );
+        Ok(())
+    }
+
+    default fn _record_inflow(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        cash: Balance,
+    ) -> Result<()> {
+        let limit = match self._outflow_limit(pool) {
+            Some(limit) if outflow_limit_enabled(&limit) => limit,
+            _ => return Ok(()),
+        };
+        let now = Self::env().block_timestamp();
+        let state = roll_outflow_state(self._outflow_state(pool), now, limit.window, cash);
+        self.data()
+            .outflow_states
+            .insert(&pool, &offset_inflow(&state, amount));
+        Ok(())
+    }
+
     default fn _assert_manager(&self) -> Result<()> {
         let manager = self._manager().ok_or(Error::ManagerIsNotSet)?;
         if Self::env().caller() != manager {
@@ -902,6 +1088,17 @@ impl<T: Storage<Data>> Internal for T {
         Ok(())
     }
 
+    default fn _assert_pool(&self, pool: AccountId) -> Result<()> {
+        if !self._is_listed(pool) {
+            return Err(Error::MarketNotListed)
+        }
+        if Self::env().caller() != pool {
+            return Err(Error::CallerIsNotPool)
+        }
+
+        Ok(())
+    }
+
     default fn _set_price_oracle(&mut self, new_oracle: AccountId) -> Result<()> {
         self.data().oracle = Some(new_oracle);
         Ok(())
@@ -1037,6 +1234,46 @@ impl<T: Storage<Data>> Internal for T {
         Ok(())
     }
 
+    default fn _set_max_utilization_mantissa(
+        &mut self,
+        pool: &AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()> {
+        if !self._is_listed(*pool) {
+            return Err(Error::MarketNotListed)
+        }
+        self.data()
+            .max_utilization_mantissa
+            .insert(pool, &new_max_utilization_mantissa);
+        Ok(())
+    }
+
+    default fn _set_outflow_limit(&mut self, pool: &AccountId, limit: OutflowLimit) -> Result<()> {
+        if !self._is_listed(*pool) {
+            return Err(Error::MarketNotListed)
+        }
+        if !outflow_limit_valid(&limit) {
+            return Err(Error::InvalidOutflowLimit)
+        }
+        self.data().outflow_limits.insert(pool, &limit);
+        Ok(())
+    }
+
+    default fn _raise_outflow_limit(
+        &mut self,
+        pool: &AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()> {
+        if !self._is_listed(*pool) {
+            return Err(Error::MarketNotListed)
+        }
+        self.data()
+            .outflow_limit_raises
+            .insert(pool, &OutflowLimitRaise { amount, expires_at });
+        Ok(())
+    }
+
     default fn _set_manager(&mut self, manager: AccountId) -> Result<()> {
         self.data().pending_manager = Some(manager);
         Ok(())
@@ -1114,6 +1351,43 @@ impl<T: Storage<Data>> Internal for T {
         self.data().borrow_caps.get(&pool)
     }
 
+    default fn _max_utilization_mantissa(&self, pool: AccountId) -> Option<WrappedU256> {
+        self.data().max_utilization_mantissa.get(&pool)
+    }
+
+    default fn _outflow_limit(&self, pool: AccountId) -> Option<OutflowLimit> {
+        self.data().outflow_limits.get(&pool)
+    }
+
+    default fn _outflow_limit_raise(&self, pool: AccountId) -> Option<OutflowLimitRaise> {
+        self.data().outflow_limit_raises.get(&pool)
+    }
+
+    default fn _outflow_state(&self, pool: AccountId) -> Option<OutflowState> {
+        self.data().outflow_states.get(&pool)
+    }
+
+    default fn _remaining_outflow_capacity(&self, pool: AccountId) -> Option<Balance> {
+        let limit = self._outflow_limit(pool).filter(outflow_limit_enabled)?;
+        let now = Self::env().block_timestamp();
+        let state = self._outflow_state(pool);
+        let in_current_bucket = state
+            .map(|state| now.saturating_sub(state.bucket_start) < limit.window)
+            .unwrap_or_default();
+        // The liquidity of a new bucket is the current cash of the pool. Within the current bucket the liquidity
+        // recorded with the last flow is used, which can only understate the capacity.
+        let cash = if in_current_bucket {
+            0
+        } else {
+            PoolRef::get_cash_prior(&pool)
+        };
+        let state = roll_outflow_state(state, now, limit.window, cash);
+        Some(
+            remaining_outflow_capacity(&limit, self._outflow_limit_raise(pool), &state, now)
+                .expect("outflow capacity calculation failed"),
+        )
+    }
+
     default fn _manager(&self) -> Option<AccountId> {
         self.data().manager
     }
@@ -1173,7 +1447,7 @@ impl<T: Storage<Data>> Internal for T {
                 token_modify,
                 redeem_tokens,
                 borrow_amount,
-            });
+            })?;
 
         // These are safe, as the underflow condition is checked first
         let value = if sum_collateral > sum_borrow_plus_effect {
@@ -1221,22 +1495,16 @@ impl<T: Storage<Data>> Internal for T {
             if oracle_price == 0 {
                 return Err(Error::PriceError)
             }
-            let oracle_price_mantissa = Exp {
-                mantissa: WrappedU256::from(U256::from(oracle_price)),
-            };
+            let oracle_price_mantissa = Exp(U256::from(oracle_price));
 
             asset_params.push(HypotheticalAccountLiquidityCalculationParam {
                 asset: attr_pool,
                 decimals: pool_attribute.decimals,
                 token_balance: pool_attribute.account_balance,
                 borrow_balance: pool_attribute.account_borrow_balance,
-                exchange_rate_mantissa: Exp {
-                    mantissa: WrappedU256::from(pool_attribute.exchange_rate),
-                },
-                collateral_factor_mantissa: Exp {
-                    mantissa: collateral_factor_mantissa,
-                },
-                oracle_price_mantissa: oracle_price_mantissa.clone(),
+                exchange_rate_mantissa: Exp(pool_attribute.exchange_rate),
+                collateral_factor_mantissa: Exp::from(collateral_factor_mantissa),
+                oracle_price_mantissa,
             });
 
             let compounded_liquidity_balance = pool_attribute.account_balance;
@@ -1304,9 +1572,7 @@ impl<T: Storage<Data>> Internal for T {
             if oracle_price == 0 {
                 return Err(Error::PriceError)
             }
-            let oracle_price_mantissa = Exp {
-                mantissa: WrappedU256::from(U256::from(oracle_price)),
-            };
+            let oracle_price_mantissa = Exp(U256::from(oracle_price));
 
             let collateral_factor_mantissa = self
                 ._collateral_factor_mantissa(asset)
@@ -1318,13 +1584,9 @@ impl<T: Storage<Data>> Internal for T {
                 decimals,
                 token_balance: compounded_liquidity_balance,
                 borrow_balance: borrow_balance_stored,
-                exchange_rate_mantissa: Exp {
-                    mantissa: WrappedU256::from(exchange_rate_mantissa),
-                },
-                collateral_factor_mantissa: Exp {
-                    mantissa: collateral_factor_mantissa,
-                },
-                oracle_price_mantissa: oracle_price_mantissa.clone(),
+                exchange_rate_mantissa: Exp(exchange_rate_mantissa),
+                collateral_factor_mantissa: Exp::from(collateral_factor_mantissa),
+                oracle_price_mantissa,
             });
 
             // Calculate data for input to calculate the capacity of balance reduction with liquidation threshold
@@ -1459,5 +1721,23 @@ impl<T: Storage<Data>> Internal for T {
 
     default fn _emit_new_borrow_cap_event(&self, _pool: AccountId, _new: Balance) {}
 
+    default fn _emit_new_max_utilization_event(
+        &self,
+        _pool: AccountId,
+        _old: WrappedU256,
+        _new: WrappedU256,
+    ) {
+    }
+
+    default fn _emit_new_outflow_limit_event(&self, _pool: AccountId, _new: OutflowLimit) {}
+
+    default fn _emit_outflow_limit_raised_event(
+        &self,
+        _pool: AccountId,
+        _amount: Balance,
+        _expires_at: Timestamp,
+    ) {
+    }
+
     default fn _emit_manager_updated_event(&self, _old: AccountId, _new: AccountId) {}
 }
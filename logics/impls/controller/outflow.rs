@@ -0,0 +1,294 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{
+    impls::fixed_point::{
+        exp_scale,
+        mul_div_down,
+        to_balance,
+        Exp,
+        MathError,
+    },
+    traits::controller::{
+        OutflowLimit,
+        OutflowLimitRaise,
+    },
+};
+use openbrush::traits::{
+    Balance,
+    Timestamp,
+};
+use primitive_types::U256;
+use scale::{
+    Decode,
+    Encode,
+};
+
+/// Net outflow of a Pool, recorded per bucket of `window` length.
+///
+/// The outflow over the rolling window is approximated by weighting the previous bucket
+/// with the part of it still inside the window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct OutflowState {
+    /// Start of the current bucket
+    pub bucket_start: Timestamp,
+    /// Net outflow in the current bucket
+    pub current: Balance,
+    /// Net outflow in the previous bucket
+    pub previous: Balance,
+    /// Liquidity of the Pool at the start of the current bucket
+    pub liquidity: Balance,
+}
+
+/// Whether the limiter is enabled by the given limit
+pub fn outflow_limit_enabled(limit: &OutflowLimit) -> bool {
+    limit.window > 0 && !U256::from(limit.max_outflow_mantissa).is_zero()
+}
+
+/// Whether the given limit is valid to be set
+pub fn outflow_limit_valid(limit: &OutflowLimit) -> bool {
+    let max_outflow_mantissa = U256::from(limit.max_outflow_mantissa);
+    if max_outflow_mantissa.is_zero() {
+        return true
+    }
+    limit.window > 0 && max_outflow_mantissa <= exp_scale()
+}
+
+/// Move the state to the bucket containing `now`.
+/// `cash` is the current cash of the pool. It is the liquidity of a newly started bucket and raises
+/// the liquidity of the current one, so that a bucket started with little cash does not block the pool.
+pub fn roll_outflow_state(
+    state: Option<OutflowState>,
+    now: Timestamp,
+    window: Timestamp,
+    cash: Balance,
+) -> OutflowState {
+    let state = match state {
+        Some(state) => state,
+        None => {
+            return OutflowState {
+                bucket_start: now,
+                current: 0,
+                previous: 0,
+                liquidity: cash,
+            }
+        }
+    };
+    let elapsed = now.saturating_sub(state.bucket_start);
+    if elapsed < window {
+        return OutflowState {
+            liquidity: state.liquidity.max(cash),
+            ..state
+        }
+    }
+    let bucket_start = now - elapsed % window;
+    let previous = if elapsed < window * 2 {
+        state.current
+    } else {
+        0
+    };
+    OutflowState {
+        bucket_start,
+        current: 0,
+        previous,
+        liquidity: cash,
+    }
+}
+
+/// Net outflow over the rolling window ending at `now`
+pub fn outflow_in_window(
+    state: &OutflowState,
+    now: Timestamp,
+    window: Timestamp,
+) -> Result<Balance, MathError> {
+    let elapsed = now.saturating_sub(state.bucket_start).min(window);
+    let weighted_previous = to_balance(mul_div_down(
+        U256::from(state.previous),
+        U256::from(window - elapsed),
+        U256::from(window),
+    )?)?;
+    Ok(weighted_previous.saturating_add(state.current))
+}
+
+/// Maximum net outflow over the rolling window ending at `now`
+pub fn outflow_capacity(
+    limit: &OutflowLimit,
+    raise: Option<OutflowLimitRaise>,
+    state: &OutflowState,
+    now: Timestamp,
+) -> Result<Balance, MathError> {
+    let base = to_balance(
+        Exp::from(limit.max_outflow_mantissa).mul_int_down(U256::from(state.liquidity))?,
+    )?;
+    match raise {
+        Some(raise) if now < raise.expires_at => Ok(base.saturating_add(raise.amount)),
+        _ => Ok(base),
+    }
+}
+
+/// Amount that can still flow out over the rolling window ending at `now`
+pub fn remaining_outflow_capacity(
+    limit: &OutflowLimit,
+    raise: Option<OutflowLimitRaise>,
+    state: &OutflowState,
+    now: Timestamp,
+) -> Result<Balance, MathError> {
+    Ok(
+        outflow_capacity(limit, raise, state, now)?.saturating_sub(outflow_in_window(
+            state,
+            now,
+            limit.window,
+        )?),
+    )
+}
+
+/// Offset the inflow against the recorded outflow, starting from the current bucket
+pub fn offset_inflow(state: &OutflowState, amount: Balance) -> OutflowState {
+    let from_current = amount.min(state.current);
+    let from_previous = (amount - from_current).min(state.previous);
+    OutflowState {
+        current: state.current - from_current,
+        previous: state.previous - from_previous,
+        ..*state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::types::WrappedU256;
+    use core::ops::{
+        Div,
+        Mul,
+    };
+
+    const WINDOW: Timestamp = 60 * 60 * 1000;
+
+    fn limit(percent: u128) -> OutflowLimit {
+        OutflowLimit {
+            max_outflow_mantissa: WrappedU256::from(
+                exp_scale().mul(U256::from(percent)).div(U256::from(100)),
+            ),
+            window: WINDOW,
+        }
+    }
+
+    fn state(bucket_start: Timestamp, current: Balance, previous: Balance) -> OutflowState {
+        OutflowState {
+            bucket_start,
+            current,
+            previous,
+            liquidity: 10_000,
+        }
+    }
+
+    #[test]
+    fn test_outflow_limit_valid() {
+        assert!(outflow_limit_valid(&OutflowLimit::default()));
+        assert!(outflow_limit_valid(&limit(10)));
+        assert!(outflow_limit_valid(&limit(100)));
+        assert!(!outflow_limit_valid(&limit(101)));
+        assert!(!outflow_limit_valid(&OutflowLimit {
+            window: 0,
+            ..limit(10)
+        }));
+        assert!(!outflow_limit_enabled(&OutflowLimit::default()));
+        assert!(outflow_limit_enabled(&limit(10)));
+    }
+
+    #[test]
+    fn test_roll_outflow_state() {
+        // first record
+        assert_eq!(
+            roll_outflow_state(None, 100, WINDOW, 500),
+            OutflowState {
+                bucket_start: 100,
+                current: 0,
+                previous: 0,
+                liquidity: 500,
+            }
+        );
+        // same bucket
+        let s = state(100, 30, 20);
+        assert_eq!(
+            roll_outflow_state(Some(s), 100 + WINDOW - 1, WINDOW, 500),
+            s
+        );
+        // same bucket with more cash than at its start
+        assert_eq!(
+            roll_outflow_state(Some(s), 100 + WINDOW - 1, WINDOW, 50_000),
+            OutflowState {
+                liquidity: 50_000,
+                ..s
+            }
+        );
+        // next bucket
+        assert_eq!(
+            roll_outflow_state(Some(s), 100 + WINDOW + 5, WINDOW, 500),
+            OutflowState {
+                bucket_start: 100 + WINDOW,
+                current: 0,
+                previous: 30,
+                liquidity: 500,
+            }
+        );
+        // after a whole empty bucket
+        assert_eq!(
+            roll_outflow_state(Some(s), 100 + WINDOW * 3 + 5, WINDOW, 500),
+            OutflowState {
+                bucket_start: 100 + WINDOW * 3,
+                current: 0,
+                previous: 0,
+                liquidity: 500,
+            }
+        );
+    }
+
+    #[test]
+    fn test_outflow_in_window() {
+        let s = state(0, 30, 100);
+        assert_eq!(outflow_in_window(&s, 0, WINDOW), Ok(130));
+        assert_eq!(outflow_in_window(&s, WINDOW / 4, WINDOW), Ok(105));
+        assert_eq!(outflow_in_window(&s, WINDOW, WINDOW), Ok(30));
+    }
+
+    #[test]
+    fn test_remaining_outflow_capacity() {
+        let s = state(0, 300, 0);
+        // 10% of 10_000
+        assert_eq!(remaining_outflow_capacity(&limit(10), None, &s, 0), Ok(700));
+        let raise = OutflowLimitRaise {
+            amount: 500,
+            expires_at: 100,
+        };
+        assert_eq!(
+            remaining_outflow_capacity(&limit(10), Some(raise), &s, 99),
+            Ok(1200)
+        );
+        assert_eq!(
+            remaining_outflow_capacity(&limit(10), Some(raise), &s, 100),
+            Ok(700)
+        );
+        let exceeded = state(0, 2_000, 0);
+        assert_eq!(
+            remaining_outflow_capacity(&limit(10), None, &exceeded, 0),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_offset_inflow() {
+        let s = state(0, 30, 100);
+        assert_eq!(offset_inflow(&s, 10), state(0, 20, 100));
+        assert_eq!(offset_inflow(&s, 50), state(0, 0, 80));
+        assert_eq!(offset_inflow(&s, 1_000), state(0, 0, 0));
+    }
+}
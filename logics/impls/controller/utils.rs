@@ -1,21 +1,22 @@
-pub use crate::traits::controller::*;
-use crate::{
-    impls::{
-        exp_no_err::{
-            exp_scale,
-            Exp,
-        },
-        percent_math::Percent,
-        price_oracle::PRICE_PRECISION,
-        wad_ray_math::Wad,
+use crate::impls::{
+    fixed_point::{
+        checked_add,
+        checked_mul,
+        checked_sub,
+        exp_scale,
+        mul_div_down,
+        mul_div_up,
+        to_balance,
+        Exp,
+        Percent,
+        Wad,
     },
-    traits::types::WrappedU256,
+    price_oracle::PRICE_PRECISION,
 };
+pub use crate::traits::controller::*;
 use core::ops::{
-    Add,
     Div,
     Mul,
-    Sub,
 };
 use ink::prelude::vec::Vec;
 use openbrush::traits::{
@@ -37,26 +38,21 @@ pub struct LiquidateCalculateSeizeTokensInput {
 }
 
 /// Calculate seize value when liquidation
-pub fn liquidate_calculate_seize_tokens(input: &LiquidateCalculateSeizeTokensInput) -> Balance {
-    let numerator = Exp {
-        mantissa: WrappedU256::from(input.liquidation_incentive_mantissa),
-    }
-    .mul(Exp {
-        mantissa: WrappedU256::from(input.price_borrowed_mantissa),
-    });
-    let denominator = Exp {
-        mantissa: WrappedU256::from(input.price_collateral_mantissa),
-    }
-    .mul(Exp {
-        mantissa: WrappedU256::from(input.exchange_rate_mantissa),
-    });
-    let ratio = numerator.div(denominator);
-    let seize_tokens = ratio.mul_scalar_truncate(
-        U256::from(input.actual_repay_amount)
-            .mul(U256::from(10).pow(input.decimals_collateral.into()))
-            .div(U256::from(10).pow(input.decimals_borrowed.into())),
-    );
-    seize_tokens.as_u128()
+/// NOTE: Rounded down so that the liquidator never seizes more than the repayment is worth
+pub fn liquidate_calculate_seize_tokens(
+    input: &LiquidateCalculateSeizeTokensInput,
+) -> Result<Balance> {
+    let numerator =
+        Exp(input.liquidation_incentive_mantissa).mul_down(Exp(input.price_borrowed_mantissa))?;
+    let denominator =
+        Exp(input.price_collateral_mantissa).mul_up(Exp(input.exchange_rate_mantissa))?;
+    let ratio = numerator.div_down(denominator)?;
+    let seize_tokens = ratio.mul_int_down(mul_div_down(
+        U256::from(input.actual_repay_amount),
+        U256::from(10).pow(input.decimals_collateral.into()),
+        U256::from(10).pow(input.decimals_borrowed.into()),
+    )?)?;
+    Ok(to_balance(seize_tokens)?)
 }
 
 #[derive(Clone, Debug)]
@@ -69,37 +65,47 @@ pub struct BalanceDecreaseAllowedParam {
     pub total_debt_in_base_currency: U256,
 }
 
+/// NOTE: Returns false if the calculation fails
 pub fn balance_decrease_allowed(param: BalanceDecreaseAllowedParam) -> bool {
-    let amount_to_decrease_in_base_currency = param
-        .asset_price
-        .mul(param.amount_in_base_currency_unit)
-        .div(U256::from(PRICE_PRECISION));
+    health_factor_after_decrease(param)
+        .map(|health_factor| health_factor >= U256::from(HEALTH_FACTOR_LIQUIDATION_THRESHOLD))
+        .unwrap_or(false)
+}
 
-    if param.total_collateral_in_base_currency < amount_to_decrease_in_base_currency {
-        return false
-    }
+fn health_factor_after_decrease(param: BalanceDecreaseAllowedParam) -> Result<U256> {
+    // the decreased amount is rounded up, the remaining collateral down
+    let amount_to_decrease_in_base_currency = mul_div_up(
+        param.asset_price,
+        param.amount_in_base_currency_unit,
+        U256::from(PRICE_PRECISION),
+    )?;
 
-    let collateral_balance_after_decrease = param
-        .total_collateral_in_base_currency
-        .sub(amount_to_decrease_in_base_currency);
+    let collateral_balance_after_decrease = checked_sub(
+        param.total_collateral_in_base_currency,
+        amount_to_decrease_in_base_currency,
+    )?;
 
     if collateral_balance_after_decrease.is_zero() {
-        return false
+        return Ok(U256::zero())
     }
 
-    let liquidation_threshold_after_decrease = param
-        .total_collateral_in_base_currency
-        .mul(param.avg_liquidation_threshold)
-        .sub(amount_to_decrease_in_base_currency.mul(param.liquidation_threshold))
-        .div(collateral_balance_after_decrease);
-
-    let health_factor_after_decrease = calculate_health_factor_from_balances(
+    let liquidation_threshold_after_decrease = checked_sub(
+        checked_mul(
+            param.total_collateral_in_base_currency,
+            param.avg_liquidation_threshold,
+        )?,
+        checked_mul(
+            amount_to_decrease_in_base_currency,
+            param.liquidation_threshold,
+        )?,
+    )?
+    .div(collateral_balance_after_decrease);
+
+    Ok(calculate_health_factor_from_balances(
         collateral_balance_after_decrease,
         param.total_debt_in_base_currency,
         liquidation_threshold_after_decrease,
-    );
-
-    health_factor_after_decrease >= U256::from(HEALTH_FACTOR_LIQUIDATION_THRESHOLD)
+    ))
 }
 
 /// Maximum value of Collateral Factor
@@ -129,7 +135,7 @@ pub struct HypotheticalAccountLiquidityCalculationParam {
 /// NOTE: This function has no state and calculates its arguments as source information
 pub fn get_hypothetical_account_liquidity(
     input: GetHypotheticalAccountLiquidityInput,
-) -> (U256, U256) {
+) -> Result<(U256, U256)> {
     let mut sum_collateral = U256::from(0);
     let mut sum_borrow_plus_effect = U256::from(0);
 
@@ -146,45 +152,57 @@ pub fn get_hypothetical_account_liquidity(
                 param.token_balance,
                 param.borrow_balance,
                 param.decimals,
-                param.exchange_rate_mantissa.clone(),
-                param.collateral_factor_mantissa.clone(),
-                param.oracle_price_mantissa.clone(),
-            );
+                param.exchange_rate_mantissa,
+                param.collateral_factor_mantissa,
+                param.oracle_price_mantissa,
+            )?;
 
-        sum_collateral = sum_collateral.add(collateral);
-        sum_borrow_plus_effect = sum_borrow_plus_effect.add(borrow_plus_effect);
+        sum_collateral = checked_add(sum_collateral, collateral)?;
+        sum_borrow_plus_effect = checked_add(sum_borrow_plus_effect, borrow_plus_effect)?;
 
         // Calculate effects of interacting with cTokenModify
         if token_modify.is_some() && param.asset == token_modify.unwrap() {
-            let to_flatten = |volume: U256| {
-                volume
-                    .mul(exp_scale())
-                    .div(U256::from(10_u128.pow(param.decimals.into())))
-            };
             // redeem effect
             // sumBorrowPlusEffects += tokensToDenom * redeemTokens
-            sum_borrow_plus_effect = token_to_denom.clone().mul_scalar_truncate_add_uint(
-                to_flatten(U256::from(redeem_tokens)),
+            sum_borrow_plus_effect = checked_add(
+                token_to_denom
+                    .mul_int_up(to_flatten_up(U256::from(redeem_tokens), param.decimals)?)?,
                 sum_borrow_plus_effect,
-            );
+            )?;
 
             // borrow effect
             // sumBorrowPlusEffects += oraclePrice * borrowAmount
-            sum_borrow_plus_effect = param
-                .oracle_price_mantissa
-                .clone()
-                .mul_scalar_truncate_add_uint(
-                    to_flatten(U256::from(borrow_amount)),
-                    sum_borrow_plus_effect,
-                );
+            sum_borrow_plus_effect = checked_add(
+                param
+                    .oracle_price_mantissa
+                    .mul_int_up(to_flatten_up(U256::from(borrow_amount), param.decimals)?)?,
+                sum_borrow_plus_effect,
+            )?;
         }
     }
 
-    (sum_collateral, sum_borrow_plus_effect)
+    Ok((sum_collateral, sum_borrow_plus_effect))
+}
+
+fn to_flatten_down(volume: U256, decimals: u8) -> Result<U256> {
+    Ok(mul_div_down(
+        volume,
+        exp_scale(),
+        U256::from(10_u128.pow(decimals.into())),
+    )?)
+}
+
+fn to_flatten_up(volume: U256, decimals: u8) -> Result<U256> {
+    Ok(mul_div_up(
+        volume,
+        exp_scale(),
+        U256::from(10_u128.pow(decimals.into())),
+    )?)
 }
 
 /// Calculate the available capacity in a pool for a given user
 /// NOTE: This function has no state and calculates its arguments as source information
+/// NOTE: Collateral is rounded down and borrows are rounded up
 pub fn get_hypothetical_account_liquidity_per_asset(
     token_balance: Balance,
     borrow_balance: Balance,
@@ -192,36 +210,28 @@ pub fn get_hypothetical_account_liquidity_per_asset(
     exchange_rate_mantissa: Exp,
     collateral_factor_mantissa: Exp,
     oracle_price_mantissa: Exp,
-) -> (Exp, U256, U256) {
+) -> Result<(Exp, U256, U256)> {
     // Pre-compute a conversion factor from tokens -> base token (normalized price value)
     let token_to_denom = collateral_factor_mantissa
-        .mul(exchange_rate_mantissa)
-        .mul(oracle_price_mantissa.clone());
+        .mul_down(exchange_rate_mantissa)?
+        .mul_down(oracle_price_mantissa)?;
 
-    let to_flatten = |volume: U256| {
-        volume
-            .mul(exp_scale())
-            .div(U256::from(10_u128.pow(decimals.into())))
-    };
     // sumCollateral += tokensToDenom * cTokenBalance
-    let collateral = token_to_denom
-        .clone()
-        .mul_scalar_truncate(U256::from(token_balance));
-    let flatten_collateral = to_flatten(collateral);
+    let collateral = token_to_denom.mul_int_down(U256::from(token_balance))?;
+    let flatten_collateral = to_flatten_down(collateral, decimals)?;
     // sumBorrowPlusEffects += oraclePrice * borrowBalance
-    let borrow_plus_effect = oracle_price_mantissa
-        .clone()
-        .mul_scalar_truncate(U256::from(borrow_balance));
-    let flatten_borrow_plus_effect = to_flatten(borrow_plus_effect);
+    let borrow_plus_effect = oracle_price_mantissa.mul_int_up(U256::from(borrow_balance))?;
+    let flatten_borrow_plus_effect = to_flatten_up(borrow_plus_effect, decimals)?;
 
-    return (
+    Ok((
         token_to_denom,
         flatten_collateral,
         flatten_borrow_plus_effect,
-    )
+    ))
 }
 
 /// Calculate Health Factor from Balance
+/// NOTE: Rounded down, returns 0 if the calculation fails
 pub fn calculate_health_factor_from_balances(
     total_collateral_in_base_currency: U256,
     total_debt_in_base_currency: U256,
@@ -231,54 +241,35 @@ pub fn calculate_health_factor_from_balances(
         return U256::MAX
     }
 
-    let percent_mul_result = (Percent {
-        percentage: liquidation_threshold,
-    })
-    .percent_mul(total_collateral_in_base_currency);
-
-    if percent_mul_result.is_err() {
-        return U256::from(0)
-    }
-
-    let wad_div_result = (Wad {
-        mantissa: WrappedU256::from(percent_mul_result.unwrap()),
-    })
-    .wad_div(Wad {
-        mantissa: WrappedU256::from(total_debt_in_base_currency),
-    });
-
-    if wad_div_result.is_err() {
-        return U256::from(0)
-    }
-
-    U256::from(wad_div_result.unwrap().mantissa)
+    Percent(liquidation_threshold)
+        .mul_int_down(total_collateral_in_base_currency)
+        .and_then(|collateral| Wad(collateral).div_down(Wad(total_debt_in_base_currency)))
+        .map(|health_factor| health_factor.raw())
+        .unwrap_or_default()
 }
 
+/// NOTE: Rounded down, returns 0 if the calculation fails
 pub fn calculate_available_borrow_in_base_currency(
     total_collateral_in_base_currency: U256,
     total_debt_in_base_currency: U256,
     ltv: U256,
 ) -> U256 {
-    let percent_mul_result =
-        (Percent { percentage: ltv }).percent_mul(total_collateral_in_base_currency);
-
-    if percent_mul_result.is_err() {
-        return U256::from(0)
-    }
-
-    let available_borrows_in_base_currency = percent_mul_result.unwrap();
-    if available_borrows_in_base_currency < total_debt_in_base_currency {
-        return U256::from(0)
-    }
-
-    return available_borrows_in_base_currency.sub(total_debt_in_base_currency)
+    Percent(ltv)
+        .mul_int_down(total_collateral_in_base_currency)
+        .ok()
+        .and_then(|available| available.checked_sub(total_debt_in_base_currency))
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::impls::exp_no_err::exp_scale;
+    use crate::impls::fixed_point::{
+        exp_scale,
+        MathError,
+    };
     use core::ops::{
+        Add,
         Div,
         Mul,
     };
@@ -372,7 +363,7 @@ mod tests {
             },
         ];
         for case in cases {
-            let got = liquidate_calculate_seize_tokens(case.input);
+            let got = liquidate_calculate_seize_tokens(case.input).unwrap();
             //  seize_amount = actual_repay_amount * liquidation_incentive * price_borrowed / price_collateral
 
             //  seize_tokens = seize_amount / exchange_rate
@@ -389,6 +380,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_liquidate_calculate_seize_tokens_rounds_down() {
+        let input = LiquidateCalculateSeizeTokensInput {
+            price_borrowed_mantissa: mts(1),
+            decimals_borrowed: 0,
+            price_collateral_mantissa: mts(3),
+            decimals_collateral: 0,
+            exchange_rate_mantissa: mts(1),
+            liquidation_incentive_mantissa: mts(1),
+            actual_repay_amount: 5,
+        };
+        assert_eq!(liquidate_calculate_seize_tokens(&input).unwrap(), 1);
+
+        let overflow = LiquidateCalculateSeizeTokensInput {
+            price_collateral_mantissa: U256::one(),
+            exchange_rate_mantissa: U256::one(),
+            liquidation_incentive_mantissa: U256::MAX,
+            ..input
+        };
+        assert_eq!(
+            liquidate_calculate_seize_tokens(&overflow),
+            Err(Error::Math(MathError::MathMultiplicationOverflow))
+        );
+    }
+
+    #[test]
+    fn test_get_hypothetical_account_liquidity_per_asset_rounding() {
+        // 1 token of 6 decimals at a price of 1/3, 50% collateral factor
+        let price = Exp(mts(1).div(U256::from(3)));
+        let (_, collateral, borrow_plus_effect) = get_hypothetical_account_liquidity_per_asset(
+            1,
+            1,
+            6,
+            Exp(mts(1)),
+            Exp(mts(1).div(U256::from(2))),
+            price,
+        )
+        .unwrap();
+        // collateral is rounded down and borrows are rounded up
+        assert_eq!(collateral, U256::zero());
+        assert_eq!(borrow_plus_effect, U256::from(10_u128.pow(12)));
+    }
+
     #[test]
     fn test_get_hypothetical_account_liquidity_per_asset() {
         let mantissa = 10_u128.pow(18);
@@ -460,16 +494,11 @@ mod tests {
                 case.input.token_balance,
                 case.input.borrow_balance,
                 case.input.decimals,
-                Exp {
-                    mantissa: WrappedU256::from(U256::from(case.input.exchange_rate_mantissa)),
-                },
-                Exp {
-                    mantissa: WrappedU256::from(U256::from(case.input.collateral_factor_mantissa)),
-                },
-                Exp {
-                    mantissa: WrappedU256::from(U256::from(case.input.oracle_price_mantissa)),
-                },
-            );
+                Exp(U256::from(case.input.exchange_rate_mantissa)),
+                Exp(U256::from(case.input.collateral_factor_mantissa)),
+                Exp(U256::from(case.input.oracle_price_mantissa)),
+            )
+            .unwrap();
             assert_eq!(collateral, U256::from(case.expected.collateral));
             assert_eq!(
                 borrow_plus_effect,
@@ -483,11 +512,7 @@ mod tests {
         let mantissa = 10_u128.pow(18);
         let pow10_6 = 10_u128.pow(6);
         let pow10_18 = 10_u128.pow(18);
-        let to_exp = |val: u128| {
-            Exp {
-                mantissa: WrappedU256::from(U256::from(val)),
-            }
-        };
+        let to_exp = |val: u128| Exp(U256::from(val));
 
         struct Case {
             input: GetHypotheticalAccountLiquidityInput,
@@ -681,7 +706,7 @@ mod tests {
 
         for case in cases {
             let (sum_collateral, sum_borrow_plus_effect) =
-                get_hypothetical_account_liquidity(case.input);
+                get_hypothetical_account_liquidity(case.input).unwrap();
             assert_eq!(sum_collateral, U256::from(case.expected.sum_collateral));
             assert_eq!(
                 sum_borrow_plus_effect,
@@ -716,6 +741,13 @@ mod tests {
                 liquidation_threshold: one_percent,
                 expected: one,
             },
+            Case {
+                name: "rounded down",
+                total_collateral_in_base_currency: one,
+                total_debt_in_base_currency: U256::from(3).mul(one),
+                liquidation_threshold: one_percent.mul(U256::from(100)),
+                expected: U256::from(333_333_333_333_333_333_u128),
+            },
         ];
         for case in cases {
             let got = calculate_health_factor_from_balances(
@@ -796,6 +828,19 @@ mod tests {
                 },
                 expected: false,
             },
+            Case {
+                name: "one unit over the boundary of liquidation threshold: fail",
+                input: BalanceDecreaseAllowedParam {
+                    asset_price: price_one,
+                    avg_liquidation_threshold: one_percent.mul(U256::from(80)),
+                    liquidation_threshold: one_percent.mul(U256::from(80)),
+                    total_collateral_in_base_currency: one.mul(U256::from(110)),
+                    total_debt_in_base_currency: one.mul(U256::from(80)),
+                    // the health factor is rounded down
+                    amount_in_base_currency_unit: one.mul(U256::from(10)).add(U256::from(1)),
+                },
+                expected: false,
+            },
             Case {
                 name: "health factor is 2",
                 input: BalanceDecreaseAllowedParam {
@@ -0,0 +1,174 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::flashloan_adapter::{
+    Data,
+    Internal as FlashloanAdapterInternal,
+    ReceivedFlashloan,
+};
+pub use crate::traits::debt_swap::*;
+use crate::traits::flashloan_gateway::FlashLoanType;
+use ink::prelude::vec::Vec;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+use scale::Encode;
+
+pub trait Internal {
+    fn _swap_debt(
+        &mut self,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        max_new_debt_amount: Balance,
+    ) -> Result<()>;
+
+    /// Body of `FlashloanReceiver::execute_operation`
+    fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()>;
+
+    // event emission
+    fn _emit_debt_swap_event(
+        &self,
+        user: AccountId,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        new_debt_amount: Balance,
+    );
+}
+
+impl<T: Storage<Data>> DebtSwap for T {
+    default fn flashloan_gateway(&self) -> Option<AccountId> {
+        self._flashloan_gateway()
+    }
+
+    default fn controller(&self) -> Option<AccountId> {
+        self._controller()
+    }
+
+    default fn router(&self) -> Option<AccountId> {
+        self._router()
+    }
+
+    default fn swap_debt(
+        &mut self,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        max_new_debt_amount: Balance,
+    ) -> Result<()> {
+        self._swap_debt(
+            debt_asset_from,
+            debt_asset_to,
+            repay_amount,
+            max_new_debt_amount,
+        )
+    }
+}
+
+impl<T: Storage<Data>> Internal for T {
+    default fn _swap_debt(
+        &mut self,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        max_new_debt_amount: Balance,
+    ) -> Result<()> {
+        if debt_asset_from == debt_asset_to {
+            return Err(Error::SameAsset)
+        }
+        self._pool_of(debt_asset_to)?;
+
+        let caller = Self::env().caller();
+        let borrow_balance = self._borrow_balance_of(debt_asset_from, caller)?;
+        let repay_amount = repay_amount.min(borrow_balance);
+        if repay_amount == 0 {
+            return Err(Error::NoBorrowToRepay)
+        }
+
+        // open as much new debt as the swap needs to repay the old one
+        let new_debt_amount = self._get_amount_in(debt_asset_to, debt_asset_from, repay_amount)?;
+        if new_debt_amount > max_new_debt_amount {
+            return Err(Error::ExcessiveNewDebtAmount)
+        }
+
+        let params = DebtSwapParams {
+            user: caller,
+            debt_asset_from,
+            repay_amount,
+        };
+        self._flashloan(
+            debt_asset_to,
+            new_debt_amount,
+            FlashLoanType::Borrowing,
+            params.encode(),
+        )?;
+        Ok(())
+    }
+
+    default fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()> {
+        let ReceivedFlashloan {
+            asset: debt_asset_to,
+            amount: new_debt_amount,
+            params:
+                DebtSwapParams {
+                    user,
+                    debt_asset_from,
+                    repay_amount,
+                },
+            ..
+        } = self._receive_flashloan(assets, amounts, premiums, initiator, params)?;
+
+        // the flashloan is kept as the user's new debt, so only the old one has to be repaid
+        let amount_in = self._swap_exact_out(
+            debt_asset_to,
+            debt_asset_from,
+            repay_amount,
+            new_debt_amount,
+        )?;
+        self._repay_on_behalf(debt_asset_from, user, repay_amount)?;
+
+        if amount_in < new_debt_amount {
+            self._transfer(debt_asset_to, user, new_debt_amount - amount_in)?;
+        }
+
+        self._emit_debt_swap_event(
+            user,
+            debt_asset_from,
+            debt_asset_to,
+            repay_amount,
+            new_debt_amount,
+        );
+        Ok(())
+    }
+
+    default fn _emit_debt_swap_event(
+        &self,
+        _user: AccountId,
+        _debt_asset_from: AccountId,
+        _debt_asset_to: AccountId,
+        _repay_amount: Balance,
+        _new_debt_amount: Balance,
+    ) {
+    }
+}
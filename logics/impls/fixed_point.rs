@@ -0,0 +1,370 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checked fixed-point arithmetic.
+//!
+//! Every operation returns `MathError` instead of panicking or wrapping, and every
+//! multiplication / division states its rounding direction explicitly so that callers
+//! can decide which side of the rounding the protocol should be on.
+
+pub use crate::traits::types::MathError;
+use crate::traits::types::WrappedU256;
+use core::ops::{
+    Add,
+    Div,
+    Rem,
+};
+use openbrush::traits::Balance;
+use primitive_types::{
+    U256,
+    U512,
+};
+
+pub type Result<T> = core::result::Result<T, MathError>;
+
+/// Calculates `a * b / denominator`, rounded down
+pub fn mul_div_down(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    if denominator.is_zero() {
+        return Err(MathError::MathDivisionByZero)
+    }
+    narrow(a.full_mul(b).div(U512::from(denominator)))
+}
+
+/// Calculates `a * b / denominator`, rounded up
+pub fn mul_div_up(a: U256, b: U256, denominator: U256) -> Result<U256> {
+    if denominator.is_zero() {
+        return Err(MathError::MathDivisionByZero)
+    }
+    let product = a.full_mul(b);
+    let denominator = U512::from(denominator);
+    let quotient = product.div(denominator);
+    if product.rem(denominator).is_zero() {
+        return narrow(quotient)
+    }
+    narrow(quotient.add(U512::one()))
+}
+
+/// Converts a value into `Balance`, failing instead of truncating the upper bits
+pub fn to_balance(value: U256) -> Result<Balance> {
+    if value > U256::from(Balance::MAX) {
+        return Err(MathError::MathConversionOverflow)
+    }
+    Ok(value.as_u128())
+}
+
+pub fn checked_add(a: U256, b: U256) -> Result<U256> {
+    a.checked_add(b).ok_or(MathError::MathAdditionOverflow)
+}
+
+pub fn checked_sub(a: U256, b: U256) -> Result<U256> {
+    a.checked_sub(b).ok_or(MathError::MathSubtractionUnderflow)
+}
+
+pub fn checked_mul(a: U256, b: U256) -> Result<U256> {
+    a.checked_mul(b)
+        .ok_or(MathError::MathMultiplicationOverflow)
+}
+
+/// Scale of `Exp`, i.e. the mantissa of one
+pub fn exp_scale() -> U256 {
+    Exp::scale()
+}
+
+fn narrow(value: U512) -> Result<U256> {
+    U256::try_from(value).map_err(|_| MathError::MathMultiplicationOverflow)
+}
+
+macro_rules! fixed_point {
+    ($(#[$attr:meta])* $name:ident, $scale:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+        pub struct $name(pub U256);
+
+        impl $name {
+            pub fn scale() -> U256 {
+                U256::from($scale)
+            }
+
+            pub fn one() -> Self {
+                Self(Self::scale())
+            }
+
+            pub fn zero() -> Self {
+                Self(U256::zero())
+            }
+
+            /// Scales an integer up to the fixed-point representation
+            pub fn from_int(value: U256) -> Result<Self> {
+                checked_mul(value, Self::scale()).map(Self)
+            }
+
+            pub fn raw(&self) -> U256 {
+                self.0
+            }
+
+            pub fn is_zero(&self) -> bool {
+                self.0.is_zero()
+            }
+
+            pub fn checked_add(&self, other: Self) -> Result<Self> {
+                checked_add(self.0, other.0).map(Self)
+            }
+
+            pub fn checked_sub(&self, other: Self) -> Result<Self> {
+                checked_sub(self.0, other.0).map(Self)
+            }
+
+            pub fn mul_down(&self, other: Self) -> Result<Self> {
+                mul_div_down(self.0, other.0, Self::scale()).map(Self)
+            }
+
+            pub fn mul_up(&self, other: Self) -> Result<Self> {
+                mul_div_up(self.0, other.0, Self::scale()).map(Self)
+            }
+
+            pub fn div_down(&self, other: Self) -> Result<Self> {
+                mul_div_down(self.0, Self::scale(), other.0).map(Self)
+            }
+
+            pub fn div_up(&self, other: Self) -> Result<Self> {
+                mul_div_up(self.0, Self::scale(), other.0).map(Self)
+            }
+
+            /// Multiplies an integer by this value and truncates the result to an integer
+            pub fn mul_int_down(&self, value: U256) -> Result<U256> {
+                mul_div_down(value, self.0, Self::scale())
+            }
+
+            /// Multiplies an integer by this value and rounds the result up to an integer
+            pub fn mul_int_up(&self, value: U256) -> Result<U256> {
+                mul_div_up(value, self.0, Self::scale())
+            }
+
+            /// Divides an integer by this value and truncates the result to an integer
+            pub fn div_int_down(&self, value: U256) -> Result<U256> {
+                mul_div_down(value, Self::scale(), self.0)
+            }
+
+            /// Divides an integer by this value and rounds the result up to an integer
+            pub fn div_int_up(&self, value: U256) -> Result<U256> {
+                mul_div_up(value, Self::scale(), self.0)
+            }
+        }
+
+        impl From<WrappedU256> for $name {
+            fn from(value: WrappedU256) -> Self {
+                Self(U256::from(value))
+            }
+        }
+
+        impl From<$name> for WrappedU256 {
+            fn from(value: $name) -> Self {
+                WrappedU256::from(value.0)
+            }
+        }
+    };
+}
+
+fixed_point!(
+    /// Mantissa with 18 decimals, used for rates, prices and factors
+    Exp,
+    10_u128.pow(18)
+);
+fixed_point!(
+    /// Value with 18 decimals, used for health factors and base currency amounts
+    Wad,
+    10_u128.pow(18)
+);
+fixed_point!(
+    /// Value with 27 decimals, used where the extra precision of intermediate terms matters
+    Ray,
+    10_u128.pow(27)
+);
+fixed_point!(
+    /// Percentage with 4 decimals (10000 = 100%)
+    Percent,
+    10_u128.pow(4)
+);
+
+impl From<Exp> for Wad {
+    fn from(value: Exp) -> Self {
+        Wad(value.0)
+    }
+}
+
+impl From<Wad> for Exp {
+    fn from(value: Wad) -> Self {
+        Exp(value.0)
+    }
+}
+
+/// Ratio between the scales of `Ray` and `Exp`
+pub fn exp_ray_ratio() -> U256 {
+    Ray::scale().div(Exp::scale())
+}
+
+impl Exp {
+    pub fn to_ray(&self) -> Result<Ray> {
+        checked_mul(self.0, exp_ray_ratio()).map(Ray)
+    }
+}
+
+impl Ray {
+    /// Converts to `Exp`, truncating the extra decimals
+    pub fn to_exp_down(&self) -> Exp {
+        Exp(self.0.div(exp_ray_ratio()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exp(val: u128) -> Exp {
+        Exp(U256::from(val))
+    }
+
+    #[test]
+    fn test_mul_div_rounding() {
+        assert_eq!(
+            mul_div_down(U256::from(5), U256::from(1), U256::from(3)),
+            Ok(U256::from(1))
+        );
+        assert_eq!(
+            mul_div_up(U256::from(5), U256::from(1), U256::from(3)),
+            Ok(U256::from(2))
+        );
+        assert_eq!(
+            mul_div_up(U256::from(6), U256::from(1), U256::from(3)),
+            Ok(U256::from(2))
+        );
+        assert_eq!(
+            mul_div_down(U256::one(), U256::one(), U256::zero()),
+            Err(MathError::MathDivisionByZero)
+        );
+        assert_eq!(
+            mul_div_up(U256::one(), U256::one(), U256::zero()),
+            Err(MathError::MathDivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_does_not_overflow_on_intermediate_product() {
+        // MAX * 2 / 4 does not fit in U256 before the division but the result does
+        assert_eq!(
+            mul_div_down(U256::MAX, U256::from(2), U256::from(4)),
+            Ok(U256::MAX.div(U256::from(2)))
+        );
+        assert_eq!(
+            mul_div_down(U256::MAX, U256::from(2), U256::one()),
+            Err(MathError::MathMultiplicationOverflow)
+        );
+        assert_eq!(
+            mul_div_up(U256::MAX, U256::one(), U256::one()),
+            Ok(U256::MAX)
+        );
+    }
+
+    #[test]
+    fn test_checked_ops() {
+        assert_eq!(
+            checked_add(U256::MAX, U256::one()),
+            Err(MathError::MathAdditionOverflow)
+        );
+        assert_eq!(
+            checked_sub(U256::zero(), U256::one()),
+            Err(MathError::MathSubtractionUnderflow)
+        );
+        assert_eq!(
+            exp(1).checked_sub(exp(2)),
+            Err(MathError::MathSubtractionUnderflow)
+        );
+        assert_eq!(exp(1).checked_add(exp(2)), Ok(exp(3)));
+        assert_eq!(
+            Exp::from_int(U256::MAX),
+            Err(MathError::MathMultiplicationOverflow)
+        );
+    }
+
+    #[test]
+    fn test_to_balance() {
+        assert_eq!(to_balance(U256::from(Balance::MAX)), Ok(Balance::MAX));
+        assert_eq!(
+            to_balance(U256::from(Balance::MAX).add(U256::one())),
+            Err(MathError::MathConversionOverflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_and_div() {
+        let one_and_half = exp(15 * 10_u128.pow(17));
+        let third = Exp::one().div_down(exp(3 * 10_u128.pow(18))).unwrap();
+        assert_eq!(third, exp(333_333_333_333_333_333));
+        assert_eq!(
+            Exp::one().div_up(exp(3 * 10_u128.pow(18))).unwrap(),
+            exp(333_333_333_333_333_334)
+        );
+        assert_eq!(
+            one_and_half.mul_down(one_and_half).unwrap(),
+            exp(225 * 10_u128.pow(16))
+        );
+        assert_eq!(third.mul_down(exp(2)).unwrap(), exp(0));
+        assert_eq!(third.mul_up(exp(2)).unwrap(), exp(1));
+        assert_eq!(
+            Exp::one().div_down(Exp::zero()),
+            Err(MathError::MathDivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_int_ops() {
+        let rate = exp(15 * 10_u128.pow(17)); // 1.5
+        assert_eq!(rate.mul_int_down(U256::from(3)), Ok(U256::from(4)));
+        assert_eq!(rate.mul_int_up(U256::from(3)), Ok(U256::from(5)));
+        assert_eq!(rate.div_int_down(U256::from(4)), Ok(U256::from(2)));
+        assert_eq!(rate.div_int_up(U256::from(4)), Ok(U256::from(3)));
+    }
+
+    #[test]
+    fn test_percent() {
+        let eighty = Percent(U256::from(8000));
+        assert_eq!(eighty.mul_int_down(U256::from(99)), Ok(U256::from(79)));
+        assert_eq!(eighty.mul_int_up(U256::from(99)), Ok(U256::from(80)));
+        assert_eq!(eighty.div_int_down(U256::from(100)), Ok(U256::from(125)));
+        assert_eq!(
+            Percent::zero().div_int_down(U256::one()),
+            Err(MathError::MathDivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_ray() {
+        let rate = exp(15 * 10_u128.pow(17)); // 1.5
+        let ray = rate.to_ray().unwrap();
+        assert_eq!(ray, Ray(U256::from(15 * 10_u128.pow(26))));
+        assert_eq!(ray.to_exp_down(), rate);
+        assert_eq!(
+            Ray(U256::from(10_u128.pow(9) - 1)).to_exp_down(),
+            Exp::zero()
+        );
+        assert_eq!(
+            ray.mul_down(ray).unwrap(),
+            Ray(U256::from(225 * 10_u128.pow(25)))
+        );
+        assert_eq!(
+            Exp(U256::MAX).to_ray(),
+            Err(MathError::MathMultiplicationOverflow)
+        );
+    }
+
+    #[test]
+    fn test_wrapped_conversion() {
+        let wrapped = WrappedU256::from(U256::from(42));
+        assert_eq!(Exp::from(wrapped), exp(42));
+        assert_eq!(WrappedU256::from(exp(42)), wrapped);
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp22::PSP22Ref,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+    },
+};
+
+pub use crate::traits::flash_borrower::*;
+
+pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+#[derive(Debug, Default)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct Data {
+    /// AccountId of Flash Lender
+    pub flash_lender: Option<AccountId>,
+    /// For mock only: Set flash loan callback as success or fail
+    pub fail_execution: bool,
+}
+
+pub trait Internal {
+    fn _initialize(&mut self, flash_lender: AccountId);
+    fn _set_fail_execution(&mut self, fail: bool);
+    fn _fail_execution(&self) -> bool;
+}
+
+impl<T: Storage<Data>> Internal for T {
+    default fn _initialize(&mut self, flash_lender: AccountId) {
+        self.data().flash_lender = Some(flash_lender);
+    }
+
+    default fn _set_fail_execution(&mut self, fail: bool) {
+        self.data().fail_execution = fail;
+    }
+
+    default fn _fail_execution(&self) -> bool {
+        self.data().fail_execution
+    }
+}
+
+impl<T: Storage<Data>> FlashBorrower for T {
+    default fn on_flash_loan(
+        &mut self,
+        initiator: AccountId,
+        token: AccountId,
+        amount: Balance,
+        fee: Balance,
+        _data: Vec<u8>,
+    ) -> [u8; 32] {
+        let failure = [0; 32];
+        if self._fail_execution() {
+            return failure
+        }
+        let lender = match self.data().flash_lender {
+            Some(lender) => lender,
+            None => return failure,
+        };
+        if Self::env().caller() != lender {
+            return failure
+        }
+
+        let contract_addr = Self::env().account_id();
+        if PSP22Ref::transfer_from(&token, initiator, contract_addr, fee, Vec::<u8>::new()).is_err()
+        {
+            return failure
+        }
+
+        let amount_to_return = amount + fee;
+        if PSP22Ref::balance_of(&token, contract_addr) < amount_to_return {
+            return failure
+        }
+        if PSP22Ref::approve(&token, lender, amount_to_return).is_err() {
+            return failure
+        }
+
+        FLASH_BORROWER_CALLBACK_SUCCESS
+    }
+}
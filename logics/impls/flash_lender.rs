@@ -0,0 +1,88 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{
+    impls::flashloan_gateway::{
+        Data,
+        Error,
+        Internal,
+        Result,
+        FLASHLOAN_PREMIUM_DENOMINATOR,
+        MINUMUM_FLASHLOAN_AMOUNT,
+    },
+    traits::{
+        controller::ControllerRef,
+        flash_borrower::{
+            FlashBorrowerRef,
+            FLASH_BORROWER_CALLBACK_SUCCESS,
+        },
+        pool::PoolRef,
+    },
+};
+use ink::prelude::vec::Vec;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+
+pub use crate::traits::flash_lender::*;
+
+impl<T: Storage<Data>> FlashLender for T {
+    default fn max_flash_loan(&self, token: AccountId) -> Balance {
+        let market = match self
+            ._controller()
+            .and_then(|controller| ControllerRef::market_of_underlying(&controller, token))
+        {
+            Some(market) => market,
+            None => return 0,
+        };
+        PoolRef::get_cash_prior(&market)
+    }
+
+    default fn flash_fee(&self, token: AccountId, amount: Balance) -> Result<Balance> {
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        ControllerRef::market_of_underlying(&controller, token).ok_or(Error::MarketNotListed)?;
+        Ok(amount * self._flashloan_premium_of(Self::env().caller())
+            / FLASHLOAN_PREMIUM_DENOMINATOR)
+    }
+
+    default fn flash_loan(
+        &self,
+        receiver: AccountId,
+        token: AccountId,
+        amount: Balance,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        if self._whitelist_only() && !self._is_whitelisted_receiver(receiver) {
+            return Err(Error::ReceiverIsNotWhitelisted)
+        }
+        if amount < MINUMUM_FLASHLOAN_AMOUNT {
+            return Err(Error::InvalidFlashloanAmount)
+        }
+
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        let market = ControllerRef::market_of_underlying(&controller, token)
+            .ok_or(Error::MarketNotListed)?;
+        let caller = Self::env().caller();
+        let fee = amount * self._flashloan_premium_of(caller) / FLASHLOAN_PREMIUM_DENOMINATOR;
+
+        PoolRef::transfer_underlying(&market, receiver, amount)?;
+
+        let callback_result =
+            FlashBorrowerRef::on_flash_loan(&receiver, caller, token, amount, fee, data);
+        if callback_result != FLASH_BORROWER_CALLBACK_SUCCESS {
+            return Err(Error::InvalidFlashloanExecutorReturn)
+        }
+
+        let fee_to_protocol = self._repay_flashloan(receiver, token, market, amount, fee)?;
+
+        self._emit_flashloan_event(receiver, caller, token, amount, fee, fee_to_protocol);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{
+    controller::ControllerRef,
+    pool::PoolRef,
+};
+pub use crate::traits::flashloan_adapter::*;
+use crate::traits::{
+    dex_router::DexRouterRef,
+    flashloan_gateway::{
+        FlashLoanType,
+        FlashloanGatewayRef,
+    },
+};
+use ink::prelude::{
+    vec,
+    vec::Vec,
+};
+use openbrush::{
+    contracts::psp22::PSP22Ref,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+    },
+};
+use scale::Decode;
+
+pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+#[derive(Debug, Default)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct Data {
+    /// AccountId of Flashloan Gateway
+    pub flashloan_gateway: Option<AccountId>,
+    /// AccountId of Controller
+    pub controller: Option<AccountId>,
+    /// AccountId of DEX Router
+    pub router: Option<AccountId>,
+}
+
+/// Flashloan of a single asset received by `execute_operation`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedFlashloan<P> {
+    pub asset: AccountId,
+    pub amount: Balance,
+    pub premium: Balance,
+    /// Parameters of the adapter passed through the Flashloan Gateway
+    pub params: P,
+}
+
+/// Check that `execute_operation` of `this` is called back by `flashloan_gateway`
+/// for a flashloan of a single asset initiated by `this`, and decode its parameters
+#[allow(clippy::too_many_arguments)]
+pub fn receive_flashloan<P: Decode>(
+    flashloan_gateway: AccountId,
+    caller: AccountId,
+    this: AccountId,
+    assets: Vec<AccountId>,
+    amounts: Vec<Balance>,
+    premiums: Vec<Balance>,
+    initiator: AccountId,
+    params: Vec<u8>,
+) -> Result<ReceivedFlashloan<P>> {
+    if caller != flashloan_gateway {
+        return Err(Error::CallerIsNotFlashloanGateway)
+    }
+    if initiator != this {
+        return Err(Error::InitiatorIsNotThis)
+    }
+    if assets.len() != 1 || amounts.len() != 1 || premiums.len() != 1 {
+        return Err(Error::InvalidFlashloanParams)
+    }
+    let params = P::decode(&mut &params[..]).map_err(|_| Error::InvalidFlashloanParams)?;
+    Ok(ReceivedFlashloan {
+        asset: assets[0],
+        amount: amounts[0],
+        premium: premiums[0],
+        params,
+    })
+}
+
+/// Plumbing shared by the adapters built on a flashloan of the Flashloan Gateway,
+/// through which all of their cross-contract calls go
+pub trait Internal {
+    fn _initialize(
+        &mut self,
+        flashloan_gateway: AccountId,
+        controller: AccountId,
+        router: AccountId,
+    );
+
+    fn _flashloan_gateway(&self) -> Option<AccountId>;
+
+    fn _controller(&self) -> Option<AccountId>;
+
+    fn _router(&self) -> Option<AccountId>;
+
+    fn _pool_of(&self, asset: AccountId) -> Result<AccountId>;
+
+    /// Flashloan premium applied to this contract, which initiates the flashloans
+    fn _flashloan_premium(&self) -> Result<u128>;
+
+    /// Flashloan `amount` of `asset` to this contract on behalf of the caller,
+    /// `params` are passed back to `execute_operation`
+    fn _flashloan(
+        &self,
+        asset: AccountId,
+        amount: Balance,
+        loan_type: FlashLoanType,
+        params: Vec<u8>,
+    ) -> Result<()>;
+
+    /// Check that `execute_operation` is called back by the Flashloan Gateway
+    /// for a flashloan of a single asset initiated by this contract, and decode its parameters
+    fn _receive_flashloan<P: Decode>(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<ReceivedFlashloan<P>>;
+
+    /// Allow the Flashloan Gateway to pull the repayment of the flashloan with its premium
+    fn _approve_repayment(&self, asset: AccountId, amount: Balance) -> Result<()>;
+
+    /// Supply `amount` of `asset` held by this contract on behalf of `user`
+    fn _supply_on_behalf(&self, asset: AccountId, user: AccountId, amount: Balance) -> Result<()>;
+
+    /// Redeem `amount` of `asset` supplied by `user` to this contract, consuming the allowance of the pool tokens
+    fn _redeem_on_behalf(&self, asset: AccountId, user: AccountId, amount: Balance) -> Result<()>;
+
+    /// Repay `amount` of the borrow of `asset` of `user` with the balance of this contract
+    fn _repay_on_behalf(&self, asset: AccountId, user: AccountId, amount: Balance) -> Result<()>;
+
+    fn _borrow_balance_of(&self, asset: AccountId, user: AccountId) -> Result<Balance>;
+
+    fn _get_amount_out(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_in: Balance,
+    ) -> Result<Balance>;
+
+    fn _get_amount_in(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_out: Balance,
+    ) -> Result<Balance>;
+
+    /// Swap exact `amount_in` held by this contract, returns the amount received
+    fn _swap_exact_in(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_in: Balance,
+        min_amount_out: Balance,
+    ) -> Result<Balance>;
+
+    /// Swap for exact `amount_out` with the balance of this contract, returns the amount spent
+    fn _swap_exact_out(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_out: Balance,
+        max_amount_in: Balance,
+    ) -> Result<Balance>;
+
+    fn _transfer(&self, asset: AccountId, to: AccountId, amount: Balance) -> Result<()>;
+}
+
+impl<T: Storage<Data>> Internal for T {
+    default fn _initialize(
+        &mut self,
+        flashloan_gateway: AccountId,
+        controller: AccountId,
+        router: AccountId,
+    ) {
+        let data = self.data::<Data>();
+        data.flashloan_gateway = Some(flashloan_gateway);
+        data.controller = Some(controller);
+        data.router = Some(router);
+    }
+
+    default fn _flashloan_gateway(&self) -> Option<AccountId> {
+        self.data::<Data>().flashloan_gateway
+    }
+
+    default fn _controller(&self) -> Option<AccountId> {
+        self.data::<Data>().controller
+    }
+
+    default fn _router(&self) -> Option<AccountId> {
+        self.data::<Data>().router
+    }
+
+    default fn _pool_of(&self, asset: AccountId) -> Result<AccountId> {
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        ControllerRef::market_of_underlying(&controller, asset).ok_or(Error::MarketNotListed)
+    }
+
+    default fn _flashloan_premium(&self) -> Result<u128> {
+        let flashloan_gateway = self
+            ._flashloan_gateway()
+            .ok_or(Error::FlashloanGatewayIsNotSet)?;
+        Ok(FlashloanGatewayRef::flashloan_premium_of(
+            &flashloan_gateway,
+            Self::env().account_id(),
+        ))
+    }
+
+    default fn _flashloan(
+        &self,
+        asset: AccountId,
+        amount: Balance,
+        loan_type: FlashLoanType,
+        params: Vec<u8>,
+    ) -> Result<()> {
+        let flashloan_gateway = self
+            ._flashloan_gateway()
+            .ok_or(Error::FlashloanGatewayIsNotSet)?;
+        // the gateway calls back `execute_operation` of this contract
+        FlashloanGatewayRef::flashloan_builder(
+            &flashloan_gateway,
+            Self::env().account_id(),
+            vec![asset],
+            vec![amount],
+            vec![loan_type as u8],
+            Self::env().caller(),
+            params,
+        )
+        .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
+        .try_invoke()???;
+        Ok(())
+    }
+
+    default fn _receive_flashloan<P: Decode>(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<ReceivedFlashloan<P>> {
+        let flashloan_gateway = self
+            ._flashloan_gateway()
+            .ok_or(Error::FlashloanGatewayIsNotSet)?;
+        receive_flashloan(
+            flashloan_gateway,
+            Self::env().caller(),
+            Self::env().account_id(),
+            assets,
+            amounts,
+            premiums,
+            initiator,
+            params,
+        )
+    }
+
+    default fn _approve_repayment(&self, asset: AccountId, amount: Balance) -> Result<()> {
+        let flashloan_gateway = self
+            ._flashloan_gateway()
+            .ok_or(Error::FlashloanGatewayIsNotSet)?;
+        PSP22Ref::approve(&asset, flashloan_gateway, amount)?;
+        Ok(())
+    }
+
+    default fn _supply_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> Result<()> {
+        let pool = self._pool_of(asset)?;
+        PSP22Ref::approve(&asset, pool, amount)?;
+        PoolRef::mint_to_builder(&pool, user, amount)
+            .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
+            .try_invoke()???;
+        Ok(())
+    }
+
+    default fn _redeem_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> Result<()> {
+        let pool = self._pool_of(asset)?;
+        PoolRef::redeem_underlying_on_behalf(&pool, user, amount, Self::env().account_id())?;
+        Ok(())
+    }
+
+    default fn _repay_on_behalf(
+        &self,
+        asset: AccountId,
+        user: AccountId,
+        amount: Balance,
+    ) -> Result<()> {
+        let pool = self._pool_of(asset)?;
+        PSP22Ref::approve(&asset, pool, amount)?;
+        PoolRef::repay_borrow_behalf(&pool, user, amount)?;
+        Ok(())
+    }
+
+    default fn _borrow_balance_of(&self, asset: AccountId, user: AccountId) -> Result<Balance> {
+        let pool = self._pool_of(asset)?;
+        Ok(PoolRef::borrow_balance_current(&pool, user)?)
+    }
+
+    default fn _get_amount_out(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_in: Balance,
+    ) -> Result<Balance> {
+        let router = self._router().ok_or(Error::RouterIsNotSet)?;
+        Ok(DexRouterRef::get_amount_out(
+            &router, asset_in, asset_out, amount_in,
+        )?)
+    }
+
+    default fn _get_amount_in(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_out: Balance,
+    ) -> Result<Balance> {
+        let router = self._router().ok_or(Error::RouterIsNotSet)?;
+        Ok(DexRouterRef::get_amount_in(
+            &router, asset_in, asset_out, amount_out,
+        )?)
+    }
+
+    default fn _swap_exact_in(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_in: Balance,
+        min_amount_out: Balance,
+    ) -> Result<Balance> {
+        let router = self._router().ok_or(Error::RouterIsNotSet)?;
+        PSP22Ref::approve(&asset_in, router, amount_in)?;
+        Ok(DexRouterRef::swap_exact_tokens_for_tokens(
+            &router,
+            asset_in,
+            asset_out,
+            amount_in,
+            min_amount_out,
+            Self::env().account_id(),
+        )?)
+    }
+
+    default fn _swap_exact_out(
+        &self,
+        asset_in: AccountId,
+        asset_out: AccountId,
+        amount_out: Balance,
+        max_amount_in: Balance,
+    ) -> Result<Balance> {
+        let router = self._router().ok_or(Error::RouterIsNotSet)?;
+        PSP22Ref::approve(&asset_in, router, max_amount_in)?;
+        Ok(DexRouterRef::swap_tokens_for_exact_tokens(
+            &router,
+            asset_in,
+            asset_out,
+            amount_out,
+            max_amount_in,
+            Self::env().account_id(),
+        )?)
+    }
+
+    default fn _transfer(&self, asset: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+        PSP22Ref::transfer(&asset, to, amount, Vec::<u8>::new())?;
+        Ok(())
+    }
+}
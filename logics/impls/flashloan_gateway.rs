@@ -14,6 +14,7 @@ use crate::traits::{
 use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::psp22::PSP22Ref,
+    storage::Mapping,
     traits::{
         AccountId,
         Balance,
@@ -22,7 +23,11 @@ use openbrush::{
 };
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+/// Version of the storage layout of `Data`, incremented when a migration is required
+pub const STORAGE_VERSION: u32 = 1;
 pub const MINUMUM_FLASHLOAN_AMOUNT: u128 = 100000;
+/// Denominator of flashloan premiums, which are in percentage * 100
+pub const FLASHLOAN_PREMIUM_DENOMINATOR: u128 = 10000;
 
 #[derive(Debug, Default)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
@@ -32,13 +37,42 @@ pub struct Data {
     pub flashloan_premium_total: u128,
     /// AccountId of Controller managing Flashloan Gateway
     pub controller: Option<AccountId>,
+    /// Share of Flashloan Fee added to the reserves of Pool in percentage * 100.
+    /// The rest accrues to suppliers. Default value is 0
+    pub flashloan_premium_to_protocol: u128,
+    /// Flashloan Fee overriding `flashloan_premium_total` for the initiator, in percentage * 100
+    pub borrower_premiums: Mapping<AccountId, u128>,
+    /// Whether flash loans are restricted to whitelisted receivers
+    pub whitelist_only: bool,
+    /// Receivers allowed to take flash loans while `whitelist_only` is enabled
+    pub whitelisted_receivers: Mapping<AccountId, bool>,
 }
 
 pub trait Internal {
     fn _initialize(&mut self, controller: AccountId);
 
+    fn _set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()>;
+    fn _set_flashloan_premium_to_protocol(&mut self, new_premium_to_protocol: u128) -> Result<()>;
+    fn _set_borrower_premium(&mut self, borrower: AccountId, premium: Option<u128>) -> Result<()>;
+    fn _set_whitelist_only(&mut self, enabled: bool) -> Result<()>;
+    fn _set_whitelisted_receiver(&mut self, receiver: AccountId, whitelisted: bool) -> Result<()>;
+    fn _assert_manager(&self) -> Result<()>;
+    fn _repay_flashloan(
+        &self,
+        receiver: AccountId,
+        asset: AccountId,
+        market: AccountId,
+        amount: Balance,
+        premium: Balance,
+    ) -> Result<Balance>;
+
     // View function
     fn _flashloan_premium_total(&self) -> u128;
+    fn _flashloan_premium_to_protocol(&self) -> u128;
+    fn _flashloan_premium_of(&self, borrower: AccountId) -> u128;
+    fn _borrower_premium(&self, borrower: AccountId) -> Option<u128>;
+    fn _whitelist_only(&self) -> bool;
+    fn _is_whitelisted_receiver(&self, receiver: AccountId) -> bool;
     fn _controller(&self) -> Option<AccountId>;
     // events
     fn _emit_flashloan_event(
@@ -48,7 +82,13 @@ pub trait Internal {
         asset: AccountId,
         amount: Balance,
         premium: Balance,
+        premium_to_protocol: Balance,
     );
+    fn _emit_new_flashloan_premium_total_event(&self, old: u128, new: u128);
+    fn _emit_new_flashloan_premium_to_protocol_event(&self, old: u128, new: u128);
+    fn _emit_new_borrower_premium_event(&self, borrower: AccountId, premium: Option<u128>);
+    fn _emit_new_whitelist_only_event(&self, enabled: bool);
+    fn _emit_new_whitelisted_receiver_event(&self, receiver: AccountId, whitelisted: bool);
 }
 
 impl<T: Storage<Data>> FlashloanGateway for T {
@@ -58,7 +98,7 @@ impl<T: Storage<Data>> FlashloanGateway for T {
         assets: Vec<AccountId>,
         amounts: Vec<Balance>,
         mods: Vec<u8>,
-        _on_behalf_of: AccountId,
+        on_behalf_of: AccountId,
         params: Vec<u8>,
     ) -> Result<()> {
         if assets.len() != amounts.len() {
@@ -77,19 +117,32 @@ impl<T: Storage<Data>> FlashloanGateway for T {
             return Err(Error::DuplicatedFlashloanAssets)
         }
 
+        if self._whitelist_only() && !self._is_whitelisted_receiver(receiver_address) {
+            return Err(Error::ReceiverIsNotWhitelisted)
+        }
+
         let mut lp_token_addresses: Vec<AccountId> = Vec::with_capacity(assets.len());
         let mut premiums: Vec<Balance> = Vec::with_capacity(assets.len());
 
         let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
-        let flashloan_premium_total = self._flashloan_premium_total();
+        let caller = Self::env().caller();
+        let flashloan_premium_total = self._flashloan_premium_of(caller);
         for index in 0..assets.len() {
             if amounts[index] < MINUMUM_FLASHLOAN_AMOUNT {
                 return Err(Error::InvalidFlashloanAmount)
             }
             let market = ControllerRef::market_of_underlying(&controller, assets[index])
                 .ok_or(Error::MarketNotListed)?;
+            // debt is opened for on_behalf_of, who must have delegated enough to the caller
+            if mods[index] != FlashLoanType::None as u8
+                && on_behalf_of != caller
+                && PoolRef::delegate_allowance(&market, on_behalf_of, caller) < amounts[index]
+            {
+                return Err(Error::InsufficientDelegateAllowance)
+            }
             lp_token_addresses.push(market);
-            let premium: u128 = amounts[index] * flashloan_premium_total / 10000;
+            let premium: u128 =
+                amounts[index] * flashloan_premium_total / FLASHLOAN_PREMIUM_DENOMINATOR;
             premiums.push(premium);
 
             PoolRef::transfer_underlying(
@@ -99,7 +152,6 @@ impl<T: Storage<Data>> FlashloanGateway for T {
             )?;
         }
 
-        let caller = Self::env().caller();
         let operation_result = FlashloanReceiverRef::execute_operation(
             &receiver_address,
             assets.clone(),
@@ -118,20 +170,23 @@ impl<T: Storage<Data>> FlashloanGateway for T {
             let current_amount = amounts[index];
             let current_premium = premiums[index];
             let current_lp_token = lp_token_addresses[index];
-            let current_amount_plus_premium = current_amount + current_premium;
 
+            let mut current_premium_to_protocol = 0;
             if mods[index] == FlashLoanType::None as u8 {
-                PoolRef::accrue_interest(&current_lp_token)?;
-
-                PSP22Ref::transfer_from(
-                    &current_asset,
+                current_premium_to_protocol = self._repay_flashloan(
                     receiver_address,
+                    current_asset,
                     current_lp_token,
-                    current_amount_plus_premium,
-                    Vec::<u8>::new(),
+                    current_amount,
+                    current_premium,
                 )?;
             } else {
-                PoolRef::borrow_for_flashloan(&current_lp_token, caller, current_amount)?;
+                PoolRef::borrow_for_flashloan(
+                    &current_lp_token,
+                    on_behalf_of,
+                    caller,
+                    current_amount,
+                )?;
             }
 
             self._emit_flashloan_event(
@@ -140,6 +195,7 @@ impl<T: Storage<Data>> FlashloanGateway for T {
                 current_asset,
                 current_amount,
                 current_premium,
+                current_premium_to_protocol,
             );
         }
 
@@ -150,6 +206,62 @@ impl<T: Storage<Data>> FlashloanGateway for T {
         self._flashloan_premium_total()
     }
 
+    default fn flashloan_premium_to_protocol(&self) -> u128 {
+        self._flashloan_premium_to_protocol()
+    }
+
+    default fn set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()> {
+        self._assert_manager()?;
+        self._set_flashloan_premium_total(new_premium_total)
+    }
+
+    default fn set_flashloan_premium_to_protocol(
+        &mut self,
+        new_premium_to_protocol: u128,
+    ) -> Result<()> {
+        self._assert_manager()?;
+        self._set_flashloan_premium_to_protocol(new_premium_to_protocol)
+    }
+
+    default fn flashloan_premium_of(&self, borrower: AccountId) -> u128 {
+        self._flashloan_premium_of(borrower)
+    }
+
+    default fn borrower_premium(&self, borrower: AccountId) -> Option<u128> {
+        self._borrower_premium(borrower)
+    }
+
+    default fn set_borrower_premium(
+        &mut self,
+        borrower: AccountId,
+        premium: Option<u128>,
+    ) -> Result<()> {
+        self._assert_manager()?;
+        self._set_borrower_premium(borrower, premium)
+    }
+
+    default fn whitelist_only(&self) -> bool {
+        self._whitelist_only()
+    }
+
+    default fn set_whitelist_only(&mut self, enabled: bool) -> Result<()> {
+        self._assert_manager()?;
+        self._set_whitelist_only(enabled)
+    }
+
+    default fn is_whitelisted_receiver(&self, receiver: AccountId) -> bool {
+        self._is_whitelisted_receiver(receiver)
+    }
+
+    default fn set_whitelisted_receiver(
+        &mut self,
+        receiver: AccountId,
+        whitelisted: bool,
+    ) -> Result<()> {
+        self._assert_manager()?;
+        self._set_whitelisted_receiver(receiver, whitelisted)
+    }
+
     default fn controller(&self) -> Option<AccountId> {
         self._controller()
     }
@@ -161,10 +273,135 @@ impl<T: Storage<Data>> Internal for T {
         self.data::<Data>().controller = Some(controller);
     }
 
+    default fn _set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()> {
+        if new_premium_total > FLASHLOAN_PREMIUM_DENOMINATOR {
+            return Err(Error::InvalidFlashloanPremium)
+        }
+        let old = self._flashloan_premium_total();
+        self.data::<Data>().flashloan_premium_total = new_premium_total;
+        self._emit_new_flashloan_premium_total_event(old, new_premium_total);
+        Ok(())
+    }
+
+    default fn _set_flashloan_premium_to_protocol(
+        &mut self,
+        new_premium_to_protocol: u128,
+    ) -> Result<()> {
+        if new_premium_to_protocol > FLASHLOAN_PREMIUM_DENOMINATOR {
+            return Err(Error::InvalidFlashloanPremium)
+        }
+        let old = self._flashloan_premium_to_protocol();
+        self.data::<Data>().flashloan_premium_to_protocol = new_premium_to_protocol;
+        self._emit_new_flashloan_premium_to_protocol_event(old, new_premium_to_protocol);
+        Ok(())
+    }
+
+    default fn _set_borrower_premium(
+        &mut self,
+        borrower: AccountId,
+        premium: Option<u128>,
+    ) -> Result<()> {
+        match premium {
+            Some(value) => {
+                if value > FLASHLOAN_PREMIUM_DENOMINATOR {
+                    return Err(Error::InvalidFlashloanPremium)
+                }
+                self.data::<Data>()
+                    .borrower_premiums
+                    .insert(&borrower, &value);
+            }
+            None => self.data::<Data>().borrower_premiums.remove(&borrower),
+        }
+        self._emit_new_borrower_premium_event(borrower, premium);
+        Ok(())
+    }
+
+    default fn _set_whitelist_only(&mut self, enabled: bool) -> Result<()> {
+        self.data::<Data>().whitelist_only = enabled;
+        self._emit_new_whitelist_only_event(enabled);
+        Ok(())
+    }
+
+    default fn _set_whitelisted_receiver(
+        &mut self,
+        receiver: AccountId,
+        whitelisted: bool,
+    ) -> Result<()> {
+        if whitelisted {
+            self.data::<Data>()
+                .whitelisted_receivers
+                .insert(&receiver, &true);
+        } else {
+            self.data::<Data>().whitelisted_receivers.remove(&receiver);
+        }
+        self._emit_new_whitelisted_receiver_event(receiver, whitelisted);
+        Ok(())
+    }
+
+    default fn _assert_manager(&self) -> Result<()> {
+        // Flashloan Gateway is managed by the manager of Controller
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        let manager = ControllerRef::manager(&controller);
+        if manager != Some(Self::env().caller()) {
+            return Err(Error::CallerIsNotManager)
+        }
+        Ok(())
+    }
+
+    default fn _repay_flashloan(
+        &self,
+        receiver: AccountId,
+        asset: AccountId,
+        market: AccountId,
+        amount: Balance,
+        premium: Balance,
+    ) -> Result<Balance> {
+        PoolRef::accrue_interest(&market)?;
+
+        PSP22Ref::transfer_from(&asset, receiver, market, amount + premium, Vec::<u8>::new())?;
+        PoolRef::settle_flashloan(&market, amount)?;
+
+        // the rest of the premium is left in the pool as cash for suppliers
+        let premium_to_protocol =
+            premium * self._flashloan_premium_to_protocol() / FLASHLOAN_PREMIUM_DENOMINATOR;
+        if premium_to_protocol > 0 {
+            PoolRef::add_reserves_from_flashloan(&market, premium_to_protocol)?;
+        }
+        Ok(premium_to_protocol)
+    }
+
     default fn _flashloan_premium_total(&self) -> u128 {
         self.data::<Data>().flashloan_premium_total
     }
 
+    default fn _flashloan_premium_to_protocol(&self) -> u128 {
+        self.data::<Data>().flashloan_premium_to_protocol
+    }
+
+    default fn _flashloan_premium_of(&self, borrower: AccountId) -> u128 {
+        let premium_total = self._flashloan_premium_total();
+        // an override can only lower the fee
+        match self._borrower_premium(borrower) {
+            Some(premium) if premium < premium_total => premium,
+            _ => premium_total,
+        }
+    }
+
+    default fn _borrower_premium(&self, borrower: AccountId) -> Option<u128> {
+        self.data::<Data>().borrower_premiums.get(&borrower)
+    }
+
+    default fn _whitelist_only(&self) -> bool {
+        self.data::<Data>().whitelist_only
+    }
+
+    default fn _is_whitelisted_receiver(&self, receiver: AccountId) -> bool {
+        self.data::<Data>()
+            .whitelisted_receivers
+            .get(&receiver)
+            .unwrap_or(false)
+    }
+
     default fn _controller(&self) -> Option<AccountId> {
         self.data::<Data>().controller
     }
@@ -176,6 +413,27 @@ impl<T: Storage<Data>> Internal for T {
         _asset: AccountId,
         _amount: Balance,
         _premium: Balance,
+        _premium_to_protocol: Balance,
+    ) {
+    }
+
+    default fn _emit_new_flashloan_premium_total_event(&self, _old: u128, _new: u128) {}
+
+    default fn _emit_new_flashloan_premium_to_protocol_event(&self, _old: u128, _new: u128) {}
+
+    default fn _emit_new_borrower_premium_event(
+        &self,
+        _borrower: AccountId,
+        _premium: Option<u128>,
+    ) {
+    }
+
+    default fn _emit_new_whitelist_only_event(&self, _enabled: bool) {}
+
+    default fn _emit_new_whitelisted_receiver_event(
+        &self,
+        _receiver: AccountId,
+        _whitelisted: bool,
     ) {
     }
 }
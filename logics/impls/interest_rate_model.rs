@@ -47,14 +47,15 @@ fn supply_rate(utilization_rate: U256, borrow_rate: U256, one_minus_reserve_fact
     utilization_rate.mul(rate_to_pol).div(base())
 }
 
-fn milliseconds_per_year() -> U256 {
+pub fn milliseconds_per_year() -> U256 {
     U256::from(60 * 60 * 24 * 365).mul(U256::from(1000))
 }
 
 fn u256_from_balance(b: Balance) -> U256 {
     U256::from(b)
 }
-fn utilization_rate(cash: Balance, borrows: Balance, reserves: Balance) -> U256 {
+/// Utilization rate of the Pool (Decimals: 18)
+pub fn utilization_rate(cash: Balance, borrows: Balance, reserves: Balance) -> U256 {
     let (_cash, _borrows, _reserves) = (
         u256_from_balance(cash),
         u256_from_balance(borrows),
@@ -75,6 +76,10 @@ pub trait Internal {
         reserves: Balance,
         reserve_factor_mantissa: WrappedU256,
     ) -> WrappedU256;
+    fn _base_rate_per_milli_second(&self) -> WrappedU256;
+    fn _multiplier_per_milli_second_slope_1(&self) -> WrappedU256;
+    fn _multiplier_per_milli_second_slope_2(&self) -> WrappedU256;
+    fn _kink(&self) -> WrappedU256;
 }
 
 impl Data {
@@ -95,7 +100,13 @@ impl Data {
         }
     }
 
-    fn borrow_rate(&self, _cash: Balance, _borrows: Balance, _reserves: Balance) -> WrappedU256 {
+    /// Borrow rate per milliseconds for the given pool state (Decimals: 18)
+    pub fn borrow_rate(
+        &self,
+        _cash: Balance,
+        _borrows: Balance,
+        _reserves: Balance,
+    ) -> WrappedU256 {
         let util = utilization_rate(_cash, _borrows, _reserves);
         let data = self;
         if util.le(&U256::from(data.kink)) {
@@ -162,6 +173,22 @@ impl<T: Storage<Data>> InterestRateModel for T {
     ) -> WrappedU256 {
         self._get_supply_rate(cash, borrows, reserves, reserve_factor_mantissa)
     }
+
+    default fn base_rate_per_milli_second(&self) -> WrappedU256 {
+        self._base_rate_per_milli_second()
+    }
+
+    default fn multiplier_per_milli_second_slope_1(&self) -> WrappedU256 {
+        self._multiplier_per_milli_second_slope_1()
+    }
+
+    default fn multiplier_per_milli_second_slope_2(&self) -> WrappedU256 {
+        self._multiplier_per_milli_second_slope_2()
+    }
+
+    default fn kink(&self) -> WrappedU256 {
+        self._kink()
+    }
 }
 
 impl<T: Storage<Data>> Internal for T {
@@ -183,6 +210,18 @@ impl<T: Storage<Data>> Internal for T {
         self.data()
             .supply_rate(cash, borrows, reserves, reserve_factor_mantissa)
     }
+    default fn _base_rate_per_milli_second(&self) -> WrappedU256 {
+        self.data().base_rate_per_milli_second
+    }
+    default fn _multiplier_per_milli_second_slope_1(&self) -> WrappedU256 {
+        self.data().multiplier_per_milli_second_slope_1
+    }
+    default fn _multiplier_per_milli_second_slope_2(&self) -> WrappedU256 {
+        self.data().multiplier_per_milli_second_slope_2
+    }
+    default fn _kink(&self) -> WrappedU256 {
+        self.data().kink
+    }
 }
 
 #[cfg(test)]
@@ -6,7 +6,7 @@
 // except according to those terms.
 
 use core::ops::{
-    // Add,
+    Add,
     Div,
     Mul,
     Sub,
@@ -19,6 +19,10 @@ use super::{
         AccountData,
         ControllerRef,
     },
+    flashloan_adapter::{
+        receive_flashloan,
+        ReceivedFlashloan,
+    },
     pool::PoolRef,
     price_oracle::{
         PriceOracleRef,
@@ -26,10 +30,18 @@ use super::{
     },
     weth::WETHRef,
 };
+use crate::traits::flashloan_gateway::{
+    FlashLoanType,
+    FlashloanGatewayRef,
+};
 pub use crate::traits::{
     leverager::*,
     types::WrappedU256,
 };
+use ink::prelude::{
+    vec,
+    vec::Vec,
+};
 use openbrush::{
     contracts::psp22::PSP22Ref,
     traits::{
@@ -39,8 +51,13 @@ use openbrush::{
     },
 };
 use primitive_types::U256;
+use scale::Encode;
 
 pub const CLOSE_MAX_LOOPS: u128 = 40;
+/// Health factor to be kept after withdrawing if not configured: 1.01
+pub const DEFAULT_HEALTH_FACTOR_LIMIT: u128 = 101 * 10_u128.pow(16);
+/// Maximum iterations of the binary search in `calculate_withdrawable_amount`
+pub const WITHDRAWABLE_MAX_ITERATIONS: u32 = 128;
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
 
@@ -55,6 +72,108 @@ pub struct Data {
     pub price_oracle: Option<AccountId>,
     /// AccountId of Manager
     pub manager: Option<AccountId>,
+    /// Health factor to be kept after withdrawing, `DEFAULT_HEALTH_FACTOR_LIMIT` if not set
+    pub health_factor_limit: Option<WrappedU256>,
+}
+
+/// Health factor after withdrawing collateral, in the same way as `Leverager::get_health_factor`
+pub fn calculate_health_factor_after_withdraw(
+    total_collateral_in_base_currency: U256,
+    total_debt_in_base_currency: U256,
+    avg_liquidation_threshold: U256,
+    liquidation_threshold: U256,
+    withdraw_amount_in_base_currency: U256,
+) -> U256 {
+    let total_collateral_after =
+        total_collateral_in_base_currency.saturating_sub(withdraw_amount_in_base_currency);
+
+    let factor = avg_liquidation_threshold
+        .mul(total_collateral_in_base_currency)
+        .saturating_sub(liquidation_threshold.mul(withdraw_amount_in_base_currency));
+
+    let liquidation_threshold_after = if total_collateral_after.is_zero() {
+        U256::from(0)
+    } else {
+        factor.div(total_collateral_after)
+    };
+
+    calculate_health_factor_from_balances(
+        total_collateral_after,
+        total_debt_in_base_currency,
+        liquidation_threshold_after,
+    )
+}
+
+/// Maximum amount of the asset which can be withdrawn keeping the health factor above `health_factor_limit`.
+/// Withdrawing `x` in base currency gives
+/// health_factor = (total_collateral * avg_lt - x * lt) * 1e18 / (total_debt * 10000),
+/// which is solved for `x` and then corrected for rounding by a bounded binary search.
+pub fn calculate_withdrawable_amount(
+    total_collateral_in_base_currency: U256,
+    total_debt_in_base_currency: U256,
+    avg_liquidation_threshold: U256,
+    liquidation_threshold: U256,
+    price: U256,
+    health_factor_limit: U256,
+) -> U256 {
+    if liquidation_threshold.is_zero() || price.is_zero() {
+        return U256::from(0)
+    }
+    let precision = U256::from(PRICE_PRECISION);
+    let to_amount = |value_in_base_currency: U256| value_in_base_currency.mul(precision).div(price);
+    let health_factor_after = |amount: U256| {
+        calculate_health_factor_after_withdraw(
+            total_collateral_in_base_currency,
+            total_debt_in_base_currency,
+            avg_liquidation_threshold,
+            liquidation_threshold,
+            amount.mul(price).div(precision),
+        )
+    };
+
+    let max_in_base_currency = if total_debt_in_base_currency.is_zero() {
+        total_collateral_in_base_currency
+    } else {
+        let afford = total_collateral_in_base_currency
+            .mul(avg_liquidation_threshold)
+            .mul(precision)
+            .saturating_sub(
+                health_factor_limit
+                    .mul(U256::from(10000))
+                    .mul(total_debt_in_base_currency),
+            );
+        let denominator = liquidation_threshold.mul(precision);
+        let max = afford.div(denominator);
+        // keep the health factor strictly above the limit
+        let max = if !max.is_zero() && (afford % denominator).is_zero() {
+            max.sub(U256::from(1))
+        } else {
+            max
+        };
+        max.min(total_collateral_in_base_currency)
+    };
+
+    let mut high = to_amount(max_in_base_currency);
+    if high.is_zero() || health_factor_after(high) > health_factor_limit {
+        return high
+    }
+    if health_factor_after(U256::from(0)) <= health_factor_limit {
+        return U256::from(0)
+    }
+
+    // rounding pushed the closed-form amount to the limit
+    let mut low = U256::from(0);
+    let mut iterations = 0;
+    while low.add(U256::from(1)) < high && iterations < WITHDRAWABLE_MAX_ITERATIONS {
+        let middle = low.add(high.sub(low).div(U256::from(2)));
+        if health_factor_after(middle) > health_factor_limit {
+            low = middle;
+        } else {
+            high = middle;
+        }
+        iterations += 1;
+    }
+    low
 }
 
 pub trait Internal {
@@ -66,6 +185,10 @@ pub trait Internal {
 
     fn _manager(&self) -> Option<AccountId>;
 
+    fn _health_factor_limit(&self) -> U256;
+
+    fn _set_health_factor_limit(&mut self, health_factor_limit: WrappedU256) -> Result<()>;
+
     fn _get_available_borrows(&self, account: AccountId) -> Option<AvailableBorrows>;
 
     fn _loan_to_value(&self, asset: AccountId) -> u128;
@@ -83,6 +206,8 @@ pub trait Internal {
 
     fn _withdrawable_amount(&self, account: AccountId, asset: AccountId) -> U256;
 
+    fn _redeemable_amount(&self, account: AccountId, asset: AccountId) -> Balance;
+
     fn _assert_manager(&mut self) -> Result<()>;
 
     fn _initialize(
@@ -111,6 +236,50 @@ pub trait Internal {
     ) -> Result<()>;
 
     fn _close(&mut self, asset: AccountId) -> Result<()>;
+
+    fn _leverage_with_flashloan(
+        &mut self,
+        asset: AccountId,
+        amount: Balance,
+        target_leverage: u128,
+    ) -> Result<()>;
+
+    fn _deleverage_with_flashloan(&mut self, asset: AccountId, repay_amount: Balance)
+        -> Result<()>;
+
+    fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()>;
+
+    fn _flashloan_gateway(&self) -> Result<AccountId>;
+
+    fn _deleverage_to(
+        &mut self,
+        asset: AccountId,
+        target_health_factor: U256,
+    ) -> Result<(Balance, Balance)>;
+
+    fn _deleverage_to_eth(&mut self, target_health_factor: U256) -> Result<(Balance, Balance)>;
+
+    /// Withdraw and repay asset of the caller until their health factor reaches `target_health_factor`,
+    /// keeping the withdrawn amount not taken by the repayments in this contract
+    fn _deleverage(
+        &mut self,
+        asset: AccountId,
+        target_health_factor: U256,
+    ) -> Result<(Balance, Balance)>;
+
+    fn _deleverage_amount(
+        &self,
+        account: AccountId,
+        asset: AccountId,
+        target_health_factor: U256,
+    ) -> Balance;
 }
 
 impl<T: Storage<Data>> Leverager for T {
@@ -130,6 +299,15 @@ impl<T: Storage<Data>> Leverager for T {
         self._manager()
     }
 
+    default fn health_factor_limit(&self) -> WrappedU256 {
+        WrappedU256::from(self._health_factor_limit())
+    }
+
+    default fn set_health_factor_limit(&mut self, health_factor_limit: WrappedU256) -> Result<()> {
+        self._assert_manager()?;
+        self._set_health_factor_limit(health_factor_limit)
+    }
+
     default fn get_available_borrows(&self, account: AccountId) -> Option<AvailableBorrows> {
         self._get_available_borrows(account)
     }
@@ -186,6 +364,38 @@ impl<T: Storage<Data>> Leverager for T {
     default fn loop_eth(&mut self, borrow_ratio: u128, loop_count: u128) -> Result<()> {
         self._loop_eth(borrow_ratio, loop_count)
     }
+
+    default fn leverage_with_flashloan(
+        &mut self,
+        asset: AccountId,
+        amount: Balance,
+        target_leverage: u128,
+    ) -> Result<()> {
+        self._leverage_with_flashloan(asset, amount, target_leverage)
+    }
+
+    default fn deleverage_with_flashloan(
+        &mut self,
+        asset: AccountId,
+        repay_amount: Balance,
+    ) -> Result<()> {
+        self._deleverage_with_flashloan(asset, repay_amount)
+    }
+
+    default fn deleverage_to(
+        &mut self,
+        asset: AccountId,
+        target_health_factor: WrappedU256,
+    ) -> Result<(Balance, Balance)> {
+        self._deleverage_to(asset, U256::from(target_health_factor))
+    }
+
+    default fn deleverage_to_eth(
+        &mut self,
+        target_health_factor: WrappedU256,
+    ) -> Result<(Balance, Balance)> {
+        self._deleverage_to_eth(U256::from(target_health_factor))
+    }
 }
 
 impl<T: Storage<Data>> Internal for T {
@@ -227,6 +437,21 @@ impl<T: Storage<Data>> Internal for T {
         self.data().manager
     }
 
+    default fn _health_factor_limit(&self) -> U256 {
+        self.data()
+            .health_factor_limit
+            .map(U256::from)
+            .unwrap_or(U256::from(DEFAULT_HEALTH_FACTOR_LIMIT))
+    }
+
+    default fn _set_health_factor_limit(&mut self, health_factor_limit: WrappedU256) -> Result<()> {
+        if U256::from(health_factor_limit) < U256::from(PRICE_PRECISION) {
+            return Err(Error::InappropriateHealthFactor)
+        }
+        self.data().health_factor_limit = Some(health_factor_limit);
+        Ok(())
+    }
+
     default fn _get_available_borrows(&self, account: AccountId) -> Option<AvailableBorrows> {
         if let Some(controller) = self._controller() {
             let account_data_result =
@@ -289,34 +514,12 @@ impl<T: Storage<Data>> Internal for T {
                         .mul(U256::from(withdraw_amount))
                         .div(U256::from(PRICE_PRECISION));
 
-                    let total_collateral_after = if account_data.total_collateral_in_base_currency
-                        > withdraw_amount_in_base_currency
-                    {
-                        account_data
-                            .total_collateral_in_base_currency
-                            .sub(withdraw_amount_in_base_currency)
-                    } else {
-                        U256::from(0)
-                    };
-
-                    let factor = account_data
-                        .avg_liquidation_threshold
-                        .mul(account_data.total_collateral_in_base_currency)
-                        .sub(
-                            U256::from(liquidation_threshold).mul(withdraw_amount_in_base_currency),
-                        );
-
-                    let liquidation_threshold_after =
-                        if !total_collateral_after.is_zero() || factor >= U256::from(0) {
-                            factor.div(total_collateral_after)
-                        } else {
-                            U256::from(0)
-                        };
-
-                    return calculate_health_factor_from_balances(
-                        total_collateral_after,
+                    return calculate_health_factor_after_withdraw(
+                        account_data.total_collateral_in_base_currency,
                         account_data.total_debt_in_base_currency,
-                        liquidation_threshold_after,
+                        account_data.avg_liquidation_threshold,
+                        U256::from(liquidation_threshold),
+                        withdraw_amount_in_base_currency,
                     )
                 }
             }
@@ -342,33 +545,34 @@ impl<T: Storage<Data>> Internal for T {
             let afford_in_base_currency = account_data
                 .total_collateral_in_base_currency
                 .mul(account_data.avg_liquidation_threshold)
-                .sub(
+                .saturating_sub(
                     account_data
                         .total_debt_in_base_currency
                         .mul(U256::from(10000)),
                 );
 
-            let withdrawable_collateral_in_base_currency =
-                afford_in_base_currency.div(U256::from(liquidation_threshold));
+            let withdrawable_collateral_in_base_currency = if liquidation_threshold == 0 {
+                U256::from(0)
+            } else {
+                afford_in_base_currency.div(U256::from(liquidation_threshold))
+            };
 
             if let Some(price_oracle) = self._price_oracle() {
                 if let Some(price_asset) = PriceOracleRef::get_price(&price_oracle, asset) {
+                    if price_asset == 0 {
+                        return None
+                    }
                     let withdrawable_collateral = withdrawable_collateral_in_base_currency
                         .mul(U256::from(PRICE_PRECISION))
                         .div(U256::from(price_asset));
-                    let mut withdraw_amount = withdrawable_collateral;
-
-                    let mut health_factor =
-                        self._get_health_factor(account, asset, withdraw_amount.as_u128());
-
-                    // 1.01
-                    let health_factor_limit = U256::from(101 * (10_u128.pow(18)) / 100);
-                    while health_factor <= health_factor_limit {
-                        // Decrease withdraw amount
-                        withdraw_amount = withdraw_amount.mul(U256::from(95)).div(U256::from(100));
-                        health_factor =
-                            self._get_health_factor(account, asset, withdraw_amount.as_u128());
-                    }
+                    let withdraw_amount = calculate_withdrawable_amount(
+                        account_data.total_collateral_in_base_currency,
+                        account_data.total_debt_in_base_currency,
+                        account_data.avg_liquidation_threshold,
+                        U256::from(liquidation_threshold),
+                        U256::from(price_asset),
+                        self._health_factor_limit(),
+                    );
 
                     return Some(Withdrawable {
                         total_collateral_in_base_currency: account_data
@@ -393,6 +597,44 @@ impl<T: Storage<Data>> Internal for T {
         U256::from(0)
     }
 
+    default fn _redeemable_amount(&self, account: AccountId, asset: AccountId) -> Balance {
+        // the Controller allows redeeming within the collateral factors, not the liquidation thresholds
+        let loan_to_value = self._loan_to_value(asset);
+        if loan_to_value == 0 {
+            return Balance::MAX
+        }
+        let controller = match self._controller() {
+            Some(controller) => controller,
+            None => return 0,
+        };
+        let account_data =
+            match ControllerRef::calculate_user_account_data(&controller, account, None) {
+                Ok(account_data) => account_data,
+                Err(_) => return 0,
+            };
+        let price_asset = match self
+            ._price_oracle()
+            .and_then(|price_oracle| PriceOracleRef::get_price(&price_oracle, asset))
+        {
+            Some(price) if price > 0 => price,
+            _ => return 0,
+        };
+
+        let afford_in_base_currency = account_data
+            .total_collateral_in_base_currency
+            .mul(account_data.avg_ltv)
+            .saturating_sub(
+                account_data
+                    .total_debt_in_base_currency
+                    .mul(U256::from(PRICE_PRECISION)),
+            );
+        afford_in_base_currency
+            .mul(U256::from(10000))
+            .div(U256::from(loan_to_value))
+            .div(U256::from(price_asset))
+            .as_u128()
+    }
+
     default fn _loan_to_value(&self, asset: AccountId) -> u128 {
         if let Some(controller) = self._controller() {
             if let Some(pool) = ControllerRef::market_of_underlying(&controller, asset) {
@@ -487,9 +729,7 @@ impl<T: Storage<Data>> Internal for T {
         for _i in 0..loop_count {
             PoolRef::mint_to_builder(&pool, caller, next_deposit_amount)
                 .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
-                .try_invoke()
-                .unwrap()
-                .unwrap()?;
+                .try_invoke()???;
 
             next_deposit_amount = (next_deposit_amount * borrow_ratio) / 10000;
 
@@ -499,16 +739,12 @@ impl<T: Storage<Data>> Internal for T {
 
             PoolRef::borrow_for_builder(&pool, caller, next_deposit_amount)
                 .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
-                .try_invoke()
-                .unwrap()
-                .unwrap()?;
+                .try_invoke()???;
         }
         if next_deposit_amount != 0 {
             PoolRef::mint_to_builder(&pool, caller, next_deposit_amount)
                 .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
-                .try_invoke()
-                .unwrap()
-                .unwrap()?;
+                .try_invoke()???;
         }
         Ok(())
     }
@@ -558,4 +794,485 @@ impl<T: Storage<Data>> Internal for T {
 
         Ok(())
     }
+
+    default fn _leverage_with_flashloan(
+        &mut self,
+        asset: AccountId,
+        amount: Balance,
+        target_leverage: u128,
+    ) -> Result<()> {
+        if target_leverage <= 10000 {
+            return Err(Error::InappropriateLeverage)
+        }
+        let total_amount = U256::from(amount)
+            .mul(U256::from(target_leverage))
+            .div(U256::from(10000))
+            .as_u128();
+        let flashloan_amount = total_amount - amount;
+        // the borrowed share of the deposit must not exceed the loan to value
+        let ltv = self._loan_to_value(asset);
+        if U256::from(flashloan_amount).mul(U256::from(10000))
+            > U256::from(total_amount).mul(U256::from(ltv))
+        {
+            return Err(Error::InappropriateLeverage)
+        }
+
+        let caller = Self::env().caller();
+        let contract_addr = Self::env().account_id();
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        let pool = ControllerRef::market_of_underlying(&controller, asset)
+            .ok_or(Error::MarketNotListed)?;
+        let flashloan_gateway = self._flashloan_gateway()?;
+        PSP22Ref::transfer_from(&asset, caller, contract_addr, amount, Default::default())?;
+        PSP22Ref::approve(&asset, pool, u128::MAX)?;
+
+        // the debt for the flashloan is opened on behalf of the caller
+        let operation = FlashloanOperation::Leverage {
+            user: caller,
+            amount,
+        };
+        FlashloanGatewayRef::flashloan_builder(
+            &flashloan_gateway,
+            contract_addr,
+            vec![asset],
+            vec![flashloan_amount],
+            vec![FlashLoanType::Borrowing as u8],
+            caller,
+            operation.encode(),
+        )
+        .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
+        .try_invoke()???;
+        Ok(())
+    }
+
+    default fn _deleverage_with_flashloan(
+        &mut self,
+        asset: AccountId,
+        repay_amount: Balance,
+    ) -> Result<()> {
+        let caller = Self::env().caller();
+        let contract_addr = Self::env().account_id();
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        let pool = ControllerRef::market_of_underlying(&controller, asset)
+            .ok_or(Error::MarketNotListed)?;
+        let flashloan_gateway = self._flashloan_gateway()?;
+
+        let borrow_balance = PoolRef::borrow_balance_current(&pool, caller)?;
+        let repay_amount = repay_amount.min(borrow_balance);
+        if repay_amount == 0 {
+            return Err(Error::NoBorrowToRepay)
+        }
+
+        let operation = FlashloanOperation::Deleverage { user: caller };
+        FlashloanGatewayRef::flashloan_builder(
+            &flashloan_gateway,
+            contract_addr,
+            vec![asset],
+            vec![repay_amount],
+            vec![FlashLoanType::None as u8],
+            caller,
+            operation.encode(),
+        )
+        .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
+        .try_invoke()???;
+        Ok(())
+    }
+
+    default fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()> {
+        let flashloan_gateway = self._flashloan_gateway()?;
+        let contract_addr = Self::env().account_id();
+        let ReceivedFlashloan {
+            asset,
+            amount: flashloan_amount,
+            premium,
+            params: operation,
+        } = receive_flashloan::<FlashloanOperation>(
+            flashloan_gateway,
+            Self::env().caller(),
+            contract_addr,
+            assets,
+            amounts,
+            premiums,
+            initiator,
+            params,
+        )?;
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        let pool = ControllerRef::market_of_underlying(&controller, asset)
+            .ok_or(Error::MarketNotListed)?;
+
+        match operation {
+            FlashloanOperation::Leverage { user, amount } => {
+                // deposit the user's amount and the flashloaned amount at once
+                PoolRef::mint_to_builder(&pool, user, amount + flashloan_amount)
+                    .call_flags(ink_env::CallFlags::default().set_allow_reentry(true))
+                    .try_invoke()???;
+            }
+            FlashloanOperation::Deleverage { user } => {
+                PSP22Ref::approve(&asset, pool, flashloan_amount)?;
+                PoolRef::repay_borrow_behalf(&pool, user, flashloan_amount)?;
+
+                // redeem the user's deposit to return the flashloan with the premium
+                let amount_to_return = flashloan_amount + premium;
+                PoolRef::redeem_underlying_on_behalf(&pool, user, amount_to_return, contract_addr)?;
+                PSP22Ref::approve(&asset, flashloan_gateway, amount_to_return)?;
+            }
+        }
+        Ok(())
+    }
+
+    default fn _flashloan_gateway(&self) -> Result<AccountId> {
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        ControllerRef::flashloan_gateway(&controller).ok_or(Error::FlashloanGatewayIsNotSet)
+    }
+
+    default fn _deleverage_to(
+        &mut self,
+        asset: AccountId,
+        target_health_factor: U256,
+    ) -> Result<(Balance, Balance)> {
+        let (withdrawn, repaid) = self._deleverage(asset, target_health_factor)?;
+        if withdrawn > repaid {
+            PSP22Ref::transfer(
+                &asset,
+                Self::env().caller(),
+                withdrawn - repaid,
+                Vec::<u8>::new(),
+            )?;
+        }
+        Ok((withdrawn, repaid))
+    }
+
+    default fn _deleverage_to_eth(
+        &mut self,
+        target_health_factor: U256,
+    ) -> Result<(Balance, Balance)> {
+        let weth = self._weth_address().ok_or(Error::WETHIsNotSet)?;
+        let (withdrawn, repaid) = self._deleverage(weth, target_health_factor)?;
+        if withdrawn > repaid {
+            // unwrap in the same way as the WETH Gateway
+            WETHRef::withdraw(&weth, withdrawn - repaid)?;
+            if Self::env()
+                .transfer(Self::env().caller(), withdrawn - repaid)
+                .is_err()
+            {
+                return Err(Error::SafeETHTransferFailed)
+            }
+        }
+        Ok((withdrawn, repaid))
+    }
+
+    default fn _deleverage(
+        &mut self,
+        asset: AccountId,
+        target_health_factor: U256,
+    ) -> Result<(Balance, Balance)> {
+        // 1.00
+        if target_health_factor <= U256::from(PRICE_PRECISION) {
+            return Err(Error::InappropriateHealthFactor)
+        }
+        let caller = Self::env().caller();
+        let contract_addr = Self::env().account_id();
+        let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+        let pool = ControllerRef::market_of_underlying(&controller, asset)
+            .ok_or(Error::MarketNotListed)?;
+        PSP22Ref::approve(&asset, pool, u128::MAX)?;
+
+        let mut withdrawn: Balance = 0;
+        let mut repaid: Balance = 0;
+        let mut loop_remains = CLOSE_MAX_LOOPS;
+        while loop_remains > 0 {
+            if self._get_health_factor(caller, asset, 0) >= target_health_factor {
+                break
+            }
+            let borrow_balance = PoolRef::borrow_balance_current(&pool, caller)?;
+            let withdraw_amount = self
+                ._deleverage_amount(caller, asset, target_health_factor)
+                .min(self._withdrawable_amount(caller, asset).as_u128())
+                .min(self._redeemable_amount(caller, asset))
+                .min(borrow_balance);
+            if withdraw_amount == 0 {
+                break
+            }
+
+            // consumes the caller's allowance of the pool tokens
+            PoolRef::redeem_underlying_on_behalf(&pool, caller, withdraw_amount, contract_addr)?;
+            PoolRef::repay_borrow_behalf(&pool, caller, withdraw_amount)?;
+
+            withdrawn += withdraw_amount;
+            // the pool repays at most the borrow balance
+            repaid += borrow_balance.saturating_sub(PoolRef::borrow_balance_stored(&pool, caller));
+            loop_remains -= 1;
+        }
+
+        Ok((withdrawn, repaid))
+    }
+
+    default fn _deleverage_amount(
+        &self,
+        account: AccountId,
+        asset: AccountId,
+        target_health_factor: U256,
+    ) -> Balance {
+        let controller = match self._controller() {
+            Some(controller) => controller,
+            None => return 0,
+        };
+        let account_data =
+            match ControllerRef::calculate_user_account_data(&controller, account, None) {
+                Ok(account_data) => account_data,
+                Err(_) => return 0,
+            };
+        let price_asset = match self
+            ._price_oracle()
+            .and_then(|price_oracle| PriceOracleRef::get_price(&price_oracle, asset))
+        {
+            Some(price) if price > 0 => price,
+            _ => return 0,
+        };
+        let liquidation_threshold = U256::from(self._liquidation_threshold(asset));
+
+        // Withdrawing and repaying x in base currency gives
+        // target = (collateral * avg_lt - x * lt) * 1e18 / ((debt - x) * 10000)
+        let precision = U256::from(PRICE_PRECISION);
+        let numerator = target_health_factor
+            .mul(U256::from(10000))
+            .mul(account_data.total_debt_in_base_currency);
+        let subtrahend = account_data
+            .total_collateral_in_base_currency
+            .mul(account_data.avg_liquidation_threshold)
+            .mul(precision);
+        let denominator = target_health_factor.mul(U256::from(10000));
+        let denominator_subtrahend = liquidation_threshold.mul(precision);
+        if numerator <= subtrahend || denominator <= denominator_subtrahend {
+            return 0
+        }
+        let denominator = denominator.sub(denominator_subtrahend);
+        // round up not to stop just below the target
+        let amount_in_base_currency = numerator
+            .sub(subtrahend)
+            .add(denominator)
+            .sub(U256::from(1))
+            .div(denominator);
+        let amount = amount_in_base_currency
+            .mul(precision)
+            .add(U256::from(price_asset))
+            .sub(U256::from(1))
+            .div(U256::from(price_asset));
+        if amount > U256::from(u128::MAX) {
+            return u128::MAX
+        }
+        amount.as_u128()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health_factor_after(
+        total_collateral: U256,
+        total_debt: U256,
+        avg_liquidation_threshold: U256,
+        liquidation_threshold: U256,
+        price: U256,
+        amount: U256,
+    ) -> U256 {
+        calculate_health_factor_after_withdraw(
+            total_collateral,
+            total_debt,
+            avg_liquidation_threshold,
+            liquidation_threshold,
+            amount.mul(price).div(U256::from(PRICE_PRECISION)),
+        )
+    }
+
+    // previous implementation: start from the amount keeping the health factor at 1
+    // and decrease it by 5% until the health factor exceeds the limit
+    fn approximate_withdrawable_amount(
+        total_collateral: U256,
+        total_debt: U256,
+        avg_liquidation_threshold: U256,
+        liquidation_threshold: U256,
+        price: U256,
+        health_factor_limit: U256,
+    ) -> Option<U256> {
+        let afford = total_collateral
+            .mul(avg_liquidation_threshold)
+            .checked_sub(total_debt.mul(U256::from(10000)))?;
+        let mut amount = afford
+            .div(liquidation_threshold)
+            .mul(U256::from(PRICE_PRECISION))
+            .div(price);
+        for _ in 0..1000 {
+            let health_factor = health_factor_after(
+                total_collateral,
+                total_debt,
+                avg_liquidation_threshold,
+                liquidation_threshold,
+                price,
+                amount,
+            );
+            if health_factor > health_factor_limit {
+                return Some(amount)
+            }
+            amount = amount.mul(U256::from(95)).div(U256::from(100));
+        }
+        None
+    }
+
+    #[test]
+    fn test_calculate_withdrawable_amount_against_approximation() {
+        let limit = U256::from(DEFAULT_HEALTH_FACTOR_LIMIT);
+        let mut compared = 0;
+        for collateral in [
+            10_u128.pow(6),
+            123_456_789,
+            10_u128.pow(21),
+            7 * 10_u128.pow(24),
+        ] {
+            for price in [
+                10_u128.pow(18),
+                2 * 10_u128.pow(18),
+                35 * 10_u128.pow(16),
+                1_234_567,
+            ] {
+                for avg_liquidation_threshold in [5000_u128, 8000, 9000, 10000] {
+                    for liquidation_threshold in [5000_u128, 8000, 9000, 10000] {
+                        // debt in percentage of the liquidation threshold weighted collateral
+                        for debt_ratio in [1_u128, 10, 50, 90, 98, 100, 110] {
+                            let total_collateral = U256::from(collateral);
+                            let total_debt = U256::from(
+                                collateral * avg_liquidation_threshold / 10000 * debt_ratio / 100,
+                            );
+                            let avg_lt = U256::from(avg_liquidation_threshold);
+                            let lt = U256::from(liquidation_threshold);
+                            let price = U256::from(price);
+
+                            let amount = calculate_withdrawable_amount(
+                                total_collateral,
+                                total_debt,
+                                avg_lt,
+                                lt,
+                                price,
+                                limit,
+                            );
+                            let health_factor = health_factor_after(
+                                total_collateral,
+                                total_debt,
+                                avg_lt,
+                                lt,
+                                price,
+                                amount,
+                            );
+                            if amount.is_zero() {
+                                // nothing can be withdrawn only if the account is at the limit
+                                assert!(
+                                    health_factor <= limit
+                                        || health_factor_after(
+                                            total_collateral,
+                                            total_debt,
+                                            avg_lt,
+                                            lt,
+                                            price,
+                                            U256::from(1),
+                                        ) <= limit
+                                );
+                            } else {
+                                assert!(health_factor > limit);
+                            }
+
+                            if let Some(approximation) = approximate_withdrawable_amount(
+                                total_collateral,
+                                total_debt,
+                                avg_lt,
+                                lt,
+                                price,
+                                limit,
+                            ) {
+                                // exact amount is at least the approximation and at most 5% more
+                                assert!(amount >= approximation);
+                                assert!(
+                                    approximation.mul(U256::from(100)).add(U256::from(100))
+                                        >= amount.mul(U256::from(95))
+                                );
+                                compared += 1;
+                            } else {
+                                // the previous implementation does not terminate or underflows
+                                assert!(debt_ratio >= 100 || amount.is_zero());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert!(compared > 0);
+    }
+
+    #[test]
+    fn test_calculate_withdrawable_amount_is_maximal() {
+        let limit = U256::from(DEFAULT_HEALTH_FACTOR_LIMIT);
+        let price = U256::from(PRICE_PRECISION);
+        let total_collateral = U256::from(10_u128.pow(21));
+        let total_debt = U256::from(5 * 10_u128.pow(20));
+        let lt = U256::from(8000);
+        let amount =
+            calculate_withdrawable_amount(total_collateral, total_debt, lt, lt, price, limit);
+        // (1e21 * 0.8 - x * 0.8) / 5e20 > 1.01, less by the rounding of the health factor
+        let exact = U256::from(368_750_000_000_000_000_000_u128);
+        assert!(amount < exact);
+        assert!(amount.add(U256::from(1000)) > exact);
+        assert!(health_factor_after(total_collateral, total_debt, lt, lt, price, amount) > limit);
+        assert!(
+            health_factor_after(total_collateral, total_debt, lt, lt, price, amount.add(1))
+                <= limit
+        );
+    }
+
+    #[test]
+    fn test_calculate_withdrawable_amount_edge_cases() {
+        let limit = U256::from(DEFAULT_HEALTH_FACTOR_LIMIT);
+        let price = U256::from(PRICE_PRECISION);
+        let lt = U256::from(8000);
+        let total_collateral = U256::from(10_u128.pow(21));
+        // no debt
+        assert_eq!(
+            calculate_withdrawable_amount(total_collateral, U256::from(0), lt, lt, price, limit),
+            total_collateral
+        );
+        // underwater
+        assert_eq!(
+            calculate_withdrawable_amount(total_collateral, total_collateral, lt, lt, price, limit),
+            U256::from(0)
+        );
+        // no liquidation threshold or price
+        assert_eq!(
+            calculate_withdrawable_amount(
+                total_collateral,
+                U256::from(1),
+                lt,
+                U256::from(0),
+                price,
+                limit
+            ),
+            U256::from(0)
+        );
+        assert_eq!(
+            calculate_withdrawable_amount(
+                total_collateral,
+                U256::from(1),
+                lt,
+                lt,
+                U256::from(0),
+                limit
+            ),
+            U256::from(0)
+        );
+    }
 }
@@ -5,18 +5,27 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use super::{
+    controller::outflow_limit_enabled,
+    fixed_point::exp_scale,
+};
 pub use crate::traits::manager::*;
 use crate::traits::{
     controller::{
         ControllerRef,
         Error as ControllerError,
+        OutflowLimit,
     },
+    flashloan_gateway::FlashloanGatewayRef,
+    interest_rate_model::InterestRateModelRef,
     pool::{
         Error as PoolError,
         PoolRef,
     },
     types::WrappedU256,
+    upgradeable::UpgradeableRef,
 };
+use core::ops::Sub;
 use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::{
@@ -28,31 +37,79 @@ use openbrush::{
         psp22::PSP22Ref,
     },
     modifiers,
+    storage::Mapping,
     traits::{
         AccountId,
         Balance,
+        Hash,
         Storage,
+        Timestamp,
     },
 };
+use primitive_types::U256;
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+/// Version of the storage layout of `Data`, incremented when a migration is required
+pub const STORAGE_VERSION: u32 = 1;
 
 #[derive(Debug)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
 pub struct Data {
     /// AccountId of Controller
     pub controller: AccountId,
+    /// Bounds applied to Risk Steward's updates
+    pub risk_steward_config: RiskStewardConfig,
+    /// Last time Risk Steward updated the parameter of pool
+    pub last_risk_parameter_update: Mapping<(AccountId, RiskParameter), Timestamp>,
+    /// Interest rate models Risk Steward is allowed to set
+    pub steward_interest_rate_models: Mapping<AccountId, bool>,
 }
 
 pub const CONTROLLER_ADMIN: RoleType = ink::selector_id!("CONTROLLER_ADMIN");
 pub const TOKEN_ADMIN: RoleType = ink::selector_id!("TOKEN_ADMIN");
 pub const BORROW_CAP_GUARDIAN: RoleType = ink::selector_id!("BORROW_CAP_GUARDIAN");
 pub const PAUSE_GUARDIAN: RoleType = ink::selector_id!("PAUSE_GUARDIAN");
+pub const RISK_STEWARD: RoleType = ink::selector_id!("RISK_STEWARD");
+
+/// Check if the change from current to new is within max_change_mantissa relative to current
+pub fn risk_parameter_change_allowed(current: U256, new: U256, max_change_mantissa: U256) -> bool {
+    if current.is_zero() || new.is_zero() {
+        // zero means "not set" or "unlimited" for caps, so it can not be bounded relatively
+        return current == new
+    }
+    let diff = if new > current {
+        new.sub(current)
+    } else {
+        current.sub(new)
+    };
+    match (
+        diff.checked_mul(exp_scale()),
+        current.checked_mul(max_change_mantissa),
+    ) {
+        (Some(lhs), Some(rhs)) => lhs.le(&rhs),
+        (None, _) => false,
+        (Some(_), None) => true,
+    }
+}
 
 pub trait Internal {
     fn _controller(&self) -> AccountId;
     fn _set_controller(&mut self, id: AccountId) -> Result<()>;
     fn _set_price_oracle(&mut self, new_oracle: AccountId) -> Result<()>;
     fn _set_flashloan_gateway(&mut self, new_flashloan_gateway: AccountId) -> Result<()>;
+    fn _set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()>;
+    fn _set_flashloan_premium_to_protocol(&mut self, new_premium_to_protocol: u128) -> Result<()>;
+    fn _set_flashloan_borrower_premium(
+        &mut self,
+        borrower: AccountId,
+        premium: Option<u128>,
+    ) -> Result<()>;
+    fn _set_flashloan_whitelisted_receiver(
+        &mut self,
+        receiver: AccountId,
+        whitelisted: bool,
+    ) -> Result<()>;
+    fn _set_flashloan_whitelist_only(&mut self, enabled: bool) -> Result<()>;
+    fn _flashloan_gateway(&self) -> Result<AccountId>;
     fn _support_market(&mut self, pool: AccountId, underlying: AccountId) -> Result<()>;
     fn _support_market_with_collateral_factor_mantissa(
         &mut self,
@@ -73,6 +130,18 @@ pub trait Internal {
         new_liquidation_incentive_mantissa: WrappedU256,
     ) -> Result<()>;
     fn _set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()>;
+    fn _set_max_utilization_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()>;
+    fn _set_outflow_limit(&mut self, pool: AccountId, limit: OutflowLimit) -> Result<()>;
+    fn _raise_outflow_limit(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()>;
     fn _set_reserve_factor_mantissa(
         &mut self,
         pool: AccountId,
@@ -102,6 +171,49 @@ pub trait Internal {
         pool: AccountId,
         new_interest_rate_model: AccountId,
     ) -> Result<()>;
+
+    // risk steward
+    fn _risk_steward_config(&self) -> RiskStewardConfig;
+    fn _last_risk_parameter_update(
+        &self,
+        pool: AccountId,
+        parameter: RiskParameter,
+    ) -> Option<Timestamp>;
+    fn _set_risk_steward_config(&mut self, config: RiskStewardConfig) -> Result<()>;
+    fn _steward_interest_rate_model_allowed(&self, interest_rate_model: AccountId) -> bool;
+    fn _set_steward_interest_rate_model_allowed(
+        &mut self,
+        interest_rate_model: AccountId,
+        allowed: bool,
+    ) -> Result<()>;
+    fn _assert_risk_parameter_update_allowed(
+        &self,
+        pool: AccountId,
+        parameter: RiskParameter,
+    ) -> Result<()>;
+    fn _assert_risk_parameter_change_allowed(&self, current: U256, new: U256) -> Result<()>;
+    fn _record_risk_parameter_update(&mut self, pool: AccountId, parameter: RiskParameter);
+    fn _steward_set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()>;
+    fn _steward_set_collateral_factor_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_collateral_factor_mantissa: WrappedU256,
+    ) -> Result<()>;
+    fn _steward_set_liquidation_threshold(
+        &mut self,
+        pool: AccountId,
+        liquidation_threshold: u128,
+    ) -> Result<()>;
+    fn _steward_set_interest_rate_model(
+        &mut self,
+        pool: AccountId,
+        new_interest_rate_model: AccountId,
+    ) -> Result<()>;
+
+    // upgrade
+    fn _upgrade_controller(&mut self, code_hash: Hash) -> Result<()>;
+    fn _upgrade_pool(&mut self, pool: AccountId, code_hash: Hash) -> Result<()>;
+    fn _upgrade_flashloan_gateway(&mut self, code_hash: Hash) -> Result<()>;
 }
 
 impl<T: Storage<Data> + Storage<access_control::Data>> Manager for T {
@@ -127,6 +239,37 @@ impl<T: Storage<Data> + Storage<access_control::Data>> Manager for T {
         self._set_flashloan_gateway(new_flashloan_gateway)
     }
 
+    #[modifiers(access_control::only_role(CONTROLLER_ADMIN))]
+    default fn set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()> {
+        self._set_flashloan_premium_total(new_premium_total)
+    }
+
+    #[modifiers(access_control::only_role(CONTROLLER_ADMIN))]
+    default fn set_flashloan_premium_to_protocol(
+        &mut self,
+        new_premium_to_protocol: u128,
+    ) -> Result<()> {
+        self._set_flashloan_premium_to_protocol(new_premium_to_protocol)
+    }
+
+    #[modifiers(access_control::only_role(CONTROLLER_ADMIN))]
+    default fn set_flashloan_borrower_premium(
+        &mut self,
+        borrower: AccountId,
+        premium: Option<u128>,
+    ) -> Result<()> {
+        self._set_flashloan_borrower_premium(borrower, premium)
+    }
+
+    #[modifiers(access_control::only_role(CONTROLLER_ADMIN))]
+    default fn set_flashloan_whitelisted_receiver(
+        &mut self,
+        receiver: AccountId,
+        whitelisted: bool,
+    ) -> Result<()> {
+        self._set_flashloan_whitelisted_receiver(receiver, whitelisted)
+    }
+
     #[modifiers(access_control::only_role(CONTROLLER_ADMIN))]
     default fn support_market(&mut self, pool: AccountId, underlying: AccountId) -> Result<()> {
         self._support_market(pool, underlying)
@@ -187,6 +330,15 @@ impl<T: Storage<Data> + Storage<access_control::Data>> Manager for T {
         self._set_borrow_cap(pool, new_cap)
     }
 
+    #[modifiers(access_control::only_role(BORROW_CAP_GUARDIAN))]
+    default fn set_max_utilization_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()> {
+        self._set_max_utilization_mantissa(pool, new_max_utilization_mantissa)
+    }
+
     // For Pause Guardian
     #[modifiers(access_control::only_role(PAUSE_GUARDIAN))]
     default fn set_mint_guardian_paused(&mut self, pool: AccountId, paused: bool) -> Result<()> {
@@ -208,6 +360,26 @@ impl<T: Storage<Data> + Storage<access_control::Data>> Manager for T {
         self._set_transfer_guardian_paused(paused)
     }
 
+    #[modifiers(access_control::only_role(PAUSE_GUARDIAN))]
+    default fn set_flashloan_whitelist_only(&mut self, enabled: bool) -> Result<()> {
+        self._set_flashloan_whitelist_only(enabled)
+    }
+
+    #[modifiers(access_control::only_role(PAUSE_GUARDIAN))]
+    default fn raise_outflow_limit(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()> {
+        self._raise_outflow_limit(pool, amount, expires_at)
+    }
+
+    #[modifiers(access_control::only_role(CONTROLLER_ADMIN))]
+    default fn set_outflow_limit(&mut self, pool: AccountId, limit: OutflowLimit) -> Result<()> {
+        self._set_outflow_limit(pool, limit)
+    }
+
     // For Pool Admin
     #[modifiers(access_control::only_role(TOKEN_ADMIN))]
     default fn add_reserves(&mut self, pool: AccountId, amount: Balance) -> Result<()> {
@@ -269,6 +441,217 @@ impl<T: Storage<Data> + Storage<access_control::Data>> Manager for T {
     ) -> Result<()> {
         self._set_interest_rate_model(pool, new_interest_rate_model)
     }
+
+    default fn risk_steward_config(&self) -> RiskStewardConfig {
+        self._risk_steward_config()
+    }
+
+    default fn last_risk_parameter_update(
+        &self,
+        pool: AccountId,
+        parameter: RiskParameter,
+    ) -> Option<Timestamp> {
+        self._last_risk_parameter_update(pool, parameter)
+    }
+
+    #[modifiers(access_control::only_role(DEFAULT_ADMIN_ROLE))]
+    default fn set_risk_steward_config(&mut self, config: RiskStewardConfig) -> Result<()> {
+        self._set_risk_steward_config(config)
+    }
+
+    default fn steward_interest_rate_model_allowed(&self, interest_rate_model: AccountId) -> bool {
+        self._steward_interest_rate_model_allowed(interest_rate_model)
+    }
+
+    #[modifiers(access_control::only_role(DEFAULT_ADMIN_ROLE))]
+    default fn set_steward_interest_rate_model_allowed(
+        &mut self,
+        interest_rate_model: AccountId,
+        allowed: bool,
+    ) -> Result<()> {
+        self._set_steward_interest_rate_model_allowed(interest_rate_model, allowed)
+    }
+
+    // For Risk Steward
+    #[modifiers(access_control::only_role(RISK_STEWARD))]
+    default fn steward_set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()> {
+        self._steward_set_borrow_cap(pool, new_cap)
+    }
+
+    #[modifiers(access_control::only_role(RISK_STEWARD))]
+    default fn steward_set_collateral_factor_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_collateral_factor_mantissa: WrappedU256,
+    ) -> Result<()> {
+        self._steward_set_collateral_factor_mantissa(pool, new_collateral_factor_mantissa)
+    }
+
+    #[modifiers(access_control::only_role(RISK_STEWARD))]
+    default fn steward_set_liquidation_threshold(
+        &mut self,
+        pool: AccountId,
+        liquidation_threshold: u128,
+    ) -> Result<()> {
+        self._steward_set_liquidation_threshold(pool, liquidation_threshold)
+    }
+
+    #[modifiers(access_control::only_role(RISK_STEWARD))]
+    default fn steward_set_interest_rate_model(
+        &mut self,
+        pool: AccountId,
+        new_interest_rate_model: AccountId,
+    ) -> Result<()> {
+        self._steward_set_interest_rate_model(pool, new_interest_rate_model)
+    }
+
+    #[modifiers(access_control::only_role(DEFAULT_ADMIN_ROLE))]
+    default fn upgrade_controller(&mut self, code_hash: Hash) -> Result<()> {
+        self._upgrade_controller(code_hash)
+    }
+
+    #[modifiers(access_control::only_role(DEFAULT_ADMIN_ROLE))]
+    default fn upgrade_pool(&mut self, pool: AccountId, code_hash: Hash) -> Result<()> {
+        self._upgrade_pool(pool, code_hash)
+    }
+
+    #[modifiers(access_control::only_role(DEFAULT_ADMIN_ROLE))]
+    default fn upgrade_flashloan_gateway(&mut self, code_hash: Hash) -> Result<()> {
+        self._upgrade_flashloan_gateway(code_hash)
+    }
+
+    default fn batch_configure(&mut self, actions: Vec<ConfigAction>) -> Result<()> {
+        // Every action is dispatched to its message so that the role is checked per action.
+        // Returning an error reverts the state changes made by the preceding actions.
+        for action in actions {
+            match action {
+                ConfigAction::SetController(id) => self.set_controller(id)?,
+                ConfigAction::SetPriceOracle(new_oracle) => self.set_price_oracle(new_oracle)?,
+                ConfigAction::SetFlashloanGateway(new_flashloan_gateway) => {
+                    self.set_flashloan_gateway(new_flashloan_gateway)?
+                }
+                ConfigAction::SetFlashloanPremiumTotal(new_premium_total) => {
+                    self.set_flashloan_premium_total(new_premium_total)?
+                }
+                ConfigAction::SetFlashloanPremiumToProtocol(new_premium_to_protocol) => {
+                    self.set_flashloan_premium_to_protocol(new_premium_to_protocol)?
+                }
+                ConfigAction::SetFlashloanBorrowerPremium { borrower, premium } => {
+                    self.set_flashloan_borrower_premium(borrower, premium)?
+                }
+                ConfigAction::SetFlashloanWhitelistedReceiver {
+                    receiver,
+                    whitelisted,
+                } => self.set_flashloan_whitelisted_receiver(receiver, whitelisted)?,
+                ConfigAction::SupportMarket { pool, underlying } => {
+                    self.support_market(pool, underlying)?
+                }
+                ConfigAction::SupportMarketWithCollateralFactorMantissa {
+                    pool,
+                    underlying,
+                    collateral_factor_mantissa,
+                } => {
+                    self.support_market_with_collateral_factor_mantissa(
+                        pool,
+                        underlying,
+                        collateral_factor_mantissa,
+                    )?
+                }
+                ConfigAction::SetCollateralFactorMantissa {
+                    pool,
+                    new_collateral_factor_mantissa,
+                } => self.set_collateral_factor_mantissa(pool, new_collateral_factor_mantissa)?,
+                ConfigAction::SetMintGuardianPaused { pool, paused } => {
+                    self.set_mint_guardian_paused(pool, paused)?
+                }
+                ConfigAction::SetBorrowGuardianPaused { pool, paused } => {
+                    self.set_borrow_guardian_paused(pool, paused)?
+                }
+                ConfigAction::SetSeizeGuardianPaused(paused) => {
+                    self.set_seize_guardian_paused(paused)?
+                }
+                ConfigAction::SetTransferGuardianPaused(paused) => {
+                    self.set_transfer_guardian_paused(paused)?
+                }
+                ConfigAction::SetFlashloanWhitelistOnly(enabled) => {
+                    self.set_flashloan_whitelist_only(enabled)?
+                }
+                ConfigAction::SetCloseFactorMantissa(new_close_factor_mantissa) => {
+                    self.set_close_factor_mantissa(new_close_factor_mantissa)?
+                }
+                ConfigAction::SetLiquidationIncentiveMantissa(
+                    new_liquidation_incentive_mantissa,
+                ) => self.set_liquidation_incentive_mantissa(new_liquidation_incentive_mantissa)?,
+                ConfigAction::SetBorrowCap { pool, new_cap } => {
+                    self.set_borrow_cap(pool, new_cap)?
+                }
+                ConfigAction::SetMaxUtilizationMantissa {
+                    pool,
+                    new_max_utilization_mantissa,
+                } => self.set_max_utilization_mantissa(pool, new_max_utilization_mantissa)?,
+                ConfigAction::SetOutflowLimit { pool, limit } => {
+                    self.set_outflow_limit(pool, limit)?
+                }
+                ConfigAction::RaiseOutflowLimit {
+                    pool,
+                    amount,
+                    expires_at,
+                } => self.raise_outflow_limit(pool, amount, expires_at)?,
+                ConfigAction::SetReserveFactorMantissa {
+                    pool,
+                    new_reserve_factor_mantissa,
+                } => self.set_reserve_factor_mantissa(pool, new_reserve_factor_mantissa)?,
+                ConfigAction::SetLiquidationThreshold {
+                    pool,
+                    liquidation_threshold,
+                } => self.set_liquidation_threshold(pool, liquidation_threshold)?,
+                ConfigAction::SetIncentivesController {
+                    pool,
+                    incentives_controller,
+                } => self.set_incentives_controller(pool, incentives_controller)?,
+                ConfigAction::SetInterestRateModel {
+                    pool,
+                    new_interest_rate_model,
+                } => self.set_interest_rate_model(pool, new_interest_rate_model)?,
+                ConfigAction::SetControllerManager(manager) => {
+                    self.set_controller_manager(manager)?
+                }
+                ConfigAction::AcceptControllerManager => self.accept_controller_manager()?,
+                ConfigAction::SetPoolManager { pool, manager } => {
+                    self.set_pool_manager(pool, manager)?
+                }
+                ConfigAction::AcceptPoolManager(pool) => self.accept_pool_manager(pool)?,
+                ConfigAction::SetRiskStewardConfig(config) => {
+                    self.set_risk_steward_config(config)?
+                }
+                ConfigAction::StewardSetBorrowCap { pool, new_cap } => {
+                    self.steward_set_borrow_cap(pool, new_cap)?
+                }
+                ConfigAction::StewardSetCollateralFactorMantissa {
+                    pool,
+                    new_collateral_factor_mantissa,
+                } => {
+                    self.steward_set_collateral_factor_mantissa(
+                        pool,
+                        new_collateral_factor_mantissa,
+                    )?
+                }
+                ConfigAction::StewardSetLiquidationThreshold {
+                    pool,
+                    liquidation_threshold,
+                } => self.steward_set_liquidation_threshold(pool, liquidation_threshold)?,
+                ConfigAction::StewardSetInterestRateModel {
+                    pool,
+                    new_interest_rate_model,
+                } => self.steward_set_interest_rate_model(pool, new_interest_rate_model)?,
+                ConfigAction::SetStewardInterestRateModelAllowed {
+                    interest_rate_model,
+                    allowed,
+                } => self.set_steward_interest_rate_model_allowed(interest_rate_model, allowed)?,
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T: Storage<Data>> Internal for T {
@@ -292,6 +675,56 @@ impl<T: Storage<Data>> Internal for T {
         ControllerRef::set_flashloan_gateway(&self._controller(), new_flashloan_gateway)?;
         Ok(())
     }
+
+    default fn _set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()> {
+        FlashloanGatewayRef::set_flashloan_premium_total(
+            &self._flashloan_gateway()?,
+            new_premium_total,
+        )?;
+        Ok(())
+    }
+
+    default fn _set_flashloan_premium_to_protocol(
+        &mut self,
+        new_premium_to_protocol: u128,
+    ) -> Result<()> {
+        FlashloanGatewayRef::set_flashloan_premium_to_protocol(
+            &self._flashloan_gateway()?,
+            new_premium_to_protocol,
+        )?;
+        Ok(())
+    }
+
+    default fn _set_flashloan_borrower_premium(
+        &mut self,
+        borrower: AccountId,
+        premium: Option<u128>,
+    ) -> Result<()> {
+        FlashloanGatewayRef::set_borrower_premium(&self._flashloan_gateway()?, borrower, premium)?;
+        Ok(())
+    }
+
+    default fn _set_flashloan_whitelisted_receiver(
+        &mut self,
+        receiver: AccountId,
+        whitelisted: bool,
+    ) -> Result<()> {
+        FlashloanGatewayRef::set_whitelisted_receiver(
+            &self._flashloan_gateway()?,
+            receiver,
+            whitelisted,
+        )?;
+        Ok(())
+    }
+
+    default fn _set_flashloan_whitelist_only(&mut self, enabled: bool) -> Result<()> {
+        FlashloanGatewayRef::set_whitelist_only(&self._flashloan_gateway()?, enabled)?;
+        Ok(())
+    }
+
+    default fn _flashloan_gateway(&self) -> Result<AccountId> {
+        ControllerRef::flashloan_gateway(&self._controller()).ok_or(Error::FlashloanGatewayIsNotSet)
+    }
     default fn _support_market(&mut self, pool: AccountId, underlying: AccountId) -> Result<()> {
         ControllerRef::support_market(&self._controller(), pool, underlying)?;
         Ok(())
@@ -361,6 +794,32 @@ impl<T: Storage<Data>> Internal for T {
         ControllerRef::set_borrow_cap(&self._controller(), pool, new_cap)?;
         Ok(())
     }
+    default fn _set_max_utilization_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()> {
+        ControllerRef::set_max_utilization_mantissa(
+            &self._controller(),
+            pool,
+            new_max_utilization_mantissa,
+        )?;
+        Ok(())
+    }
+    default fn _set_outflow_limit(&mut self, pool: AccountId, limit: OutflowLimit) -> Result<()> {
+        ControllerRef::set_outflow_limit(&self._controller(), pool, limit)?;
+        PoolRef::set_outflow_limit_enabled(&pool, outflow_limit_enabled(&limit))?;
+        Ok(())
+    }
+    default fn _raise_outflow_limit(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()> {
+        ControllerRef::raise_outflow_limit(&self._controller(), pool, amount, expires_at)?;
+        Ok(())
+    }
     default fn _set_reserve_factor_mantissa(
         &mut self,
         pool: AccountId,
@@ -511,4 +970,281 @@ impl<T: Storage<Data>> Internal for T {
         PoolRef::accept_manager(&pool)?;
         Ok(())
     }
+
+    default fn _risk_steward_config(&self) -> RiskStewardConfig {
+        self.data().risk_steward_config
+    }
+
+    default fn _last_risk_parameter_update(
+        &self,
+        pool: AccountId,
+        parameter: RiskParameter,
+    ) -> Option<Timestamp> {
+        self.data()
+            .last_risk_parameter_update
+            .get(&(pool, parameter))
+    }
+
+    default fn _set_risk_steward_config(&mut self, config: RiskStewardConfig) -> Result<()> {
+        self.data().risk_steward_config = config;
+        Ok(())
+    }
+
+    default fn _steward_interest_rate_model_allowed(&self, interest_rate_model: AccountId) -> bool {
+        self.data()
+            .steward_interest_rate_models
+            .get(&interest_rate_model)
+            .unwrap_or(false)
+    }
+
+    default fn _set_steward_interest_rate_model_allowed(
+        &mut self,
+        interest_rate_model: AccountId,
+        allowed: bool,
+    ) -> Result<()> {
+        if allowed {
+            self.data()
+                .steward_interest_rate_models
+                .insert(&interest_rate_model, &true);
+        } else {
+            self.data()
+                .steward_interest_rate_models
+                .remove(&interest_rate_model);
+        }
+        Ok(())
+    }
+
+    default fn _assert_risk_parameter_update_allowed(
+        &self,
+        pool: AccountId,
+        parameter: RiskParameter,
+    ) -> Result<()> {
+        if let Some(last_update) = self._last_risk_parameter_update(pool, parameter) {
+            let min_delay = self._risk_steward_config().min_delay;
+            if Self::env().block_timestamp() < last_update.saturating_add(min_delay) {
+                return Err(Error::RiskParameterUpdateTooEarly)
+            }
+        }
+        Ok(())
+    }
+
+    default fn _assert_risk_parameter_change_allowed(
+        &self,
+        current: U256,
+        new: U256,
+    ) -> Result<()> {
+        let max_change_mantissa = U256::from(self._risk_steward_config().max_change_mantissa);
+        if !risk_parameter_change_allowed(current, new, max_change_mantissa) {
+            return Err(Error::RiskParameterChangeOutOfBounds)
+        }
+        Ok(())
+    }
+
+    default fn _record_risk_parameter_update(&mut self, pool: AccountId, parameter: RiskParameter) {
+        let now = Self::env().block_timestamp();
+        self.data()
+            .last_risk_parameter_update
+            .insert(&(pool, parameter), &now);
+    }
+
+    default fn _steward_set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()> {
+        self._assert_risk_parameter_update_allowed(pool, RiskParameter::BorrowCap)?;
+        let current: Option<Balance> = ControllerRef::borrow_cap(&self._controller(), pool);
+        self._assert_risk_parameter_change_allowed(
+            U256::from(current.unwrap_or_default()),
+            U256::from(new_cap),
+        )?;
+
+        self._set_borrow_cap(pool, new_cap)?;
+        self._record_risk_parameter_update(pool, RiskParameter::BorrowCap);
+        Ok(())
+    }
+
+    default fn _steward_set_collateral_factor_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_collateral_factor_mantissa: WrappedU256,
+    ) -> Result<()> {
+        self._assert_risk_parameter_update_allowed(pool, RiskParameter::CollateralFactor)?;
+        let current: Option<WrappedU256> =
+            ControllerRef::collateral_factor_mantissa(&self._controller(), pool);
+        self._assert_risk_parameter_change_allowed(
+            U256::from(current.unwrap_or_default()),
+            U256::from(new_collateral_factor_mantissa),
+        )?;
+
+        self._set_collateral_factor_mantissa(pool, new_collateral_factor_mantissa)?;
+        self._record_risk_parameter_update(pool, RiskParameter::CollateralFactor);
+        Ok(())
+    }
+
+    default fn _steward_set_liquidation_threshold(
+        &mut self,
+        pool: AccountId,
+        liquidation_threshold: u128,
+    ) -> Result<()> {
+        self._assert_risk_parameter_update_allowed(pool, RiskParameter::LiquidationThreshold)?;
+        let controller = self.data().controller;
+        let is_listed: bool = ControllerRef::is_listed(&controller, pool);
+        if !is_listed {
+            return Err(Error::from(ControllerError::MarketNotListed))
+        }
+        let current: u128 = PoolRef::liquidation_threshold(&pool);
+        self._assert_risk_parameter_change_allowed(
+            U256::from(current),
+            U256::from(liquidation_threshold),
+        )?;
+
+        self._set_liquidation_threshold(pool, liquidation_threshold)?;
+        self._record_risk_parameter_update(pool, RiskParameter::LiquidationThreshold);
+        Ok(())
+    }
+
+    default fn _steward_set_interest_rate_model(
+        &mut self,
+        pool: AccountId,
+        new_interest_rate_model: AccountId,
+    ) -> Result<()> {
+        // the rates are computed by the model itself, so only models vetted by the admin are accepted
+        if !self._steward_interest_rate_model_allowed(new_interest_rate_model) {
+            return Err(Error::StewardInterestRateModelNotAllowed)
+        }
+        self._assert_risk_parameter_update_allowed(pool, RiskParameter::InterestRateModel)?;
+        let controller = self.data().controller;
+        let is_listed: bool = ControllerRef::is_listed(&controller, pool);
+        if !is_listed {
+            return Err(Error::from(ControllerError::MarketNotListed))
+        }
+        let current: Option<AccountId> = PoolRef::interest_rate_model(&pool);
+        let current_model = current.ok_or(Error::from(PoolError::InterestRateModelIsNotSet))?;
+
+        // Interest rate models are immutable, so each rate and slope of the new model is bounded instead
+        let params = |model: &AccountId| -> [WrappedU256; 4] {
+            [
+                InterestRateModelRef::base_rate_per_milli_second(model),
+                InterestRateModelRef::multiplier_per_milli_second_slope_1(model),
+                InterestRateModelRef::multiplier_per_milli_second_slope_2(model),
+                InterestRateModelRef::kink(model),
+            ]
+        };
+        let (current_params, new_params) =
+            (params(&current_model), params(&new_interest_rate_model));
+        for (current, new) in current_params.iter().zip(new_params.iter()) {
+            self._assert_risk_parameter_change_allowed(U256::from(*current), U256::from(*new))?;
+        }
+
+        self._set_interest_rate_model(pool, new_interest_rate_model)?;
+        self._record_risk_parameter_update(pool, RiskParameter::InterestRateModel);
+        Ok(())
+    }
+
+    default fn _upgrade_controller(&mut self, code_hash: Hash) -> Result<()> {
+        UpgradeableRef::upgrade(&self._controller(), code_hash)?;
+        Ok(())
+    }
+
+    default fn _upgrade_pool(&mut self, pool: AccountId, code_hash: Hash) -> Result<()> {
+        let controller = self.data().controller;
+        let is_listed: bool = ControllerRef::is_listed(&controller, pool);
+        if !is_listed {
+            return Err(Error::from(ControllerError::MarketNotListed))
+        }
+
+        UpgradeableRef::upgrade(&pool, code_hash)?;
+        Ok(())
+    }
+
+    default fn _upgrade_flashloan_gateway(&mut self, code_hash: Hash) -> Result<()> {
+        UpgradeableRef::upgrade(&self._flashloan_gateway()?, code_hash)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mantissa(percent: u128) -> U256 {
+        exp_scale() * U256::from(percent) / U256::from(100)
+    }
+
+    #[test]
+    fn test_risk_parameter_change_allowed() {
+        struct Case {
+            current: u128,
+            new: u128,
+            max_change_percent: u128,
+            want: bool,
+        }
+        let cases = vec![
+            Case {
+                current: 1000,
+                new: 1000,
+                max_change_percent: 0,
+                want: true,
+            },
+            Case {
+                current: 1000,
+                new: 1001,
+                max_change_percent: 0,
+                want: false,
+            },
+            Case {
+                current: 1000,
+                new: 1100,
+                max_change_percent: 10,
+                want: true,
+            },
+            Case {
+                current: 1000,
+                new: 1101,
+                max_change_percent: 10,
+                want: false,
+            },
+            Case {
+                current: 1000,
+                new: 900,
+                max_change_percent: 10,
+                want: true,
+            },
+            Case {
+                current: 1000,
+                new: 899,
+                max_change_percent: 10,
+                want: false,
+            },
+            Case {
+                current: 0,
+                new: 0,
+                max_change_percent: 10,
+                want: true,
+            },
+            Case {
+                current: 0,
+                new: 1,
+                max_change_percent: 100,
+                want: false,
+            },
+            Case {
+                current: 1000,
+                new: 0,
+                max_change_percent: 100,
+                want: false,
+            },
+        ];
+        for case in cases {
+            assert_eq!(
+                risk_parameter_change_allowed(
+                    U256::from(case.current),
+                    U256::from(case.new),
+                    mantissa(case.max_change_percent)
+                ),
+                case.want,
+                "current: {}, new: {}, max_change_percent: {}",
+                case.current,
+                case.new,
+                case.max_change_percent
+            );
+        }
+    }
 }
@@ -5,17 +5,22 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub mod collateral_swap;
 pub mod controller;
-pub mod exp_no_err;
+pub mod debt_swap;
+pub mod fixed_point;
+pub mod flash_borrower;
+pub mod flash_lender;
+pub mod flashloan_adapter;
 pub mod flashloan_gateway;
 pub mod flashloan_receiver;
 pub mod incentives_controller;
 pub mod interest_rate_model;
 pub mod leverager;
 pub mod manager;
-pub mod percent_math;
 pub mod pool;
 pub mod price_oracle;
-pub mod wad_ray_math;
+pub mod repay_with_collateral;
+pub mod upgradeable;
 pub mod weth;
 pub mod weth_gateway;
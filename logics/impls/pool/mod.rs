@@ -10,8 +10,10 @@ use super::{
         PoolAttributes,
         PoolAttributesForSeizeCalculation,
     },
-    exp_no_err::{
+    fixed_point::{
         exp_scale,
+        mul_div_up,
+        to_balance,
         Exp,
     },
 };
@@ -30,7 +32,6 @@ pub use crate::traits::{
 };
 use core::ops::{
     Add,
-    Div,
     Mul,
     Sub,
 };
@@ -61,10 +62,13 @@ use openbrush::{
 };
 use primitive_types::U256;
 
+#[cfg(test)]
+mod tests;
 pub mod utils;
 use self::utils::{
     calculate_interest,
     exchange_rate,
+    pool_balance,
     protocol_seize_amount,
     protocol_seize_share_mantissa,
     reserve_factor_max_mantissa,
@@ -74,6 +78,8 @@ use self::utils::{
 };
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+/// Version of the storage layout of `Data`, incremented when a migration is required
+pub const STORAGE_VERSION: u32 = 1;
 pub const COLLATERAL_FACTOR_MANTISSA_DECIMALS: u32 = 18;
 pub const LIQUIDATION_THRESHOLD_DECIMALS: u32 = 4;
 
@@ -122,6 +128,10 @@ pub struct Data {
     pub delegate_allowance: Mapping<(AccountId, AccountId), Balance, AllowancesKey>,
     /// Represent if user is using his reserve as collateral or not
     pub using_reserve_as_collateral: Mapping<AccountId, bool>,
+    /// Whether the Controller has an outflow limit for this pool
+    pub outflow_limit_enabled: bool,
+    /// Underlying lent out by flashloans in progress, still counted as cash for the exchange rate
+    pub flashloaned: Balance,
 }
 
 pub struct AllowancesKey;
@@ -149,6 +159,8 @@ impl Default for Data {
             reserve_factor_mantissa: WrappedU256::from(U256::zero()),
             liquidation_threshold: 10000,
             using_reserve_as_collateral: Default::default(),
+            outflow_limit_enabled: false,
+            flashloaned: 0,
         }
     }
 }
@@ -168,7 +180,7 @@ pub trait Internal {
         data: Vec<u8>,
     ) -> core::result::Result<(), PSP22Error>;
     fn _mint(&mut self, minter: AccountId, mint_amount: Balance) -> Result<()>;
-    fn _redeem(&mut self, redeemer: AccountId, amount: Balance) -> Result<()>;
+    fn _redeem(&mut self, redeemer: AccountId, amount: Balance, to: AccountId) -> Result<()>;
     fn _borrow(
         &mut self,
         borrower: AccountId,
@@ -204,9 +216,12 @@ pub trait Internal {
     ) -> Result<()>;
     fn _set_interest_rate_model(&mut self, new_interest_rate_model: AccountId) -> Result<()>;
     fn _add_reserves(&mut self, amount: Balance) -> Result<()>;
+    fn _add_reserves_from_flashloan(&mut self, amount: Balance) -> Result<()>;
+    fn _settle_flashloan(&mut self, amount: Balance);
     fn _reduce_reserves(&mut self, admin: AccountId, amount: Balance) -> Result<()>;
     fn _sweep_token(&mut self, asset: AccountId) -> Result<()>;
     fn _set_liquidation_threshold(&mut self, new_liquidation_threshold: u128) -> Result<()>;
+    fn _set_outflow_limit_enabled(&mut self, enabled: bool);
     fn _approve_delegate(
         &mut self,
         owner: AccountId,
@@ -256,14 +271,16 @@ pub trait Internal {
         reserves: Balance,
         reserve_factor: WrappedU256,
     ) -> WrappedU256;
-    fn _borrow_balance_stored(&self, account: AccountId) -> Balance;
+    fn _borrow_balance_stored(&self, account: AccountId) -> Result<Balance>;
     fn _balance_of_underlying(&self, account: AccountId) -> Balance;
     fn _accrual_block_timestamp(&self) -> Timestamp;
     fn _borrow_index(&self) -> WrappedU256;
     fn _initial_exchange_rate_mantissa(&self) -> WrappedU256;
     fn _reserve_factor_mantissa(&self) -> WrappedU256;
-    fn _exchange_rate_stored(&self) -> U256;
+    fn _exchange_rate_stored(&self) -> Result<U256>;
     fn _liquidation_threshold(&self) -> u128;
+    fn _outflow_limit_enabled(&self) -> bool;
+    fn _flashloaned(&self) -> Balance;
     fn _delegate_allowance(&self, owner: &AccountId, delegatee: &AccountId) -> Balance;
     fn _using_reserve_as_collateral(&self, user: AccountId) -> Option<bool>;
     fn _get_interest_at(&self, at: Timestamp) -> Result<CalculateInterestOutput>;
@@ -382,14 +399,40 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
 
     default fn redeem_underlying(&mut self, redeem_amount: Balance) -> Result<()> {
         self._accrue_interest()?;
-        self._redeem(Self::env().caller(), redeem_amount)
+        let caller = Self::env().caller();
+        self._redeem(caller, redeem_amount, caller)
     }
 
     default fn redeem_all(&mut self) -> Result<()> {
         self._accrue_interest()?;
         let caller = Self::env().caller();
         let all_tokens_redeemed = self._balance_of(&caller);
-        self._redeem(caller, all_tokens_redeemed)
+        self._redeem(caller, all_tokens_redeemed, caller)
+    }
+
+    default fn redeem_underlying_on_behalf(
+        &mut self,
+        owner: AccountId,
+        redeem_amount: Balance,
+        to: AccountId,
+    ) -> Result<()> {
+        // `_redeem` is a no-op in these cases, which must not consume the allowance
+        if redeem_amount == 0 {
+            return Err(Error::InvalidParameter)
+        }
+        if !self._using_reserve_as_collateral(owner).unwrap_or_default() {
+            return Err(Error::ReserveIsNotEnabledAsCollateral)
+        }
+        let spender = Self::env().caller();
+        if spender != owner {
+            let allowance = self._allowance(&owner, &spender);
+            if allowance < redeem_amount {
+                return Err(Error::from(PSP22Error::InsufficientAllowance))
+            }
+            self._approve_from_to(owner, spender, allowance - redeem_amount)?;
+        }
+        self._accrue_interest()?;
+        self._redeem(owner, redeem_amount, to)
     }
 
     default fn borrow(&mut self, borrow_amount: Balance) -> Result<()> {
@@ -411,10 +454,23 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
     default fn borrow_for_flashloan(
         &mut self,
         borrower: AccountId,
+        delegatee: AccountId,
         borrow_amount: Balance,
     ) -> Result<()> {
         self._accrue_interest()?;
-        self._borrow(borrower, borrow_amount, false)
+        if borrower == delegatee {
+            self._borrow(borrower, borrow_amount, false)?;
+            self._settle_flashloan(borrow_amount);
+            return Ok(())
+        }
+
+        let delegate_allowance = self._delegate_allowance(&borrower, &delegatee);
+        if delegate_allowance < borrow_amount {
+            return Err(Error::InsufficientDelegateAllowance)
+        }
+        self._borrow(borrower, borrow_amount, false)?;
+        self._settle_flashloan(borrow_amount);
+        self._approve_delegate(borrower, delegatee, delegate_allowance - borrow_amount)
     }
 
     default fn repay_borrow(&mut self, repay_amount: Balance) -> Result<()> {
@@ -465,7 +521,11 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
     #[modifiers(only_flashloan_gateway)]
     default fn transfer_underlying(&mut self, to: AccountId, amount: Balance) -> Result<()> {
         self._accrue_interest()?;
-        self._transfer_underlying(to, amount)
+        self._transfer_underlying(to, amount)?;
+        // keeps the exchange rate until the flashloan is repaid or borrowed
+        let flashloaned = self._flashloaned().add(amount);
+        self.data::<Data>().flashloaned = flashloaned;
+        Ok(())
     }
 
     default fn set_controller(&mut self, new_controller: AccountId) -> Result<()> {
@@ -505,6 +565,18 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self._add_reserves(amount)
     }
 
+    #[modifiers(only_flashloan_gateway)]
+    default fn add_reserves_from_flashloan(&mut self, amount: Balance) -> Result<()> {
+        self._accrue_interest()?;
+        self._add_reserves_from_flashloan(amount)
+    }
+
+    #[modifiers(only_flashloan_gateway)]
+    default fn settle_flashloan(&mut self, amount: Balance) -> Result<()> {
+        self._settle_flashloan(amount);
+        Ok(())
+    }
+
     default fn reduce_reserves(&mut self, amount: Balance) -> Result<()> {
         self._assert_manager()?;
         self._accrue_interest()?;
@@ -521,6 +593,12 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self._set_liquidation_threshold(new_liquidation_threshold)
     }
 
+    default fn set_outflow_limit_enabled(&mut self, enabled: bool) -> Result<()> {
+        self._assert_manager()?;
+        self._set_outflow_limit_enabled(enabled);
+        Ok(())
+    }
+
     default fn approve_delegate(&mut self, delegatee: AccountId, amount: Balance) -> Result<()> {
         self._approve_delegate(Self::env().caller(), delegatee, amount)
     }
@@ -601,13 +679,21 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self._incentives_controller()
     }
 
+    default fn interest_rate_model(&self) -> Option<AccountId> {
+        self._rate_model()
+    }
+
+    // NOTE: views keep their infallible signatures, so a math error traps as before rather than returning a wrong value
     default fn exchange_rate_stored(&self) -> WrappedU256 {
-        WrappedU256::from(self._exchange_rate_stored())
+        WrappedU256::from(
+            self._exchange_rate_stored()
+                .expect("exchange rate calculation failed"),
+        )
     }
 
     default fn exchange_rate_current(&mut self) -> Result<WrappedU256> {
         self._accrue_interest()?;
-        Ok(self.exchange_rate_stored())
+        Ok(WrappedU256::from(self._exchange_rate_stored()?))
     }
 
     default fn get_cash_prior(&self) -> Balance {
@@ -635,24 +721,25 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         if using_as_collateral.unwrap_or(false) {
             return Ok((
                 self._balance_of(&account),
-                self._borrow_balance_stored(account),
-                self._exchange_rate_stored(),
+                self._borrow_balance_stored(account)?,
+                self._exchange_rate_stored()?,
             ))
         }
         Ok((
             0,
-            self._borrow_balance_stored(account),
-            self._exchange_rate_stored(),
+            self._borrow_balance_stored(account)?,
+            self._exchange_rate_stored()?,
         ))
     }
 
     default fn borrow_balance_stored(&self, account: AccountId) -> Balance {
         self._borrow_balance_stored(account)
+            .expect("borrow balance calculation failed")
     }
 
     default fn borrow_balance_current(&mut self, account: AccountId) -> Result<Balance> {
         self._accrue_interest()?;
-        Ok(self._borrow_balance_stored(account))
+        self._borrow_balance_stored(account)
     }
 
     default fn borrow_rate_per_msec(&self) -> WrappedU256 {
@@ -682,6 +769,10 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self._liquidation_threshold()
     }
 
+    default fn outflow_limit_enabled(&self) -> bool {
+        self._outflow_limit_enabled()
+    }
+
     default fn delegate_allowance(&self, owner: AccountId, delegatee: AccountId) -> Balance {
         self._delegate_allowance(&owner, &delegatee)
     }
@@ -702,7 +793,9 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         PoolStatus {
             total_supply: self._total_supply(),
             total_borrows: self._total_borrows(),
-            exchange_rate: self._exchange_rate_stored(),
+            exchange_rate: self
+                ._exchange_rate_stored()
+                .expect("exchange rate calculation failed"),
         }
     }
 }
@@ -721,7 +814,7 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         }
 
         let out = self._get_interest_at(at)?;
-        let mut data = self.data::<Data>();
+        let data = self.data::<Data>();
         data.accrual_block_timestamp = at;
         data.borrow_index = out.borrow_index.into();
         data.total_borrows = out.total_borrows;
@@ -740,10 +833,11 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         let reserves = self._total_reserves();
         let idx = self._borrow_index();
 
-        let rate_model = self._rate_model().ok_or(Error::InterestRateModelIsNotSet)?;
+        if self._rate_model().is_none() {
+            return Err(Error::InterestRateModelIsNotSet)
+        }
 
-        let borrow_rate =
-            InterestRateModelRef::get_borrow_rate(&rate_model, cash, borrows, reserves);
+        let borrow_rate = self._borrow_rate_per_msec(cash, borrows, reserves);
         calculate_interest(&CalculateInterestInput {
             total_borrows: borrows,
             total_reserves: reserves,
@@ -797,6 +891,8 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             account_borrow_balance,
             exchange_rate,
             total_borrows: self._total_borrows(),
+            cash: self._get_cash_prior(),
+            total_reserves: self._total_reserves(),
         };
 
         let controller = self
@@ -847,14 +943,19 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             return Err(Error::AccrualBlockNumberIsNotFresh)
         };
 
-        let exchange_rate = self._exchange_rate_stored(); // NOTE: need exchange_rate calculation before transfer underlying
+        let exchange_rate = self._exchange_rate_stored()?; // NOTE: need exchange_rate calculation before transfer underlying
         let caller = Self::env().caller();
 
+        if self._outflow_limit_enabled() {
+            ControllerRef::record_inflow(
+                &controller,
+                contract_addr,
+                mint_amount,
+                self._get_cash_prior(),
+            )?;
+        }
         self._transfer_underlying_from(caller, contract_addr, mint_amount)?;
-        let minted_tokens = U256::from(mint_amount)
-            .mul(exp_scale())
-            .div(exchange_rate)
-            .as_u128();
+        let minted_tokens = pool_balance(Exp(exchange_rate), mint_amount)?;
 
         // Check if it is first deposit.
         let lp_balance = self._balance_of(&minter);
@@ -868,7 +969,12 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         Ok(())
     }
 
-    default fn _redeem(&mut self, redeemer: AccountId, redeem_amount: Balance) -> Result<()> {
+    default fn _redeem(
+        &mut self,
+        redeemer: AccountId,
+        redeem_amount: Balance,
+        to: AccountId,
+    ) -> Result<()> {
         self._accrue_reward(redeemer)?;
         if redeem_amount == 0
             || !self
@@ -882,6 +988,7 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         let (_, account_borrow_balance, exchange_rate) = self.get_account_snapshot(redeemer)?;
         let account_balance = self._balance_of(&redeemer);
         let contract_addr = Self::env().account_id();
+        let cash = self._get_cash_prior();
 
         let pool_attribute = PoolAttributes {
             pool: Some(contract_addr),
@@ -892,6 +999,8 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             account_borrow_balance,
             exchange_rate,
             total_borrows: self._total_borrows(),
+            cash,
+            total_reserves: self._total_reserves(),
         };
         ControllerRef::redeem_allowed(
             &controller,
@@ -905,9 +1014,12 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             return Err(Error::AccrualBlockNumberIsNotFresh)
         }
 
-        if self._get_cash_prior() < redeem_amount {
+        if cash < redeem_amount {
             return Err(Error::RedeemTransferOutNotPossible)
         }
+        if self._outflow_limit_enabled() {
+            ControllerRef::record_outflow(&controller, contract_addr, redeem_amount, cash)?;
+        }
 
         let lp_balance = self._balance_of(&redeemer);
         if lp_balance == redeem_amount {
@@ -915,7 +1027,7 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         }
 
         self._burn_from(redeemer, redeem_amount)?;
-        self._transfer_underlying(redeemer, redeem_amount)?;
+        self._transfer_underlying(to, redeem_amount)?;
 
         self._emit_redeem_event(redeemer, redeem_amount);
 
@@ -935,6 +1047,13 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         let caller: ink_primitives::AccountId = Self::env().caller();
         let (account_balance, account_borrow_balance, exchange_rate) =
             self.get_account_snapshot(borrower)?;
+        let cash = self._get_cash_prior();
+        // The underlying of a flashloan has already been transferred out
+        let cash_before_borrow = if release_underlying {
+            cash
+        } else {
+            cash + borrow_amount
+        };
 
         let pool_attribute = PoolAttributes {
             pool: Some(contract_addr),
@@ -945,6 +1064,8 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             exchange_rate,
             total_borrows: self._total_borrows(),
             liquidation_threshold: self._liquidation_threshold(),
+            cash: cash_before_borrow,
+            total_reserves: self._total_reserves(),
         };
 
         ControllerRef::borrow_allowed(
@@ -959,11 +1080,19 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         if self._accrual_block_timestamp() != current_block_timestamp {
             return Err(Error::AccrualBlockNumberIsNotFresh)
         };
-        if self._get_cash_prior() < borrow_amount {
+        if cash < borrow_amount {
             return Err(Error::BorrowCashNotAvailable)
         }
+        if self._outflow_limit_enabled() {
+            ControllerRef::record_outflow(
+                &controller,
+                contract_addr,
+                borrow_amount,
+                cash_before_borrow,
+            )?;
+        }
 
-        let account_borrows_prev = self._borrow_balance_stored(borrower);
+        let account_borrows_prev = self._borrow_balance_stored(borrower)?;
         let account_borrows_new = account_borrows_prev + borrow_amount;
         let total_borrows_new = self._total_borrows() + borrow_amount;
 
@@ -1007,17 +1136,28 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             return Err(Error::AccrualBlockNumberIsNotFresh)
         };
 
-        let account_borrow_prev = self._borrow_balance_stored(borrower);
+        let account_borrow_prev = self._borrow_balance_stored(borrower)?;
         let repay_amount_final = if repay_amount > account_borrow_prev {
             account_borrow_prev
         } else {
             repay_amount
         };
 
+        if self._outflow_limit_enabled() {
+            let controller = self._controller().ok_or(Error::ControllerIsNotSet)?;
+            ControllerRef::record_inflow(
+                &controller,
+                contract_addr,
+                repay_amount_final,
+                self._get_cash_prior(),
+            )?;
+        }
         self._transfer_underlying_from(payer, contract_addr, repay_amount_final)?;
 
         let account_borrows_new = account_borrow_prev - repay_amount_final;
-        let total_borrows_new = self._total_borrows() - repay_amount_final;
+        // NOTE: debts are rounded up per borrower, so the debt of the last borrower can exceed total borrows by the rounding dust,
+        //   which the pool model bounds in its invariants
+        let total_borrows_new = self._total_borrows().saturating_sub(repay_amount_final);
 
         let borrow_index = self._borrow_index();
 
@@ -1064,6 +1204,8 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             exchange_rate,
             total_borrows: self._total_borrows(),
             liquidation_threshold: self._liquidation_threshold(),
+            cash: self._get_cash_prior(),
+            total_reserves: self._total_reserves(),
         };
 
         let current_block_timestamp = Self::env().block_timestamp();
@@ -1103,7 +1245,7 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
                 &controller,
                 contract_addr,
                 collateral,
-                WrappedU256::from(self._exchange_rate_stored()),
+                WrappedU256::from(self._exchange_rate_stored()?),
                 actual_repay_amount,
                 pool_borrowed_attributes,
                 pool_collateral_attributes,
@@ -1180,11 +1322,9 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         )?;
 
         // calculate the new borrower and liquidator token balances
-        let exchange_rate = Exp {
-            mantissa: WrappedU256::from(self._exchange_rate_stored()),
-        };
+        let exchange_rate = Exp(self._exchange_rate_stored()?);
         let (liquidator_seize_tokens, protocol_seize_amount, _) =
-            protocol_seize_amount(exchange_rate, seize_tokens, protocol_seize_share_mantissa());
+            protocol_seize_amount(exchange_rate, seize_tokens, protocol_seize_share_mantissa())?;
         let total_reserves_new = self._total_reserves() + protocol_seize_amount;
 
         // EFFECTS & INTERACTIONS
@@ -1253,6 +1393,20 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         Ok(())
     }
 
+    default fn _add_reserves_from_flashloan(&mut self, amount: Balance) -> Result<()> {
+        // the premium has been transferred with the repayment of the flashloan
+        let total_reserves_new = self._total_reserves().add(amount);
+        self.data::<Data>().total_reserves = total_reserves_new;
+
+        self._emit_reserves_added_event(Self::env().caller(), amount, total_reserves_new);
+        Ok(())
+    }
+
+    default fn _settle_flashloan(&mut self, amount: Balance) {
+        let flashloaned = self._flashloaned().saturating_sub(amount);
+        self.data::<Data>().flashloaned = flashloaned;
+    }
+
     default fn _reduce_reserves(&mut self, admin: AccountId, amount: Balance) -> Result<()> {
         let current_block_timestamp = Self::env().block_timestamp();
         if self._accrual_block_timestamp() != current_block_timestamp {
@@ -1307,6 +1461,10 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         Ok(())
     }
 
+    default fn _set_outflow_limit_enabled(&mut self, enabled: bool) {
+        self.data::<Data>().outflow_limit_enabled = enabled;
+    }
+
     default fn _approve_delegate(
         &mut self,
         owner: AccountId,
@@ -1376,6 +1534,8 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             account_borrow_balance,
             exchange_rate,
             total_borrows: self._total_borrows(),
+            cash: self._get_cash_prior(),
+            total_reserves: self._total_reserves(),
         };
 
         ControllerRef::balance_decrease_allowed(
@@ -1396,7 +1556,7 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
                 self._total_supply(),
                 self._total_borrows(),
                 self._balance_of(&user),
-                self._borrow_balance_stored(user),
+                self._borrow_balance_stored(user)?,
             );
 
             if handle_result.is_ok() {
@@ -1555,7 +1715,7 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self.data::<Data>().borrow_index
     }
 
-    default fn _borrow_balance_stored(&self, account: AccountId) -> Balance {
+    default fn _borrow_balance_stored(&self, account: AccountId) -> Result<Balance> {
         let snapshot = self
             .data::<Data>()
             .account_borrows
@@ -1563,22 +1723,26 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
             .unwrap_or_default();
 
         if snapshot.principal == 0 {
-            return 0
+            return Ok(0)
         }
 
-        let borrow_index = self._borrow_index();
-        let principal_times_index = U256::from(snapshot.principal).mul(U256::from(borrow_index));
-        principal_times_index
-            .div(U256::from(snapshot.interest_index))
-            .as_u128()
+        // NOTE: calculated in full precision as principal * index can exceed U256 long before the debt exceeds Balance,
+        // and rounded up so that the debt is never understated
+        let borrow_balance = mul_div_up(
+            U256::from(snapshot.principal),
+            U256::from(self._borrow_index()),
+            U256::from(snapshot.interest_index),
+        )?;
+        Ok(to_balance(borrow_balance)?)
     }
 
     default fn _balance_of_underlying(&self, account: AccountId) -> Balance {
-        let exchange_rate = Exp {
-            mantissa: self._exchange_rate_stored().into(),
-        };
+        let exchange_rate = Exp(self
+            ._exchange_rate_stored()
+            .expect("exchange rate calculation failed"));
         let pool_token_balance = self._balance_of(&account);
         underlying_balance(exchange_rate, pool_token_balance)
+            .expect("underlying balance calculation failed")
     }
 
     default fn _initial_exchange_rate_mantissa(&self) -> WrappedU256 {
@@ -1589,10 +1753,10 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self.data::<Data>().reserve_factor_mantissa
     }
 
-    default fn _exchange_rate_stored(&self) -> U256 {
+    default fn _exchange_rate_stored(&self) -> Result<U256> {
         exchange_rate(
             self.data::<PSP22Data>().supply,
-            self._get_cash_prior(),
+            self._get_cash_prior().add(self._flashloaned()),
             self._total_borrows(),
             self._total_reserves(),
             U256::from(self._initial_exchange_rate_mantissa()),
@@ -1603,6 +1767,14 @@ impl<T: Storage<Data> + Storage<psp22::Data> + Storage<psp22::extensions::metada
         self.data::<Data>().liquidation_threshold
     }
 
+    default fn _outflow_limit_enabled(&self) -> bool {
+        self.data::<Data>().outflow_limit_enabled
+    }
+
+    default fn _flashloaned(&self) -> Balance {
+        self.data::<Data>().flashloaned
+    }
+
     default fn _delegate_allowance(&self, owner: &AccountId, delegatee: &AccountId) -> Balance {
         self.data::<Data>()
             .delegate_allowance
@@ -1727,7 +1899,12 @@ impl From<controller::Error> for PSP22Error {
             controller::Error::MarketCountReachedToMaximum => {
                 convert("MarketCountReachedToMaximum")
             }
+            controller::Error::CallerIsNotPool => convert("CallerIsNotPool"),
+            controller::Error::InvalidOutflowLimit => convert("InvalidOutflowLimit"),
+            controller::Error::OutflowLimitExceeded => convert("OutflowLimitExceeded"),
+            controller::Error::MaxUtilizationExceeded => convert("MaxUtilizationExceeded"),
             controller::Error::PoolError => convert("PoolError"),
+            controller::Error::Math(_) => convert("Math"),
         }
     }
 }
@@ -0,0 +1,573 @@
+//! Property based tests of the pool accounting.
+//!
+//! `Model` mirrors the balance sheet kept in `Data` together with the PSP22 supply and
+//! applies the same arithmetic as `Internal` through the functions in `utils`. The
+//! Controller checks and cross-contract calls are left out, so liquidations do not
+//! require a shortfall and every account can always borrow the available cash.
+//!
+//! After every action the state of the model is loaded into `PoolContract`, which runs
+//! the real `Internal` implementation, and both have to agree on the stored borrow
+//! balances, the exchange rate and the accrual of interest.
+
+use super::{
+    utils::{
+        borrow_rate_max_mantissa,
+        calculate_interest,
+        exchange_rate,
+        pool_balance,
+        protocol_seize_amount,
+        protocol_seize_share_mantissa,
+        reserve_factor_max_mantissa,
+        CalculateInterestInput,
+    },
+    BorrowSnapshot,
+    Data,
+    Internal,
+};
+use crate::{
+    impls::{
+        controller::{
+            liquidate_calculate_seize_tokens,
+            LiquidateCalculateSeizeTokensInput,
+        },
+        fixed_point::{
+            exp_scale,
+            mul_div_up,
+            to_balance,
+            Exp,
+        },
+        interest_rate_model::utilization_rate,
+    },
+    traits::types::WrappedU256,
+};
+use core::ops::{
+    Add,
+    Div,
+    Mul,
+};
+use ink::env::{
+    test,
+    DefaultEnvironment,
+};
+use openbrush::{
+    contracts::psp22::{
+        extensions::metadata,
+        Data as PSP22Data,
+        Internal as PSP22Internal,
+    },
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+        Timestamp,
+    },
+};
+use primitive_types::U256;
+use proptest::prelude::*;
+
+const ACCOUNTS: usize = 4;
+const DECIMALS: u8 = 18;
+
+fn liquidation_incentive_mantissa() -> U256 {
+    U256::from(108).mul(U256::from(10_u128.pow(16))) // 1.08
+}
+
+fn account_id(account: usize) -> AccountId {
+    AccountId::from([account as u8; 32])
+}
+
+fn borrow_rate_per_milli_second(
+    cash: Balance,
+    borrows: Balance,
+    reserves: Balance,
+    multiplier_per_milli_second: U256,
+) -> U256 {
+    utilization_rate(cash, borrows, reserves)
+        .mul(multiplier_per_milli_second)
+        .div(exp_scale())
+}
+
+/// Pool storage driven through the real `Internal` implementation, with the cash and the
+/// borrow rate taken from the model instead of the underlying token and the rate model
+#[derive(Default, Storage)]
+#[ink::storage_item]
+struct PoolContract {
+    #[storage_field]
+    pool: Data,
+    #[storage_field]
+    psp22: PSP22Data,
+    #[storage_field]
+    metadata: metadata::Data,
+    cash: Balance,
+    multiplier_per_milli_second: WrappedU256,
+}
+
+impl Internal for PoolContract {
+    fn _get_cash_prior(&self) -> Balance {
+        self.cash
+    }
+
+    fn _borrow_rate_per_msec(
+        &self,
+        cash: Balance,
+        borrows: Balance,
+        reserves: Balance,
+    ) -> WrappedU256 {
+        borrow_rate_per_milli_second(
+            cash,
+            borrows,
+            reserves,
+            self.multiplier_per_milli_second.into(),
+        )
+        .into()
+    }
+}
+
+impl From<&Model> for PoolContract {
+    fn from(model: &Model) -> Self {
+        let mut contract = Self {
+            cash: model.cash,
+            multiplier_per_milli_second: model.multiplier_per_milli_second.into(),
+            ..Default::default()
+        };
+        // only has to be set, the borrow rate is overridden above
+        contract.pool.rate_model = Some(account_id(ACCOUNTS));
+        contract.pool.total_borrows = model.total_borrows;
+        contract.pool.total_reserves = model.total_reserves;
+        contract.pool.borrow_index = model.borrow_index.into();
+        contract.pool.accrual_block_timestamp = model.accrual_block_timestamp;
+        contract.pool.reserve_factor_mantissa = model.reserve_factor_mantissa.into();
+        contract.pool.initial_exchange_rate_mantissa = model.initial_exchange_rate_mantissa.into();
+        contract.psp22.supply = model.total_supply;
+        // every entry is written, the off-chain storage outlives the contract
+        for account in 0..ACCOUNTS {
+            let id = account_id(account);
+            let snapshot = model.account_borrows[account];
+            contract.pool.account_borrows.insert(
+                &id,
+                &BorrowSnapshot {
+                    principal: snapshot.principal,
+                    interest_index: snapshot.interest_index.into(),
+                },
+            );
+            contract
+                .pool
+                .using_reserve_as_collateral
+                .insert(&id, &model.using_as_collateral[account]);
+            contract
+                .psp22
+                .balances
+                .insert(&id, &model.balances[account]);
+        }
+        contract
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Mint {
+        account: usize,
+        amount: Balance,
+    },
+    Redeem {
+        account: usize,
+        amount: Balance,
+    },
+    Borrow {
+        account: usize,
+        amount: Balance,
+    },
+    Repay {
+        account: usize,
+        amount: Balance,
+    },
+    Accrue {
+        elapsed: Timestamp,
+    },
+    Liquidate {
+        liquidator: usize,
+        borrower: usize,
+        amount: Balance,
+    },
+}
+
+impl Action {
+    /// Returns whether the action is allowed to decrease the debt of `account`
+    fn repays(&self, account: usize) -> bool {
+        match *self {
+            Action::Repay { account: a, .. } => a == account,
+            Action::Liquidate { borrower, .. } => borrower == account,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Snapshot {
+    principal: Balance,
+    interest_index: U256,
+}
+
+/// Reason for which the pool reverts an action, the state is left untouched
+type Revert = &'static str;
+
+#[derive(Clone, Debug)]
+struct Model {
+    cash: Balance,
+    total_borrows: Balance,
+    total_reserves: Balance,
+    borrow_index: U256,
+    accrual_block_timestamp: Timestamp,
+    reserve_factor_mantissa: U256,
+    initial_exchange_rate_mantissa: U256,
+    // borrow rate per millisecond at 100% utilization
+    multiplier_per_milli_second: U256,
+    total_supply: Balance,
+    balances: [Balance; ACCOUNTS],
+    using_as_collateral: [bool; ACCOUNTS],
+    account_borrows: [Snapshot; ACCOUNTS],
+    // upper bound of what the debts can exceed total borrows by, not part of the pool:
+    // borrows and repayments round the debt up once, and the dust accrues interest like a debt
+    rounding_dust: U256,
+}
+
+impl Model {
+    fn new(reserve_factor_mantissa: U256, multiplier_per_milli_second: U256) -> Self {
+        Self {
+            cash: 0,
+            total_borrows: 0,
+            total_reserves: 0,
+            borrow_index: exp_scale(),
+            accrual_block_timestamp: 0,
+            reserve_factor_mantissa,
+            initial_exchange_rate_mantissa: exp_scale(),
+            multiplier_per_milli_second,
+            total_supply: 0,
+            balances: [0; ACCOUNTS],
+            using_as_collateral: [false; ACCOUNTS],
+            account_borrows: [Snapshot::default(); ACCOUNTS],
+            rounding_dust: U256::zero(),
+        }
+    }
+
+    fn borrow_balance(&self, account: usize) -> Result<Balance, Revert> {
+        let snapshot = self.account_borrows[account];
+        if snapshot.principal == 0 {
+            return Ok(0)
+        }
+        mul_div_up(
+            U256::from(snapshot.principal),
+            self.borrow_index,
+            snapshot.interest_index,
+        )
+        .and_then(to_balance)
+        .map_err(|_| "borrow balance")
+    }
+
+    fn exchange_rate(&self) -> Result<U256, Revert> {
+        exchange_rate(
+            self.total_supply,
+            self.cash,
+            self.total_borrows,
+            self.total_reserves,
+            self.initial_exchange_rate_mantissa,
+        )
+        .map_err(|_| "exchange rate")
+    }
+
+    fn apply(&mut self, action: &Action) -> Result<(), Revert> {
+        match *action {
+            Action::Mint { account, amount } => self.mint(account, amount),
+            Action::Redeem { account, amount } => self.redeem(account, amount),
+            Action::Borrow { account, amount } => self.borrow(account, amount),
+            Action::Repay { account, amount } => self.repay_borrow(account, amount).map(|_| ()),
+            Action::Accrue { elapsed } => {
+                self.accrue_interest_at(self.accrual_block_timestamp + elapsed)
+            }
+            Action::Liquidate {
+                liquidator,
+                borrower,
+                amount,
+            } => self.liquidate_borrow(liquidator, borrower, amount),
+        }
+    }
+
+    fn accrue_interest_at(&mut self, at: Timestamp) -> Result<(), Revert> {
+        if self.accrual_block_timestamp == at {
+            return Ok(())
+        }
+        let borrow_rate = borrow_rate_per_milli_second(
+            self.cash,
+            self.total_borrows,
+            self.total_reserves,
+            self.multiplier_per_milli_second,
+        );
+        let out = calculate_interest(&CalculateInterestInput {
+            total_borrows: self.total_borrows,
+            total_reserves: self.total_reserves,
+            borrow_index: self.borrow_index,
+            borrow_rate,
+            old_block_timestamp: self.accrual_block_timestamp,
+            new_block_timestamp: at,
+            reserve_factor_mantissa: self.reserve_factor_mantissa,
+        })
+        .map_err(|_| "calculate interest")?;
+        self.rounding_dust = mul_div_up(self.rounding_dust, out.borrow_index, self.borrow_index)
+            .map_err(|_| "rounding dust")?;
+        self.accrual_block_timestamp = at;
+        self.borrow_index = out.borrow_index;
+        self.total_borrows = out.total_borrows;
+        self.total_reserves = out.total_reserves;
+        Ok(())
+    }
+
+    fn mint(&mut self, minter: usize, mint_amount: Balance) -> Result<(), Revert> {
+        let exchange_rate = self.exchange_rate()?;
+        let minted_tokens =
+            pool_balance(Exp(exchange_rate), mint_amount).map_err(|_| "pool balance")?;
+        if self.balances[minter] == 0 {
+            self.using_as_collateral[minter] = true;
+        }
+        self.cash += mint_amount;
+        self.total_supply += minted_tokens;
+        self.balances[minter] += minted_tokens;
+        Ok(())
+    }
+
+    fn redeem(&mut self, redeemer: usize, redeem_amount: Balance) -> Result<(), Revert> {
+        if redeem_amount == 0 || !self.using_as_collateral[redeemer] {
+            return Ok(())
+        }
+        if self.cash < redeem_amount {
+            return Err("redeem transfer out not possible")
+        }
+        if self.balances[redeemer] < redeem_amount {
+            return Err("insufficient balance")
+        }
+        if self.balances[redeemer] == redeem_amount {
+            self.using_as_collateral[redeemer] = false;
+        }
+        self.balances[redeemer] -= redeem_amount;
+        self.total_supply -= redeem_amount;
+        self.cash -= redeem_amount;
+        Ok(())
+    }
+
+    fn borrow(&mut self, borrower: usize, borrow_amount: Balance) -> Result<(), Revert> {
+        if self.cash < borrow_amount {
+            return Err("borrow cash not available")
+        }
+        let account_borrows_new = self.borrow_balance(borrower)? + borrow_amount;
+        self.account_borrows[borrower] = Snapshot {
+            principal: account_borrows_new,
+            interest_index: self.borrow_index,
+        };
+        self.total_borrows += borrow_amount;
+        self.cash -= borrow_amount;
+        self.rounding_dust += U256::one();
+        Ok(())
+    }
+
+    fn repay_borrow(&mut self, borrower: usize, repay_amount: Balance) -> Result<Balance, Revert> {
+        let account_borrow_prev = self.borrow_balance(borrower)?;
+        let repay_amount_final = repay_amount.min(account_borrow_prev);
+        self.account_borrows[borrower] = Snapshot {
+            principal: account_borrow_prev - repay_amount_final,
+            interest_index: self.borrow_index,
+        };
+        self.total_borrows = self.total_borrows.saturating_sub(repay_amount_final);
+        self.cash += repay_amount_final;
+        self.rounding_dust += U256::one();
+        Ok(repay_amount_final)
+    }
+
+    fn liquidate_borrow(
+        &mut self,
+        liquidator: usize,
+        borrower: usize,
+        repay_amount: Balance,
+    ) -> Result<(), Revert> {
+        if liquidator == borrower {
+            return Err("liquidator is borrower")
+        }
+        if repay_amount == 0 {
+            return Err("close amount is zero")
+        }
+        let actual_repay_amount = self.repay_borrow(borrower, repay_amount)?;
+        let seize_tokens = liquidate_calculate_seize_tokens(&LiquidateCalculateSeizeTokensInput {
+            price_borrowed_mantissa: exp_scale(),
+            decimals_borrowed: DECIMALS,
+            price_collateral_mantissa: exp_scale(),
+            decimals_collateral: DECIMALS,
+            exchange_rate_mantissa: self.exchange_rate()?,
+            liquidation_incentive_mantissa: liquidation_incentive_mantissa(),
+            actual_repay_amount,
+        })
+        .map_err(|_| "seize tokens")?;
+        self.seize(liquidator, borrower, seize_tokens)
+    }
+
+    fn seize(
+        &mut self,
+        liquidator: usize,
+        borrower: usize,
+        seize_tokens: Balance,
+    ) -> Result<(), Revert> {
+        if !self.using_as_collateral[borrower] {
+            return Err("reserve is not enabled as collateral")
+        }
+        if self.balances[borrower] < seize_tokens {
+            return Err("insufficient balance")
+        }
+        let (liquidator_seize_tokens, protocol_seize_amount, _) = protocol_seize_amount(
+            Exp(self.exchange_rate()?),
+            seize_tokens,
+            protocol_seize_share_mantissa(),
+        )
+        .map_err(|_| "protocol seize amount")?;
+        self.total_reserves += protocol_seize_amount;
+        self.balances[borrower] -= seize_tokens;
+        self.total_supply -= seize_tokens;
+        self.balances[liquidator] += liquidator_seize_tokens;
+        self.total_supply += liquidator_seize_tokens;
+        Ok(())
+    }
+
+    fn assert_invariants(&self, prev: &Self, action: &Action) -> Result<(), TestCaseError> {
+        let assets = U256::from(self.cash).add(U256::from(self.total_borrows));
+        prop_assert!(
+            U256::from(self.total_reserves) <= assets,
+            "reserves exceed cash + borrows"
+        );
+        if self.total_supply > 0 {
+            // the exchange rate is rounded down, so the underlying of the supply is short of
+            // the net assets by less than one unit per whole token
+            let net_assets = assets - U256::from(self.total_reserves);
+            let supply = U256::from(self.total_supply);
+            let backed = self.exchange_rate().unwrap().mul(supply).div(exp_scale());
+            prop_assert!(backed <= net_assets, "supply is worth more than net assets");
+            prop_assert!(
+                net_assets <= backed.add(supply.div(exp_scale())).add(U256::one()),
+                "net assets exceed the supply by more than rounding"
+            );
+        }
+        prop_assert!(
+            self.borrow_index >= prev.borrow_index,
+            "borrow index decreased"
+        );
+        // each debt is rounded up by less than one unit on top of the dust
+        let debts = (0..ACCOUNTS).fold(U256::zero(), |sum, account| {
+            sum.add(U256::from(
+                self.borrow_balance(account).unwrap_or(Balance::MAX),
+            ))
+        });
+        prop_assert!(
+            debts
+                <= U256::from(self.total_borrows)
+                    .add(self.rounding_dust)
+                    .add(U256::from(ACCOUNTS)),
+            "debts exceed total borrows by more than rounding"
+        );
+        for account in 0..ACCOUNTS {
+            if !action.repays(account) {
+                // a debt that no longer fits in a Balance has not decreased either
+                prop_assert!(
+                    self.borrow_balance(account).unwrap_or(Balance::MAX)
+                        >= prev.borrow_balance(account).unwrap_or(Balance::MAX),
+                    "debt of account {} decreased without a repayment",
+                    account
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn assert_agrees_with_contract(&self) -> Result<(), TestCaseError> {
+        let contract = PoolContract::from(self);
+        prop_assert_eq!(
+            contract._exchange_rate_stored().ok(),
+            self.exchange_rate().ok()
+        );
+        for account in 0..ACCOUNTS {
+            prop_assert_eq!(
+                contract._borrow_balance_stored(account_id(account)).ok(),
+                self.borrow_balance(account).ok(),
+                "borrow balance of account {}",
+                account
+            );
+            prop_assert_eq!(
+                contract._balance_of(&account_id(account)),
+                self.balances[account]
+            );
+        }
+        Ok(())
+    }
+
+    fn assert_contract_accrues_interest_at(&self, at: Timestamp) -> Result<(), TestCaseError> {
+        let mut model = self.clone();
+        let expected = model.accrue_interest_at(at);
+        let mut contract = PoolContract::from(self);
+        let accrued = contract._accrue_interest_at(at);
+        prop_assert_eq!(accrued.is_ok(), expected.is_ok());
+        if accrued.is_ok() {
+            prop_assert_eq!(contract.pool.accrual_block_timestamp, at);
+            prop_assert_eq!(U256::from(contract.pool.borrow_index), model.borrow_index);
+            prop_assert_eq!(contract.pool.total_borrows, model.total_borrows);
+            prop_assert_eq!(contract.pool.total_reserves, model.total_reserves);
+        }
+        Ok(())
+    }
+}
+
+fn account() -> impl Strategy<Value = usize> {
+    0..ACCOUNTS
+}
+
+// mixes dust with large amounts to exercise rounding on both ends
+fn amount() -> impl Strategy<Value = Balance> {
+    prop_oneof![0..1_000_u128, 0..10_u128.pow(24)]
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        (account(), amount()).prop_map(|(account, amount)| Action::Mint { account, amount }),
+        (account(), amount()).prop_map(|(account, amount)| Action::Redeem { account, amount }),
+        (account(), amount()).prop_map(|(account, amount)| Action::Borrow { account, amount }),
+        (account(), amount()).prop_map(|(account, amount)| Action::Repay { account, amount }),
+        prop_oneof![1..1_000_u64, 1..30 * 24 * 60 * 60 * 1_000_u64]
+            .prop_map(|elapsed| Action::Accrue { elapsed }),
+        (account(), account(), amount()).prop_map(|(liquidator, borrower, amount)| {
+            Action::Liquidate {
+                liquidator,
+                borrower,
+                amount,
+            }
+        }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_pool_accounting_invariants(
+        reserve_factor in 0..=reserve_factor_max_mantissa().as_u128(),
+        multiplier in 0..=borrow_rate_max_mantissa().as_u128(),
+        actions in prop::collection::vec(action(), 1..64),
+    ) {
+        // the storage of `PoolContract` belongs to the callee
+        test::set_callee::<DefaultEnvironment>(account_id(ACCOUNTS));
+        let mut model = Model::new(U256::from(reserve_factor), U256::from(multiplier));
+        for action in actions.iter() {
+            if let Action::Accrue { elapsed } = *action {
+                model.assert_contract_accrues_interest_at(model.accrual_block_timestamp + elapsed)?;
+            }
+            let prev = model.clone();
+            let mut next = model.clone();
+            // a reverted action leaves the state untouched
+            if next.apply(action).is_ok() {
+                model = next;
+            }
+            model.assert_invariants(&prev, action)?;
+            model.assert_agrees_with_contract()?;
+        }
+    }
+}
@@ -5,17 +5,23 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::super::exp_no_err::{
-    exp_scale,
-    Exp,
+use crate::impls::{
+    fixed_point::{
+        checked_add,
+        checked_mul,
+        checked_sub,
+        exp_scale,
+        mul_div_down,
+        mul_div_up,
+        to_balance,
+        Exp,
+        MathError,
+        Ray,
+    },
+    interest_rate_model::milliseconds_per_year,
 };
 pub use crate::traits::pool::*;
-use crate::{
-    impls::wad_ray_math::exp_ray_ratio,
-    traits::types::WrappedU256,
-};
 use core::ops::{
-    Add,
     Div,
     Mul,
     Sub,
@@ -57,11 +63,9 @@ pub struct CalculateInterestOutput {
     pub interest_accumulated: Balance,
 }
 
-fn compound_interest(borrow_rate_per_millisec: &Exp, delta: U256) -> Exp {
+fn compound_interest(borrow_rate_per_millisec: &Exp, delta: U256) -> Result<Exp> {
     if delta.is_zero() {
-        return Exp {
-            mantissa: U256::zero().into(),
-        }
+        return Ok(Exp::zero())
     };
     let delta_minus_one = delta.sub(U256::one());
     let delta_minus_two = if delta.gt(&U256::from(2)) {
@@ -69,27 +73,32 @@ fn compound_interest(borrow_rate_per_millisec: &Exp, delta: U256) -> Exp {
     } else {
         U256::zero()
     };
-    let base_power_two = borrow_rate_per_millisec
-        .to_ray()
-        .mul(borrow_rate_per_millisec.to_ray());
-    let base_power_three = base_power_two.mul(borrow_rate_per_millisec.to_ray());
-    let second_term_ray = delta
-        .mul(delta_minus_one)
-        .mul(U256::from(base_power_two.mantissa))
-        .div(U256::from(2));
-    let third_term_ray = delta
-        .mul(delta_minus_one)
-        .mul(delta_minus_two)
-        .mul(U256::from(base_power_three.mantissa))
-        .div(U256::from(6));
+    let rate_ray = borrow_rate_per_millisec.to_ray()?;
+    let base_power_two = rate_ray.mul_down(rate_ray)?;
+    let base_power_three = base_power_two.mul_down(rate_ray)?;
+    let second_term_ray = Ray(checked_mul(
+        checked_mul(delta, delta_minus_one)?,
+        base_power_two.raw(),
+    )?
+    .div(U256::from(2)));
+    let third_term_ray = Ray(checked_mul(
+        checked_mul(checked_mul(delta, delta_minus_one)?, delta_minus_two)?,
+        base_power_three.raw(),
+    )?
+    .div(U256::from(6)));
 
-    Exp {
-        mantissa: U256::from(borrow_rate_per_millisec.mantissa)
-            .mul(delta)
-            .add(second_term_ray.div(exp_ray_ratio()))
-            .add(third_term_ray.div(exp_ray_ratio()))
-            .into(),
-    }
+    Ok(Exp(checked_add(
+        checked_add(
+            checked_mul(borrow_rate_per_millisec.raw(), delta)?,
+            second_term_ray.to_exp_down().raw(),
+        )?,
+        third_term_ray.to_exp_down().raw(),
+    )?))
+}
+
+/// Annual percentage yield of a rate per milliseconds, compounded in the same way as the interest accrual (Decimals: 18)
+pub fn calculate_apy(rate_per_msec: U256) -> Result<U256> {
+    Ok(compound_interest(&Exp(rate_per_msec), milliseconds_per_year())?.raw())
 }
 
 pub fn calculate_interest(input: &CalculateInterestInput) -> Result<CalculateInterestOutput> {
@@ -100,82 +109,85 @@ pub fn calculate_interest(input: &CalculateInterestInput) -> Result<CalculateInt
         .new_block_timestamp
         .abs_diff(input.old_block_timestamp);
 
-    let compound_interest_factor = compound_interest(
-        &Exp {
-            mantissa: input.borrow_rate.into(),
-        },
-        U256::from(delta),
-    );
-
-    let interest_accumulated =
-        compound_interest_factor.mul_scalar_truncate(U256::from(input.total_borrows));
+    let compound_interest_factor = compound_interest(&Exp(input.borrow_rate), U256::from(delta))?;
 
-    let total_borrows_new = interest_accumulated.as_u128().add(input.total_borrows);
-    let total_reserves_new = Exp {
-        mantissa: WrappedU256::from(input.reserve_factor_mantissa),
-    }
-    .mul_scalar_truncate_add_uint(interest_accumulated, U256::from(input.total_reserves));
-    let borrow_index_new = compound_interest_factor
-        .mul_scalar_truncate_add_uint(input.borrow_index.into(), input.borrow_index.into());
+    // NOTE: the borrow index (what each borrower owes) is rounded up and total borrows grow by the same ratio, also rounded up,
+    //   so that total borrows keep covering the debts of the borrowers
+    let borrow_index_new = checked_add(
+        compound_interest_factor.mul_int_up(input.borrow_index)?,
+        input.borrow_index,
+    )?;
+    let total_borrows_new = mul_div_up(
+        U256::from(input.total_borrows),
+        borrow_index_new,
+        input.borrow_index,
+    )?;
+    let interest_accumulated = checked_sub(total_borrows_new, U256::from(input.total_borrows))?;
+    let total_reserves_new = checked_add(
+        Exp(input.reserve_factor_mantissa).mul_int_up(interest_accumulated)?,
+        U256::from(input.total_reserves),
+    )?;
     Ok(CalculateInterestOutput {
         borrow_index: borrow_index_new,
-        interest_accumulated: interest_accumulated.as_u128(),
-        total_borrows: total_borrows_new,
-        total_reserves: total_reserves_new.as_u128(),
+        interest_accumulated: to_balance(interest_accumulated)?,
+        total_borrows: to_balance(total_borrows_new)?,
+        total_reserves: to_balance(total_reserves_new)?,
     })
 }
 
 // returns liquidator_seize_tokens, protocol_seize_amount and protocol_seize_tokens
+// NOTE: the protocol share is rounded up, the liquidator receives the remainder
 pub fn protocol_seize_amount(
     exchange_rate: Exp,
     seize_tokens: Balance,
     protocol_seize_share_mantissa: U256,
-) -> (Balance, Balance, Balance) {
-    let protocol_seize_tokens = Exp {
-        mantissa: WrappedU256::from(U256::from(seize_tokens).mul(protocol_seize_share_mantissa)),
-    }
-    .truncate();
-    let liquidator_seize_tokens = U256::from(seize_tokens).sub(protocol_seize_tokens);
-    (
-        liquidator_seize_tokens.as_u128(),
-        exchange_rate
-            .mul_scalar_truncate(protocol_seize_tokens)
-            .as_u128(),
-        protocol_seize_tokens.as_u128(),
-    )
+) -> Result<(Balance, Balance, Balance)> {
+    let protocol_seize_tokens =
+        Exp(protocol_seize_share_mantissa).mul_int_up(U256::from(seize_tokens))?;
+    let liquidator_seize_tokens = checked_sub(U256::from(seize_tokens), protocol_seize_tokens)?;
+    Ok((
+        to_balance(liquidator_seize_tokens)?,
+        to_balance(exchange_rate.mul_int_down(protocol_seize_tokens)?)?,
+        to_balance(protocol_seize_tokens)?,
+    ))
 }
 
+// NOTE: rounded down so that pool tokens are never worth more than the underlying backing them
 pub fn exchange_rate(
     total_supply: Balance,
     total_cash: Balance,
     total_borrows: Balance,
     total_reserves: Balance,
     default_exchange_rate_mantissa: U256,
-) -> U256 {
+) -> Result<U256> {
     if total_supply == 0 {
-        return default_exchange_rate_mantissa
+        return Ok(default_exchange_rate_mantissa)
     };
-    let cash_plus_borrows_minus_reserves = total_cash.add(total_borrows).sub(total_reserves);
-    U256::from(cash_plus_borrows_minus_reserves)
-        .mul(exp_scale())
-        .div(U256::from(total_supply))
+    let cash_plus_borrows_minus_reserves = checked_sub(
+        checked_add(U256::from(total_cash), U256::from(total_borrows))?,
+        U256::from(total_reserves),
+    )?;
+    Ok(mul_div_down(
+        cash_plus_borrows_minus_reserves,
+        exp_scale(),
+        U256::from(total_supply),
+    )?)
 }
 
-pub fn underlying_balance(exchange_rate: Exp, pool_token_balance: Balance) -> Balance {
-    exchange_rate
-        .mul_scalar_truncate(pool_token_balance.into())
-        .as_u128()
+/// Underlying amount of pool tokens, rounded down
+pub fn underlying_balance(
+    exchange_rate: Exp,
+    pool_token_balance: Balance,
+) -> core::result::Result<Balance, MathError> {
+    to_balance(exchange_rate.mul_int_down(U256::from(pool_token_balance))?)
 }
 
-pub fn pool_balance(exchange_rate: Exp, underlying_token_balance: Balance) -> Balance {
-    U256::from(
-        Exp {
-            mantissa: U256::from(underlying_token_balance).into(),
-        }
-        .div(exchange_rate)
-        .mantissa,
-    )
-    .as_u128()
+/// Pool tokens of underlying amount, rounded down
+pub fn pool_balance(
+    exchange_rate: Exp,
+    underlying_token_balance: Balance,
+) -> core::result::Result<Balance, MathError> {
+    to_balance(exchange_rate.div_int_down(U256::from(underlying_token_balance))?)
 }
 
 #[cfg(test)]
@@ -184,6 +196,7 @@ mod tests {
     use super::Exp;
 
     use super::*;
+    use core::ops::Add;
     use primitive_types::U256;
     fn mantissa() -> U256 {
         U256::from(10).pow(U256::from(18))
@@ -212,20 +225,14 @@ mod tests {
             want: Exp,
         }
         let inputs: &[TestInput] = &[TestInput {
-            borrow_rate_per_millisec: Exp {
-                mantissa: WrappedU256::from(U256::from(1).mul(mantissa())),
-            },
+            borrow_rate_per_millisec: Exp(U256::from(1).mul(mantissa())),
             delta: U256::from(1000_i128 * 60 * 60 * 24 * 30 * 12), // 1 year
-            want: Exp {
-                mantissa: WrappedU256::from(
-                    U256::from(501530650214400000002592_i128)
-                        .mul(U256::from(10000000000000000000000000_i128)),
-                ),
-            },
+            want: Exp(U256::from(501530650214400000002592_i128)
+                .mul(U256::from(10000000000000000000000000_i128))),
         }];
         for input in inputs {
-            let got = compound_interest(&input.borrow_rate_per_millisec, input.delta);
-            assert_eq!(got.mantissa, input.want.mantissa)
+            let got = compound_interest(&input.borrow_rate_per_millisec, input.delta).unwrap();
+            assert_eq!(got, input.want)
         }
     }
 
@@ -246,13 +253,13 @@ mod tests {
             .mul(multiplier_per_milliseconds_slope_one_mantissa)
             .div(mantissa())
             .add(base_rate_per_milli_sec);
-        let got = compound_interest(
-            &Exp {
-                mantissa: borrow_rate_mantissa.into(),
-            },
-            milliseconds_per_year,
+        let got = compound_interest(&Exp(borrow_rate_mantissa), milliseconds_per_year).unwrap();
+        assert_eq!(got.raw(), U256::from(444436848000000_i128));
+        assert_eq!(
+            calculate_apy(borrow_rate_mantissa).unwrap(),
+            U256::from(444436848000000_i128)
         );
-        assert_eq!(U256::from(got.mantissa), U256::from(444436848000000_i128))
+        assert_eq!(calculate_apy(U256::zero()).unwrap(), U256::zero());
     }
 
     #[test]
@@ -321,29 +328,79 @@ mod tests {
     // protocol_seize_amount = exchangeRate * protocolSeizeTokens
     fn test_protocol_seize_amount() {
         // 1%
-        let exchange_rate = Exp {
-            mantissa: (WrappedU256::from(
-                U256::from(10)
-                    .pow(U256::from(18))
-                    .mul(U256::one())
-                    .div(U256::from(100)),
-            )),
-        };
+        let exchange_rate = Exp(U256::from(10)
+            .pow(U256::from(18))
+            .mul(U256::one())
+            .div(U256::from(100)));
         let seize_tokens = 10_u128.pow(18).mul(100000000000);
         let protocol_seize_tokens = seize_tokens.mul(10).div(100);
         let protocol_seize_share_mantissa = U256::from(10_u128.pow(18).div(10)); // 10%
         let liquidator_seize_tokens_want = seize_tokens.mul(9).div(10);
         let protocol_seize_amount_want = protocol_seize_tokens.mul(1).div(100); // 1%
         let (liquidator_seize_tokens_got, protocol_seize_amount_got, protocol_seize_tokens_got) =
-            protocol_seize_amount(exchange_rate, seize_tokens, protocol_seize_share_mantissa);
+            protocol_seize_amount(exchange_rate, seize_tokens, protocol_seize_share_mantissa)
+                .unwrap();
         assert_eq!(liquidator_seize_tokens_got, liquidator_seize_tokens_want);
         assert_eq!(protocol_seize_amount_got, protocol_seize_amount_want);
         assert_eq!(protocol_seize_tokens_got, protocol_seize_tokens);
     }
+    #[test]
+    fn test_protocol_seize_amount_rounds_in_favor_of_protocol() {
+        // 2.8% of 1001 = 28.028
+        let (liquidator_seize_tokens, protocol_seize_amount_got, protocol_seize_tokens) =
+            protocol_seize_amount(Exp(exp_scale()), 1001, protocol_seize_share_mantissa()).unwrap();
+        assert_eq!(protocol_seize_tokens, 29);
+        assert_eq!(liquidator_seize_tokens, 972);
+        assert_eq!(protocol_seize_amount_got, 29);
+    }
+
+    #[test]
+    fn test_calculate_interest_rounds_up() {
+        let out = calculate_interest(&CalculateInterestInput {
+            old_block_timestamp: 0,
+            new_block_timestamp: 1,
+            borrow_index: exp_scale(),
+            borrow_rate: U256::one(),
+            reserve_factor_mantissa: mantissa().div(10),
+            total_borrows: 10,
+            total_reserves: 0,
+        })
+        .unwrap();
+        // 1e-18 of interest is owed by borrowers, and total borrows round up with their debts
+        assert_eq!(out.borrow_index, exp_scale().add(U256::one()));
+        assert_eq!(out.total_borrows, 11);
+        assert_eq!(out.interest_accumulated, 1);
+        assert_eq!(out.total_reserves, 1);
+    }
+
+    #[test]
+    fn test_exchange_rate_fails_if_reserves_exceed_assets() {
+        assert_eq!(
+            exchange_rate(1, 1, 1, 3, U256::zero()),
+            Err(Error::Math(MathError::MathSubtractionUnderflow))
+        );
+    }
+
+    #[test]
+    fn test_underlying_and_pool_balance_round_down() {
+        // 1.5
+        let exchange_rate = Exp(exp_scale().mul(U256::from(3)).div(U256::from(2)));
+        assert_eq!(underlying_balance(exchange_rate, 3).unwrap(), 4);
+        assert_eq!(pool_balance(exchange_rate, 4).unwrap(), 2);
+        assert_eq!(
+            pool_balance(Exp::zero(), 4),
+            Err(MathError::MathDivisionByZero)
+        );
+        assert_eq!(
+            underlying_balance(Exp(U256::MAX), u128::MAX),
+            Err(MathError::MathMultiplicationOverflow)
+        );
+    }
+
     #[test]
     fn test_exchange_rate_in_case_total_supply_is_zero() {
         let initial = U256::one().mul(exp_scale());
-        assert_eq!(exchange_rate(0, 1, 1, 1, initial), initial);
+        assert_eq!(exchange_rate(0, 1, 1, 1, initial).unwrap(), initial);
     }
 
     #[test]
@@ -394,7 +451,8 @@ mod tests {
                     case.total_borrows,
                     case.total_reserves,
                     U256::from(0)
-                ),
+                )
+                .unwrap(),
                 rate_want
             )
         }
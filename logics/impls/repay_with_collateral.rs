@@ -0,0 +1,179 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::flashloan_adapter::{
+    Data,
+    Internal as FlashloanAdapterInternal,
+    ReceivedFlashloan,
+};
+use crate::traits::flashloan_gateway::FlashLoanType;
+pub use crate::traits::repay_with_collateral::*;
+use ink::prelude::vec::Vec;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+    Storage,
+};
+use scale::Encode;
+
+pub trait Internal {
+    fn _repay_with_collateral(
+        &mut self,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        repay_amount: Balance,
+        max_collateral_amount: Balance,
+    ) -> Result<()>;
+
+    /// Body of `FlashloanReceiver::execute_operation`
+    fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()>;
+
+    // event emission
+    fn _emit_repay_with_collateral_event(
+        &self,
+        user: AccountId,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        collateral_amount: Balance,
+        repay_amount: Balance,
+    );
+}
+
+impl<T: Storage<Data>> RepayWithCollateral for T {
+    default fn flashloan_gateway(&self) -> Option<AccountId> {
+        self._flashloan_gateway()
+    }
+
+    default fn controller(&self) -> Option<AccountId> {
+        self._controller()
+    }
+
+    default fn router(&self) -> Option<AccountId> {
+        self._router()
+    }
+
+    default fn repay_with_collateral(
+        &mut self,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        repay_amount: Balance,
+        max_collateral_amount: Balance,
+    ) -> Result<()> {
+        self._repay_with_collateral(
+            collateral_asset,
+            debt_asset,
+            repay_amount,
+            max_collateral_amount,
+        )
+    }
+}
+
+impl<T: Storage<Data>> Internal for T {
+    default fn _repay_with_collateral(
+        &mut self,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        repay_amount: Balance,
+        max_collateral_amount: Balance,
+    ) -> Result<()> {
+        if collateral_asset == debt_asset {
+            return Err(Error::SameAsset)
+        }
+        self._pool_of(collateral_asset)?;
+
+        let caller = Self::env().caller();
+        let borrow_balance = self._borrow_balance_of(debt_asset, caller)?;
+        let repay_amount = repay_amount.min(borrow_balance);
+        if repay_amount == 0 {
+            return Err(Error::NoBorrowToRepay)
+        }
+
+        let params = RepayWithCollateralParams {
+            user: caller,
+            collateral_asset,
+            max_collateral_amount,
+        };
+        self._flashloan(
+            debt_asset,
+            repay_amount,
+            FlashLoanType::None,
+            params.encode(),
+        )?;
+        Ok(())
+    }
+
+    default fn _execute_operation(
+        &self,
+        assets: Vec<AccountId>,
+        amounts: Vec<Balance>,
+        premiums: Vec<Balance>,
+        initiator: AccountId,
+        params: Vec<u8>,
+    ) -> Result<()> {
+        let ReceivedFlashloan {
+            asset: debt_asset,
+            amount: repay_amount,
+            premium,
+            params:
+                RepayWithCollateralParams {
+                    user,
+                    collateral_asset,
+                    max_collateral_amount,
+                },
+        } = self._receive_flashloan(assets, amounts, premiums, initiator, params)?;
+
+        // repay the user's borrow with the flashloaned asset
+        self._repay_on_behalf(debt_asset, user, repay_amount)?;
+
+        let amount_to_return = repay_amount + premium;
+        let collateral_amount =
+            self._get_amount_in(collateral_asset, debt_asset, amount_to_return)?;
+        if collateral_amount > max_collateral_amount {
+            return Err(Error::ExcessiveCollateralAmount)
+        }
+
+        // withdraw the user's collateral to be swapped
+        self._redeem_on_behalf(collateral_asset, user, collateral_amount)?;
+        let amount_in = self._swap_exact_out(
+            collateral_asset,
+            debt_asset,
+            amount_to_return,
+            collateral_amount,
+        )?;
+        if amount_in < collateral_amount {
+            // supply back the collateral not spent by the swap
+            self._supply_on_behalf(collateral_asset, user, collateral_amount - amount_in)?;
+        }
+
+        self._approve_repayment(debt_asset, amount_to_return)?;
+        self._emit_repay_with_collateral_event(
+            user,
+            collateral_asset,
+            debt_asset,
+            amount_in,
+            repay_amount,
+        );
+        Ok(())
+    }
+
+    default fn _emit_repay_with_collateral_event(
+        &self,
+        _user: AccountId,
+        _collateral_asset: AccountId,
+        _debt_asset: AccountId,
+        _collateral_amount: Balance,
+        _repay_amount: Balance,
+    ) {
+    }
+}
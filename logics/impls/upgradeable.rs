@@ -0,0 +1,135 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+pub use crate::traits::upgradeable::*;
+use ink::{
+    env::CallFlags,
+    prelude::vec::Vec,
+};
+use openbrush::{
+    storage::Mapping,
+    traits::{
+        AccountId,
+        Hash,
+        Storage,
+    },
+};
+
+pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+/// Storage versions are kept under their own storage key,
+/// so that they can be read by any version of the code.
+#[derive(Debug, Default)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct Data {
+    /// Storage version each storage struct has been migrated to, by its storage key
+    pub storage_versions: Mapping<u32, u32>,
+}
+
+pub trait Internal {
+    /// Storage key and storage version of each storage struct of the current code. Should be overridden by the contract
+    fn _code_storage_versions(&self) -> Vec<(u32, u32)>;
+    /// AccountId allowed to upgrade. Should be overridden by the contract
+    fn _upgrader(&self) -> Option<AccountId>;
+    /// Migration of the storage struct under storage_key from the given storage version to the one of the current code
+    fn _migrate(&mut self, storage_key: u32, from: u32) -> Result<()>;
+
+    fn _storage_version(&self, storage_key: u32) -> u32;
+    fn _init_storage_versions(&mut self);
+    fn _assert_upgrader(&self) -> Result<()>;
+    fn _upgrade(&self, code_hash: Hash) -> Result<()>;
+    fn _migrate_storage(&mut self) -> Result<()>;
+
+    // event emission
+    fn _emit_upgraded_event(&self, code_hash: Hash);
+    fn _emit_migrated_event(&self, storage_key: u32, from: u32, to: u32);
+}
+
+impl<T: Storage<Data>> Upgradeable for T {
+    default fn storage_versions(&self) -> Vec<(u32, u32)> {
+        self._code_storage_versions()
+            .into_iter()
+            .map(|(storage_key, _)| (storage_key, self._storage_version(storage_key)))
+            .collect()
+    }
+
+    default fn upgrade(&self, code_hash: Hash) -> Result<()> {
+        self._assert_upgrader()?;
+        self._upgrade(code_hash)?;
+        self._emit_upgraded_event(code_hash);
+        Ok(())
+    }
+
+    default fn migrate(&mut self) -> Result<()> {
+        if Self::env().caller() != Self::env().account_id() {
+            self._assert_upgrader()?;
+        }
+        self._migrate_storage()
+    }
+}
+
+impl<T: Storage<Data>> Internal for T {
+    default fn _code_storage_versions(&self) -> Vec<(u32, u32)> {
+        Vec::new()
+    }
+
+    default fn _upgrader(&self) -> Option<AccountId> {
+        None
+    }
+
+    default fn _migrate(&mut self, _storage_key: u32, _from: u32) -> Result<()> {
+        Ok(())
+    }
+
+    default fn _storage_version(&self, storage_key: u32) -> u32 {
+        self.data()
+            .storage_versions
+            .get(&storage_key)
+            .unwrap_or_default()
+    }
+
+    default fn _init_storage_versions(&mut self) {
+        for (storage_key, version) in self._code_storage_versions() {
+            self.data().storage_versions.insert(&storage_key, &version);
+        }
+    }
+
+    default fn _assert_upgrader(&self) -> Result<()> {
+        let upgrader = self._upgrader().ok_or(Error::ManagerIsNotSet)?;
+        if Self::env().caller() != upgrader {
+            return Err(Error::CallerIsNotManager)
+        }
+        Ok(())
+    }
+
+    default fn _upgrade(&self, code_hash: Hash) -> Result<()> {
+        Self::env()
+            .set_code_hash(&code_hash)
+            .map_err(|_| Error::SetCodeHashFailed)?;
+        // The new code runs from the next call on, so the migration is called on this contract itself.
+        // `upgrade` does not write the storage back, so the migrated storage is kept.
+        UpgradeableRef::migrate_builder(&Self::env().account_id())
+            .call_flags(CallFlags::default().set_allow_reentry(true))
+            .try_invoke()???;
+        Ok(())
+    }
+
+    default fn _migrate_storage(&mut self) -> Result<()> {
+        for (storage_key, to) in self._code_storage_versions() {
+            let from = self._storage_version(storage_key);
+            if from >= to {
+                continue
+            }
+            self._migrate(storage_key, from)?;
+            self.data().storage_versions.insert(&storage_key, &to);
+            self._emit_migrated_event(storage_key, from, to);
+        }
+        Ok(())
+    }
+
+    default fn _emit_upgraded_event(&self, _code_hash: Hash) {}
+    default fn _emit_migrated_event(&self, _storage_key: u32, _from: u32, _to: u32) {}
+}
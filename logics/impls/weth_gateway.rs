@@ -7,8 +7,8 @@
 
 pub use crate::traits::weth_gateway::*;
 use crate::traits::{
+    controller::ControllerRef,
     pool::PoolRef,
-    types::WrappedU256,
     weth::*,
 };
 use ink::prelude::vec::Vec;
@@ -24,24 +24,20 @@ use openbrush::{
     },
 };
 
-use super::{
-    exp_no_err::Exp,
-    pool::utils::pool_balance,
-};
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
 #[derive(Debug)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
 pub struct Data {
     /// Account Id of Wrapped Native Token(PSP22)
     pub weth: AccountId,
-    /// Pool address of WETH
-    pub pool: AccountId,
+    /// Account Id of Controller to resolve the pool of WETH
+    pub controller: AccountId,
 }
 
 pub trait Internal {
-    fn _deposit_eth(&mut self) -> Result<()>;
+    fn _deposit_eth(&mut self, on_behalf_of: AccountId) -> Result<()>;
     fn _withdraw_eth(&mut self, amount: Balance) -> Result<()>;
-    fn _repay_eth(&mut self, amount: Balance) -> Result<()>;
+    fn _repay_eth(&mut self, amount: Balance, on_behalf_of: AccountId) -> Result<()>;
     fn _borrow_eth(&mut self, amount: Balance) -> Result<()>;
     fn _emergency_token_transfer(
         &mut self,
@@ -56,57 +52,38 @@ pub trait Internal {
     fn _emit_borrow_eth_event_(&self, pool: AccountId, to: AccountId, value: Balance);
     fn _emit_repay_eth_event_(&self, pool: AccountId, from: AccountId, value: Balance);
     fn _weth_address(&self) -> AccountId;
-    fn _pool_address(&self) -> AccountId;
+    fn _controller_address(&self) -> AccountId;
+    fn _pool_address(&self) -> Option<AccountId>;
 }
 
 impl<T: Storage<Data> + Storage<ownable::Data>> Internal for T {
-    default fn _deposit_eth(&mut self) -> Result<()> {
+    default fn _deposit_eth(&mut self, on_behalf_of: AccountId) -> Result<()> {
         let deposit_value = Self::env().transferred_value();
-        let caller = Self::env().caller();
         let weth = self._weth_address();
-        let pool = self._pool_address();
+        let pool = self._pool_address().ok_or(Error::MarketNotListed)?;
 
         WETHRef::deposit_builder(&weth)
             .transferred_value(deposit_value)
             .invoke()?;
         WETHRef::approve(&weth, pool, deposit_value)?;
-        PoolRef::mint_to(&pool, caller, deposit_value)?;
-        self._emit_deposit_eth_event_(pool, caller, deposit_value);
+        PoolRef::mint_to(&pool, on_behalf_of, deposit_value)?;
+        self._emit_deposit_eth_event_(pool, on_behalf_of, deposit_value);
         Ok(())
     }
 
     default fn _withdraw_eth(&mut self, amount: Balance) -> Result<()> {
-        let caller: ink_primitives::AccountId = Self::env().caller();
+        let caller = Self::env().caller();
         let contract_address = Self::env().account_id();
-        let pool = self._pool_address();
+        let pool = self._pool_address().ok_or(Error::MarketNotListed)?;
 
-        let user_balance: Balance = PoolRef::balance_of(&pool, caller);
         let mut amount_to_withdraw: Balance = amount;
-
         if amount == u128::MAX {
-            amount_to_withdraw = user_balance;
+            amount_to_withdraw = PoolRef::balance_of(&pool, caller);
         }
 
         let weth = self._weth_address();
-        PoolRef::set_use_reserve_as_collateral(&pool, true)?;
-
-        let exchange_rate: WrappedU256 = PoolRef::exchange_rate_current(&pool)?;
-        let pool_token_amount = pool_balance(
-            Exp {
-                mantissa: exchange_rate,
-            },
-            amount_to_withdraw,
-        );
-
         let balance_before = WETHRef::balance_of(&weth, contract_address);
-        PoolRef::transfer_from(
-            &pool,
-            caller,
-            contract_address,
-            pool_token_amount,
-            Vec::<u8>::new(),
-        )?;
-        PoolRef::redeem_underlying(&pool, amount_to_withdraw)?;
+        PoolRef::redeem_underlying_on_behalf(&pool, caller, amount_to_withdraw, contract_address)?;
 
         let balance_after = WETHRef::balance_of(&weth, contract_address);
         let actual_transferred = balance_after - balance_before;
@@ -116,12 +93,12 @@ impl<T: Storage<Data> + Storage<ownable::Data>> Internal for T {
         self._safe_transfer_eth(caller, actual_transferred)
     }
 
-    default fn _repay_eth(&mut self, amount: Balance) -> Result<()> {
+    default fn _repay_eth(&mut self, amount: Balance, on_behalf_of: AccountId) -> Result<()> {
         let transferred_value = Self::env().transferred_value();
         let caller = Self::env().caller();
-        let pool = self._pool_address();
+        let pool = self._pool_address().ok_or(Error::MarketNotListed)?;
 
-        let mut payback_amount = PoolRef::borrow_balance_current(&pool, caller)?;
+        let mut payback_amount = PoolRef::borrow_balance_current(&pool, on_behalf_of)?;
         if amount < payback_amount {
             payback_amount = amount;
         }
@@ -134,8 +111,8 @@ impl<T: Storage<Data> + Storage<ownable::Data>> Internal for T {
             .transferred_value(payback_amount)
             .invoke()?;
         WETHRef::approve(&weth, pool, payback_amount)?;
-        PoolRef::repay_borrow_behalf(&pool, caller, payback_amount)?;
-        self._emit_repay_eth_event_(pool, caller, payback_amount);
+        PoolRef::repay_borrow_behalf(&pool, on_behalf_of, payback_amount)?;
+        self._emit_repay_eth_event_(pool, on_behalf_of, payback_amount);
         if transferred_value > payback_amount {
             self._safe_transfer_eth(caller, transferred_value - payback_amount)?;
         }
@@ -145,8 +122,9 @@ impl<T: Storage<Data> + Storage<ownable::Data>> Internal for T {
     default fn _borrow_eth(&mut self, amount: Balance) -> Result<()> {
         let caller = Self::env().caller();
         let weth = self._weth_address();
-        let pool = self._pool_address();
+        let pool = self._pool_address().ok_or(Error::MarketNotListed)?;
 
+        // The borrow is opened for caller and consumes the delegate allowance granted to this contract
         PoolRef::borrow_for(&pool, caller, amount)?;
         WETHRef::withdraw(&weth, amount)?;
         self._emit_borrow_eth_event_(pool, caller, amount);
@@ -179,8 +157,12 @@ impl<T: Storage<Data> + Storage<ownable::Data>> Internal for T {
         self.data::<Data>().weth
     }
 
-    default fn _pool_address(&self) -> AccountId {
-        self.data::<Data>().pool
+    default fn _controller_address(&self) -> AccountId {
+        self.data::<Data>().controller
+    }
+
+    default fn _pool_address(&self) -> Option<AccountId> {
+        ControllerRef::market_of_underlying(&self._controller_address(), self._weth_address())
     }
 
     default fn _emit_deposit_eth_event_(
@@ -204,15 +186,31 @@ where
     T: Storage<Data> + Storage<ownable::Data>,
 {
     default fn deposit_eth(&mut self) -> Result<()> {
-        self._deposit_eth()
+        self._deposit_eth(Self::env().caller())
+    }
+
+    default fn deposit_eth_on_behalf(&mut self, on_behalf_of: AccountId) -> Result<()> {
+        self._deposit_eth(on_behalf_of)
     }
 
     default fn withdraw_eth(&mut self, amount: Balance) -> Result<()> {
         self._withdraw_eth(amount)
     }
 
+    default fn withdraw_all_eth(&mut self) -> Result<()> {
+        self._withdraw_eth(u128::MAX)
+    }
+
     default fn repay_eth(&mut self, amount: Balance) -> Result<()> {
-        self._repay_eth(amount)
+        self._repay_eth(amount, Self::env().caller())
+    }
+
+    default fn repay_eth_on_behalf(
+        &mut self,
+        amount: Balance,
+        on_behalf_of: AccountId,
+    ) -> Result<()> {
+        self._repay_eth(amount, on_behalf_of)
     }
 
     default fn borrow_eth(&mut self, amount: Balance) -> Result<()> {
@@ -236,7 +234,11 @@ where
         self._weth_address()
     }
 
-    default fn get_pool_address(&self) -> AccountId {
+    default fn get_controller_address(&self) -> AccountId {
+        self._controller_address()
+    }
+
+    default fn get_pool_address(&self) -> Option<AccountId> {
         self._pool_address()
     }
 }
@@ -1,5 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 #![feature(min_specialization)]
 
+#[cfg(feature = "std")]
+pub mod events;
 pub mod impls;
 pub mod traits;
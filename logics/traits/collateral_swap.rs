@@ -0,0 +1,74 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use openbrush::traits::{
+    AccountId,
+    Balance,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+use super::flashloan_adapter::Error as FlashloanAdapterError;
+
+#[openbrush::wrapper]
+pub type CollateralSwapRef = dyn CollateralSwap;
+
+/// Trait defines the interface for the Collateral Swap adapter
+#[openbrush::trait_definition]
+pub trait CollateralSwap {
+    /// Get Flashloan Gateway AccountId
+    #[ink(message)]
+    fn flashloan_gateway(&self) -> Option<AccountId>;
+
+    /// Get Controller AccountId
+    #[ink(message)]
+    fn controller(&self) -> Option<AccountId>;
+
+    /// Get DEX Router AccountId
+    #[ink(message)]
+    fn router(&self) -> Option<AccountId>;
+
+    /// Swap `amount` of the caller's `asset_from` collateral into `asset_to` collateral.
+    /// `asset_to` is flashloaned and supplied on behalf of the caller, then the caller's `asset_from` collateral
+    /// is redeemed on their behalf, consuming the allowance of their pool tokens, and swapped to repay the flashloan.
+    #[ink(message)]
+    fn swap_collateral(
+        &mut self,
+        asset_from: AccountId,
+        asset_to: AccountId,
+        amount: Balance,
+        min_amount_to_receive: Balance,
+    ) -> Result<()>;
+}
+
+/// Parameters passed through the Flashloan Gateway to `execute_operation`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct CollateralSwapParams {
+    pub user: AccountId,
+    pub asset_from: AccountId,
+    pub amount: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    SameAsset,
+    InsufficientAmountToReceive,
+    /// Error of the flashloan, pool and DEX router calls shared by the adapters
+    FlashloanAdapter(FlashloanAdapterError),
+}
+
+impl From<FlashloanAdapterError> for Error {
+    fn from(error: FlashloanAdapterError) -> Self {
+        Error::FlashloanAdapter(error)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
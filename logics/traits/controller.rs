@@ -9,6 +9,7 @@ use ink::prelude::vec::Vec;
 use openbrush::traits::{
     AccountId,
     Balance,
+    Timestamp,
 };
 use primitive_types::U256;
 use scale::{
@@ -16,7 +17,10 @@ use scale::{
     Encode,
 };
 
-use super::types::WrappedU256;
+use super::types::{
+    MathError,
+    WrappedU256,
+};
 
 #[openbrush::wrapper]
 pub type ControllerRef = dyn Controller;
@@ -83,6 +87,15 @@ pub trait Controller {
         pool_attribute: Option<PoolAttributes>,
     ) -> Result<()>;
 
+    /// Record the outflow of the underlying from the given market (only Pool).
+    /// Fails if the outflow exceeds the outflow limit of the current window.
+    #[ink(message)]
+    fn record_outflow(&mut self, pool: AccountId, amount: Balance, cash: Balance) -> Result<()>;
+
+    /// Record the inflow of the underlying to the given market (only Pool)
+    #[ink(message)]
+    fn record_inflow(&mut self, pool: AccountId, amount: Balance, cash: Balance) -> Result<()>;
+
     /// Checks if the account should be allowed to transfer tokens in the given market
     #[ink(message)]
     fn liquidate_calculate_seize_tokens(
@@ -157,6 +170,29 @@ pub trait Controller {
     #[ink(message)]
     fn set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()>;
 
+    /// Set the outflow limit for the given pool.
+    /// Zero `max_outflow_mantissa` disables the limiter.
+    #[ink(message)]
+    fn set_outflow_limit(&mut self, pool: AccountId, limit: OutflowLimit) -> Result<()>;
+
+    /// Temporarily raise the outflow limit of the given pool by `amount` until `expires_at`
+    #[ink(message)]
+    fn raise_outflow_limit(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()>;
+
+    /// Set the maximum utilization for the given pool (Decimals: 18).
+    /// Borrowing that brings the utilization above it will revert. Zero means no limit.
+    #[ink(message)]
+    fn set_max_utilization_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()>;
+
     /// Set Manager
     #[ink(message)]
     fn set_manager(&mut self, manager: AccountId) -> Result<()>;
@@ -217,6 +253,23 @@ pub trait Controller {
     #[ink(message)]
     fn borrow_cap(&self, pool: AccountId) -> Option<Balance>;
 
+    /// Returns the maximum utilization for a given pool
+    #[ink(message)]
+    fn max_utilization_mantissa(&self, pool: AccountId) -> Option<WrappedU256>;
+
+    /// Returns the outflow limit for a given pool
+    #[ink(message)]
+    fn outflow_limit(&self, pool: AccountId) -> Option<OutflowLimit>;
+
+    /// Returns the temporary raise of the outflow limit for a given pool
+    #[ink(message)]
+    fn outflow_limit_raise(&self, pool: AccountId) -> Option<OutflowLimitRaise>;
+
+    /// Returns the amount of the underlying that can still flow out of a given pool in the current window.
+    /// None if the pool has no outflow limit.
+    #[ink(message)]
+    fn remaining_outflow_capacity(&self, pool: AccountId) -> Option<Balance>;
+
     /// Returns the account id of the manager account
     #[ink(message)]
     fn manager(&self) -> Option<AccountId>;
@@ -278,6 +331,8 @@ pub struct PoolAttributes {
     pub account_borrow_balance: Balance,
     pub exchange_rate: U256,
     pub total_borrows: Balance,
+    pub cash: Balance,
+    pub total_reserves: Balance,
 }
 
 /// Structure for having information for Seize about the Pool
@@ -318,6 +373,32 @@ pub struct AccountCollateralData {
     pub health_factor: U256,
 }
 
+/// Outflow limit of a Pool
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct OutflowLimit {
+    /// Maximum share of the liquidity that can flow out per window (Decimals: 18)
+    pub max_outflow_mantissa: WrappedU256,
+    /// Length of the window (milliseconds)
+    pub window: Timestamp,
+}
+
+/// Temporary raise of the outflow limit of a Pool
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct OutflowLimitRaise {
+    /// Amount added to the outflow limit
+    pub amount: Balance,
+    /// Timestamp until which the raise is active (milliseconds)
+    pub expires_at: Timestamp,
+}
+
 /// Custom error definitions for Controller
 #[derive(Debug, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -345,6 +426,17 @@ pub enum Error {
     BalanceDecreaseNotAllowed,
     MarketCountReachedToMaximum,
     PoolError,
+    CallerIsNotPool,
+    InvalidOutflowLimit,
+    OutflowLimitExceeded,
+    MaxUtilizationExceeded,
+    Math(MathError),
+}
+
+impl From<MathError> for Error {
+    fn from(error: MathError) -> Self {
+        Error::Math(error)
+    }
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,76 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use openbrush::traits::{
+    AccountId,
+    Balance,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+use super::flashloan_adapter::Error as FlashloanAdapterError;
+
+#[openbrush::wrapper]
+pub type DebtSwapRef = dyn DebtSwap;
+
+/// Trait defines the interface for the Debt Swap adapter
+#[openbrush::trait_definition]
+pub trait DebtSwap {
+    /// Get Flashloan Gateway AccountId
+    #[ink(message)]
+    fn flashloan_gateway(&self) -> Option<AccountId>;
+
+    /// Get Controller AccountId
+    #[ink(message)]
+    fn controller(&self) -> Option<AccountId>;
+
+    /// Get DEX Router AccountId
+    #[ink(message)]
+    fn router(&self) -> Option<AccountId>;
+
+    /// Move up to `repay_amount` of the caller's `debt_asset_from` borrow to a `debt_asset_to` borrow.
+    /// `debt_asset_to` is flashloaned as the caller's debt, which needs the delegate allowance of the
+    /// `debt_asset_to` Pool granted to this contract, and is swapped to repay the `debt_asset_from` borrow.
+    /// Fails if more than `max_new_debt_amount` of new debt is needed.
+    #[ink(message)]
+    fn swap_debt(
+        &mut self,
+        debt_asset_from: AccountId,
+        debt_asset_to: AccountId,
+        repay_amount: Balance,
+        max_new_debt_amount: Balance,
+    ) -> Result<()>;
+}
+
+/// Parameters passed through the Flashloan Gateway to `execute_operation`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct DebtSwapParams {
+    pub user: AccountId,
+    pub debt_asset_from: AccountId,
+    pub repay_amount: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    SameAsset,
+    NoBorrowToRepay,
+    ExcessiveNewDebtAmount,
+    /// Error of the flashloan, pool and DEX router calls shared by the adapters
+    FlashloanAdapter(FlashloanAdapterError),
+}
+
+impl From<FlashloanAdapterError> for Error {
+    fn from(error: FlashloanAdapterError) -> Self {
+        Error::FlashloanAdapter(error)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
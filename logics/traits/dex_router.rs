@@ -0,0 +1,87 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use openbrush::{
+    contracts::psp22::PSP22Error,
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+#[openbrush::wrapper]
+pub type DexRouterRef = dyn DexRouter;
+
+/// Trait defines the interface of a DEX router used by the swap adapters
+#[openbrush::trait_definition]
+pub trait DexRouter {
+    /// Get the amount of `token_out` received by swapping `amount_in` of `token_in`
+    #[ink(message)]
+    fn get_amount_out(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        amount_in: Balance,
+    ) -> Result<Balance>;
+
+    /// Get the amount of `token_in` required to receive `amount_out` of `token_out`
+    #[ink(message)]
+    fn get_amount_in(
+        &self,
+        token_in: AccountId,
+        token_out: AccountId,
+        amount_out: Balance,
+    ) -> Result<Balance>;
+
+    /// Swap exact `amount_in` of `token_in` for at least `min_amount_out` of `token_out`.
+    /// `token_in` is pulled from the caller by allowance and `token_out` is sent to `to`.
+    /// Returns the amount of `token_out` sent.
+    #[ink(message)]
+    fn swap_exact_tokens_for_tokens(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        amount_in: Balance,
+        min_amount_out: Balance,
+        to: AccountId,
+    ) -> Result<Balance>;
+
+    /// Swap at most `max_amount_in` of `token_in` for exact `amount_out` of `token_out`.
+    /// `token_in` is pulled from the caller by allowance and `token_out` is sent to `to`.
+    /// Returns the amount of `token_in` spent.
+    #[ink(message)]
+    fn swap_tokens_for_exact_tokens(
+        &mut self,
+        token_in: AccountId,
+        token_out: AccountId,
+        amount_out: Balance,
+        max_amount_in: Balance,
+        to: AccountId,
+    ) -> Result<Balance>;
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    PairNotSupported,
+    InsufficientOutputAmount,
+    ExcessiveInputAmount,
+    InsufficientLiquidity,
+    PSP22(PSP22Error),
+}
+
+impl From<PSP22Error> for Error {
+    fn from(error: PSP22Error) -> Self {
+        Error::PSP22(error)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,36 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use ink::prelude::vec::Vec;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+};
+
+/// keccak256("ERC3156FlashBorrower.onFlashLoan")
+pub const FLASH_BORROWER_CALLBACK_SUCCESS: [u8; 32] = [
+    0x43, 0x91, 0x48, 0xf0, 0xbb, 0xc6, 0x82, 0xca, 0x07, 0x9e, 0x46, 0xd6, 0xe2, 0xc2, 0xf0, 0xc1,
+    0xe3, 0xb8, 0x20, 0xf1, 0xa2, 0x91, 0xb0, 0x69, 0xd8, 0x88, 0x2a, 0xbf, 0x8c, 0xf1, 0x8d, 0xd9,
+];
+
+#[openbrush::wrapper]
+pub type FlashBorrowerRef = dyn FlashBorrower;
+
+/// Receiver of flash loans compatible with ERC-3156
+#[openbrush::trait_definition]
+pub trait FlashBorrower {
+    /// Receive a flash loan, returns `FLASH_BORROWER_CALLBACK_SUCCESS` on success
+    #[ink(message)]
+    fn on_flash_loan(
+        &mut self,
+        initiator: AccountId,
+        token: AccountId,
+        amount: Balance,
+        fee: Balance,
+        data: Vec<u8>,
+    ) -> [u8; 32];
+}
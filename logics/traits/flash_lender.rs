@@ -0,0 +1,39 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::traits::flashloan_gateway::Result;
+use ink::prelude::vec::Vec;
+use openbrush::traits::{
+    AccountId,
+    Balance,
+};
+
+#[openbrush::wrapper]
+pub type FlashLenderRef = dyn FlashLender;
+
+/// Single-asset flash lending compatible with ERC-3156
+#[openbrush::trait_definition]
+pub trait FlashLender {
+    /// Returns the amount of `token` available to be lent, 0 if it is not supported
+    #[ink(message)]
+    fn max_flash_loan(&self, token: AccountId) -> Balance;
+
+    /// Returns the fee charged to the caller for a loan of `amount` of `token`
+    #[ink(message)]
+    fn flash_fee(&self, token: AccountId, amount: Balance) -> Result<Balance>;
+
+    /// Lends `amount` of `token` to `receiver`, calls its `on_flash_loan` and pulls back `amount` plus the fee.
+    /// `receiver` must approve this contract for the repayment and return `FLASH_BORROWER_CALLBACK_SUCCESS`.
+    #[ink(message)]
+    fn flash_loan(
+        &self,
+        receiver: AccountId,
+        token: AccountId,
+        amount: Balance,
+        data: Vec<u8>,
+    ) -> Result<()>;
+}
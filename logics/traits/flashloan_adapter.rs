@@ -0,0 +1,78 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use ink::LangError;
+use openbrush::contracts::psp22::PSP22Error;
+use scale::{
+    Decode,
+    Encode,
+};
+
+use super::{
+    dex_router::Error as DexRouterError,
+    flashloan_gateway::Error as FlashloanGatewayError,
+    pool::Error as PoolError,
+};
+
+/// Errors of the plumbing shared by the flashloan adapters,
+/// which wrap them in their own errors
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    FlashloanGatewayIsNotSet,
+    ControllerIsNotSet,
+    RouterIsNotSet,
+    MarketNotListed,
+    CallerIsNotFlashloanGateway,
+    InitiatorIsNotThis,
+    InvalidFlashloanParams,
+    Pool(PoolError),
+    PSP22(PSP22Error),
+    DexRouter(DexRouterError),
+    FlashloanGateway(FlashloanGatewayError),
+    Lang(LangError),
+    /// The environment failed to execute a cross-contract call, e.g. the callee trapped
+    CallFailed,
+}
+
+impl From<PoolError> for Error {
+    fn from(error: PoolError) -> Self {
+        Error::Pool(error)
+    }
+}
+
+impl From<PSP22Error> for Error {
+    fn from(error: PSP22Error) -> Self {
+        Error::PSP22(error)
+    }
+}
+
+impl From<DexRouterError> for Error {
+    fn from(error: DexRouterError) -> Self {
+        Error::DexRouter(error)
+    }
+}
+
+impl From<FlashloanGatewayError> for Error {
+    fn from(error: FlashloanGatewayError) -> Self {
+        Error::FlashloanGateway(error)
+    }
+}
+
+impl From<LangError> for Error {
+    fn from(error: LangError) -> Self {
+        Error::Lang(error)
+    }
+}
+
+impl From<ink::env::Error> for Error {
+    fn from(_: ink::env::Error) -> Self {
+        Error::CallFailed
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
@@ -26,6 +26,8 @@ pub type FlashloanGatewayRef = dyn FlashloanGateway;
 pub trait FlashloanGateway {
     /// Allows smartcontracts to access the liquidity of the pool within one transaction, as long as the amount taken plus a fee is returned.
     ///  IMPORTANT There are security concerns for developers of flashloan receiver contracts that must be kept into consideration.
+    /// For assets with `mods` other than None, the debt is opened for `on_behalf_of`,
+    /// consuming the delegate allowance granted to the caller when `on_behalf_of` is not the caller.
     #[ink(message)]
     fn flashloan(
         &self,
@@ -41,6 +43,46 @@ pub trait FlashloanGateway {
     #[ink(message)]
     fn flashloan_premium_total(&self) -> u128;
 
+    /// Returns the share of the fee added to the reserves of Pool
+    #[ink(message)]
+    fn flashloan_premium_to_protocol(&self) -> u128;
+
+    /// Set the fee on flash loans (only Manager of Controller)
+    #[ink(message)]
+    fn set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()>;
+
+    /// Set the share of the fee added to the reserves of Pool (only Manager of Controller)
+    #[ink(message)]
+    fn set_flashloan_premium_to_protocol(&mut self, new_premium_to_protocol: u128) -> Result<()>;
+
+    /// Returns the fee on flash loans initiated by `borrower`, taking its premium override into account
+    #[ink(message)]
+    fn flashloan_premium_of(&self, borrower: AccountId) -> u128;
+
+    /// Returns the premium override of `borrower`, if any
+    #[ink(message)]
+    fn borrower_premium(&self, borrower: AccountId) -> Option<u128>;
+
+    /// Set the premium override of `borrower`, `Some(0)` exempts it and `None` removes the override (only Manager of Controller)
+    #[ink(message)]
+    fn set_borrower_premium(&mut self, borrower: AccountId, premium: Option<u128>) -> Result<()>;
+
+    /// Returns whether flash loans are restricted to whitelisted receivers
+    #[ink(message)]
+    fn whitelist_only(&self) -> bool;
+
+    /// Restrict flash loans to whitelisted receivers or lift the restriction (only Manager of Controller)
+    #[ink(message)]
+    fn set_whitelist_only(&mut self, enabled: bool) -> Result<()>;
+
+    /// Returns whether `receiver` is whitelisted
+    #[ink(message)]
+    fn is_whitelisted_receiver(&self, receiver: AccountId) -> bool;
+
+    /// Add `receiver` to or remove it from the whitelist (only Manager of Controller)
+    #[ink(message)]
+    fn set_whitelisted_receiver(&mut self, receiver: AccountId, whitelisted: bool) -> Result<()>;
+
     /// Returns Controller Address
     #[ink(message)]
     fn controller(&self) -> Option<AccountId>;
@@ -57,6 +99,10 @@ pub enum Error {
     ControllerIsNotSet,
     PSP22(PSP22Error),
     Pool(PoolError),
+    InsufficientDelegateAllowance,
+    InvalidFlashloanPremium,
+    CallerIsNotManager,
+    ReceiverIsNotWhitelisted,
 }
 
 #[derive(Debug, PartialEq, Eq, Encode, Decode)]
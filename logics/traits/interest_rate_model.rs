@@ -28,4 +28,20 @@ pub trait InterestRateModel {
         reserves: Balance,
         reserve_factor_mantissa: WrappedU256,
     ) -> WrappedU256;
+
+    /// Borrow interest rate per milliseconds at zero utilization
+    #[ink(message)]
+    fn base_rate_per_milli_second(&self) -> WrappedU256;
+
+    /// Slope of the borrow interest rate below kink
+    #[ink(message)]
+    fn multiplier_per_milli_second_slope_1(&self) -> WrappedU256;
+
+    /// Slope of the borrow interest rate above kink
+    #[ink(message)]
+    fn multiplier_per_milli_second_slope_2(&self) -> WrappedU256;
+
+    /// Utilization rate at which slope_2 is applied
+    #[ink(message)]
+    fn kink(&self) -> WrappedU256;
 }
@@ -5,6 +5,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use ink::LangError;
 use openbrush::{
     contracts::psp22::PSP22Error,
     traits::{
@@ -20,7 +21,10 @@ use scale::{
 
 use super::{
     controller::Error as ControllerError,
+    flashloan_adapter::Error as FlashloanAdapterError,
+    flashloan_gateway::Error as FlashloanGatewayError,
     pool::Error as PoolError,
+    types::WrappedU256,
 };
 
 #[openbrush::wrapper]
@@ -45,6 +49,14 @@ pub trait Leverager {
     #[ink(message)]
     fn manager(&self) -> Option<AccountId>;
 
+    /// Get health factor to be kept after withdrawing
+    #[ink(message)]
+    fn health_factor_limit(&self) -> WrappedU256;
+
+    /// Set health factor to be kept after withdrawing (only Manager)
+    #[ink(message)]
+    fn set_health_factor_limit(&mut self, health_factor_limit: WrappedU256) -> Result<()>;
+
     /// Get Borrowable information of an account
     #[ink(message)]
     fn get_available_borrows(&self, account: AccountId) -> Option<AvailableBorrows>;
@@ -99,6 +111,50 @@ pub trait Leverager {
     /// Loop the withdrawing and repaying asset
     #[ink(message)]
     fn close(&mut self, asset: AccountId) -> Result<()>;
+
+    /// Leverage the deposit of asset in one transaction with a flashloan.
+    /// `target_leverage` is the ratio of the resulting deposit to `amount` in percentage * 100.
+    /// The extra amount is borrowed on behalf of the caller, who must delegate it to this contract on the pool.
+    #[ink(message)]
+    fn leverage_with_flashloan(
+        &mut self,
+        asset: AccountId,
+        amount: Balance,
+        target_leverage: u128,
+    ) -> Result<()>;
+
+    /// Repay the borrow of asset with a flashloan and redeem the caller's deposit to return it.
+    /// The caller must approve this contract for the pool tokens to be redeemed.
+    #[ink(message)]
+    fn deleverage_with_flashloan(&mut self, asset: AccountId, repay_amount: Balance) -> Result<()>;
+
+    /// Loop the withdrawing and repaying asset until the health factor of the caller reaches `target_health_factor`.
+    /// The withdrawn asset not taken by the repayments is returned to the caller.
+    /// The caller must approve this contract for the pool tokens to be redeemed.
+    /// Returns the amounts withdrawn and repaid.
+    #[ink(message)]
+    fn deleverage_to(
+        &mut self,
+        asset: AccountId,
+        target_health_factor: WrappedU256,
+    ) -> Result<(Balance, Balance)>;
+
+    /// Loop the withdrawing and repaying eth until the health factor of the caller reaches `target_health_factor`.
+    /// The withdrawn eth not taken by the repayments is unwrapped and returned as native tokens.
+    /// Returns the amounts withdrawn and repaid.
+    #[ink(message)]
+    fn deleverage_to_eth(
+        &mut self,
+        target_health_factor: WrappedU256,
+    ) -> Result<(Balance, Balance)>;
+}
+
+/// Operation executed by Leverager in the flashloan callback
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum FlashloanOperation {
+    Leverage { user: AccountId, amount: Balance },
+    Deleverage { user: AccountId },
 }
 
 #[derive(Clone, Decode, Encode, Default)]
@@ -137,6 +193,16 @@ pub enum Error {
     Controller(ControllerError),
     Pool(PoolError),
     PSP22(PSP22Error),
+    InappropriateLeverage,
+    FlashloanGatewayIsNotSet,
+    NoBorrowToRepay,
+    FlashloanGateway(FlashloanGatewayError),
+    InappropriateHealthFactor,
+    FlashloanAdapter(FlashloanAdapterError),
+    Lang(LangError),
+    /// The environment failed to execute a cross-contract call, e.g. the callee trapped
+    CallFailed,
+    SafeETHTransferFailed,
 }
 
 impl From<ControllerError> for Error {
@@ -157,4 +223,28 @@ impl From<PSP22Error> for Error {
     }
 }
 
+impl From<FlashloanGatewayError> for Error {
+    fn from(error: FlashloanGatewayError) -> Self {
+        Error::FlashloanGateway(error)
+    }
+}
+
+impl From<FlashloanAdapterError> for Error {
+    fn from(error: FlashloanAdapterError) -> Self {
+        Error::FlashloanAdapter(error)
+    }
+}
+
+impl From<LangError> for Error {
+    fn from(error: LangError) -> Self {
+        Error::Lang(error)
+    }
+}
+
+impl From<ink::env::Error> for Error {
+    fn from(_: ink::env::Error) -> Self {
+        Error::CallFailed
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
@@ -6,9 +6,15 @@
 // except according to those terms.
 
 use super::{
-    controller::Error as ControllerError,
+    controller::{
+        Error as ControllerError,
+        OutflowLimit,
+    },
+    flashloan_gateway::Error as FlashloanGatewayError,
     pool::Error as PoolError,
+    upgradeable::Error as UpgradeableError,
 };
+use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::{
         psp22::PSP22Error,
@@ -17,6 +23,8 @@ use openbrush::{
     traits::{
         AccountId,
         Balance,
+        Hash,
+        Timestamp,
     },
 };
 use scale::{
@@ -48,6 +56,30 @@ pub trait Manager {
     #[ink(message)]
     fn set_flashloan_gateway(&mut self, new_flashloan_gateway: AccountId) -> Result<()>;
 
+    /// Sets the fee on flash loans (call FlashloanGateway)
+    #[ink(message)]
+    fn set_flashloan_premium_total(&mut self, new_premium_total: u128) -> Result<()>;
+
+    /// Sets the share of the fee on flash loans added to the reserves of pools (call FlashloanGateway)
+    #[ink(message)]
+    fn set_flashloan_premium_to_protocol(&mut self, new_premium_to_protocol: u128) -> Result<()>;
+
+    /// Sets the flashloan fee override of the given borrower (call FlashloanGateway)
+    #[ink(message)]
+    fn set_flashloan_borrower_premium(
+        &mut self,
+        borrower: AccountId,
+        premium: Option<u128>,
+    ) -> Result<()>;
+
+    /// Adds the given receiver to or removes it from the flashloan whitelist (call FlashloanGateway)
+    #[ink(message)]
+    fn set_flashloan_whitelisted_receiver(
+        &mut self,
+        receiver: AccountId,
+        whitelisted: bool,
+    ) -> Result<()>;
+
     /// Add the market to the markets mapping and set it as listed (call Controller)
     #[ink(message)]
     fn support_market(&mut self, pool: AccountId, underlying: AccountId) -> Result<()>;
@@ -92,6 +124,14 @@ pub trait Manager {
     #[ink(message)]
     fn set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()>;
 
+    /// Set the maximum utilization after borrowing for the given pool (call Controller)
+    #[ink(message)]
+    fn set_max_utilization_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    ) -> Result<()>;
+
     /// accrues interest and sets a new reserve factor for the protocol using _set_reserve_factor_mantissa (call Pool)
     #[ink(message)]
     fn set_reserve_factor_mantissa(
@@ -136,6 +176,23 @@ pub trait Manager {
     #[ink(message)]
     fn set_transfer_guardian_paused(&mut self, paused: bool) -> Result<()>;
 
+    /// Restricts flash loans to whitelisted receivers or lifts the restriction (call FlashloanGateway)
+    #[ink(message)]
+    fn set_flashloan_whitelist_only(&mut self, enabled: bool) -> Result<()>;
+
+    /// Set the outflow limit for the given pool (call Controller)
+    #[ink(message)]
+    fn set_outflow_limit(&mut self, pool: AccountId, limit: OutflowLimit) -> Result<()>;
+
+    /// Temporarily raise the outflow limit of the given pool (call Controller)
+    #[ink(message)]
+    fn raise_outflow_limit(
+        &mut self,
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    ) -> Result<()>;
+
     /// A public function to Set Controller Manager
     #[ink(message)]
     fn set_controller_manager(&mut self, manager: AccountId) -> Result<()>;
@@ -159,6 +216,212 @@ pub trait Manager {
         pool: AccountId,
         new_interest_rate_model: AccountId,
     ) -> Result<()>;
+
+    /// Get the bounds applied to Risk Steward's updates
+    #[ink(message)]
+    fn risk_steward_config(&self) -> RiskStewardConfig;
+
+    /// Get the last time the parameter of pool was updated by Risk Steward
+    #[ink(message)]
+    fn last_risk_parameter_update(
+        &self,
+        pool: AccountId,
+        parameter: RiskParameter,
+    ) -> Option<Timestamp>;
+
+    /// Set the bounds applied to Risk Steward's updates
+    #[ink(message)]
+    fn set_risk_steward_config(&mut self, config: RiskStewardConfig) -> Result<()>;
+
+    /// Get whether Risk Steward is allowed to set the interest rate model
+    #[ink(message)]
+    fn steward_interest_rate_model_allowed(&self, interest_rate_model: AccountId) -> bool;
+
+    /// Allow or disallow Risk Steward to set the interest rate model
+    #[ink(message)]
+    fn set_steward_interest_rate_model_allowed(
+        &mut self,
+        interest_rate_model: AccountId,
+        allowed: bool,
+    ) -> Result<()>;
+
+    /// Set the borrow cap of pool within the bounds of Risk Steward (call Controller)
+    #[ink(message)]
+    fn steward_set_borrow_cap(&mut self, pool: AccountId, new_cap: Balance) -> Result<()>;
+
+    /// Set the collateral factor of pool within the bounds of Risk Steward (call Controller)
+    #[ink(message)]
+    fn steward_set_collateral_factor_mantissa(
+        &mut self,
+        pool: AccountId,
+        new_collateral_factor_mantissa: WrappedU256,
+    ) -> Result<()>;
+
+    /// Set the liquidation threshold of pool within the bounds of Risk Steward (call Pool)
+    #[ink(message)]
+    fn steward_set_liquidation_threshold(
+        &mut self,
+        pool: AccountId,
+        liquidation_threshold: u128,
+    ) -> Result<()>;
+
+    /// Replace the interest rate model of pool with an allowed one whose rates and slopes are within the bounds of Risk Steward (call Pool)
+    #[ink(message)]
+    fn steward_set_interest_rate_model(
+        &mut self,
+        pool: AccountId,
+        new_interest_rate_model: AccountId,
+    ) -> Result<()>;
+
+    /// Replace the code of Controller (call Controller)
+    #[ink(message)]
+    fn upgrade_controller(&mut self, code_hash: Hash) -> Result<()>;
+
+    /// Replace the code of pool (call Pool)
+    #[ink(message)]
+    fn upgrade_pool(&mut self, pool: AccountId, code_hash: Hash) -> Result<()>;
+
+    /// Replace the code of Flashloan Gateway set in Controller (call FlashloanGateway)
+    #[ink(message)]
+    fn upgrade_flashloan_gateway(&mut self, code_hash: Hash) -> Result<()>;
+
+    /// Execute multiple configuration actions atomically
+    ///
+    /// Each action requires the same role as the corresponding message.
+    /// If any action fails, the whole batch is reverted.
+    #[ink(message)]
+    fn batch_configure(&mut self, actions: Vec<ConfigAction>) -> Result<()>;
+}
+
+/// Configuration action executed in `batch_configure`
+///
+/// Each variant corresponds to the Manager message of the same name.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ConfigAction {
+    SetController(AccountId),
+    SetPriceOracle(AccountId),
+    SetFlashloanGateway(AccountId),
+    SupportMarket {
+        pool: AccountId,
+        underlying: AccountId,
+    },
+    SupportMarketWithCollateralFactorMantissa {
+        pool: AccountId,
+        underlying: AccountId,
+        collateral_factor_mantissa: WrappedU256,
+    },
+    SetCollateralFactorMantissa {
+        pool: AccountId,
+        new_collateral_factor_mantissa: WrappedU256,
+    },
+    SetMintGuardianPaused {
+        pool: AccountId,
+        paused: bool,
+    },
+    SetBorrowGuardianPaused {
+        pool: AccountId,
+        paused: bool,
+    },
+    SetSeizeGuardianPaused(bool),
+    SetTransferGuardianPaused(bool),
+    SetCloseFactorMantissa(WrappedU256),
+    SetLiquidationIncentiveMantissa(WrappedU256),
+    SetBorrowCap {
+        pool: AccountId,
+        new_cap: Balance,
+    },
+    SetReserveFactorMantissa {
+        pool: AccountId,
+        new_reserve_factor_mantissa: WrappedU256,
+    },
+    SetLiquidationThreshold {
+        pool: AccountId,
+        liquidation_threshold: u128,
+    },
+    SetIncentivesController {
+        pool: AccountId,
+        incentives_controller: AccountId,
+    },
+    SetInterestRateModel {
+        pool: AccountId,
+        new_interest_rate_model: AccountId,
+    },
+    SetControllerManager(AccountId),
+    AcceptControllerManager,
+    SetPoolManager {
+        pool: AccountId,
+        manager: AccountId,
+    },
+    AcceptPoolManager(AccountId),
+    SetRiskStewardConfig(RiskStewardConfig),
+    StewardSetBorrowCap {
+        pool: AccountId,
+        new_cap: Balance,
+    },
+    StewardSetCollateralFactorMantissa {
+        pool: AccountId,
+        new_collateral_factor_mantissa: WrappedU256,
+    },
+    StewardSetLiquidationThreshold {
+        pool: AccountId,
+        liquidation_threshold: u128,
+    },
+    StewardSetInterestRateModel {
+        pool: AccountId,
+        new_interest_rate_model: AccountId,
+    },
+    SetOutflowLimit {
+        pool: AccountId,
+        limit: OutflowLimit,
+    },
+    RaiseOutflowLimit {
+        pool: AccountId,
+        amount: Balance,
+        expires_at: Timestamp,
+    },
+    SetMaxUtilizationMantissa {
+        pool: AccountId,
+        new_max_utilization_mantissa: WrappedU256,
+    },
+    SetFlashloanPremiumTotal(u128),
+    SetFlashloanPremiumToProtocol(u128),
+    SetFlashloanBorrowerPremium {
+        borrower: AccountId,
+        premium: Option<u128>,
+    },
+    SetFlashloanWhitelistedReceiver {
+        receiver: AccountId,
+        whitelisted: bool,
+    },
+    SetFlashloanWhitelistOnly(bool),
+    SetStewardInterestRateModelAllowed {
+        interest_rate_model: AccountId,
+        allowed: bool,
+    },
+}
+
+/// Parameters that Risk Steward is able to update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RiskParameter {
+    BorrowCap,
+    CollateralFactor,
+    LiquidationThreshold,
+    InterestRateModel,
+}
+
+/// Bounds applied to Risk Steward's updates
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RiskStewardConfig {
+    /// Maximum relative change of a parameter per update (Decimals: 18)
+    pub max_change_mantissa: WrappedU256,
+    /// Minimum interval between updates of the same parameter (milliseconds)
+    pub min_delay: Timestamp,
 }
 
 /// Custom error definitions for Manager
@@ -169,6 +432,12 @@ pub enum Error {
     Controller(ControllerError),
     Pool(PoolError),
     PSP22(PSP22Error),
+    RiskParameterChangeOutOfBounds,
+    RiskParameterUpdateTooEarly,
+    Upgradeable(UpgradeableError),
+    FlashloanGatewayIsNotSet,
+    FlashloanGateway(FlashloanGatewayError),
+    StewardInterestRateModelNotAllowed,
 }
 
 impl From<AccessControlError> for Error {
@@ -195,4 +464,16 @@ impl From<PSP22Error> for Error {
     }
 }
 
+impl From<UpgradeableError> for Error {
+    fn from(error: UpgradeableError) -> Self {
+        Error::Upgradeable(error)
+    }
+}
+
+impl From<FlashloanGatewayError> for Error {
+    fn from(error: FlashloanGatewayError) -> Self {
+        Error::FlashloanGateway(error)
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
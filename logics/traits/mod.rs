@@ -5,7 +5,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub mod collateral_swap;
 pub mod controller;
+pub mod debt_swap;
+pub mod dex_router;
+pub mod flash_borrower;
+pub mod flash_lender;
+pub mod flashloan_adapter;
 pub mod flashloan_gateway;
 pub mod flashloan_receiver;
 pub mod incentives_controller;
@@ -14,6 +20,8 @@ pub mod leverager;
 pub mod manager;
 pub mod pool;
 pub mod price_oracle;
+pub mod repay_with_collateral;
 pub mod types;
+pub mod upgradeable;
 pub mod weth;
 pub mod weth_gateway;
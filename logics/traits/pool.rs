@@ -28,7 +28,10 @@ use scale::{
 
 use super::{
     controller::Error as ControllerError,
-    types::WrappedU256,
+    types::{
+        MathError,
+        WrappedU256,
+    },
 };
 
 #[openbrush::wrapper]
@@ -57,6 +60,17 @@ pub trait Pool: PSP22 + PSP22Metadata {
     #[ink(message)]
     fn redeem_all(&mut self) -> Result<()>;
 
+    /// Sender redeems pool tokens of Owner in exchange for a specified amount of underlying asset, which is transferred to `to`.
+    /// The PSP22 allowance from Owner to Sender is consumed unless they are the same account.
+    /// Fails if the amount is zero or Owner does not use the reserve as collateral, in which case nothing is redeemed
+    #[ink(message)]
+    fn redeem_underlying_on_behalf(
+        &mut self,
+        owner: AccountId,
+        redeem_amount: Balance,
+        to: AccountId,
+    ) -> Result<()>;
+
     /// Sender borrows assets from the protocol to their own address
     #[ink(message)]
     fn borrow(&mut self, borrow_amount: Balance) -> Result<()>;
@@ -65,8 +79,15 @@ pub trait Pool: PSP22 + PSP22Metadata {
     #[ink(message)]
     fn borrow_for(&mut self, borrower: AccountId, borrow_amount: Balance) -> Result<()>;
 
+    /// Flashloan Gateway opens a borrow of Borrower for a flashloan taken by Delegatee.
+    /// The delegate allowance from Borrower to Delegatee is consumed unless they are the same account
     #[ink(message)]
-    fn borrow_for_flashloan(&mut self, borrower: AccountId, borrow_amount: Balance) -> Result<()>;
+    fn borrow_for_flashloan(
+        &mut self,
+        borrower: AccountId,
+        delegatee: AccountId,
+        borrow_amount: Balance,
+    ) -> Result<()>;
 
     /// Sender repays their own borrow
     #[ink(message)]
@@ -122,10 +143,22 @@ pub trait Pool: PSP22 + PSP22Metadata {
     #[ink(message)]
     fn set_liquidation_threshold(&mut self, new_liquidation_threshold: u128) -> Result<()>;
 
+    /// Sets whether the Controller has an outflow limit for this pool, flows are reported to it only while it does
+    #[ink(message)]
+    fn set_outflow_limit_enabled(&mut self, enabled: bool) -> Result<()>;
+
     /// The sender adds to reserves.
     #[ink(message)]
     fn add_reserves(&mut self, amount: Balance) -> Result<()>;
 
+    /// Flashloan Gateway adds the protocol share of a flashloan premium, already transferred to this pool, to reserves
+    #[ink(message)]
+    fn add_reserves_from_flashloan(&mut self, amount: Balance) -> Result<()>;
+
+    /// Flashloan Gateway settles the underlying lent out by a flashloan, once repaid to this pool
+    #[ink(message)]
+    fn settle_flashloan(&mut self, amount: Balance) -> Result<()>;
+
     /// Accrues interest and reduces reserves by transferring to admin
     #[ink(message)]
     fn reduce_reserves(&mut self, amount: Balance) -> Result<()>;
@@ -178,6 +211,9 @@ pub trait Pool: PSP22 + PSP22Metadata {
     #[ink(message)]
     /// AccountId of incentives controller
     fn incentives_controller(&self) -> Option<AccountId>;
+    /// AccountId of interest rate model
+    #[ink(message)]
+    fn interest_rate_model(&self) -> Option<AccountId>;
     /// Get Pool's underlying Balance
     #[ink(message)]
     fn get_cash_prior(&self) -> Balance;
@@ -223,6 +259,9 @@ pub trait Pool: PSP22 + PSP22Metadata {
     /// Get Liquidation Threshold for
     #[ink(message)]
     fn liquidation_threshold(&self) -> u128;
+    /// Whether flows are reported to the outflow limiter of the Controller
+    #[ink(message)]
+    fn outflow_limit_enabled(&self) -> bool;
     /// Returns the delegation allowance of the user
     #[ink(message)]
     fn delegate_allowance(&self, owner: AccountId, delegatee: AccountId) -> Balance;
@@ -292,6 +331,7 @@ pub enum Error {
     Controller(ControllerError),
     PSP22(PSP22Error),
     Lang(LangError),
+    Math(MathError),
 }
 
 impl From<ControllerError> for Error {
@@ -312,4 +352,10 @@ impl From<LangError> for Error {
     }
 }
 
+impl From<MathError> for Error {
+    fn from(error: MathError) -> Self {
+        Error::Math(error)
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
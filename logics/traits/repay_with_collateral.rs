@@ -0,0 +1,76 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use openbrush::traits::{
+    AccountId,
+    Balance,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+use super::flashloan_adapter::Error as FlashloanAdapterError;
+
+#[openbrush::wrapper]
+pub type RepayWithCollateralRef = dyn RepayWithCollateral;
+
+/// Trait defines the interface for the Repay With Collateral adapter
+#[openbrush::trait_definition]
+pub trait RepayWithCollateral {
+    /// Get Flashloan Gateway AccountId
+    #[ink(message)]
+    fn flashloan_gateway(&self) -> Option<AccountId>;
+
+    /// Get Controller AccountId
+    #[ink(message)]
+    fn controller(&self) -> Option<AccountId>;
+
+    /// Get DEX Router AccountId
+    #[ink(message)]
+    fn router(&self) -> Option<AccountId>;
+
+    /// Repay up to `repay_amount` of the caller's `debt_asset` borrow with the caller's `collateral_asset` collateral.
+    /// `debt_asset` is flashloaned to repay the borrow, then the caller's `collateral_asset` collateral
+    /// is redeemed on their behalf, consuming the allowance of their pool tokens, and swapped to repay the flashloan.
+    /// Fails if more than `max_collateral_amount` of collateral is needed.
+    #[ink(message)]
+    fn repay_with_collateral(
+        &mut self,
+        collateral_asset: AccountId,
+        debt_asset: AccountId,
+        repay_amount: Balance,
+        max_collateral_amount: Balance,
+    ) -> Result<()>;
+}
+
+/// Parameters passed through the Flashloan Gateway to `execute_operation`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct RepayWithCollateralParams {
+    pub user: AccountId,
+    pub collateral_asset: AccountId,
+    pub max_collateral_amount: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    SameAsset,
+    NoBorrowToRepay,
+    ExcessiveCollateralAmount,
+    /// Error of the flashloan, pool and DEX router calls shared by the adapters
+    FlashloanAdapter(FlashloanAdapterError),
+}
+
+impl From<FlashloanAdapterError> for Error {
+    fn from(error: FlashloanAdapterError) -> Self {
+        Error::FlashloanAdapter(error)
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
@@ -21,6 +21,17 @@ use scale::{
     Encode,
 };
 
+/// Errors raised by the checked fixed-point arithmetic
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum MathError {
+    MathMultiplicationOverflow,
+    MathDivisionByZero,
+    MathAdditionOverflow,
+    MathSubtractionUnderflow,
+    MathConversionOverflow,
+}
+
 /// Wrapper definition for easier handling of U256
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
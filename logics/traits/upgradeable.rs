@@ -0,0 +1,61 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use ink::{
+    prelude::vec::Vec,
+    LangError,
+};
+use openbrush::traits::Hash;
+use scale::{
+    Decode,
+    Encode,
+};
+
+#[openbrush::wrapper]
+pub type UpgradeableRef = dyn Upgradeable;
+
+/// Trait for replacing the code of a contract while keeping its storage
+#[openbrush::trait_definition]
+pub trait Upgradeable {
+    /// Get the storage key and the migrated storage version of each storage struct of the current code
+    #[ink(message)]
+    fn storage_versions(&self) -> Vec<(u32, u32)>;
+
+    /// Replace the code of this contract with the code uploaded as code_hash
+    /// and migrate the storage in the same call (only Manager)
+    #[ink(message)]
+    fn upgrade(&self, code_hash: Hash) -> Result<()>;
+
+    /// Migrate each storage struct behind the storage version of the current code (only Manager or this contract)
+    #[ink(message)]
+    fn migrate(&mut self) -> Result<()>;
+}
+
+/// Custom error definitions for Upgradeable
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    CallerIsNotManager,
+    ManagerIsNotSet,
+    SetCodeHashFailed,
+    Lang(LangError),
+    /// The environment failed to execute the call of the migration
+    CallFailed,
+}
+
+impl From<LangError> for Error {
+    fn from(error: LangError) -> Self {
+        Error::Lang(error)
+    }
+}
+
+impl From<ink::env::Error> for Error {
+    fn from(_: ink::env::Error) -> Self {
+        Error::CallFailed
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
@@ -32,15 +32,29 @@ pub trait WETHGateway: Ownable {
     #[ink(message, payable)]
     fn deposit_eth(&mut self) -> Result<()>;
 
-    /// Withdraws the WETH _reserves of caller.
+    /// Deposits WETH into the reserve, using native ETH. The lTokens are minted to `on_behalf_of`.
+    #[ink(message, payable)]
+    fn deposit_eth_on_behalf(&mut self, on_behalf_of: AccountId) -> Result<()>;
+
+    /// Withdraws the WETH _reserves of caller (or all of them, if Balance::MAX is specified).
+    /// Caller must have approved their lTokens to this contract in advance by `pool.approve`.
     #[ink(message)]
     fn withdraw_eth(&mut self, amount: Balance) -> Result<()>;
 
+    /// Withdraws all the WETH _reserves of caller by redeeming all of their lTokens.
+    #[ink(message)]
+    fn withdraw_all_eth(&mut self) -> Result<()>;
+
     /// Repays a borrow on the WETH reserve, for the specified amount (or for the whole amount, if Balance::MAX is specified).
     #[ink(message, payable)]
     fn repay_eth(&mut self, amount: Balance) -> Result<()>;
 
-    /// Borrow WETH, unwraps to ETH and send both the ETH and DebtTokens to caller, via `approveDelegation` and onBehalf argument in `pool.borrow`.
+    /// Repays a borrow of `on_behalf_of` on the WETH reserve, for the specified amount (or for the whole amount, if Balance::MAX is specified).
+    #[ink(message, payable)]
+    fn repay_eth_on_behalf(&mut self, amount: Balance, on_behalf_of: AccountId) -> Result<()>;
+
+    /// Borrow WETH for caller via credit delegation, unwraps to ETH and send the ETH to caller.
+    /// Caller must have approved the delegate allowance to this contract in advance by `pool.approve_delegate`.
     #[ink(message)]
     fn borrow_eth(&mut self, amount: Balance) -> Result<()>;
 
@@ -63,9 +77,13 @@ pub trait WETHGateway: Ownable {
     #[ink(message)]
     fn get_weth_address(&self) -> AccountId;
 
-    /// Get Pool address used by WETHGateway
+    /// Get Controller address used by WETHGateway
+    #[ink(message)]
+    fn get_controller_address(&self) -> AccountId;
+
+    /// Get Pool address used by WETHGateway, resolved from the market of WETH listed in the Controller
     #[ink(message)]
-    fn get_pool_address(&self) -> AccountId;
+    fn get_pool_address(&self) -> Option<AccountId>;
 }
 
 #[derive(Debug, PartialEq, Eq, Encode, Decode)]
@@ -76,6 +94,7 @@ pub enum Error {
     WethIsNotSet,
     Pool(PoolError),
     PSP22(PSP22Error),
+    MarketNotListed,
 }
 
 impl From<PSP22Error> for Error {
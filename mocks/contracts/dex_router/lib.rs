@@ -0,0 +1,155 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+/// Mock of DEX Router: swaps at a fixed rate set per pair, paying out of its own balances
+#[openbrush::contract]
+pub mod contract {
+    use core::ops::{
+        Add,
+        Div,
+        Mul,
+        Sub,
+    };
+    use ink::{
+        prelude::vec::Vec,
+        storage::Mapping,
+    };
+    use logics::{
+        impls::fixed_point::exp_scale,
+        traits::{
+            dex_router::*,
+            types::WrappedU256,
+        },
+    };
+    use openbrush::contracts::psp22::PSP22Ref;
+    use primitive_types::U256;
+
+    #[ink(storage)]
+    #[derive(Default)]
+    pub struct DexRouterContract {
+        /// Amount of token_out per one token_in, scaled by 1e18
+        rates: Mapping<(AccountId, AccountId), WrappedU256>,
+    }
+
+    impl DexRouter for DexRouterContract {
+        #[ink(message)]
+        fn get_amount_out(
+            &self,
+            token_in: AccountId,
+            token_out: AccountId,
+            amount_in: Balance,
+        ) -> Result<Balance> {
+            let rate = self._rate(token_in, token_out)?;
+            Ok(U256::from(amount_in).mul(rate).div(exp_scale()).as_u128())
+        }
+
+        #[ink(message)]
+        fn get_amount_in(
+            &self,
+            token_in: AccountId,
+            token_out: AccountId,
+            amount_out: Balance,
+        ) -> Result<Balance> {
+            let rate = self._rate(token_in, token_out)?;
+            // round up not to pay out more than the rate
+            Ok(U256::from(amount_out)
+                .mul(exp_scale())
+                .add(rate.sub(U256::one()))
+                .div(rate)
+                .as_u128())
+        }
+
+        #[ink(message)]
+        fn swap_exact_tokens_for_tokens(
+            &mut self,
+            token_in: AccountId,
+            token_out: AccountId,
+            amount_in: Balance,
+            min_amount_out: Balance,
+            to: AccountId,
+        ) -> Result<Balance> {
+            let amount_out = self.get_amount_out(token_in, token_out, amount_in)?;
+            if amount_out < min_amount_out {
+                return Err(Error::InsufficientOutputAmount)
+            }
+            self._swap(token_in, token_out, amount_in, amount_out, to)?;
+            Ok(amount_out)
+        }
+
+        #[ink(message)]
+        fn swap_tokens_for_exact_tokens(
+            &mut self,
+            token_in: AccountId,
+            token_out: AccountId,
+            amount_out: Balance,
+            max_amount_in: Balance,
+            to: AccountId,
+        ) -> Result<Balance> {
+            let amount_in = self.get_amount_in(token_in, token_out, amount_out)?;
+            if amount_in > max_amount_in {
+                return Err(Error::ExcessiveInputAmount)
+            }
+            self._swap(token_in, token_out, amount_in, amount_out, to)?;
+            Ok(amount_in)
+        }
+    }
+
+    impl DexRouterContract {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[ink(message)]
+        pub fn set_rate(&mut self, token_in: AccountId, token_out: AccountId, rate: WrappedU256) {
+            self.rates.insert((token_in, token_out), &rate);
+        }
+
+        #[ink(message)]
+        pub fn rate(&self, token_in: AccountId, token_out: AccountId) -> Option<WrappedU256> {
+            self.rates.get((token_in, token_out))
+        }
+
+        fn _rate(&self, token_in: AccountId, token_out: AccountId) -> Result<U256> {
+            let rate = U256::from(
+                self.rates
+                    .get((token_in, token_out))
+                    .ok_or(Error::PairNotSupported)?,
+            );
+            if rate.is_zero() {
+                return Err(Error::PairNotSupported)
+            }
+            Ok(rate)
+        }
+
+        fn _swap(
+            &self,
+            token_in: AccountId,
+            token_out: AccountId,
+            amount_in: Balance,
+            amount_out: Balance,
+            to: AccountId,
+        ) -> Result<()> {
+            let contract_addr = Self::env().account_id();
+            if PSP22Ref::balance_of(&token_out, contract_addr) < amount_out {
+                return Err(Error::InsufficientLiquidity)
+            }
+            PSP22Ref::transfer_from(
+                &token_in,
+                Self::env().caller(),
+                contract_addr,
+                amount_in,
+                Vec::<u8>::new(),
+            )?;
+            PSP22Ref::transfer(&token_out, to, amount_out, Vec::<u8>::new())?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod contract {
+    use logics::impls::flash_borrower::{
+        Data,
+        Internal,
+        *,
+    };
+
+    use openbrush::traits::Storage;
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct FlashBorrowerContract {
+        #[storage_field]
+        borrower: Data,
+    }
+
+    impl Internal for FlashBorrowerContract {}
+    impl FlashBorrower for FlashBorrowerContract {}
+
+    impl FlashBorrowerContract {
+        #[ink(constructor)]
+        pub fn new(flash_lender: AccountId) -> Self {
+            let mut _instance = Self::default();
+            _instance._initialize(flash_lender);
+            _instance
+        }
+
+        #[ink(message)]
+        pub fn set_fail_execution(&mut self, fail: bool) {
+            self._set_fail_execution(fail);
+        }
+
+        #[ink(message)]
+        pub fn fail_execution(&self) -> bool {
+            self._fail_execution()
+        }
+    }
+}
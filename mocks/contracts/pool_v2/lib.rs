@@ -0,0 +1,117 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+/// Storage added in the second version of Pool
+///
+/// Fields are `Lazy` so that they are not encoded with the storage of the first version.
+pub mod extension {
+    use ink::storage::Lazy;
+    use openbrush::traits::Timestamp;
+
+    pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+    #[derive(Debug, Default)]
+    #[openbrush::upgradeable_storage(STORAGE_KEY)]
+    pub struct Data {
+        /// Storage version of Pool migrated from
+        pub migrated_from: Lazy<u32>,
+        /// Timestamp of migration
+        pub migrated_at: Lazy<Timestamp>,
+    }
+}
+
+/// Definition of Pool Contract (Version 2) to test upgrade
+///
+/// Keeps the storage layout of Pool Contract, appends a new storage field
+/// and increments the storage version of Pool.
+#[openbrush::contract]
+pub mod contract {
+    use crate::extension;
+    use ink::{
+        codegen::Env,
+        prelude::{
+            vec,
+            vec::Vec,
+        },
+    };
+    use logics::impls::{
+        pool::*,
+        upgradeable,
+    };
+    use openbrush::{
+        contracts::psp22::{
+            extensions::metadata,
+            psp22,
+        },
+        traits::Storage,
+    };
+
+    pub const STORAGE_VERSION_V2: u32 = STORAGE_VERSION + 1;
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct PoolV2Contract {
+        #[storage_field]
+        pool: Data,
+        #[storage_field]
+        psp22: psp22::Data,
+        #[storage_field]
+        metadata: metadata::Data,
+        #[storage_field]
+        upgradeable: upgradeable::Data,
+        #[storage_field]
+        extension: extension::Data,
+    }
+
+    impl Pool for PoolV2Contract {}
+    impl psp22::PSP22 for PoolV2Contract {}
+    impl metadata::PSP22Metadata for PoolV2Contract {}
+    impl upgradeable::Upgradeable for PoolV2Contract {}
+    impl upgradeable::Internal for PoolV2Contract {
+        fn _code_storage_versions(&self) -> Vec<(u32, u32)> {
+            vec![(STORAGE_KEY, STORAGE_VERSION_V2)]
+        }
+
+        fn _upgrader(&self) -> Option<AccountId> {
+            self.pool.manager
+        }
+
+        fn _migrate(&mut self, storage_key: u32, from: u32) -> upgradeable::Result<()> {
+            if storage_key == STORAGE_KEY {
+                self.extension.migrated_from.set(&from);
+                self.extension
+                    .migrated_at
+                    .set(&self.env().block_timestamp());
+            }
+            Ok(())
+        }
+    }
+
+    impl PoolV2Contract {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            let mut instance = Self::default();
+            upgradeable::Internal::_init_storage_versions(&mut instance);
+            instance
+        }
+
+        #[ink(message)]
+        pub fn migrated_from(&self) -> Option<u32> {
+            self.extension.migrated_from.get()
+        }
+
+        #[ink(message)]
+        pub fn migrated_at(&self) -> Option<Timestamp> {
+            self.extension.migrated_at.get()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
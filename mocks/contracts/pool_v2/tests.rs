@@ -0,0 +1,137 @@
+use crate::contract::*;
+use ink::{
+    env::{
+        test::{
+            self,
+            DefaultAccounts,
+        },
+        DefaultEnvironment,
+    },
+    storage::traits::Storable,
+};
+use logics::{
+    impls::{
+        fixed_point::exp_scale,
+        pool::*,
+        upgradeable::{
+            Error as UpgradeableError,
+            Upgradeable,
+        },
+    },
+    traits::types::WrappedU256,
+};
+use openbrush::{
+    contracts::psp22::{
+        extensions::metadata::PSP22Metadata,
+        PSP22,
+    },
+    traits::{
+        AccountId,
+        String,
+    },
+};
+use pool::contract::PoolContract;
+
+fn default_accounts() -> DefaultAccounts<DefaultEnvironment> {
+    test::default_accounts::<DefaultEnvironment>()
+}
+fn set_caller(id: AccountId) {
+    test::set_caller::<DefaultEnvironment>(id);
+}
+
+/// Replaces the code of Pool with the one of this contract on its storage
+fn upgrade(pool: &PoolContract) -> PoolV2Contract {
+    let mut encoded = Vec::new();
+    Storable::encode(pool, &mut encoded);
+    let mut input = &encoded[..];
+    let upgraded = <PoolV2Contract as Storable>::decode(&mut input).unwrap();
+    assert!(input.is_empty());
+
+    let mut reencoded = Vec::new();
+    Storable::encode(&upgraded, &mut reencoded);
+    assert_eq!(reencoded, encoded);
+    upgraded
+}
+
+#[ink::test]
+fn keeps_the_storage_of_pool() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let underlying = AccountId::from([0x01; 32]);
+    let controller = AccountId::from([0x02; 32]);
+    let reserve_factor = WrappedU256::from(exp_scale());
+    let mut pool = PoolContract::new(
+        None,
+        underlying,
+        controller,
+        AccountId::from([0x03; 32]),
+        accounts.bob,
+        WrappedU256::from(exp_scale()),
+        10000,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    );
+    pool.set_reserve_factor_mantissa(reserve_factor).unwrap();
+    pool.approve(accounts.alice, 100).unwrap();
+    pool.approve_delegate(accounts.alice, 200).unwrap();
+
+    let upgraded = upgrade(&pool);
+    assert_eq!(upgraded.underlying(), Some(underlying));
+    assert_eq!(upgraded.controller(), Some(controller));
+    assert_eq!(upgraded.manager(), Some(accounts.bob));
+    assert_eq!(upgraded.reserve_factor_mantissa(), reserve_factor);
+    assert_eq!(upgraded.allowance(accounts.bob, accounts.alice), 100);
+    assert_eq!(
+        upgraded.delegate_allowance(accounts.bob, accounts.alice),
+        200
+    );
+    assert_eq!(upgraded.token_symbol(), Some(String::from("symbol")));
+    assert_eq!(
+        upgraded.storage_versions(),
+        vec![(STORAGE_KEY, STORAGE_VERSION)]
+    );
+    assert_eq!(upgraded.migrated_from(), None);
+}
+
+#[ink::test]
+fn migrate_works() {
+    let accounts = default_accounts();
+    set_caller(accounts.bob);
+
+    let pool = PoolContract::new(
+        None,
+        AccountId::from([0x01; 32]),
+        AccountId::from([0x02; 32]),
+        AccountId::from([0x03; 32]),
+        accounts.bob,
+        WrappedU256::from(exp_scale()),
+        10000,
+        String::from("Token Name"),
+        String::from("symbol"),
+        8,
+    );
+    let mut upgraded = upgrade(&pool);
+
+    set_caller(accounts.charlie);
+    assert_eq!(
+        upgraded.migrate().unwrap_err(),
+        UpgradeableError::CallerIsNotManager
+    );
+
+    set_caller(accounts.bob);
+    test::set_block_timestamp::<DefaultEnvironment>(100);
+    upgraded.migrate().unwrap();
+    assert_eq!(
+        upgraded.storage_versions(),
+        vec![(STORAGE_KEY, STORAGE_VERSION_V2)]
+    );
+    assert_eq!(upgraded.migrated_from(), Some(STORAGE_VERSION));
+    assert_eq!(upgraded.migrated_at(), Some(100));
+
+    // migrates only once
+    test::set_block_timestamp::<DefaultEnvironment>(200);
+    upgraded.migrate().unwrap();
+    assert_eq!(upgraded.migrated_at(), Some(100));
+}
@@ -3,9 +3,13 @@ import type { ApiPromise } from '@polkadot/api'
 import type { KeyringPair } from '@polkadot/keyring/types'
 import { LastArrayElement } from 'type-fest'
 
+import CollateralSwap_factory from '../../types/constructors/collateral_swap'
 import Controller_factory from '../../types/constructors/controller'
+import DexRouter_factory from '../../types/constructors/dex_router'
+import DebtSwap_factory from '../../types/constructors/debt_swap'
 import DefaultInterestRateModel_factory from '../../types/constructors/default_interest_rate_model'
 import Faucet_factory from '../../types/constructors/faucet'
+import FlashBorrower_factory from '../../types/constructors/flash_borrower'
 import FlashloanGateway_factory from '../../types/constructors/flashloan_gateway'
 import FlashloanReceiver_factory from '../../types/constructors/flashloan_receiver'
 import IncentivesController_factory from '../../types/constructors/incentives_controller'
@@ -13,14 +17,20 @@ import Lens_factory from '../../types/constructors/lens'
 import Leverager_factory from '../../types/constructors/leverager'
 import Manager_factory from '../../types/constructors/manager'
 import Pool_factory from '../../types/constructors/pool'
+import PoolV2_factory from '../../types/constructors/pool_v2'
 import PriceOracle_factory from '../../types/constructors/price_oracle'
 import PSP22Token_factory from '../../types/constructors/psp22_token'
+import RepayWithCollateral_factory from '../../types/constructors/repay_with_collateral'
 import WETH_factory from '../../types/constructors/weth'
 import WETHGateway_factory from '../../types/constructors/weth_gateway'
 
+import CollateralSwap from '../../types/contracts/collateral_swap'
 import Controller from '../../types/contracts/controller'
+import DexRouter from '../../types/contracts/dex_router'
+import DebtSwap from '../../types/contracts/debt_swap'
 import DefaultInterestRateModel from '../../types/contracts/default_interest_rate_model'
 import Faucet from '../../types/contracts/faucet'
+import FlashBorrower from '../../types/contracts/flash_borrower'
 import FlashloanGateway from '../../types/contracts/flashloan_gateway'
 import FlashloanReceiver from '../../types/contracts/flashloan_receiver'
 import IncentivesController from '../../types/contracts/incentives_controller'
@@ -28,8 +38,10 @@ import Lens from '../../types/contracts/lens'
 import Leverager from '../../types/contracts/leverager'
 import Manager from '../../types/contracts/manager'
 import Pool from '../../types/contracts/pool'
+import PoolV2 from '../../types/contracts/pool_v2'
 import PriceOracle from '../../types/contracts/price_oracle'
 import PSP22Token from '../../types/contracts/psp22_token'
+import RepayWithCollateral from '../../types/contracts/repay_with_collateral'
 import WETH from '../../types/contracts/weth'
 import WETHGateway from '../../types/contracts/weth_gateway'
 
@@ -169,6 +181,19 @@ export const deployPool = async ({
   return result
 }
 // Mocks
+export const deployPoolV2 = async ({
+  api,
+  signer,
+  args,
+  option = defaultOption(api),
+}: FactoryArgs<PoolV2_factory['new']>): Promise<PoolV2> => {
+  const factory = new PoolV2_factory(api, signer)
+  const contract = await factory.new(...args, option)
+  const result = new PoolV2(contract.address, signer, api)
+  await afterDeployment(result.name, contract)
+  return result
+}
+
 // eslint-disable-next-line @typescript-eslint/naming-convention
 export const deployPSP22Token = async ({
   api,
@@ -264,6 +289,20 @@ export const deployFlashLoanReceiver = async ({
   return result
 }
 
+export const deployFlashBorrower = async ({
+  api,
+  signer,
+  args,
+  option = defaultOption(api),
+}: FactoryArgs<FlashBorrower_factory['new']>): Promise<FlashBorrower> => {
+  const factory = new FlashBorrower_factory(api, signer)
+  const contract = await factory.new(...args, option)
+
+  const result = new FlashBorrower(contract.address, signer, api)
+  await afterDeployment(result.name, contract)
+  return result
+}
+
 export const deployIncentivesController = async ({
   api,
   signer,
@@ -293,3 +332,61 @@ export const deployLeverager = async ({
   await afterDeployment(result.name, contract)
   return result
 }
+
+export const deployDexRouter = async ({
+  api,
+  signer,
+  args,
+  option = defaultOption(api),
+}: FactoryArgs<DexRouter_factory['new']>): Promise<DexRouter> => {
+  const factory = new DexRouter_factory(api, signer)
+  const contract = await factory.new(...args, option)
+
+  const result = new DexRouter(contract.address, signer, api)
+  await afterDeployment(result.name, contract)
+  return result
+}
+
+export const deployCollateralSwap = async ({
+  api,
+  signer,
+  args,
+  option = defaultOption(api),
+}: FactoryArgs<CollateralSwap_factory['new']>): Promise<CollateralSwap> => {
+  const factory = new CollateralSwap_factory(api, signer)
+  const contract = await factory.new(...args, option)
+
+  const result = new CollateralSwap(contract.address, signer, api)
+  await afterDeployment(result.name, contract)
+  return result
+}
+
+export const deployRepayWithCollateral = async ({
+  api,
+  signer,
+  args,
+  option = defaultOption(api),
+}: FactoryArgs<
+  RepayWithCollateral_factory['new']
+>): Promise<RepayWithCollateral> => {
+  const factory = new RepayWithCollateral_factory(api, signer)
+  const contract = await factory.new(...args, option)
+
+  const result = new RepayWithCollateral(contract.address, signer, api)
+  await afterDeployment(result.name, contract)
+  return result
+}
+
+export const deployDebtSwap = async ({
+  api,
+  signer,
+  args,
+  option = defaultOption(api),
+}: FactoryArgs<DebtSwap_factory['new']>): Promise<DebtSwap> => {
+  const factory = new DebtSwap_factory(api, signer)
+  const contract = await factory.new(...args, option)
+
+  const result = new DebtSwap(contract.address, signer, api)
+  await afterDeployment(result.name, contract)
+  return result
+}
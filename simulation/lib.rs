@@ -0,0 +1,81 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Off-chain simulation of the markets
+//!
+//! Replays price paths and user actions with the formulas of `logics`
+//! (interest accrual, interest rate model, account liquidity and seize calculation)
+//! to stress-test risk parameters before they are set on-chain.
+//!
+//! NOTE: Guardian pauses, borrow caps, max utilization, outflow limits,
+//! the health factor check on redeem and incentives are not simulated.
+
+pub mod market;
+pub mod report;
+pub mod scenario;
+mod simulator;
+
+pub use self::{
+    report::Report,
+    scenario::Scenario,
+    simulator::{
+        simulate,
+        Simulator,
+        LIQUIDATOR,
+    },
+};
+use logics::traits::{
+    controller::Error as ControllerError,
+    pool::Error as PoolError,
+    types::MathError,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    InvalidScenario(String),
+    InvalidDecimal(String),
+    UnknownMarket(String),
+    MissingInitialPrice(String),
+    Pool(PoolError),
+}
+
+impl From<PoolError> for Error {
+    fn from(error: PoolError) -> Self {
+        Error::Pool(error)
+    }
+}
+
+impl From<ControllerError> for Error {
+    fn from(error: ControllerError) -> Self {
+        Error::Pool(PoolError::Controller(error))
+    }
+}
+
+impl From<MathError> for Error {
+    fn from(error: MathError) -> Self {
+        Error::Pool(PoolError::Math(error))
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::InvalidScenario(reason) => write!(f, "invalid scenario: {}", reason),
+            Error::InvalidDecimal(value) => write!(f, "invalid decimal: {}", value),
+            Error::UnknownMarket(symbol) => write!(f, "unknown market: {}", symbol),
+            Error::MissingInitialPrice(symbol) => write!(f, "no price of {} at step 0", symbol),
+            Error::Pool(error) => write!(f, "simulation failed: {:?}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,24 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use simulation::{
+    simulate,
+    Scenario,
+};
+
+/// Runs a scenario file and prints the report as JSON
+///
+/// Usage: `cargo run -p simulation -- <scenario.json>`
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .ok_or("usage: simulate <scenario.json>")?;
+    let scenario = Scenario::from_json(&std::fs::read_to_string(path)?)?;
+    let report = simulate(&scenario)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,359 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! State of a Pool, updated with the same formulas as `logics::impls::pool`
+
+use crate::{
+    scenario::{
+        parse_mantissa,
+        MarketConfig,
+    },
+    Error,
+    Result,
+};
+use logics::{
+    impls::{
+        controller::HypotheticalAccountLiquidityCalculationParam,
+        fixed_point::{
+            exp_scale,
+            mul_div_down,
+            mul_div_up,
+            to_balance,
+            Exp,
+        },
+        interest_rate_model::{
+            self,
+            utilization_rate,
+        },
+        pool::utils::{
+            calculate_interest,
+            exchange_rate,
+            pool_balance,
+            protocol_seize_amount,
+            protocol_seize_share_mantissa,
+            underlying_balance,
+            CalculateInterestInput,
+        },
+    },
+    traits::{
+        pool::{
+            Error as PoolError,
+            Result as PoolResult,
+        },
+        types::WrappedU256,
+    },
+};
+use openbrush::{
+    contracts::psp22::PSP22Error,
+    traits::{
+        AccountId,
+        Balance,
+        Timestamp,
+    },
+};
+use primitive_types::U256;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+/// Index of an account in the simulation
+pub type Account = usize;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct BorrowSnapshot {
+    principal: Balance,
+    interest_index: U256,
+}
+
+#[derive(Debug)]
+pub struct Market {
+    pub symbol: String,
+    /// Identifies the market in the account liquidity calculation
+    pub asset: AccountId,
+    pub decimals: u8,
+    pub collateral_factor_mantissa: U256,
+    pub reserve_factor_mantissa: U256,
+    /// Cash held by the pool, `get_cash_prior` on-chain
+    pub cash: Balance,
+    pub total_borrows: Balance,
+    pub total_reserves: Balance,
+    pub total_supply: Balance,
+    pub borrow_index: U256,
+    pub accrual_block_timestamp: Timestamp,
+    rate_model: interest_rate_model::Data,
+    /// Price path sorted by step
+    prices: Vec<(u64, U256)>,
+    balances: BTreeMap<Account, Balance>,
+    account_borrows: BTreeMap<Account, BorrowSnapshot>,
+    using_reserve_as_collateral: BTreeSet<Account>,
+}
+
+impl Market {
+    pub fn new(symbol: &str, id: u8, config: &MarketConfig) -> Result<Self> {
+        let mut prices = config
+            .prices
+            .iter()
+            .map(|point| Ok((point.step, parse_mantissa(&point.price)?)))
+            .collect::<Result<Vec<_>>>()?;
+        prices.sort_by_key(|(step, _)| *step);
+        if prices.first().map(|(step, _)| *step) != Some(0) {
+            return Err(Error::MissingInitialPrice(symbol.to_string()))
+        }
+
+        Ok(Self {
+            symbol: symbol.to_string(),
+            asset: AccountId::from([id; 32]),
+            decimals: config.decimals,
+            collateral_factor_mantissa: parse_mantissa(&config.collateral_factor)?,
+            reserve_factor_mantissa: parse_mantissa(&config.reserve_factor)?,
+            cash: 0,
+            total_borrows: 0,
+            total_reserves: 0,
+            total_supply: 0,
+            borrow_index: exp_scale(),
+            accrual_block_timestamp: 0,
+            rate_model: rate_model(symbol, config)?,
+            prices,
+            balances: BTreeMap::new(),
+            account_borrows: BTreeMap::new(),
+            using_reserve_as_collateral: BTreeSet::new(),
+        })
+    }
+
+    /// Oracle price at `step` (Decimals: 18)
+    pub fn price(&self, step: u64) -> U256 {
+        self.prices
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= step)
+            .map(|(_, price)| *price)
+            .unwrap_or_default()
+    }
+
+    pub fn exchange_rate_stored(&self) -> PoolResult<U256> {
+        exchange_rate(
+            self.total_supply,
+            self.cash,
+            self.total_borrows,
+            self.total_reserves,
+            // `initialExchangeRateMantissa` of every market in `scripts/risk_parameters.ts`
+            exp_scale(),
+        )
+    }
+
+    pub fn utilization_rate(&self) -> U256 {
+        utilization_rate(self.cash, self.total_borrows, self.total_reserves)
+    }
+
+    pub fn balance_of(&self, account: Account) -> Balance {
+        self.balances.get(&account).copied().unwrap_or_default()
+    }
+
+    pub fn using_reserve_as_collateral(&self, account: Account) -> bool {
+        self.using_reserve_as_collateral.contains(&account)
+    }
+
+    pub fn balance_of_underlying(&self, account: Account) -> PoolResult<Balance> {
+        Ok(underlying_balance(
+            Exp(self.exchange_rate_stored()?),
+            self.balance_of(account),
+        )?)
+    }
+
+    pub fn borrow_balance_stored(&self, account: Account) -> PoolResult<Balance> {
+        let snapshot = self
+            .account_borrows
+            .get(&account)
+            .copied()
+            .unwrap_or_default();
+        if snapshot.principal == 0 {
+            return Ok(0)
+        }
+        let borrow_balance = mul_div_up(
+            U256::from(snapshot.principal),
+            self.borrow_index,
+            snapshot.interest_index,
+        )?;
+        Ok(to_balance(borrow_balance)?)
+    }
+
+    /// Parameters of `account` for `get_hypothetical_account_liquidity`,
+    /// as returned by `get_account_snapshot` on-chain
+    pub fn liquidity_param(
+        &self,
+        account: Account,
+        step: u64,
+    ) -> PoolResult<HypotheticalAccountLiquidityCalculationParam> {
+        let token_balance = if self.using_reserve_as_collateral(account) {
+            self.balance_of(account)
+        } else {
+            0
+        };
+        Ok(HypotheticalAccountLiquidityCalculationParam {
+            asset: self.asset,
+            decimals: self.decimals,
+            token_balance,
+            borrow_balance: self.borrow_balance_stored(account)?,
+            exchange_rate_mantissa: Exp(self.exchange_rate_stored()?),
+            collateral_factor_mantissa: Exp(self.collateral_factor_mantissa),
+            oracle_price_mantissa: Exp(self.price(step)),
+        })
+    }
+
+    pub fn accrue_interest(&mut self, timestamp: Timestamp) -> PoolResult<()> {
+        if self.accrual_block_timestamp == timestamp {
+            return Ok(())
+        }
+        let borrow_rate =
+            self.rate_model
+                .borrow_rate(self.cash, self.total_borrows, self.total_reserves);
+        let output = calculate_interest(&CalculateInterestInput {
+            total_borrows: self.total_borrows,
+            total_reserves: self.total_reserves,
+            borrow_index: self.borrow_index,
+            borrow_rate: U256::from(borrow_rate),
+            old_block_timestamp: self.accrual_block_timestamp,
+            new_block_timestamp: timestamp,
+            reserve_factor_mantissa: self.reserve_factor_mantissa,
+        })?;
+        self.accrual_block_timestamp = timestamp;
+        self.borrow_index = output.borrow_index;
+        self.total_borrows = output.total_borrows;
+        self.total_reserves = output.total_reserves;
+        Ok(())
+    }
+
+    pub fn mint(&mut self, minter: Account, mint_amount: Balance) -> PoolResult<()> {
+        let exchange_rate = self.exchange_rate_stored()?;
+        let minted_tokens = pool_balance(Exp(exchange_rate), mint_amount)?;
+        if self.balance_of(minter) == 0 {
+            self.using_reserve_as_collateral.insert(minter);
+        }
+        self.cash += mint_amount;
+        self.mint_to(minter, minted_tokens);
+        Ok(())
+    }
+
+    /// NOTE: the controller check must have passed, as in `Pool::_redeem`
+    pub fn redeem(&mut self, redeemer: Account, redeem_amount: Balance) -> PoolResult<()> {
+        if redeem_amount == 0 || !self.using_reserve_as_collateral(redeemer) {
+            return Ok(())
+        }
+        if self.cash < redeem_amount {
+            return Err(PoolError::RedeemTransferOutNotPossible)
+        }
+        if self.balance_of(redeemer) == redeem_amount {
+            self.using_reserve_as_collateral.remove(&redeemer);
+        }
+        self.burn_from(redeemer, redeem_amount)?;
+        self.cash -= redeem_amount;
+        Ok(())
+    }
+
+    /// NOTE: the controller check must have passed, as in `Pool::_borrow`
+    pub fn borrow(&mut self, borrower: Account, borrow_amount: Balance) -> PoolResult<()> {
+        if self.cash < borrow_amount {
+            return Err(PoolError::BorrowCashNotAvailable)
+        }
+        let account_borrows_new = self.borrow_balance_stored(borrower)? + borrow_amount;
+        self.account_borrows.insert(
+            borrower,
+            BorrowSnapshot {
+                principal: account_borrows_new,
+                interest_index: self.borrow_index,
+            },
+        );
+        self.total_borrows += borrow_amount;
+        self.cash -= borrow_amount;
+        Ok(())
+    }
+
+    /// Returns the actual repaid amount
+    pub fn repay_borrow(&mut self, borrower: Account, repay_amount: Balance) -> PoolResult<Balance> {
+        let account_borrow_prev = self.borrow_balance_stored(borrower)?;
+        let repay_amount_final = repay_amount.min(account_borrow_prev);
+        self.account_borrows.insert(
+            borrower,
+            BorrowSnapshot {
+                principal: account_borrow_prev - repay_amount_final,
+                interest_index: self.borrow_index,
+            },
+        );
+        // NOTE: the debt of the last borrower can exceed total borrows by the rounding dust
+        self.total_borrows = self.total_borrows.saturating_sub(repay_amount_final);
+        self.cash += repay_amount_final;
+        Ok(repay_amount_final)
+    }
+
+    /// Returns the pool tokens received by the liquidator and the amount added to reserves
+    pub fn seize(
+        &mut self,
+        liquidator: Account,
+        borrower: Account,
+        seize_tokens: Balance,
+    ) -> PoolResult<(Balance, Balance)> {
+        if liquidator == borrower {
+            return Err(PoolError::LiquidateSeizeLiquidatorIsBorrower)
+        }
+        if !self.using_reserve_as_collateral(borrower) {
+            return Err(PoolError::ReserveIsNotEnabledAsCollateral)
+        }
+        let exchange_rate = Exp(self.exchange_rate_stored()?);
+        let (liquidator_seize_tokens, protocol_seize_amount, _) =
+            protocol_seize_amount(exchange_rate, seize_tokens, protocol_seize_share_mantissa())?;
+        self.total_reserves += protocol_seize_amount;
+        self.burn_from(borrower, seize_tokens)?;
+        self.mint_to(liquidator, liquidator_seize_tokens);
+        Ok((liquidator_seize_tokens, protocol_seize_amount))
+    }
+
+    fn mint_to(&mut self, account: Account, amount: Balance) {
+        *self.balances.entry(account).or_default() += amount;
+        self.total_supply += amount;
+    }
+
+    fn burn_from(&mut self, account: Account, amount: Balance) -> PoolResult<()> {
+        let balance = self.balance_of(account);
+        if balance < amount {
+            return Err(PoolError::from(PSP22Error::InsufficientBalance))
+        }
+        self.balances.insert(account, balance - amount);
+        self.total_supply -= amount;
+        Ok(())
+    }
+}
+
+/// Interest rate model of the market,
+/// derived from the parameters as `StarlayInterestRateModel` in `scripts/interest_rates.ts`
+fn rate_model(symbol: &str, config: &MarketConfig) -> Result<interest_rate_model::Data> {
+    let base = exp_scale();
+    let params = &config.rate_model;
+    let optimal_utilization_rate = parse_mantissa(&params.optimal_utilization_rate)?;
+    if optimal_utilization_rate.is_zero() || optimal_utilization_rate >= base {
+        return Err(Error::InvalidScenario(format!(
+            "optimal utilization rate of {} must be between 0 and 1",
+            symbol
+        )))
+    }
+    let multiplier_slope_1 = mul_div_down(
+        parse_mantissa(&params.slope_1)?,
+        base,
+        optimal_utilization_rate,
+    )?;
+    let multiplier_slope_2 = mul_div_down(
+        parse_mantissa(&params.slope_2)?,
+        base,
+        base - optimal_utilization_rate,
+    )?;
+    Ok(interest_rate_model::Data::new(
+        WrappedU256::from(parse_mantissa(&params.base_rate)?),
+        WrappedU256::from(multiplier_slope_1),
+        WrappedU256::from(multiplier_slope_2),
+        WrappedU256::from(optimal_utilization_rate),
+    ))
+}
@@ -0,0 +1,86 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Result of a simulation
+//!
+//! Token amounts are formatted with the decimals of the market,
+//! values in base currency and ratios with 18 decimals.
+
+use crate::scenario::Action;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    pub steps: u64,
+    pub markets: Vec<MarketReport>,
+    pub liquidations: Vec<Liquidation>,
+    pub bad_debt: BadDebtReport,
+    /// Actions reverted by the protocol, with the error returned on-chain
+    pub rejected_actions: Vec<RejectedAction>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarketReport {
+    pub symbol: String,
+    pub utilization: UtilizationReport,
+    pub cash: String,
+    pub total_borrows: String,
+    pub total_reserves: String,
+    pub total_supply: String,
+    pub exchange_rate: String,
+    pub borrow_index: String,
+    /// Liquidations repaying a debt of this market
+    pub liquidations: usize,
+}
+
+/// Utilization rate of a market sampled at the end of each step
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UtilizationReport {
+    pub average: String,
+    pub max: String,
+    pub last: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Liquidation {
+    pub step: u64,
+    pub borrower: String,
+    pub borrowed_market: String,
+    pub collateral_market: String,
+    pub repay_amount: String,
+    pub seize_tokens: String,
+    /// Pool tokens of the collateral received by the liquidator
+    pub liquidator_seize_tokens: String,
+    /// Underlying of the collateral added to the reserves
+    pub protocol_seize_amount: String,
+    /// Shortfall of the borrower before the liquidation, in base currency
+    pub shortfall: String,
+}
+
+/// Debt not covered by the collateral at its market value, in base currency
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BadDebtReport {
+    pub max: String,
+    pub max_step: u64,
+    pub last: String,
+    pub accounts: Vec<AccountBadDebt>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountBadDebt {
+    pub account: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedAction {
+    pub step: u64,
+    pub account: String,
+    #[serde(flatten)]
+    pub action: Action,
+    pub reason: String,
+}
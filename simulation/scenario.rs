@@ -0,0 +1,170 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Scenario file format
+//!
+//! Ratios, prices and amounts are decimal strings in natural units
+//! (`"0.8"` for a collateral factor of 80%, `"1850.5"` for a price, `"1000"` for 1000 tokens),
+//! so that the values of `scripts/risk_parameters.ts` and `scripts/interest_rates.ts`
+//! can be copied as is.
+
+use crate::{
+    Error,
+    Result,
+};
+use openbrush::traits::Balance;
+use primitive_types::U256;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::collections::BTreeMap;
+
+/// Decimals of ratios and prices, same as the mantissas on-chain
+pub const MANTISSA_DECIMALS: u8 = 18;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    /// Milliseconds elapsed between two steps
+    pub step_interval: u64,
+    /// Number of steps to simulate
+    pub steps: u64,
+    /// `closeFactor` of the Controller
+    pub close_factor: String,
+    /// `liquidationIncentive` of the Controller
+    pub liquidation_incentive: String,
+    /// Markets by symbol
+    pub markets: BTreeMap<String, MarketConfig>,
+    /// User actions, applied in order within a step
+    #[serde(default)]
+    pub actions: Vec<ActionConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketConfig {
+    pub decimals: u8,
+    pub collateral_factor: String,
+    pub reserve_factor: String,
+    pub rate_model: RateModelConfig,
+    /// Price path, each price applies from its step until the next one
+    pub prices: Vec<PricePoint>,
+}
+
+/// Parameters of the interest rate model, as in `scripts/interest_rates.ts`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateModelConfig {
+    pub base_rate: String,
+    pub slope_1: String,
+    pub slope_2: String,
+    pub optimal_utilization_rate: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PricePoint {
+    pub step: u64,
+    pub price: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActionConfig {
+    pub step: u64,
+    pub account: String,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Deposit { market: String, amount: String },
+    Redeem { market: String, amount: String },
+    Borrow { market: String, amount: String },
+    Repay { market: String, amount: String },
+}
+
+impl Action {
+    pub fn market(&self) -> &str {
+        match self {
+            Action::Deposit { market, .. }
+            | Action::Redeem { market, .. }
+            | Action::Borrow { market, .. }
+            | Action::Repay { market, .. } => market,
+        }
+    }
+
+    pub fn amount(&self) -> &str {
+        match self {
+            Action::Deposit { amount, .. }
+            | Action::Redeem { amount, .. }
+            | Action::Borrow { amount, .. }
+            | Action::Repay { amount, .. } => amount,
+        }
+    }
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::InvalidScenario(e.to_string()))
+    }
+}
+
+/// Parses a decimal string into an integer with `decimals` decimals,
+/// e.g. `"1.5"` with 6 decimals into `1500000`
+pub fn parse_units(value: &str, decimals: u8) -> Result<U256> {
+    let invalid = || Error::InvalidDecimal(value.to_string());
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if integer.is_empty()
+        || fraction.len() > decimals as usize
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid())
+    }
+    let digits = format!(
+        "{}{:0<width$}",
+        integer,
+        fraction,
+        width = decimals as usize
+    );
+    U256::from_dec_str(&digits).map_err(|_| invalid())
+}
+
+/// Parses a ratio or a price into a mantissa
+pub fn parse_mantissa(value: &str) -> Result<U256> {
+    parse_units(value, MANTISSA_DECIMALS)
+}
+
+/// Parses an amount of a token with `decimals` decimals
+pub fn parse_amount(value: &str, decimals: u8) -> Result<Balance> {
+    let amount = parse_units(value, decimals)?;
+    if amount > U256::from(Balance::MAX) {
+        return Err(Error::InvalidDecimal(value.to_string()))
+    }
+    Ok(amount.as_u128())
+}
+
+/// Formats an integer with `decimals` decimals into a decimal string, the inverse of `parse_units`
+pub fn format_units(value: U256, decimals: u8) -> String {
+    let digits = format!(
+        "{:0>width$}",
+        value.to_string(),
+        width = decimals as usize + 1
+    );
+    let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+pub fn format_mantissa(value: U256) -> String {
+    format_units(value, MANTISSA_DECIMALS)
+}
@@ -0,0 +1,443 @@
+// Copyright 2023 Asynmatrix Pte. Ltd.
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{
+    market::{
+        Account,
+        Market,
+    },
+    report::{
+        AccountBadDebt,
+        BadDebtReport,
+        Liquidation,
+        MarketReport,
+        RejectedAction,
+        Report,
+        UtilizationReport,
+    },
+    scenario::{
+        format_mantissa,
+        format_units,
+        parse_amount,
+        parse_mantissa,
+        Action,
+        Scenario,
+    },
+    Error,
+    Result,
+};
+use logics::{
+    impls::{
+        controller::{
+            get_hypothetical_account_liquidity,
+            liquidate_calculate_seize_tokens,
+            GetHypotheticalAccountLiquidityInput,
+            LiquidateCalculateSeizeTokensInput,
+            MAXIMUM_MARKETS,
+        },
+        fixed_point::{
+            mul_div_down,
+            to_balance,
+            Exp,
+        },
+    },
+    traits::{
+        controller::Error as ControllerError,
+        pool::Result as PoolResult,
+    },
+};
+use openbrush::traits::Balance;
+use primitive_types::U256;
+
+/// Name of the account liquidating every shortfall, with unlimited funds
+pub const LIQUIDATOR: &str = "liquidator";
+const LIQUIDATOR_ACCOUNT: Account = 0;
+
+struct ScheduledAction {
+    step: u64,
+    account: Account,
+    market: usize,
+    amount: Balance,
+    action: Action,
+}
+
+/// Replays a scenario step by step:
+/// interest is accrued at the beginning of each step, then the actions of the step are applied
+/// and every account with a shortfall is liquidated once, as a keeper would.
+pub struct Simulator {
+    step_interval: u64,
+    steps: u64,
+    close_factor_mantissa: U256,
+    liquidation_incentive_mantissa: U256,
+    markets: Vec<Market>,
+    accounts: Vec<String>,
+    actions: Vec<ScheduledAction>,
+}
+
+impl Simulator {
+    pub fn new(scenario: &Scenario) -> Result<Self> {
+        if scenario.steps == 0 {
+            return Err(Error::InvalidScenario("no steps to simulate".to_string()))
+        }
+        if scenario.markets.len() > MAXIMUM_MARKETS {
+            return Err(Error::InvalidScenario(format!(
+                "the Controller supports up to {} markets",
+                MAXIMUM_MARKETS
+            )))
+        }
+        let markets = scenario
+            .markets
+            .iter()
+            .enumerate()
+            .map(|(index, (symbol, config))| Market::new(symbol, index as u8 + 1, config))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut accounts = vec![LIQUIDATOR.to_string()];
+        let mut actions = Vec::new();
+        for config in &scenario.actions {
+            if config.step >= scenario.steps {
+                return Err(Error::InvalidScenario(format!(
+                    "action at step {} after the last step",
+                    config.step
+                )))
+            }
+            let market = markets
+                .iter()
+                .position(|market| market.symbol == config.action.market())
+                .ok_or_else(|| Error::UnknownMarket(config.action.market().to_string()))?;
+            let account = match accounts.iter().position(|name| *name == config.account) {
+                Some(account) => account,
+                None => {
+                    accounts.push(config.account.clone());
+                    accounts.len() - 1
+                }
+            };
+            actions.push(ScheduledAction {
+                step: config.step,
+                account,
+                market,
+                amount: parse_amount(config.action.amount(), markets[market].decimals)?,
+                action: config.action.clone(),
+            });
+        }
+        actions.sort_by_key(|action| action.step);
+
+        Ok(Self {
+            step_interval: scenario.step_interval,
+            steps: scenario.steps,
+            close_factor_mantissa: parse_mantissa(&scenario.close_factor)?,
+            liquidation_incentive_mantissa: parse_mantissa(&scenario.liquidation_incentive)?,
+            markets,
+            accounts,
+            actions,
+        })
+    }
+
+    pub fn run(mut self) -> Result<Report> {
+        let mut report = Report {
+            steps: self.steps,
+            ..Default::default()
+        };
+        let mut utilization_sum = vec![U256::zero(); self.markets.len()];
+        let mut utilization_max = vec![U256::zero(); self.markets.len()];
+        let mut bad_debt = Vec::new();
+        let (mut bad_debt_max, mut bad_debt_max_step) = (U256::zero(), 0);
+        let actions = core::mem::take(&mut self.actions);
+        let mut actions = actions.iter().peekable();
+
+        for step in 0..self.steps {
+            let timestamp = step * self.step_interval;
+            for market in self.markets.iter_mut() {
+                market.accrue_interest(timestamp)?;
+            }
+
+            while let Some(action) = actions.next_if(|action| action.step == step) {
+                if let Err(error) = self.apply(step, action) {
+                    report.rejected_actions.push(RejectedAction {
+                        step,
+                        account: self.accounts[action.account].clone(),
+                        action: action.action.clone(),
+                        reason: format!("{:?}", error),
+                    });
+                }
+            }
+
+            self.liquidate(step, &mut report)?;
+
+            for (index, market) in self.markets.iter().enumerate() {
+                let utilization = market.utilization_rate();
+                utilization_sum[index] += utilization;
+                utilization_max[index] = utilization_max[index].max(utilization);
+            }
+            bad_debt = self.bad_debt(step)?;
+            let total = bad_debt
+                .iter()
+                .fold(U256::zero(), |total, (_, amount)| total + amount);
+            if total > bad_debt_max {
+                (bad_debt_max, bad_debt_max_step) = (total, step);
+            }
+        }
+
+        report.markets = self
+            .markets
+            .iter()
+            .enumerate()
+            .map(|(index, market)| {
+                let amount = |value: Balance| format_units(U256::from(value), market.decimals);
+                Ok(MarketReport {
+                    symbol: market.symbol.clone(),
+                    utilization: UtilizationReport {
+                        average: format_mantissa(utilization_sum[index] / self.steps),
+                        max: format_mantissa(utilization_max[index]),
+                        last: format_mantissa(market.utilization_rate()),
+                    },
+                    cash: amount(market.cash),
+                    total_borrows: amount(market.total_borrows),
+                    total_reserves: amount(market.total_reserves),
+                    total_supply: amount(market.total_supply),
+                    exchange_rate: format_mantissa(market.exchange_rate_stored()?),
+                    borrow_index: format_mantissa(market.borrow_index),
+                    liquidations: report
+                        .liquidations
+                        .iter()
+                        .filter(|liquidation| liquidation.borrowed_market == market.symbol)
+                        .count(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        report.bad_debt = BadDebtReport {
+            max: format_mantissa(bad_debt_max),
+            max_step: bad_debt_max_step,
+            last: format_mantissa(
+                bad_debt
+                    .iter()
+                    .fold(U256::zero(), |total, (_, amount)| total + amount),
+            ),
+            accounts: bad_debt
+                .into_iter()
+                .map(|(account, amount)| {
+                    AccountBadDebt {
+                        account: self.accounts[account].clone(),
+                        amount: format_mantissa(amount),
+                    }
+                })
+                .collect(),
+        };
+        Ok(report)
+    }
+
+    fn apply(&mut self, step: u64, action: &ScheduledAction) -> PoolResult<()> {
+        let ScheduledAction {
+            account,
+            market,
+            amount,
+            ..
+        } = *action;
+        match action.action {
+            Action::Deposit { .. } => self.markets[market].mint(account, amount),
+            Action::Redeem { .. } => {
+                // `Pool::_redeem` returns before the controller check in these cases
+                if amount != 0 && self.markets[market].using_reserve_as_collateral(account) {
+                    self.assert_liquidity(account, step, market, amount, 0)?;
+                }
+                self.markets[market].redeem(account, amount)
+            }
+            Action::Borrow { .. } => {
+                self.assert_liquidity(account, step, market, 0, amount)?;
+                self.markets[market].borrow(account, amount)
+            }
+            Action::Repay { .. } => self.markets[market]
+                .repay_borrow(account, amount)
+                .map(|_| ()),
+        }
+    }
+
+    /// Returns the liquidity and the shortfall of `account`,
+    /// as `get_hypothetical_account_liquidity` of the Controller
+    fn account_liquidity(
+        &self,
+        account: Account,
+        step: u64,
+        token_modify: Option<usize>,
+        redeem_tokens: Balance,
+        borrow_amount: Balance,
+    ) -> PoolResult<(U256, U256)> {
+        let asset_params = self
+            .markets
+            .iter()
+            .map(|market| market.liquidity_param(account, step))
+            .collect::<PoolResult<Vec<_>>>()?;
+        let (sum_collateral, sum_borrow_plus_effect) =
+            get_hypothetical_account_liquidity(GetHypotheticalAccountLiquidityInput {
+                asset_params,
+                token_modify: token_modify.map(|market| self.markets[market].asset),
+                redeem_tokens,
+                borrow_amount,
+            })?;
+        Ok((
+            sum_collateral.saturating_sub(sum_borrow_plus_effect),
+            sum_borrow_plus_effect.saturating_sub(sum_collateral),
+        ))
+    }
+
+    fn assert_liquidity(
+        &self,
+        account: Account,
+        step: u64,
+        market: usize,
+        redeem_tokens: Balance,
+        borrow_amount: Balance,
+    ) -> PoolResult<()> {
+        let (_, shortfall) =
+            self.account_liquidity(account, step, Some(market), redeem_tokens, borrow_amount)?;
+        if !shortfall.is_zero() {
+            return Err(ControllerError::InsufficientLiquidity.into())
+        }
+        Ok(())
+    }
+
+    /// Liquidates the largest debt of every account with a shortfall
+    /// against its largest collateral.
+    /// The repay amount is limited by the close factor and by the collateral to seize.
+    fn liquidate(&mut self, step: u64, report: &mut Report) -> Result<()> {
+        for borrower in 0..self.accounts.len() {
+            if borrower == LIQUIDATOR_ACCOUNT {
+                continue
+            }
+            let (_, shortfall) = self.account_liquidity(borrower, step, None, 0, 0)?;
+            if shortfall.is_zero() {
+                continue
+            }
+            let (Some(borrowed), Some(collateral)) = (
+                self.largest(|market| self.debt_value(market, borrower, step))?,
+                self.largest(|market| self.collateral_value(market, borrower, step))?,
+            ) else {
+                continue
+            };
+
+            let borrow_balance = self.markets[borrowed].borrow_balance_stored(borrower)?;
+            let mut repay_amount = to_balance(
+                Exp(self.close_factor_mantissa).mul_int_down(U256::from(borrow_balance))?,
+            )?;
+            let mut seize_tokens = self.seize_tokens(borrowed, collateral, repay_amount, step)?;
+            let collateral_balance = self.markets[collateral].balance_of(borrower);
+            if seize_tokens > collateral_balance {
+                repay_amount = to_balance(mul_div_down(
+                    U256::from(repay_amount),
+                    U256::from(collateral_balance),
+                    U256::from(seize_tokens),
+                )?)?;
+                seize_tokens = self.seize_tokens(borrowed, collateral, repay_amount, step)?;
+            }
+            if repay_amount == 0 {
+                continue
+            }
+
+            let repay_amount = self.markets[borrowed].repay_borrow(borrower, repay_amount)?;
+            let (liquidator_seize_tokens, protocol_seize_amount) =
+                self.markets[collateral].seize(LIQUIDATOR_ACCOUNT, borrower, seize_tokens)?;
+            let (borrowed, collateral) = (&self.markets[borrowed], &self.markets[collateral]);
+            report.liquidations.push(Liquidation {
+                step,
+                borrower: self.accounts[borrower].clone(),
+                borrowed_market: borrowed.symbol.clone(),
+                collateral_market: collateral.symbol.clone(),
+                repay_amount: format_units(U256::from(repay_amount), borrowed.decimals),
+                seize_tokens: format_units(U256::from(seize_tokens), collateral.decimals),
+                liquidator_seize_tokens: format_units(
+                    U256::from(liquidator_seize_tokens),
+                    collateral.decimals,
+                ),
+                protocol_seize_amount: format_units(
+                    U256::from(protocol_seize_amount),
+                    collateral.decimals,
+                ),
+                shortfall: format_mantissa(shortfall),
+            });
+        }
+        Ok(())
+    }
+
+    fn seize_tokens(
+        &self,
+        borrowed: usize,
+        collateral: usize,
+        repay_amount: Balance,
+        step: u64,
+    ) -> Result<Balance> {
+        let (borrowed, collateral) = (&self.markets[borrowed], &self.markets[collateral]);
+        Ok(liquidate_calculate_seize_tokens(
+            &LiquidateCalculateSeizeTokensInput {
+                price_borrowed_mantissa: borrowed.price(step),
+                decimals_borrowed: borrowed.decimals,
+                price_collateral_mantissa: collateral.price(step),
+                decimals_collateral: collateral.decimals,
+                exchange_rate_mantissa: collateral.exchange_rate_stored()?,
+                liquidation_incentive_mantissa: self.liquidation_incentive_mantissa,
+                actual_repay_amount: repay_amount,
+            },
+        )?)
+    }
+
+    /// Debt of every account exceeding its collateral at the market value, in base currency
+    fn bad_debt(&self, step: u64) -> Result<Vec<(Account, U256)>> {
+        let mut bad_debt = Vec::new();
+        for account in 0..self.accounts.len() {
+            let (mut debt, mut collateral) = (U256::zero(), U256::zero());
+            for market in 0..self.markets.len() {
+                debt += self.debt_value(market, account, step)?;
+                collateral += self.collateral_value(market, account, step)?;
+            }
+            if debt > collateral {
+                bad_debt.push((account, debt - collateral));
+            }
+        }
+        Ok(bad_debt)
+    }
+
+    /// Index of the market with the largest non-zero `value`
+    fn largest(&self, value: impl Fn(usize) -> Result<U256>) -> Result<Option<usize>> {
+        let mut largest: Option<(usize, U256)> = None;
+        for market in 0..self.markets.len() {
+            let value = value(market)?;
+            let larger = match largest {
+                Some((_, max)) => value > max,
+                None => !value.is_zero(),
+            };
+            if larger {
+                largest = Some((market, value));
+            }
+        }
+        Ok(largest.map(|(market, _)| market))
+    }
+
+    fn debt_value(&self, market: usize, account: Account, step: u64) -> Result<U256> {
+        let market = &self.markets[market];
+        value_in_base_currency(market, market.borrow_balance_stored(account)?, step)
+    }
+
+    fn collateral_value(&self, market: usize, account: Account, step: u64) -> Result<U256> {
+        let market = &self.markets[market];
+        if !market.using_reserve_as_collateral(account) {
+            return Ok(U256::zero())
+        }
+        value_in_base_currency(market, market.balance_of_underlying(account)?, step)
+    }
+}
+
+fn value_in_base_currency(market: &Market, amount: Balance, step: u64) -> Result<U256> {
+    Ok(mul_div_down(
+        U256::from(amount),
+        market.price(step),
+        U256::from(10).pow(market.decimals.into()),
+    )?)
+}
+
+/// Runs `scenario` and returns its report
+pub fn simulate(scenario: &Scenario) -> Result<Report> {
+    Simulator::new(scenario)?.run()
+}
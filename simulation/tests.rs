@@ -0,0 +1,215 @@
+use crate::{
+    scenario::{
+        format_units,
+        parse_mantissa,
+        parse_units,
+    },
+    simulate,
+    Error,
+    Report,
+    Scenario,
+};
+use logics::traits::{
+    controller::Error as ControllerError,
+    pool::Error as PoolError,
+};
+use primitive_types::U256;
+
+/// DAI and WETH markets with the parameters of `scripts/risk_parameters.ts`
+/// and `scripts/interest_rates.ts`
+fn scenario(weth_prices: &str, actions: &str) -> Scenario {
+    Scenario::from_json(&format!(
+        r#"{{
+            "step_interval": 3600000,
+            "steps": 100,
+            "close_factor": "0.5",
+            "liquidation_incentive": "1.08",
+            "markets": {{
+                "dai": {{
+                    "decimals": 18,
+                    "collateral_factor": "0.8",
+                    "reserve_factor": "0.1",
+                    "rate_model": {{
+                        "base_rate": "0",
+                        "slope_1": "0.04",
+                        "slope_2": "0.6",
+                        "optimal_utilization_rate": "0.9"
+                    }},
+                    "prices": [{{ "step": 0, "price": "1" }}]
+                }},
+                "weth": {{
+                    "decimals": 18,
+                    "collateral_factor": "0.8",
+                    "reserve_factor": "0.1",
+                    "rate_model": {{
+                        "base_rate": "0",
+                        "slope_1": "0.08",
+                        "slope_2": "1",
+                        "optimal_utilization_rate": "0.65"
+                    }},
+                    "prices": {}
+                }}
+            }},
+            "actions": {}
+        }}"#,
+        weth_prices, actions
+    ))
+    .unwrap()
+}
+
+fn borrow_dai(amount: &str) -> String {
+    format!(
+        r#"[
+            {{"step": 0, "account": "lp", "type": "deposit", "market": "dai", "amount": "1000000"}},
+            {{"step": 1, "account": "alice", "type": "deposit", "market": "weth", "amount": "10"}},
+            {{"step": 1, "account": "alice", "type": "borrow", "market": "dai", "amount": "{}"}}
+        ]"#,
+        amount
+    )
+}
+
+fn dai(report: &Report) -> &crate::report::MarketReport {
+    report
+        .markets
+        .iter()
+        .find(|market| market.symbol == "dai")
+        .unwrap()
+}
+
+#[test]
+fn test_parse_units() {
+    assert_eq!(parse_units("1.5", 6).unwrap(), U256::from(1_500_000));
+    assert_eq!(parse_units("42", 0).unwrap(), U256::from(42));
+    assert_eq!(
+        parse_mantissa("0.08").unwrap(),
+        U256::from(8 * 10_u128.pow(16))
+    );
+    for invalid in ["", ".5", "-1", "1e18", "1.2345678"] {
+        assert_eq!(
+            parse_units(invalid, 6),
+            Err(Error::InvalidDecimal(invalid.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_format_units() {
+    assert_eq!(format_units(U256::from(1_500_000), 6), "1.5");
+    assert_eq!(format_units(U256::from(1), 6), "0.000001");
+    assert_eq!(format_units(U256::zero(), 18), "0");
+    assert_eq!(format_units(U256::from(10_u128.pow(18)), 18), "1");
+}
+
+#[test]
+fn test_interest_accrues() {
+    let report = simulate(&scenario(
+        r#"[{ "step": 0, "price": "1800" }]"#,
+        &borrow_dai("10000"),
+    ))
+    .unwrap();
+
+    let dai = dai(&report);
+    assert!(parse_mantissa(&dai.total_borrows).unwrap() > parse_mantissa("10000").unwrap());
+    assert!(!parse_mantissa(&dai.total_reserves).unwrap().is_zero());
+    assert!(parse_mantissa(&dai.exchange_rate).unwrap() > parse_mantissa("1").unwrap());
+    assert!(!parse_mantissa(&dai.utilization.max).unwrap().is_zero());
+    assert!(report.liquidations.is_empty());
+    assert!(report.rejected_actions.is_empty());
+    assert_eq!(report.bad_debt.last, "0");
+}
+
+#[test]
+fn test_borrow_exceeding_collateral_is_rejected() {
+    // collateral: 10 WETH * 1800 * 0.8 = 14400 DAI
+    let report = simulate(&scenario(
+        r#"[{ "step": 0, "price": "1800" }]"#,
+        &borrow_dai("14401"),
+    ))
+    .unwrap();
+
+    assert_eq!(report.rejected_actions.len(), 1);
+    assert_eq!(
+        report.rejected_actions[0].reason,
+        format!(
+            "{:?}",
+            PoolError::Controller(ControllerError::InsufficientLiquidity)
+        )
+    );
+    assert_eq!(dai(&report).total_borrows, "0");
+}
+
+#[test]
+fn test_shortfall_is_liquidated() {
+    let report = simulate(&scenario(
+        r#"[{ "step": 0, "price": "1800" }, { "step": 10, "price": "1700" }]"#,
+        &borrow_dai("14000"),
+    ))
+    .unwrap();
+
+    assert_eq!(report.liquidations.len(), 1);
+    let liquidation = &report.liquidations[0];
+    assert_eq!(liquidation.step, 10);
+    assert_eq!(liquidation.borrower, "alice");
+    assert_eq!(liquidation.collateral_market, "weth");
+    // half of the debt is repaid with the close factor of 0.5
+    let repay_amount = parse_mantissa(&liquidation.repay_amount).unwrap();
+    assert!(repay_amount > parse_mantissa("7000").unwrap());
+    assert!(repay_amount < parse_mantissa("7001").unwrap());
+    assert_eq!(report.bad_debt.max, "0");
+}
+
+#[test]
+fn test_crash_leaves_bad_debt() {
+    let report = simulate(&scenario(
+        r#"[{ "step": 0, "price": "1800" }, { "step": 10, "price": "1000" }]"#,
+        &borrow_dai("14000"),
+    ))
+    .unwrap();
+
+    // the collateral is exhausted by the second liquidation, the rest of the debt is never repaid
+    let steps: Vec<_> = report
+        .liquidations
+        .iter()
+        .map(|liquidation| liquidation.step)
+        .collect();
+    assert_eq!(steps, vec![10, 11]);
+    let seized = report
+        .liquidations
+        .iter()
+        .map(|liquidation| parse_mantissa(&liquidation.seize_tokens).unwrap())
+        .fold(U256::zero(), |total, seized| total + seized);
+    assert_eq!(seized, parse_mantissa("10").unwrap());
+    assert_eq!(report.bad_debt.max_step, 99);
+    assert_eq!(report.bad_debt.accounts.len(), 1);
+    assert_eq!(report.bad_debt.accounts[0].account, "alice");
+    // 14000 - 10 * 1000 / 1.08
+    let bad_debt = parse_mantissa(&report.bad_debt.last).unwrap();
+    assert!(bad_debt > parse_mantissa("4740").unwrap());
+    assert!(bad_debt < parse_mantissa("4750").unwrap());
+}
+
+#[test]
+fn test_invalid_scenario() {
+    let unknown_market = scenario(
+        r#"[{ "step": 0, "price": "1800" }]"#,
+        r#"[{"step": 0, "account": "alice", "type": "deposit", "market": "wbtc", "amount": "1"}]"#,
+    );
+    assert_eq!(
+        simulate(&unknown_market).unwrap_err(),
+        Error::UnknownMarket("wbtc".to_string())
+    );
+
+    let no_initial_price = scenario(r#"[{ "step": 1, "price": "1800" }]"#, "[]");
+    assert_eq!(
+        simulate(&no_initial_price).unwrap_err(),
+        Error::MissingInitialPrice("weth".to_string())
+    );
+}
+
+#[test]
+fn test_example_scenario() {
+    let scenario = Scenario::from_json(include_str!("scenarios/weth_crash.json")).unwrap();
+    let report = simulate(&scenario).unwrap();
+    assert_eq!(report.steps, 720);
+    assert!(!report.liquidations.is_empty());
+}
@@ -0,0 +1,224 @@
+import type { KeyringPair } from '@polkadot/keyring/types'
+import { WeightV2 } from '@polkadot/types/interfaces'
+import { BN, BN_ONE, BN_TEN } from '@polkadot/util'
+import { ONE_ETHER } from '../scripts/helper/constants'
+import {
+  deployCollateralSwap,
+  deployController,
+  deployDefaultInterestRateModel,
+  deployDexRouter,
+  deployFlashLoanGateway,
+  deployIncentivesController,
+  deployPriceOracle,
+} from '../scripts/helper/deploy_helper'
+import { getGasLimit } from '../scripts/helper/utils'
+import CollateralSwap from '../types/contracts/collateral_swap'
+import DexRouter from '../types/contracts/dex_router'
+import FlashloanGateway from '../types/contracts/flashloan_gateway'
+import {
+  PoolContracts,
+  preparePoolsWithPreparedTokens,
+} from './testContractHelper'
+import { shouldNotRevert } from './testHelpers'
+
+const MAX_CALL_WEIGHT = new BN(128_000_000_000).isub(BN_ONE).mul(BN_TEN)
+const PROOFSIZE = new BN(2_000_000)
+
+describe('CollateralSwap spec', () => {
+  const setup = async () => {
+    const { api, alice: deployer, bob } = globalThis.setup
+    const gasLimit = getGasLimit(api, MAX_CALL_WEIGHT, PROOFSIZE)
+
+    const controller = await deployController({
+      api,
+      signer: deployer,
+      args: [deployer.address],
+    })
+    const priceOracle = await deployPriceOracle({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const rateModel = await deployDefaultInterestRateModel({
+      api,
+      signer: deployer,
+      args: [[0], [0], [0], [0]],
+    })
+    const incentivesController = await deployIncentivesController({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const pools = await preparePoolsWithPreparedTokens({
+      api,
+      controller,
+      rateModel,
+      signer: deployer,
+      manager: deployer.address,
+      incentivesController,
+    })
+    const flashloanGateway = await deployFlashLoanGateway({
+      api,
+      signer: deployer,
+      args: [controller.address],
+    })
+    const router = await deployDexRouter({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const collateralSwap = await deployCollateralSwap({
+      api,
+      signer: deployer,
+      args: [flashloanGateway.address, controller.address, router.address],
+    })
+
+    // initialize
+    await controller.tx.setPriceOracle(priceOracle.address)
+    await controller.tx.setCloseFactorMantissa([ONE_ETHER])
+    await controller.tx.setFlashloanGateway(flashloanGateway.address)
+    for (const sym of [pools.dai, pools.usdc]) {
+      await priceOracle.tx.setFixedPrice(sym.token.address, ONE_ETHER)
+      await controller.tx.supportMarketWithCollateralFactorMantissa(
+        sym.pool.address,
+        sym.token.address,
+        [ONE_ETHER.mul(new BN(90)).div(new BN(100))],
+      )
+    }
+
+    return {
+      deployer,
+      bob,
+      pools,
+      flashloanGateway,
+      router,
+      collateralSwap,
+      gasLimit,
+    }
+  }
+
+  let deployer: KeyringPair
+  let bob: KeyringPair
+  let dai: PoolContracts
+  let usdc: PoolContracts
+  let flashloanGateway: FlashloanGateway
+  let router: DexRouter
+  let collateralSwap: CollateralSwap
+  let gasLimit: WeightV2
+
+  const liquidity = 1_000_000
+  const supplied = 100_000
+
+  beforeAll(async () => {
+    let pools
+    ;({
+      deployer,
+      bob,
+      pools,
+      flashloanGateway,
+      router,
+      collateralSwap,
+      gasLimit,
+    } = await setup())
+    ;({ dai, usdc } = pools)
+
+    for (const { token, pool } of [dai, usdc]) {
+      await shouldNotRevert(token, 'mint', [deployer.address, liquidity])
+      await shouldNotRevert(token, 'approve', [pool.address, liquidity])
+      await shouldNotRevert(pool, 'mint', [liquidity])
+    }
+    await shouldNotRevert(usdc.token, 'mint', [router.address, liquidity])
+    await shouldNotRevert(router, 'setRate', [
+      dai.token.address,
+      usdc.token.address,
+      [ONE_ETHER],
+    ])
+
+    await shouldNotRevert(dai.token, 'mint', [bob.address, supplied])
+    await shouldNotRevert(dai.token.withSigner(bob), 'approve', [
+      dai.pool.address,
+      supplied,
+    ])
+    await shouldNotRevert(dai.pool.withSigner(bob), 'mint', [supplied])
+  })
+
+  it('instantiate', async () => {
+    expect((await collateralSwap.query.flashloanGateway()).value.ok).toEqual(
+      flashloanGateway.address,
+    )
+    expect((await collateralSwap.query.router()).value.ok).toEqual(
+      router.address,
+    )
+  })
+
+  it('fails when the swap output is below the minimum', async () => {
+    const { value } = await collateralSwap
+      .withSigner(bob)
+      .query.swapCollateral(
+        dai.token.address,
+        usdc.token.address,
+        50_000,
+        50_000,
+        { gasLimit },
+      )
+    expect(value.ok.err).toStrictEqual({ insufficientAmountToReceive: null })
+  })
+
+  it('fails without allowance for the pool tokens', async () => {
+    const { value } = await collateralSwap
+      .withSigner(bob)
+      .query.swapCollateral(
+        dai.token.address,
+        usdc.token.address,
+        50_000,
+        49_000,
+        { gasLimit },
+      )
+    expect(value.ok.err).toStrictEqual({
+      flashloanAdapter: { flashloanGateway: { invalidFlashloanExecutorReturn: null } },
+    })
+  })
+
+  it('swaps collateral', async () => {
+    const amount = 50_000
+    const premiumTotal = (
+      await flashloanGateway.query.flashloanPremiumTotal()
+    ).value.ok.toNumber()
+    const flashloanAmount = Math.floor(
+      (amount * 10000) / (10000 + premiumTotal),
+    )
+    const premium = Math.floor((flashloanAmount * premiumTotal) / 10000)
+
+    await shouldNotRevert(dai.pool.withSigner(bob), 'approve', [
+      collateralSwap.address,
+      amount,
+    ])
+    await shouldNotRevert(collateralSwap.withSigner(bob), 'swapCollateral', [
+      dai.token.address,
+      usdc.token.address,
+      amount,
+      49_000,
+      { gasLimit },
+    ])
+
+    expect(
+      (await dai.pool.query.balanceOf(bob.address)).value.ok.toNumber(),
+    ).toBe(supplied - amount)
+    expect(
+      (await usdc.pool.query.balanceOf(bob.address)).value.ok.toNumber(),
+    ).toBe(flashloanAmount)
+    expect(
+      (await usdc.token.query.balanceOf(bob.address)).value.ok.toNumber(),
+    ).toBe(amount - flashloanAmount - premium)
+    expect(
+      (
+        await dai.token.query.balanceOf(collateralSwap.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+    expect(
+      (
+        await usdc.token.query.balanceOf(collateralSwap.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+  })
+})
@@ -255,6 +255,53 @@ describe('Controller spec', () => {
       )
       expect(value.ok.err).toBe('BorrowCapReached')
     })
+    it('check max utilization', async () => {
+      const {
+        deployer,
+        controller,
+        gasLimit,
+        pools: { dai },
+      } = await setupWithPools()
+      await controller.tx.setMaxUtilizationMantissa(dai.pool.address, [
+        ONE_ETHER.mul(new BN(80)).div(new BN(100)),
+      ])
+      expect(
+        (
+          await controller.query.maxUtilizationMantissa(dai.pool.address)
+        ).value.ok.toString(),
+      ).toBe(ONE_ETHER.mul(new BN(80)).div(new BN(100)).toString())
+
+      // no cash
+      const { value: value1 } = await controller.query.borrowAllowed(
+        dai.pool.address,
+        ZERO_ADDRESS,
+        1,
+        null,
+      )
+      expect(value1.ok.err).toBe('MaxUtilizationExceeded')
+
+      await dai.token.tx.mint(deployer.address, 100)
+      await dai.token.tx.approve(dai.pool.address, 100)
+      await shouldNotRevert(dai.pool, 'mint', [100, { gasLimit }])
+
+      // utilization: 81%
+      const { value: value2 } = await controller.query.borrowAllowed(
+        dai.pool.address,
+        ZERO_ADDRESS,
+        81,
+        null,
+      )
+      expect(value2.ok.err).toBe('MaxUtilizationExceeded')
+
+      // utilization: 80%, rejected by the account liquidity check instead
+      const { value: value3 } = await controller.query.borrowAllowed(
+        dai.pool.address,
+        ZERO_ADDRESS,
+        80,
+        null,
+      )
+      expect(value3.ok.err).toBe('InsufficientLiquidity')
+    })
     it('check account liquidity', async () => {
       const {
         controller,
@@ -0,0 +1,225 @@
+import type { KeyringPair } from '@polkadot/keyring/types'
+import { WeightV2 } from '@polkadot/types/interfaces'
+import { BN, BN_ONE, BN_TEN } from '@polkadot/util'
+import { ONE_ETHER } from '../scripts/helper/constants'
+import {
+  deployController,
+  deployDebtSwap,
+  deployDefaultInterestRateModel,
+  deployDexRouter,
+  deployFlashLoanGateway,
+  deployIncentivesController,
+  deployPriceOracle,
+} from '../scripts/helper/deploy_helper'
+import { getGasLimit } from '../scripts/helper/utils'
+import DebtSwap from '../types/contracts/debt_swap'
+import DexRouter from '../types/contracts/dex_router'
+import FlashloanGateway from '../types/contracts/flashloan_gateway'
+import {
+  PoolContracts,
+  preparePoolsWithPreparedTokens,
+} from './testContractHelper'
+import { shouldNotRevert } from './testHelpers'
+
+const MAX_CALL_WEIGHT = new BN(128_000_000_000).isub(BN_ONE).mul(BN_TEN)
+const PROOFSIZE = new BN(2_000_000)
+
+describe('DebtSwap spec', () => {
+  const setup = async () => {
+    const { api, alice: deployer, bob } = globalThis.setup
+    const gasLimit = getGasLimit(api, MAX_CALL_WEIGHT, PROOFSIZE)
+
+    const controller = await deployController({
+      api,
+      signer: deployer,
+      args: [deployer.address],
+    })
+    const priceOracle = await deployPriceOracle({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const rateModel = await deployDefaultInterestRateModel({
+      api,
+      signer: deployer,
+      args: [[0], [0], [0], [0]],
+    })
+    const incentivesController = await deployIncentivesController({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const pools = await preparePoolsWithPreparedTokens({
+      api,
+      controller,
+      rateModel,
+      signer: deployer,
+      manager: deployer.address,
+      incentivesController,
+    })
+    const flashloanGateway = await deployFlashLoanGateway({
+      api,
+      signer: deployer,
+      args: [controller.address],
+    })
+    const router = await deployDexRouter({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const debtSwap = await deployDebtSwap({
+      api,
+      signer: deployer,
+      args: [flashloanGateway.address, controller.address, router.address],
+    })
+
+    // initialize
+    await controller.tx.setPriceOracle(priceOracle.address)
+    await controller.tx.setCloseFactorMantissa([ONE_ETHER])
+    await controller.tx.setFlashloanGateway(flashloanGateway.address)
+    for (const sym of [pools.dai, pools.usdc, pools.usdt]) {
+      await priceOracle.tx.setFixedPrice(sym.token.address, ONE_ETHER)
+      await controller.tx.supportMarketWithCollateralFactorMantissa(
+        sym.pool.address,
+        sym.token.address,
+        [ONE_ETHER.mul(new BN(90)).div(new BN(100))],
+      )
+    }
+
+    return {
+      deployer,
+      bob,
+      pools,
+      flashloanGateway,
+      router,
+      debtSwap,
+      gasLimit,
+    }
+  }
+
+  let deployer: KeyringPair
+  let bob: KeyringPair
+  let dai: PoolContracts
+  let usdc: PoolContracts
+  let usdt: PoolContracts
+  let flashloanGateway: FlashloanGateway
+  let router: DexRouter
+  let debtSwap: DebtSwap
+  let gasLimit: WeightV2
+
+  const liquidity = 1_000_000
+  const supplied = 100_000
+  const borrowed = 30_000
+
+  beforeAll(async () => {
+    let pools
+    ;({ deployer, bob, pools, flashloanGateway, router, debtSwap, gasLimit } =
+      await setup())
+    ;({ dai, usdc, usdt } = pools)
+
+    for (const { token, pool } of [dai, usdc, usdt]) {
+      await shouldNotRevert(token, 'mint', [deployer.address, liquidity])
+      await shouldNotRevert(token, 'approve', [pool.address, liquidity])
+      await shouldNotRevert(pool, 'mint', [liquidity])
+    }
+    await shouldNotRevert(usdc.token, 'mint', [router.address, liquidity])
+    await shouldNotRevert(router, 'setRate', [
+      usdt.token.address,
+      usdc.token.address,
+      [ONE_ETHER],
+    ])
+
+    await shouldNotRevert(dai.token, 'mint', [bob.address, supplied])
+    await shouldNotRevert(dai.token.withSigner(bob), 'approve', [
+      dai.pool.address,
+      supplied,
+    ])
+    await shouldNotRevert(dai.pool.withSigner(bob), 'mint', [supplied])
+    await shouldNotRevert(usdc.pool.withSigner(bob), 'borrow', [
+      borrowed,
+      { gasLimit },
+    ])
+  })
+
+  it('instantiate', async () => {
+    expect((await debtSwap.query.flashloanGateway()).value.ok).toEqual(
+      flashloanGateway.address,
+    )
+    expect((await debtSwap.query.router()).value.ok).toEqual(router.address)
+  })
+
+  it('fails without borrow to repay', async () => {
+    const { value } = await debtSwap.query.swapDebt(
+      usdc.token.address,
+      usdt.token.address,
+      10_000,
+      10_000,
+      { gasLimit },
+    )
+    expect(value.ok.err).toStrictEqual({ noBorrowToRepay: null })
+  })
+
+  it('fails when more new debt than the maximum is needed', async () => {
+    const { value } = await debtSwap
+      .withSigner(bob)
+      .query.swapDebt(
+        usdc.token.address,
+        usdt.token.address,
+        20_000,
+        19_999,
+        { gasLimit },
+      )
+    expect(value.ok.err).toStrictEqual({ excessiveNewDebtAmount: null })
+  })
+
+  it('fails without delegation of the new debt', async () => {
+    const { value } = await debtSwap
+      .withSigner(bob)
+      .query.swapDebt(
+        usdc.token.address,
+        usdt.token.address,
+        20_000,
+        20_000,
+        { gasLimit },
+      )
+    expect(value.ok.err).toStrictEqual({
+      flashloanAdapter: { flashloanGateway: { insufficientDelegateAllowance: null } },
+    })
+  })
+
+  it('swaps debt', async () => {
+    const repayAmount = 20_000
+    await shouldNotRevert(usdt.pool.withSigner(bob), 'approveDelegate', [
+      debtSwap.address,
+      repayAmount,
+    ])
+    await shouldNotRevert(debtSwap.withSigner(bob), 'swapDebt', [
+      usdc.token.address,
+      usdt.token.address,
+      repayAmount,
+      repayAmount,
+      { gasLimit },
+    ])
+
+    expect(
+      (
+        await usdc.pool.query.borrowBalanceStored(bob.address)
+      ).value.ok.toNumber(),
+    ).toBe(borrowed - repayAmount)
+    expect(
+      (
+        await usdt.pool.query.borrowBalanceStored(bob.address)
+      ).value.ok.toNumber(),
+    ).toBe(repayAmount)
+    expect(
+      (
+        await usdt.pool.query.delegateAllowance(bob.address, debtSwap.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+    expect(
+      (
+        await usdt.token.query.balanceOf(debtSwap.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+  })
+})
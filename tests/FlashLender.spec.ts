@@ -0,0 +1,196 @@
+import type { KeyringPair } from '@polkadot/keyring/types'
+import { BN } from '@polkadot/util'
+import { ONE_ETHER } from '../scripts/helper/constants'
+import {
+  deployController,
+  deployDefaultInterestRateModel,
+  deployFlashBorrower,
+  deployFlashLoanGateway,
+  deployIncentivesController,
+  deployPriceOracle,
+} from '../scripts/helper/deploy_helper'
+import FlashBorrower from '../types/contracts/flash_borrower'
+import FlashloanGateway from '../types/contracts/flashloan_gateway'
+import { FlashLoan } from '../types/event-types/flashloan_gateway'
+import {
+  PoolContracts,
+  preparePoolsWithPreparedTokens,
+} from './testContractHelper'
+import { expectToEmit, shouldNotRevert } from './testHelpers'
+
+describe('FlashLender spec', () => {
+  let deployer: KeyringPair
+  let users: KeyringPair[]
+  let flashloanGateway: FlashloanGateway
+  let flashBorrower: FlashBorrower
+  let dai: PoolContracts
+  let unlisted: PoolContracts
+
+  const setup = async () => {
+    const { api, alice: deployer, bob, charlie, django } = globalThis.setup
+
+    const controller = await deployController({
+      api,
+      signer: deployer,
+      args: [deployer.address],
+    })
+    const priceOracle = await deployPriceOracle({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const rateModel = await deployDefaultInterestRateModel({
+      api,
+      signer: deployer,
+      args: [[0], [0], [0], [0]],
+    })
+    const incentivesController = await deployIncentivesController({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const pools = await preparePoolsWithPreparedTokens({
+      api,
+      controller,
+      rateModel,
+      signer: deployer,
+      manager: deployer.address,
+      incentivesController,
+    })
+    const flashloanGateway = await deployFlashLoanGateway({
+      api,
+      signer: deployer,
+      args: [controller.address],
+    })
+    const flashBorrower = await deployFlashBorrower({
+      api,
+      signer: deployer,
+      args: [flashloanGateway.address],
+    })
+
+    await controller.tx.setPriceOracle(priceOracle.address)
+    await controller.tx.setFlashloanGateway(flashloanGateway.address)
+    for (const sym of [pools.dai, pools.usdc]) {
+      await priceOracle.tx.setFixedPrice(sym.token.address, ONE_ETHER)
+      await controller.tx.supportMarketWithCollateralFactorMantissa(
+        sym.pool.address,
+        sym.token.address,
+        [ONE_ETHER.mul(new BN(90)).div(new BN(100))],
+      )
+    }
+
+    return {
+      deployer,
+      users: [bob, charlie, django],
+      flashloanGateway,
+      flashBorrower,
+      dai: pools.dai,
+      // not supported in Controller
+      unlisted: pools.usdt,
+    }
+  }
+
+  const deposited = 5_000_000
+  const flashLoanAmount = 200_000
+
+  beforeAll(async () => {
+    ;({ deployer, users, flashloanGateway, flashBorrower, dai, unlisted } =
+      await setup())
+
+    await shouldNotRevert(dai.token, 'mint', [deployer.address, deposited])
+    await shouldNotRevert(dai.token, 'approve', [dai.pool.address, deposited])
+    await shouldNotRevert(dai.pool, 'mint', [deposited])
+  })
+
+  it('returns the lendable amount', async () => {
+    expect(
+      (
+        await flashloanGateway.query.maxFlashLoan(dai.token.address)
+      ).value.ok.toNumber(),
+    ).toBe(deposited)
+    expect(
+      (
+        await flashloanGateway.query.maxFlashLoan(unlisted.token.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+  })
+
+  it('returns the fee', async () => {
+    expect(
+      (
+        await flashloanGateway.query.flashFee(
+          dai.token.address,
+          flashLoanAmount,
+        )
+      ).value.ok.ok.toNumber(),
+    ).toBe((flashLoanAmount * 9) / 10000)
+
+    const { value } = await flashloanGateway.query.flashFee(
+      unlisted.token.address,
+      flashLoanAmount,
+    )
+    expect(value.ok.err).toStrictEqual({ marketNotListed: null })
+  })
+
+  it('lends to the borrower and pulls back the amount with the fee', async () => {
+    const fee = Math.floor((flashLoanAmount * 9) / 10000)
+    await shouldNotRevert(dai.token, 'mint', [users[0].address, fee])
+    await shouldNotRevert(dai.token.withSigner(users[0]), 'approve', [
+      flashBorrower.address,
+      fee,
+    ])
+
+    const { events } = await shouldNotRevert(
+      flashloanGateway.withSigner(users[0]),
+      'flashLoan',
+      [flashBorrower.address, dai.token.address, flashLoanAmount, []],
+    )
+    expectToEmit<FlashLoan>(events[0], 'FlashLoan', {
+      target: flashBorrower.address,
+      initiator: users[0].address,
+      asset: dai.token.address,
+      amount: flashLoanAmount,
+      premium: fee,
+      premiumToProtocol: 0,
+    })
+    expect(
+      (await dai.token.query.balanceOf(users[0].address)).value.ok.toNumber(),
+    ).toBe(0)
+    expect(
+      (await dai.token.query.balanceOf(dai.pool.address)).value.ok.toNumber(),
+    ).toBe(deposited + fee)
+  })
+
+  it('fails if the callback does not return the success hash', async () => {
+    await shouldNotRevert(flashBorrower, 'setFailExecution', [true])
+    const { value } = await flashloanGateway
+      .withSigner(users[0])
+      .query.flashLoan(
+        flashBorrower.address,
+        dai.token.address,
+        flashLoanAmount,
+        [],
+      )
+    expect(value.ok.err).toStrictEqual({
+      invalidFlashloanExecutorReturn: null,
+    })
+    await shouldNotRevert(flashBorrower, 'setFailExecution', [false])
+  })
+
+  it('fails for unsupported tokens and too small amounts', async () => {
+    const { value: unlistedResult } = await flashloanGateway
+      .withSigner(users[0])
+      .query.flashLoan(
+        flashBorrower.address,
+        unlisted.token.address,
+        flashLoanAmount,
+        [],
+      )
+    expect(unlistedResult.ok.err).toStrictEqual({ marketNotListed: null })
+
+    const { value: tooSmall } = await flashloanGateway
+      .withSigner(users[0])
+      .query.flashLoan(flashBorrower.address, dai.token.address, 1, [])
+    expect(tooSmall.ok.err).toStrictEqual({ invalidFlashloanAmount: null })
+  })
+})
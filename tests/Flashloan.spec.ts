@@ -13,7 +13,11 @@ import Controller from '../types/contracts/controller'
 import Contract from '../types/contracts/default_interest_rate_model'
 import FlashloanGateway from '../types/contracts/flashloan_gateway'
 import FlashloanReceiver from '../types/contracts/flashloan_receiver'
-import { FlashLoan } from '../types/event-types/flashloan_gateway'
+import {
+  FlashLoan,
+  NewBorrowerPremium,
+  NewFlashloanPremiumTotal,
+} from '../types/event-types/flashloan_gateway'
 import {
   PoolContracts,
   Pools,
@@ -194,6 +198,7 @@ describe('Controller spec', () => {
       asset: dai.token.address,
       amount: flashLoanAmount,
       premium: premiumAmount,
+      premiumToProtocol: 0,
     })
 
     const user0Balance = (
@@ -304,6 +309,7 @@ describe('Controller spec', () => {
       asset: dai.token.address,
       amount: flashLoanAmount,
       premium: premiumAmount,
+      premiumToProtocol: 0,
     })
 
     const borrowBalance = (
@@ -313,6 +319,80 @@ describe('Controller spec', () => {
     expect(borrowBalance).toEqual(flashLoanAmount)
   })
 
+  it('User 0 takes DAI flashloan with mode = 1 on behalf of User 1 without delegation (revert expected)', async () => {
+    const flashLoanAmount = 100_000
+    const result = (
+      await flashloanGateway
+        .withSigner(users[0])
+        .query.flashloan(
+          flashloanReceiver.address,
+          [dai.token.address],
+          [flashLoanAmount],
+          [1],
+          users[1].address,
+          [],
+        )
+    ).value.ok
+    expect(result.err).toStrictEqual({ insufficientDelegateAllowance: null })
+  })
+
+  it('User 1 delegates DAI borrowing to User 0, User 0 takes DAI flashloan with mode = 1 on behalf of User 1. A variable loan for User 1 is created', async () => {
+    const deposited = 10_000_000
+    await shouldNotRevert(usdc.token, 'mint', [users[1].address, deposited])
+    await shouldNotRevert(usdc.token.withSigner(users[1]), 'approve', [
+      usdc.pool.address,
+      deposited,
+    ])
+    await shouldNotRevert(usdc.pool.withSigner(users[1]), 'mint', [deposited])
+
+    const premiumTotal = (
+      await flashloanGateway.query.flashloanPremiumTotal()
+    ).value.ok.toNumber()
+    const flashLoanAmount = 100_000
+    const delegated = 150_000
+    const premiumAmount = (flashLoanAmount * premiumTotal) / 10000
+    await shouldNotRevert(dai.pool.withSigner(users[1]), 'approveDelegate', [
+      users[0].address,
+      delegated,
+    ])
+    await shouldNotRevert(dai.token, 'mint', [users[0].address, premiumAmount])
+    await shouldNotRevert(dai.token.withSigner(users[0]), 'approve', [
+      flashloanReceiver.address,
+      premiumAmount,
+    ])
+    const borrowBalanceBefore = (
+      await dai.pool.query.borrowBalanceStored(users[0].address)
+    ).value.ok.toNumber()
+
+    await shouldNotRevert(flashloanGateway.withSigner(users[0]), 'flashloan', [
+      flashloanReceiver.address,
+      [dai.token.address],
+      [flashLoanAmount],
+      [1],
+      users[1].address,
+      [],
+    ])
+
+    expect(
+      (
+        await dai.pool.query.borrowBalanceStored(users[1].address)
+      ).value.ok.toNumber(),
+    ).toEqual(flashLoanAmount)
+    expect(
+      (
+        await dai.pool.query.borrowBalanceStored(users[0].address)
+      ).value.ok.toNumber(),
+    ).toEqual(borrowBalanceBefore)
+    expect(
+      (
+        await dai.pool.query.delegateAllowance(
+          users[1].address,
+          users[0].address,
+        )
+      ).value.ok.toNumber(),
+    ).toEqual(delegated - flashLoanAmount)
+  })
+
   it('tries to take a flashloan that is not listed in the market', async () => {
     const flashLoanAmount = 100_000
     const result = (
@@ -395,4 +475,246 @@ describe('Controller spec', () => {
     ).value.ok
     expect(result0.err).toStrictEqual({ invalidFlashloanAmount: null })
   })
+
+  it('only the manager of Controller sets flashloan premiums', async () => {
+    const { value: byUser } = await flashloanGateway
+      .withSigner(users[0])
+      .query.setFlashloanPremiumTotal(100)
+    expect(byUser.ok.err).toStrictEqual({ callerIsNotManager: null })
+
+    const { value: tooHigh } =
+      await flashloanGateway.query.setFlashloanPremiumToProtocol(10001)
+    expect(tooHigh.ok.err).toStrictEqual({ invalidFlashloanPremium: null })
+
+    const { events } = await shouldNotRevert(
+      flashloanGateway,
+      'setFlashloanPremiumTotal',
+      [100],
+    )
+    expectToEmit<NewFlashloanPremiumTotal>(
+      events[0],
+      'NewFlashloanPremiumTotal',
+      { old: 9, new: 100 },
+    )
+    await shouldNotRevert(flashloanGateway, 'setFlashloanPremiumToProtocol', [
+      3000,
+    ])
+    expect(
+      (await flashloanGateway.query.flashloanPremiumTotal()).value.ok.toNumber(),
+    ).toBe(100)
+    expect(
+      (
+        await flashloanGateway.query.flashloanPremiumToProtocol()
+      ).value.ok.toNumber(),
+    ).toBe(3000)
+  })
+
+  it('adds the protocol share of the premium to reserves', async () => {
+    await flashloanReceiver.tx.setFailExecutionTransfer(false)
+    const flashLoanAmount = 200_000
+    const premiumAmount = (flashLoanAmount * 100) / 10000
+    const premiumToProtocol = (premiumAmount * 3000) / 10000
+    await shouldNotRevert(dai.token, 'mint', [users[0].address, premiumAmount])
+    await shouldNotRevert(dai.token.withSigner(users[0]), 'approve', [
+      flashloanReceiver.address,
+      premiumAmount,
+    ])
+    const reservesBefore = (
+      await dai.pool.query.totalReserves()
+    ).value.ok.toNumber()
+    const cashBefore = (
+      await dai.token.query.balanceOf(dai.pool.address)
+    ).value.ok.toNumber()
+
+    const { events } = await shouldNotRevert(
+      flashloanGateway.withSigner(users[0]),
+      'flashloan',
+      [
+        flashloanReceiver.address,
+        [dai.token.address],
+        [flashLoanAmount],
+        [0],
+        users[0].address,
+        [],
+      ],
+    )
+    expectToEmit<FlashLoan>(events[0], 'FlashLoan', {
+      target: flashloanReceiver.address,
+      initiator: users[0].address,
+      asset: dai.token.address,
+      amount: flashLoanAmount,
+      premium: premiumAmount,
+      premiumToProtocol,
+    })
+    expect(
+      (await dai.pool.query.totalReserves()).value.ok.toNumber(),
+    ).toBe(reservesBefore + premiumToProtocol)
+    expect(
+      (await dai.token.query.balanceOf(dai.pool.address)).value.ok.toNumber(),
+    ).toBe(cashBefore + premiumAmount)
+  })
+
+  it('only the manager of Controller sets borrower premiums and the receiver whitelist', async () => {
+    const { value: premiumByUser } = await flashloanGateway
+      .withSigner(users[0])
+      .query.setBorrowerPremium(users[0].address, 0)
+    expect(premiumByUser.ok.err).toStrictEqual({ callerIsNotManager: null })
+
+    const { value: whitelistByUser } = await flashloanGateway
+      .withSigner(users[0])
+      .query.setWhitelistedReceiver(users[0].address, true)
+    expect(whitelistByUser.ok.err).toStrictEqual({ callerIsNotManager: null })
+
+    const { value: whitelistOnlyByUser } = await flashloanGateway
+      .withSigner(users[0])
+      .query.setWhitelistOnly(true)
+    expect(whitelistOnlyByUser.ok.err).toStrictEqual({
+      callerIsNotManager: null,
+    })
+
+    const { value: tooHigh } = await flashloanGateway.query.setBorrowerPremium(
+      users[0].address,
+      10001,
+    )
+    expect(tooHigh.ok.err).toStrictEqual({ invalidFlashloanPremium: null })
+  })
+
+  it('borrowers with a premium override pay a reduced premium', async () => {
+    const flashLoanAmount = 200_000
+    const { events } = await shouldNotRevert(
+      flashloanGateway,
+      'setBorrowerPremium',
+      [users[0].address, 50],
+    )
+    expectToEmit<NewBorrowerPremium>(events[0], 'NewBorrowerPremium', {
+      borrower: users[0].address,
+      premium: 50,
+    })
+    expect(
+      (
+        await flashloanGateway.query.borrowerPremium(users[0].address)
+      ).value.ok.toNumber(),
+    ).toBe(50)
+    expect(
+      (
+        await flashloanGateway.query.flashloanPremiumOf(users[0].address)
+      ).value.ok.toNumber(),
+    ).toBe(50)
+    expect(
+      (
+        await flashloanGateway.query.flashloanPremiumOf(users[1].address)
+      ).value.ok.toNumber(),
+    ).toBe(100)
+
+    const premiumAmount = (flashLoanAmount * 50) / 10000
+    await shouldNotRevert(dai.token, 'mint', [users[0].address, premiumAmount])
+    await shouldNotRevert(dai.token.withSigner(users[0]), 'approve', [
+      flashloanReceiver.address,
+      premiumAmount,
+    ])
+    const { events: reducedEvents } = await shouldNotRevert(
+      flashloanGateway.withSigner(users[0]),
+      'flashloan',
+      [
+        flashloanReceiver.address,
+        [dai.token.address],
+        [flashLoanAmount],
+        [0],
+        users[0].address,
+        [],
+      ],
+    )
+    expectToEmit<FlashLoan>(reducedEvents[0], 'FlashLoan', {
+      target: flashloanReceiver.address,
+      initiator: users[0].address,
+      asset: dai.token.address,
+      amount: flashLoanAmount,
+      premium: premiumAmount,
+      premiumToProtocol: (premiumAmount * 3000) / 10000,
+    })
+
+    // an override above the default premium does not raise the fee
+    await shouldNotRevert(flashloanGateway, 'setBorrowerPremium', [
+      users[0].address,
+      500,
+    ])
+    expect(
+      (
+        await flashloanGateway.query.flashloanPremiumOf(users[0].address)
+      ).value.ok.toNumber(),
+    ).toBe(100)
+
+    await shouldNotRevert(flashloanGateway, 'setBorrowerPremium', [
+      users[0].address,
+      0,
+    ])
+    const { events: exemptEvents } = await shouldNotRevert(
+      flashloanGateway.withSigner(users[0]),
+      'flashloan',
+      [
+        flashloanReceiver.address,
+        [dai.token.address],
+        [flashLoanAmount],
+        [0],
+        users[0].address,
+        [],
+      ],
+    )
+    expectToEmit<FlashLoan>(exemptEvents[0], 'FlashLoan', {
+      target: flashloanReceiver.address,
+      initiator: users[0].address,
+      asset: dai.token.address,
+      amount: flashLoanAmount,
+      premium: 0,
+      premiumToProtocol: 0,
+    })
+  })
+
+  it('restricts flashloans to whitelisted receivers', async () => {
+    await shouldNotRevert(flashloanGateway, 'setWhitelistOnly', [true])
+    expect((await flashloanGateway.query.whitelistOnly()).value.ok).toBe(true)
+
+    const { value: notWhitelisted } = await flashloanGateway
+      .withSigner(users[0])
+      .query.flashloan(
+        flashloanReceiver.address,
+        [dai.token.address],
+        [200_000],
+        [0],
+        users[0].address,
+        [],
+      )
+    expect(notWhitelisted.ok.err).toStrictEqual({
+      receiverIsNotWhitelisted: null,
+    })
+
+    await shouldNotRevert(flashloanGateway, 'setWhitelistedReceiver', [
+      flashloanReceiver.address,
+      true,
+    ])
+    expect(
+      (
+        await flashloanGateway.query.isWhitelistedReceiver(
+          flashloanReceiver.address,
+        )
+      ).value.ok,
+    ).toBe(true)
+    await shouldNotRevert(flashloanGateway.withSigner(users[0]), 'flashloan', [
+      flashloanReceiver.address,
+      [dai.token.address],
+      [200_000],
+      [0],
+      users[0].address,
+      [],
+    ])
+
+    await shouldNotRevert(flashloanGateway, 'setWhitelistOnly', [false])
+    await shouldNotRevert(flashloanGateway, 'setBorrowerPremium', [
+      users[0].address,
+      null,
+    ])
+    expect(
+      (await flashloanGateway.query.borrowerPremium(users[0].address)).value.ok,
+    ).toBeNull()
+  })
 })
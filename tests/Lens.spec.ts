@@ -250,6 +250,7 @@ describe('Lens', () => {
       expect(new ReturnNumber(res.borrowCap).toHuman()).toBe(
         borrowCap.toString(),
       )
+      expect(res.maxUtilizationMantissa.toHuman()).toEqual('0')
       expect(res.mintGuardianPaused).toBeFalsy()
       expect(res.borrowGuardianPaused).toBeFalsy()
     })
@@ -351,6 +352,7 @@ describe('Lens', () => {
         controller,
         faucet,
         users: [signer],
+        deployer,
       } = await setup())
       await shouldNotRevert(faucet, 'mintUnderlyingAll', [
         controller.address,
@@ -495,6 +497,84 @@ describe('Lens', () => {
         balance - depositAmount + borrowAmount - repayAmount,
       )
     })
+    it('account snapshot', async () => {
+      const depositAmount = 100
+      const borrowAmount = 50
+      const pool = pools[0].withSigner(signer)
+      const token = tokens[0].withSigner(signer)
+
+      await shouldNotRevert(token, 'approve', [pool.address, depositAmount])
+      await shouldNotRevert(pool, 'mint', [depositAmount, { gasLimit }])
+      await shouldNotRevert(pool, 'borrow', [borrowAmount, { gasLimit }])
+
+      const {
+        value: { ok: snapshot },
+      } = await lens.query.accountSnapshot(controller.address, signer.address)
+
+      expect(snapshot.pools).toHaveLength(pools.length)
+      const [position, other] = snapshot.pools
+      expect(position.pool).toBe(pool.address)
+      expect(position.underlying).toBe(token.address)
+      expect(position.supplied.toNumber()).toBe(depositAmount)
+      expect(position.borrowed.toNumber()).toBe(borrowAmount)
+      expect(position.collateralEnabled).toBeTruthy()
+      expect(position.underlyingPrice.toNumber()).toBe(1)
+      expect(position.borrowable.toNumber()).toBeLessThanOrEqual(
+        depositAmount - borrowAmount,
+      )
+      expect(other.supplied.toNumber()).toBe(0)
+      expect(other.borrowed.toNumber()).toBe(0)
+      expect(other.collateralEnabled).toBeFalsy()
+      expect(snapshot.accountData).not.toBeNull()
+    })
+    it('scan liquidatable with pagination', async () => {
+      const depositAmount = 100
+      const borrowAmount = 50
+      const pool = pools[0].withSigner(signer)
+      const token = tokens[0].withSigner(signer)
+
+      await shouldNotRevert(token, 'approve', [pool.address, depositAmount])
+      await shouldNotRevert(pool, 'mint', [depositAmount, { gasLimit }])
+      await shouldNotRevert(pool, 'borrow', [borrowAmount, { gasLimit }])
+
+      const accounts = [signer.address, deployer.address, signer.address]
+      const {
+        value: { ok: firstPage },
+      } = await lens.query.scanLiquidatable(controller.address, accounts, 0, 2)
+      expect(firstPage.accounts).toHaveLength(0)
+      expect(firstPage.nextOffset).toBe(2)
+
+      const {
+        value: { ok: lastPage },
+      } = await lens.query.scanLiquidatable(controller.address, accounts, 2, 2)
+      expect(lastPage.accounts).toHaveLength(0)
+      expect(lastPage.nextOffset).toBeNull()
+    })
+    it('protocol summary', async () => {
+      const depositAmount = 100
+      const borrowAmount = 50
+      const pool = pools[0].withSigner(signer)
+      const token = tokens[0].withSigner(signer)
+
+      await shouldNotRevert(token, 'approve', [pool.address, depositAmount])
+      await shouldNotRevert(pool, 'mint', [depositAmount, { gasLimit }])
+      await shouldNotRevert(pool, 'borrow', [borrowAmount, { gasLimit }])
+
+      const {
+        value: { ok: summary },
+      } = await lens.query.protocolSummary(controller.address)
+
+      expect(summary.markets).toHaveLength(pools.length)
+      const [market, other] = summary.markets
+      expect(market.pool).toBe(pool.address)
+      expect(market.underlying).toBe(token.address)
+      expect(market.utilizationRate.toHuman()).toEqual(
+        ONE_ETHER.mul(new BN(borrowAmount))
+          .div(new BN(depositAmount))
+          .toString(),
+      )
+      expect(other.utilizationRate.toHuman()).toEqual('0')
+    })
     it('on paused', async () => {
       const pool = pools[0]
       await shouldNotRevert(controller, 'setMintGuardianPaused', [
@@ -7,6 +7,7 @@ import { ONE_ETHER } from '../scripts/helper/constants'
 import {
   deployController,
   deployDefaultInterestRateModel,
+  deployFlashLoanGateway,
   deployLeverager,
   deployPriceOracle,
   deployWETH,
@@ -27,6 +28,7 @@ const PROOFSIZE = new BN(2_000_000)
 describe('Leverager spec', () => {
   let api: ApiPromise
   let deployer: KeyringPair
+  let users: KeyringPair[]
   let pools: Pools
   let dai: PoolContracts
   let gasLimit: WeightV2
@@ -70,9 +72,16 @@ describe('Leverager spec', () => {
 
     const users = [bob, charlie, django]
 
+    const flashloanGateway = await deployFlashLoanGateway({
+      api,
+      signer: deployer,
+      args: [controller.address],
+    })
+
     // initialize
     await controller.tx.setPriceOracle(priceOracle.address)
     await controller.tx.setCloseFactorMantissa([ONE_ETHER])
+    await controller.tx.setFlashloanGateway(flashloanGateway.address)
     //// for pool
     for (const sym of [pools.dai, pools.weth]) {
       await priceOracle.tx.setFixedPrice(sym.token.address, ONE_ETHER)
@@ -109,7 +118,7 @@ describe('Leverager spec', () => {
   }
 
   beforeAll(async () => {
-    ;({ api, deployer, gasLimit, pools, leverager } = await setup())
+    ;({ api, deployer, users, gasLimit, pools, leverager } = await setup())
     ;({ dai } = pools)
   })
 
@@ -146,4 +155,242 @@ describe('Leverager spec', () => {
       .value.ok
     expect(depositedUser.toNumber()).toEqual(borrowTotal + depositAmount)
   })
+
+  describe('.leverageWithFlashloan', () => {
+    const depositAmount = 100_000
+    const flashloanAmount = 200_000
+
+    beforeAll(async () => {
+      const liquidity = 1_000_000
+      await shouldNotRevert(dai.token, 'mint', [deployer.address, liquidity])
+      await shouldNotRevert(dai.token, 'approve', [dai.pool.address, liquidity])
+      await shouldNotRevert(dai.pool, 'mint', [liquidity])
+    })
+
+    it('fails without delegating the borrow to Leverager', async () => {
+      const user = users[0]
+      await shouldNotRevert(dai.token, 'mint', [user.address, depositAmount])
+      await shouldNotRevert(dai.token.withSigner(user), 'approve', [
+        leverager.address,
+        depositAmount,
+      ])
+      const { value } = await leverager
+        .withSigner(user)
+        .query.leverageWithFlashloan(dai.token.address, depositAmount, 30000)
+      expect(value.ok.err).toStrictEqual({
+        flashloanGateway: { insufficientDelegateAllowance: null },
+      })
+    })
+
+    it('fails if the leverage exceeds the loan to value', async () => {
+      const { value } = await leverager
+        .withSigner(users[0])
+        .query.leverageWithFlashloan(dai.token.address, depositAmount, 100000)
+      expect(value.ok.err).toStrictEqual({ inappropriateLeverage: null })
+    })
+
+    it('deposits and borrows in one transaction', async () => {
+      const user = users[0]
+      await shouldNotRevert(dai.pool.withSigner(user), 'approveDelegate', [
+        leverager.address,
+        flashloanAmount,
+      ])
+
+      await shouldNotRevertWithNetworkGas(
+        api,
+        leverager.withSigner(user),
+        'leverageWithFlashloan',
+        [dai.token.address, depositAmount, 30000],
+      )
+
+      expect(
+        (await dai.pool.query.balanceOf(user.address)).value.ok.toNumber(),
+      ).toBe(depositAmount + flashloanAmount)
+      expect(
+        (
+          await dai.pool.query.borrowBalanceStored(user.address)
+        ).value.ok.toNumber(),
+      ).toBe(flashloanAmount)
+      expect(
+        (
+          await dai.pool.query.delegateAllowance(
+            user.address,
+            leverager.address,
+          )
+        ).value.ok.toNumber(),
+      ).toBe(0)
+    })
+
+    it('repays and redeems in one transaction', async () => {
+      const user = users[0]
+      const premium = Math.floor((flashloanAmount * 9) / 10000)
+      await shouldNotRevert(dai.pool.withSigner(user), 'approve', [
+        leverager.address,
+        flashloanAmount + premium,
+      ])
+
+      await shouldNotRevertWithNetworkGas(
+        api,
+        leverager.withSigner(user),
+        'deleverageWithFlashloan',
+        [dai.token.address, flashloanAmount],
+      )
+
+      expect(
+        (
+          await dai.pool.query.borrowBalanceStored(user.address)
+        ).value.ok.toNumber(),
+      ).toBe(0)
+      expect(
+        (await dai.pool.query.balanceOf(user.address)).value.ok.toNumber(),
+      ).toBe(depositAmount - premium)
+    })
+  })
+
+  describe('.deleverageTo', () => {
+    const depositAmount = 100_000
+    const flashloanAmount = 200_000
+    const targetHealthFactor = ONE_ETHER.mul(new BN(2))
+
+    const healthFactor = async (address: string) =>
+      new BN(
+        (
+          await leverager.query.getHealthFactor(address, dai.token.address, 0)
+        ).value.ok.toString(),
+      )
+
+    beforeAll(async () => {
+      const user = users[1]
+      await shouldNotRevert(dai.token, 'mint', [user.address, depositAmount])
+      await shouldNotRevert(dai.token.withSigner(user), 'approve', [
+        leverager.address,
+        depositAmount,
+      ])
+      await shouldNotRevert(dai.pool.withSigner(user), 'approveDelegate', [
+        leverager.address,
+        flashloanAmount,
+      ])
+      await shouldNotRevertWithNetworkGas(
+        api,
+        leverager.withSigner(user),
+        'leverageWithFlashloan',
+        [dai.token.address, depositAmount, 30000],
+      )
+    })
+
+    it('fails if the target is not above 1', async () => {
+      const { value } = await leverager
+        .withSigner(users[1])
+        .query.deleverageTo(dai.token.address, [ONE_ETHER])
+      expect(value.ok.err).toStrictEqual({ inappropriateHealthFactor: null })
+    })
+
+    it('withdraws and repays until the target health factor', async () => {
+      const user = users[1]
+      expect((await healthFactor(user.address)).lt(targetHealthFactor)).toBe(
+        true,
+      )
+      await shouldNotRevert(dai.pool.withSigner(user), 'approve', [
+        leverager.address,
+        depositAmount + flashloanAmount,
+      ])
+
+      const { value } = await leverager
+        .withSigner(user)
+        .query.deleverageTo(dai.token.address, [targetHealthFactor])
+      const [withdrawn, repaid] = value.ok.ok
+      expect(withdrawn.toNumber()).toBeGreaterThan(0)
+      expect(repaid.toNumber()).toBe(withdrawn.toNumber())
+
+      await shouldNotRevertWithNetworkGas(
+        api,
+        leverager.withSigner(user),
+        'deleverageTo',
+        [dai.token.address, [targetHealthFactor]],
+      )
+
+      expect((await healthFactor(user.address)).gte(targetHealthFactor)).toBe(
+        true,
+      )
+      expect(
+        (
+          await dai.pool.query.borrowBalanceStored(user.address)
+        ).value.ok.toNumber(),
+      ).toBe(flashloanAmount - repaid.toNumber())
+      expect(
+        (await dai.pool.query.balanceOf(user.address)).value.ok.toNumber(),
+      ).toBe(depositAmount + flashloanAmount - withdrawn.toNumber())
+    })
+  })
+
+  describe('.deleverageToEth', () => {
+    const depositAmount = 100_000
+    const targetHealthFactor = ONE_ETHER.mul(new BN(2))
+    let weth: PoolContracts
+
+    const healthFactor = async (address: string) =>
+      new BN(
+        (
+          await leverager.query.getHealthFactor(address, weth.token.address, 0)
+        ).value.ok.toString(),
+      )
+
+    beforeAll(async () => {
+      ;({ weth } = pools)
+      const user = users[2]
+      await shouldNotRevert(weth.pool.withSigner(user), 'approveDelegate', [
+        leverager.address,
+        ONE_ETHER,
+      ])
+      await shouldNotRevert(leverager.withSigner(user), 'loopEth', [
+        8000,
+        3,
+        { value: depositAmount, gasLimit },
+      ])
+    })
+
+    it('fails if the target is not above 1', async () => {
+      const { value } = await leverager
+        .withSigner(users[2])
+        .query.deleverageToEth([ONE_ETHER])
+      expect(value.ok.err).toStrictEqual({ inappropriateHealthFactor: null })
+    })
+
+    it('withdraws and repays eth until the target health factor', async () => {
+      const user = users[2]
+      expect((await healthFactor(user.address)).lt(targetHealthFactor)).toBe(
+        true,
+      )
+      const borrowBefore = (
+        await weth.pool.query.borrowBalanceStored(user.address)
+      ).value.ok.toNumber()
+      await shouldNotRevert(weth.pool.withSigner(user), 'approve', [
+        leverager.address,
+        ONE_ETHER,
+      ])
+
+      const { value } = await leverager
+        .withSigner(user)
+        .query.deleverageToEth([targetHealthFactor])
+      const [withdrawn, repaid] = value.ok.ok
+      expect(withdrawn.toNumber()).toBeGreaterThan(0)
+      expect(repaid.toNumber()).toBe(withdrawn.toNumber())
+
+      await shouldNotRevertWithNetworkGas(
+        api,
+        leverager.withSigner(user),
+        'deleverageToEth',
+        [[targetHealthFactor]],
+      )
+
+      expect((await healthFactor(user.address)).gte(targetHealthFactor)).toBe(
+        true,
+      )
+      expect(
+        (
+          await weth.pool.query.borrowBalanceStored(user.address)
+        ).value.ok.toNumber(),
+      ).toBe(borrowBefore - repaid.toNumber())
+    })
+  })
 })
@@ -0,0 +1,179 @@
+import type { KeyringPair } from '@polkadot/keyring/types'
+import { BN, BN_ONE, BN_TEN } from '@polkadot/util'
+import { ONE_ETHER, ROLE, ZERO_ADDRESS } from '../scripts/helper/constants'
+import {
+  deployController,
+  deployDefaultInterestRateModel,
+  deployIncentivesController,
+  deployManager,
+  deployPriceOracle,
+} from '../scripts/helper/deploy_helper'
+import { getGasLimit } from '../scripts/helper/utils'
+import Controller from '../types/contracts/controller'
+import Manager from '../types/contracts/manager'
+import {
+  PoolContracts,
+  preparePoolsWithPreparedTokens,
+} from './testContractHelper'
+import { shouldNotRevert, toDec18 } from './testHelpers'
+
+const MAX_CALL_WEIGHT = new BN(125_000_000_000).isub(BN_ONE).mul(BN_TEN)
+const PROOFSIZE = new BN(2_000_000)
+const WINDOW = 60 * 60 * 1000
+
+describe('Outflow limit spec', () => {
+  const setup = async () => {
+    const { api, alice: deployer, bob } = globalThis.setup
+    const gasLimit = getGasLimit(api, MAX_CALL_WEIGHT, PROOFSIZE)
+
+    const manager = await deployManager({
+      api,
+      signer: deployer,
+      args: [ZERO_ADDRESS],
+    })
+    const controller = await deployController({
+      api,
+      signer: deployer,
+      args: [manager.address],
+    })
+    const priceOracle = await deployPriceOracle({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const incentivesController = await deployIncentivesController({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const rateModel = await deployDefaultInterestRateModel({
+      api,
+      signer: deployer,
+      args: [[0], [0], [0], [0]],
+    })
+    const pools = await preparePoolsWithPreparedTokens({
+      api,
+      controller,
+      rateModel,
+      signer: deployer,
+      manager: manager.address,
+      incentivesController,
+    })
+
+    // initialize
+    await shouldNotRevert(manager, 'setController', [controller.address])
+    await shouldNotRevert(manager, 'grantRole', [
+      ROLE.CONTROLLER_ADMIN,
+      deployer.address,
+    ])
+    await shouldNotRevert(manager, 'grantRole', [
+      ROLE.PAUSE_GUARDIAN,
+      bob.address,
+    ])
+    await shouldNotRevert(manager, 'setPriceOracle', [priceOracle.address])
+    await shouldNotRevert(priceOracle, 'setFixedPrice', [
+      pools.dai.token.address,
+      ONE_ETHER,
+    ])
+    await shouldNotRevert(manager, 'supportMarketWithCollateralFactorMantissa', [
+      pools.dai.pool.address,
+      pools.dai.token.address,
+      [ONE_ETHER.mul(new BN(90)).div(new BN(100))],
+    ])
+
+    return { deployer, bob, manager, controller, pools, gasLimit }
+  }
+
+  let deployer: KeyringPair
+  let bob: KeyringPair
+  let manager: Manager
+  let controller: Controller
+  let dai: PoolContracts
+  let gasLimit
+  const supplied = toDec18(10_000)
+
+  beforeAll(async () => {
+    let pools
+    ;({ deployer, bob, manager, controller, pools, gasLimit } = await setup())
+    dai = pools.dai
+
+    await dai.token.tx.mint(deployer.address, supplied)
+    await dai.token.tx.approve(dai.pool.address, supplied)
+    await shouldNotRevert(dai.pool, 'mint', [supplied, { gasLimit }])
+  })
+
+  it('has no limit by default', async () => {
+    expect(
+      (await controller.query.remainingOutflowCapacity(dai.pool.address)).value
+        .ok,
+    ).toBeNull()
+    expect((await dai.pool.query.outflowLimitEnabled()).value.ok).toBe(false)
+  })
+
+  it('rejects outflows over the limit', async () => {
+    // 10% of the liquidity per window
+    await shouldNotRevert(manager, 'setOutflowLimit', [
+      dai.pool.address,
+      {
+        maxOutflowMantissa: [ONE_ETHER.div(new BN(10))],
+        window: WINDOW,
+      },
+    ])
+    expect((await dai.pool.query.outflowLimitEnabled()).value.ok).toBe(true)
+    expect(
+      (
+        await controller.query.remainingOutflowCapacity(dai.pool.address)
+      ).value.ok.toString(),
+    ).toBe(toDec18(1_000).toString())
+
+    const { value } = await dai.pool.query.redeemUnderlying(toDec18(1_001), {
+      gasLimit,
+    })
+    expect(value.ok.err).toStrictEqual({ controller: 'OutflowLimitExceeded' })
+
+    await shouldNotRevert(dai.pool, 'redeemUnderlying', [
+      toDec18(600),
+      { gasLimit },
+    ])
+    expect(
+      (
+        await controller.query.remainingOutflowCapacity(dai.pool.address)
+      ).value.ok.toString(),
+    ).toBe(toDec18(400).toString())
+  })
+
+  it('offsets outflows by inflows', async () => {
+    await dai.token.tx.mint(deployer.address, toDec18(100))
+    await dai.token.tx.approve(dai.pool.address, toDec18(100))
+    await shouldNotRevert(dai.pool, 'mint', [toDec18(100), { gasLimit }])
+    expect(
+      (
+        await controller.query.remainingOutflowCapacity(dai.pool.address)
+      ).value.ok.toString(),
+    ).toBe(toDec18(500).toString())
+  })
+
+  it('allows pause guardian to raise the limit temporarily', async () => {
+    const { value } = await manager.query.raiseOutflowLimit(
+      dai.pool.address,
+      toDec18(1_000),
+      Date.now() + WINDOW,
+    )
+    expect(value.ok.err).toStrictEqual({ accessControl: 'MissingRole' })
+
+    await shouldNotRevert(manager.withSigner(bob), 'raiseOutflowLimit', [
+      dai.pool.address,
+      toDec18(1_000),
+      Date.now() + WINDOW,
+    ])
+    await shouldNotRevert(dai.pool, 'redeemUnderlying', [
+      toDec18(1_200),
+      { gasLimit },
+    ])
+    expect(
+      (
+        await controller.query.remainingOutflowCapacity(dai.pool.address)
+      ).value.ok.toString(),
+    ).toBe(toDec18(300).toString())
+  })
+})
@@ -277,6 +277,121 @@ describe('Pool spec 1', () => {
     })
   })
 
+  describe('.redeem_underlying_on_behalf', () => {
+    let deployer: KeyringPair
+    let spender: KeyringPair
+    let receiver: KeyringPair
+    let token: PSP22Token
+    let pool: Pool
+    let gasLimit: WeightV2
+
+    beforeAll(async () => {
+      let users: KeyringPair[]
+      ;({
+        deployer,
+        users,
+        pools: {
+          dai: { token, pool },
+        },
+        gasLimit,
+      } = await setup())
+      ;[spender, receiver] = users
+    })
+
+    const deposited = 10_000
+    const allowance = 1_001
+    it('setup', async () => {
+      await shouldNotRevert(token, 'mint', [deployer.address, deposited])
+      await shouldNotRevert(token, 'approve', [pool.address, deposited])
+      await shouldNotRevert(pool, 'mint', [deposited, { gasLimit }])
+      await shouldNotRevert(pool, 'approve', [spender.address, allowance])
+    })
+
+    it('redeems exact odd and single unit amounts', async () => {
+      let redeemed = 0
+      for (const amount of [333, 1, 667]) {
+        await shouldNotRevert(
+          pool.withSigner(spender),
+          'redeemUnderlyingOnBehalf',
+          [deployer.address, amount, receiver.address, { gasLimit }],
+        )
+        redeemed += amount
+
+        expect(
+          (await pool.query.balanceOf(deployer.address)).value.ok.toNumber(),
+        ).toBe(deposited - redeemed)
+        expect(
+          (await token.query.balanceOf(receiver.address)).value.ok.toNumber(),
+        ).toBe(redeemed)
+        expect(
+          (
+            await pool.query.allowance(deployer.address, spender.address)
+          ).value.ok.toNumber(),
+        ).toBe(allowance - redeemed)
+      }
+      expect(
+        (await token.query.balanceOf(pool.address)).value.ok.toNumber(),
+      ).toBe(deposited - redeemed)
+      expect(
+        (await pool.query.balanceOf(spender.address)).value.ok.toNumber(),
+      ).toBe(0)
+    })
+
+    it('fails beyond the allowance', async () => {
+      const { value } = await pool
+        .withSigner(spender)
+        .query.redeemUnderlyingOnBehalf(deployer.address, 1, receiver.address)
+      expect(value.ok.err).toStrictEqual({
+        psp22: { insufficientAllowance: null },
+      })
+    })
+
+    it('fails without consuming the allowance when nothing is redeemed', async () => {
+      await shouldNotRevert(pool, 'approve', [spender.address, 100])
+
+      const { value: zero } = await pool
+        .withSigner(spender)
+        .query.redeemUnderlyingOnBehalf(deployer.address, 0, receiver.address)
+      expect(zero.ok.err).toStrictEqual({ invalidParameter: null })
+
+      const { value: noCollateral } = await pool
+        .withSigner(spender)
+        .query.redeemUnderlyingOnBehalf(receiver.address, 1, spender.address)
+      expect(noCollateral.ok.err).toStrictEqual({
+        reserveIsNotEnabledAsCollateral: null,
+      })
+
+      expect(
+        (
+          await pool.query.allowance(deployer.address, spender.address)
+        ).value.ok.toNumber(),
+      ).toBe(100)
+    })
+
+    it('redeems the whole balance and disables the collateral', async () => {
+      const balance = (
+        await pool.query.balanceOf(deployer.address)
+      ).value.ok.toNumber()
+      await shouldNotRevert(pool, 'approve', [spender.address, balance])
+      await shouldNotRevert(
+        pool.withSigner(spender),
+        'redeemUnderlyingOnBehalf',
+        [deployer.address, balance, receiver.address, { gasLimit }],
+      )
+      expect(
+        (await pool.query.balanceOf(deployer.address)).value.ok.toNumber(),
+      ).toBe(0)
+      expect(
+        (await token.query.balanceOf(receiver.address)).value.ok.toNumber(),
+      ).toBe(deposited)
+      expect(
+        (
+          await pool.query.usingReserveAsCollateral(deployer.address)
+        ).value.ok,
+      ).toBe(false)
+    })
+  })
+
   describe('.redeem (fail case)', () => {
     it('when no cash in pool', async () => {
       const {
@@ -0,0 +1,247 @@
+import type { KeyringPair } from '@polkadot/keyring/types'
+import { WeightV2 } from '@polkadot/types/interfaces'
+import { BN, BN_ONE, BN_TEN } from '@polkadot/util'
+import { ONE_ETHER } from '../scripts/helper/constants'
+import {
+  deployController,
+  deployDefaultInterestRateModel,
+  deployDexRouter,
+  deployFlashLoanGateway,
+  deployIncentivesController,
+  deployPriceOracle,
+  deployRepayWithCollateral,
+} from '../scripts/helper/deploy_helper'
+import { getGasLimit } from '../scripts/helper/utils'
+import DexRouter from '../types/contracts/dex_router'
+import FlashloanGateway from '../types/contracts/flashloan_gateway'
+import RepayWithCollateral from '../types/contracts/repay_with_collateral'
+import {
+  PoolContracts,
+  preparePoolsWithPreparedTokens,
+} from './testContractHelper'
+import { shouldNotRevert } from './testHelpers'
+
+const MAX_CALL_WEIGHT = new BN(128_000_000_000).isub(BN_ONE).mul(BN_TEN)
+const PROOFSIZE = new BN(2_000_000)
+
+describe('RepayWithCollateral spec', () => {
+  const setup = async () => {
+    const { api, alice: deployer, bob } = globalThis.setup
+    const gasLimit = getGasLimit(api, MAX_CALL_WEIGHT, PROOFSIZE)
+
+    const controller = await deployController({
+      api,
+      signer: deployer,
+      args: [deployer.address],
+    })
+    const priceOracle = await deployPriceOracle({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const rateModel = await deployDefaultInterestRateModel({
+      api,
+      signer: deployer,
+      args: [[0], [0], [0], [0]],
+    })
+    const incentivesController = await deployIncentivesController({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const pools = await preparePoolsWithPreparedTokens({
+      api,
+      controller,
+      rateModel,
+      signer: deployer,
+      manager: deployer.address,
+      incentivesController,
+    })
+    const flashloanGateway = await deployFlashLoanGateway({
+      api,
+      signer: deployer,
+      args: [controller.address],
+    })
+    const router = await deployDexRouter({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const repayWithCollateral = await deployRepayWithCollateral({
+      api,
+      signer: deployer,
+      args: [flashloanGateway.address, controller.address, router.address],
+    })
+
+    // initialize
+    await controller.tx.setPriceOracle(priceOracle.address)
+    await controller.tx.setCloseFactorMantissa([ONE_ETHER])
+    await controller.tx.setFlashloanGateway(flashloanGateway.address)
+    for (const sym of [pools.dai, pools.usdc]) {
+      await priceOracle.tx.setFixedPrice(sym.token.address, ONE_ETHER)
+      await controller.tx.supportMarketWithCollateralFactorMantissa(
+        sym.pool.address,
+        sym.token.address,
+        [ONE_ETHER.mul(new BN(90)).div(new BN(100))],
+      )
+    }
+
+    return {
+      deployer,
+      bob,
+      pools,
+      flashloanGateway,
+      router,
+      repayWithCollateral,
+      gasLimit,
+    }
+  }
+
+  let deployer: KeyringPair
+  let bob: KeyringPair
+  let dai: PoolContracts
+  let usdc: PoolContracts
+  let flashloanGateway: FlashloanGateway
+  let router: DexRouter
+  let repayWithCollateral: RepayWithCollateral
+  let gasLimit: WeightV2
+
+  const liquidity = 1_000_000
+  const supplied = 100_000
+  const borrowed = 30_000
+
+  beforeAll(async () => {
+    let pools
+    ;({
+      deployer,
+      bob,
+      pools,
+      flashloanGateway,
+      router,
+      repayWithCollateral,
+      gasLimit,
+    } = await setup())
+    ;({ dai, usdc } = pools)
+
+    for (const { token, pool } of [dai, usdc]) {
+      await shouldNotRevert(token, 'mint', [deployer.address, liquidity])
+      await shouldNotRevert(token, 'approve', [pool.address, liquidity])
+      await shouldNotRevert(pool, 'mint', [liquidity])
+    }
+    await shouldNotRevert(usdc.token, 'mint', [router.address, liquidity])
+    await shouldNotRevert(router, 'setRate', [
+      dai.token.address,
+      usdc.token.address,
+      [ONE_ETHER],
+    ])
+
+    await shouldNotRevert(dai.token, 'mint', [bob.address, supplied])
+    await shouldNotRevert(dai.token.withSigner(bob), 'approve', [
+      dai.pool.address,
+      supplied,
+    ])
+    await shouldNotRevert(dai.pool.withSigner(bob), 'mint', [supplied])
+    await shouldNotRevert(usdc.pool.withSigner(bob), 'borrow', [
+      borrowed,
+      { gasLimit },
+    ])
+  })
+
+  it('instantiate', async () => {
+    expect(
+      (await repayWithCollateral.query.flashloanGateway()).value.ok,
+    ).toEqual(flashloanGateway.address)
+    expect((await repayWithCollateral.query.router()).value.ok).toEqual(
+      router.address,
+    )
+  })
+
+  it('fails without borrow to repay', async () => {
+    const { value } = await repayWithCollateral.query.repayWithCollateral(
+      dai.token.address,
+      usdc.token.address,
+      10_000,
+      20_000,
+      { gasLimit },
+    )
+    expect(value.ok.err).toStrictEqual({ noBorrowToRepay: null })
+  })
+
+  it('fails when more collateral than the maximum is needed', async () => {
+    await shouldNotRevert(dai.pool.withSigner(bob), 'approve', [
+      repayWithCollateral.address,
+      supplied,
+    ])
+    const { value } = await repayWithCollateral
+      .withSigner(bob)
+      .query.repayWithCollateral(
+        dai.token.address,
+        usdc.token.address,
+        20_000,
+        20_000,
+        { gasLimit },
+      )
+    expect(value.ok.err).toStrictEqual({
+      flashloanAdapter: { flashloanGateway: { invalidFlashloanExecutorReturn: null } },
+    })
+  })
+
+  it('repays borrow with collateral', async () => {
+    const repayAmount = 20_000
+    const premiumTotal = (
+      await flashloanGateway.query.flashloanPremiumTotal()
+    ).value.ok.toNumber()
+    const premium = Math.floor((repayAmount * premiumTotal) / 10000)
+
+    await shouldNotRevert(
+      repayWithCollateral.withSigner(bob),
+      'repayWithCollateral',
+      [
+        dai.token.address,
+        usdc.token.address,
+        repayAmount,
+        repayAmount + premium,
+        { gasLimit },
+      ],
+    )
+
+    expect(
+      (
+        await usdc.pool.query.borrowBalanceStored(bob.address)
+      ).value.ok.toNumber(),
+    ).toBe(borrowed - repayAmount)
+    expect(
+      (await dai.pool.query.balanceOf(bob.address)).value.ok.toNumber(),
+    ).toBe(supplied - repayAmount - premium)
+    expect(
+      (
+        await usdc.token.query.balanceOf(repayWithCollateral.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+  })
+
+  it('caps the repay amount to the borrow', async () => {
+    const remaining = borrowed - 20_000
+    const premiumTotal = (
+      await flashloanGateway.query.flashloanPremiumTotal()
+    ).value.ok.toNumber()
+    const premium = Math.floor((remaining * premiumTotal) / 10000)
+
+    await shouldNotRevert(
+      repayWithCollateral.withSigner(bob),
+      'repayWithCollateral',
+      [
+        dai.token.address,
+        usdc.token.address,
+        remaining * 2,
+        remaining + premium,
+        { gasLimit },
+      ],
+    )
+    expect(
+      (
+        await usdc.pool.query.borrowBalanceStored(bob.address)
+      ).value.ok.toNumber(),
+    ).toBe(0)
+  })
+})
@@ -0,0 +1,155 @@
+import type { ApiPromise } from '@polkadot/api'
+import type { KeyringPair } from '@polkadot/keyring/types'
+import { BN, BN_ONE, BN_TEN } from '@polkadot/util'
+import { ONE_ETHER, ROLE, ZERO_ADDRESS } from '../scripts/helper/constants'
+import {
+  deployController,
+  deployDefaultInterestRateModel,
+  deployIncentivesController,
+  deployManager,
+  deployPoolV2,
+  deployPriceOracle,
+} from '../scripts/helper/deploy_helper'
+import { getGasLimit } from '../scripts/helper/utils'
+import Manager from '../types/contracts/manager'
+import PoolV2 from '../types/contracts/pool_v2'
+import {
+  PoolContracts,
+  preparePoolsWithPreparedTokens,
+} from './testContractHelper'
+import { shouldNotRevert, toDec18 } from './testHelpers'
+
+const MAX_CALL_WEIGHT = new BN(125_000_000_000).isub(BN_ONE).mul(BN_TEN)
+const PROOFSIZE = new BN(2_000_000)
+
+const codeHashOf = async (api: ApiPromise, address: string) =>
+  (await api.query.contracts.contractInfoOf(address)).unwrap().codeHash.toHex()
+
+describe('Upgrade spec', () => {
+  const setup = async () => {
+    const { api, alice: deployer, bob } = globalThis.setup
+    const gasLimit = getGasLimit(api, MAX_CALL_WEIGHT, PROOFSIZE)
+
+    const manager = await deployManager({
+      api,
+      signer: deployer,
+      args: [ZERO_ADDRESS],
+    })
+    const controller = await deployController({
+      api,
+      signer: deployer,
+      args: [manager.address],
+    })
+    const priceOracle = await deployPriceOracle({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const incentivesController = await deployIncentivesController({
+      api,
+      signer: deployer,
+      args: [],
+    })
+    const rateModel = await deployDefaultInterestRateModel({
+      api,
+      signer: deployer,
+      args: [[0], [0], [0], [0]],
+    })
+    const pools = await preparePoolsWithPreparedTokens({
+      api,
+      controller,
+      rateModel,
+      signer: deployer,
+      manager: manager.address,
+      incentivesController,
+    })
+
+    // initialize
+    await shouldNotRevert(manager, 'setController', [controller.address])
+    await shouldNotRevert(manager, 'grantRole', [
+      ROLE.CONTROLLER_ADMIN,
+      deployer.address,
+    ])
+    await shouldNotRevert(manager, 'setPriceOracle', [priceOracle.address])
+    await shouldNotRevert(priceOracle, 'setFixedPrice', [
+      pools.dai.token.address,
+      ONE_ETHER,
+    ])
+    await shouldNotRevert(manager, 'supportMarketWithCollateralFactorMantissa', [
+      pools.dai.pool.address,
+      pools.dai.token.address,
+      [ONE_ETHER.mul(new BN(90)).div(new BN(100))],
+    ])
+
+    const poolV2 = await deployPoolV2({ api, signer: deployer, args: [] })
+
+    return { api, deployer, bob, manager, pools, poolV2, gasLimit }
+  }
+
+  describe('Pool', () => {
+    let api: ApiPromise
+    let deployer: KeyringPair
+    let bob: KeyringPair
+    let manager: Manager
+    let dai: PoolContracts
+    let poolV2: PoolV2
+    let upgraded: PoolV2
+    const supplied = toDec18(1_000)
+
+    beforeAll(async () => {
+      let pools
+      let gasLimit
+      ;({ api, deployer, bob, manager, pools, poolV2, gasLimit } =
+        await setup())
+      dai = pools.dai
+
+      await dai.token.tx.mint(deployer.address, supplied)
+      await dai.token.tx.approve(dai.pool.address, supplied)
+      await shouldNotRevert(dai.pool, 'mint', [supplied, { gasLimit }])
+    })
+
+    it('fails to upgrade without default admin role', async () => {
+      const codeHash = await codeHashOf(api, poolV2.address)
+      const { value } = await manager
+        .withSigner(bob)
+        .query.upgradePool(dai.pool.address, codeHash)
+      expect(value.ok.err).toStrictEqual({ accessControl: 'MissingRole' })
+
+      const { value: direct } = await dai.pool.query.upgrade(codeHash)
+      expect(direct.ok.err).toBe('CallerIsNotManager')
+    })
+
+    it('upgrades, migrates and keeps the storage', async () => {
+      const codeHash = await codeHashOf(api, poolV2.address)
+      await shouldNotRevert(manager, 'upgradePool', [
+        dai.pool.address,
+        codeHash,
+      ])
+      expect(await codeHashOf(api, dai.pool.address)).toBe(codeHash)
+
+      upgraded = new PoolV2(dai.pool.address, deployer, api)
+      expect(
+        (await upgraded.query.balanceOf(deployer.address)).value.ok.toString(),
+      ).toBe(supplied.toString())
+      expect((await upgraded.query.totalSupply()).value.ok.toString()).toBe(
+        supplied.toString(),
+      )
+      expect((await upgraded.query.underlying()).value.ok).toBe(
+        dai.token.address,
+      )
+      expect((await upgraded.query.manager()).value.ok).toBe(manager.address)
+      expect((await upgraded.query.tokenSymbol()).value.ok).toBe('sDAI')
+      const versions = (await upgraded.query.storageVersions()).value.ok
+      expect(versions.map(([, version]) => version)).toStrictEqual([2])
+      expect((await upgraded.query.migratedFrom()).value.ok).toBe(1)
+    })
+
+    it('fails to migrate from other than Manager', async () => {
+      const { value: byOther } = await upgraded.withSigner(bob).query.migrate()
+      expect(byOther.ok.err).toBe('CallerIsNotManager')
+
+      const { value: byAdmin } = await upgraded.query.migrate()
+      expect(byAdmin.ok.err).toBe('CallerIsNotManager')
+    })
+  })
+})
@@ -29,7 +29,7 @@ describe('WETHGateway spec', () => {
   // let rateModel: DefaultInterestRateModel
   // let controller: Controller
   // let priceOracle: PriceOracle
-  // let users: KeyringPair[]
+  let users: KeyringPair[]
   let weth: WETH
   let wethGateway: WETHGateway
   let gasLimit: WeightV2
@@ -81,7 +81,7 @@ describe('WETHGateway spec', () => {
     const wethGateway = await deployWETHGateway({
       api,
       signer: deployer,
-      args: [weth.address, pools.weth.pool.address],
+      args: [weth.address, controller.address],
     })
 
     const users = [bob, charlie, django]
@@ -123,7 +123,7 @@ describe('WETHGateway spec', () => {
       // rateModel,
       // controller,
       // priceOracle,
-      // users,
+      users,
     } = await setup())
 
     expect(weth.address).not.toBe(ZERO_ADDRESS)
@@ -132,6 +132,9 @@ describe('WETHGateway spec', () => {
     expect((await wethGateway.query.getWethAddress()).value.ok).toEqual(
       weth.address,
     )
+    expect((await wethGateway.query.getPoolAddress()).value.ok).toEqual(
+      pools.weth.pool.address,
+    )
 
     expect((await weth.query.tokenName()).value.ok).toEqual('Wrapped Astar')
     expect((await weth.query.tokenSymbol()).value.ok).toEqual('WASTR')
@@ -289,4 +292,124 @@ describe('WETHGateway spec', () => {
       beforeWethContractBalance.sub(afterWethContractBalance).toNumber(),
     ).toEqual(withdrawAmount)
   })
+
+  describe('Withdraw WETH with pool token allowance', () => {
+    it('Should Fail without allowance', async () => {
+      const { pool } = pools.weth
+      await shouldNotRevert(pool, 'approve', [
+        wethGateway.address,
+        0,
+        { gasLimit },
+      ])
+
+      const result = await wethGateway.query.withdrawEth(1)
+      expect(result.value.ok.err).toBeTruthy()
+    })
+
+    it('Withdraw exact odd amounts without rounding loss', async () => {
+      const { pool } = pools.weth
+      const amounts = [1, 7, 333]
+      const total = amounts.reduce((acc, cur) => acc + cur, 0)
+      await shouldNotRevert(pool, 'approve', [
+        wethGateway.address,
+        total,
+        { gasLimit },
+      ])
+
+      for (const amount of amounts) {
+        const before = (
+          await pool.query.balanceOf(deployer.address)
+        ).value.ok.toNumber()
+        await shouldNotRevert(wethGateway, 'withdrawEth', [
+          amount,
+          { gasLimit },
+        ])
+        const after = (
+          await pool.query.balanceOf(deployer.address)
+        ).value.ok.toNumber()
+        expect(before - after).toEqual(amount)
+      }
+
+      expect(
+        (
+          await pool.query.allowance(deployer.address, wethGateway.address)
+        ).value.ok.toNumber(),
+      ).toEqual(0)
+      expect(
+        (await pool.query.balanceOf(wethGateway.address)).value.ok.toNumber(),
+      ).toEqual(0)
+      expect(
+        (
+          await pool.query.usingReserveAsCollateral(wethGateway.address)
+        ).value.ok,
+      ).toBe(false)
+    })
+  })
+
+  it('Deposit WETH on behalf of another account', async () => {
+    const { pool } = pools.weth
+    const [user] = users
+
+    await shouldNotRevert(wethGateway, 'depositEthOnBehalf', [
+      user.address,
+      {
+        value: depositAmount,
+        gasLimit,
+      },
+    ])
+
+    expect(
+      (await pool.query.balanceOf(user.address)).value.ok.toString(),
+    ).toEqual(depositAmount.toString())
+  })
+
+  it('Repay WETH on behalf of another account', async () => {
+    const { pool } = pools.weth
+    const [user] = users
+
+    await shouldNotRevert(pool.withSigner(user), 'approveDelegate', [
+      wethGateway.address,
+      borrowAmount,
+      { gasLimit },
+    ])
+    await shouldNotRevert(wethGateway.withSigner(user), 'borrowEth', [
+      borrowAmount,
+      { gasLimit },
+    ])
+    expect(
+      (
+        await pool.query.delegateAllowance(user.address, wethGateway.address)
+      ).value.ok.toNumber(),
+    ).toEqual(0)
+
+    await shouldNotRevert(wethGateway, 'repayEthOnBehalf', [
+      repayAmount,
+      user.address,
+      {
+        value: repayAmount,
+        gasLimit,
+      },
+    ])
+    expect(
+      (await pool.query.borrowBalanceStored(user.address)).value.ok.toNumber(),
+    ).toEqual(borrowAmount - repayAmount)
+  })
+
+  it('Withdraw all WETH', async () => {
+    const { pool } = pools.weth
+    const [user] = users
+
+    await shouldNotRevert(pool.withSigner(user), 'approve', [
+      wethGateway.address,
+      depositAmount,
+      { gasLimit },
+    ])
+    await shouldNotRevert(wethGateway.withSigner(user), 'withdrawAllEth', [
+      { gasLimit },
+    ])
+
+    expect(
+      (await pool.query.balanceOf(user.address)).value.ok.toNumber(),
+    ).toEqual(0)
+  })
 })